@@ -4,10 +4,12 @@
 //! Coordinates native audio recorder and VAD pipeline.
 //! Provides safe concurrent access via AtomicBool, AtomicU32, and Mutex.
 
+use sha2::{Digest, Sha256};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 
-use crate::native_audio::NativeAudioRecorder;
+use crate::native_audio::{DeviceLostEvent, NativeAudioRecorder, SpeechSegment};
+use crate::storage::{get_storage_manager, InputDeviceMeta, RecordingPause};
 use crate::vad::VadPipeline;
 
 // AudioState that is Send + Sync for Tauri
@@ -259,20 +261,49 @@ impl AudioFeatures {
 pub struct NativeAudioState {
     recorder: Mutex<Option<NativeAudioRecorder>>,
     is_recording: AtomicBool,
+    is_paused: AtomicBool,
     current_level: Arc<AtomicU32>,
     shutdown_flag: Arc<AtomicBool>,
     vad_model_path: Mutex<Option<String>>,
+    /// Device of the current recording (follows a failover)
+    recording_device: Arc<Mutex<Option<InputDeviceMeta>>>,
+    /// Last stopped recording, until it is saved
+    last_recording: Mutex<Option<LastRecording>>,
+    device_lost_cb: Mutex<Option<DeviceLostCallback>>,
+    segment_cb: Mutex<Option<SegmentCallback>>,
 }
 
+/// Capture details of a native recording, attached when its audio is saved
+#[derive(Debug)]
+pub struct RecordingCapture {
+    pub input_device: Option<InputDeviceMeta>,
+    pub pauses: Vec<RecordingPause>,
+}
+
+/// Last stopped recording and the WAV it was returned as
+struct LastRecording {
+    /// SHA-256 of the WAV returned by [`NativeAudioState::stop`]
+    wav_sha256: Vec<u8>,
+    capture: RecordingCapture,
+}
+
+type DeviceLostCallback = Arc<dyn Fn(&DeviceLostEvent) + Send + Sync + 'static>;
+type SegmentCallback = Arc<dyn Fn(SpeechSegment) + Send + Sync + 'static>;
+
 impl NativeAudioState {
     /// Create a new NativeAudioState
     pub fn new() -> Self {
         Self {
             recorder: Mutex::new(None),
             is_recording: AtomicBool::new(false),
+            is_paused: AtomicBool::new(false),
             current_level: Arc::new(AtomicU32::new(0)),
             shutdown_flag: Arc::new(AtomicBool::new(false)),
             vad_model_path: Mutex::new(None),
+            recording_device: Arc::new(Mutex::new(None)),
+            last_recording: Mutex::new(None),
+            device_lost_cb: Mutex::new(None),
+            segment_cb: Mutex::new(None),
         }
     }
 
@@ -295,7 +326,14 @@ impl NativeAudioState {
             }
         };
 
-        match VadPipeline::new(vad_path) {
+        let vad_settings = crate::settings::get_settings_store().get().vad;
+        match VadPipeline::with_params(
+            vad_path,
+            vad_settings.threshold,
+            vad_settings.prefill_frames,
+            vad_settings.hangover_frames,
+            vad_settings.onset_frames,
+        ) {
             Ok(vad) => {
                 tracing::debug!("VAD pipeline initialized successfully");
                 Some(vad)
@@ -316,14 +354,58 @@ impl NativeAudioState {
         *guard = Some(path);
     }
 
-    /// Open the audio device and initialize the recorder
+    /// Set the callback for lost input devices (called once during app setup)
+    pub fn set_device_lost_callback<F>(&self, cb: F)
+    where
+        F: Fn(&DeviceLostEvent) + Send + Sync + 'static,
+    {
+        let mut guard = self.device_lost_cb.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!("device_lost_cb Mutex poisoned, recovering");
+            poisoned.into_inner()
+        });
+        *guard = Some(Arc::new(cb));
+    }
+
+    /// Set the callback for completed speech segments of a running recording
+    /// (called once during app setup)
+    pub fn set_speech_segment_callback<F>(&self, cb: F)
+    where
+        F: Fn(SpeechSegment) + Send + Sync + 'static,
+    {
+        let mut guard = self.segment_cb.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!("segment_cb Mutex poisoned, recovering");
+            poisoned.into_inner()
+        });
+        *guard = Some(Arc::new(cb));
+    }
+
+    /// Open the selected input device (`audio.inputDevice` setting, else the
+    /// system default) and initialize the recorder
+    ///
+    /// A recorder opened with another device, or whose device was lost, is
+    /// reopened. If the selected device is not connected, opening fails
+    /// instead of falling back to the default device; failover to the default
+    /// device only applies to a device lost while open.
     ///
     /// If VAD initialization fails (e.g. ONNX Runtime not found on Windows),
     /// recording continues without VAD filtering (graceful degradation).
     pub fn open(&self) -> Result<(), String> {
+        let audio_settings = crate::settings::get_settings_store().get().audio;
+        let device_id = audio_settings.input_device;
+
         let mut guard = self.recorder.lock().map_err(|e| e.to_string())?;
-        if guard.is_some() {
-            return Ok(()); // Already open
+        if let Some(recorder) = guard.as_ref() {
+            if recorder.device_id() == device_id.as_deref() && !recorder.is_device_lost() {
+                return Ok(()); // Already open
+            }
+            if self.is_recording() {
+                return Err("Cannot switch the input device while recording".to_string());
+            }
+            // Selected device changed or was lost since the recorder was opened
+            self.shutdown_flag.store(true, Ordering::SeqCst);
+            if let Some(mut recorder) = guard.take() {
+                recorder.close()?;
+            }
         }
 
         // Try to create VAD pipeline (may fail on Windows if ONNX Runtime DLL is missing)
@@ -353,11 +435,43 @@ impl NativeAudioState {
         }
         recorder = recorder.with_level_callback(level_callback);
 
-        // Open the default audio device
-        recorder.open(None)?;
+        let lost_cb = self
+            .device_lost_cb
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone();
+        let recording_device = Arc::clone(&self.recording_device);
+        recorder = recorder
+            .with_failover(audio_settings.failover_to_default)
+            .with_device_lost_callback(move |event| {
+                // The rest of the recording comes from the failover device
+                if let Some(failover) = event.failover.as_ref().filter(|_| event.recording) {
+                    if let Ok(mut device) = recording_device.lock() {
+                        *device = Some(failover.clone());
+                    }
+                }
+                if let Some(cb) = &lost_cb {
+                    cb(&event);
+                }
+            });
+
+        if let Some(segment_cb) = self.segment_cb.lock().map_err(|e| e.to_string())?.clone() {
+            recorder = recorder.with_segment_callback(move |segment| segment_cb(segment));
+        }
 
+        match &device_id {
+            Some(device_id) => recorder.open_device(device_id)?,
+            None => recorder.open(None)?,
+        }
+
+        tracing::info!(
+            device = recorder
+                .device_meta()
+                .map(|d| d.name.as_str())
+                .unwrap_or("Unknown"),
+            "NativeAudioState: Audio device opened"
+        );
         *guard = Some(recorder);
-        tracing::info!("NativeAudioState: Audio device opened");
         Ok(())
     }
 
@@ -369,19 +483,81 @@ impl NativeAudioState {
         // Reset shutdown flag für neuen Recording-Zyklus
         self.shutdown_flag.store(false, Ordering::SeqCst);
 
-        recorder.start()?;
+        // Write the recording to disk while it runs (kept in memory without a spool)
+        let spool = match get_storage_manager().create_spool(recorder.device_meta().cloned()) {
+            Ok(spool) => spool,
+            Err(e) => {
+                tracing::warn!(error = %e, "NativeAudioState: Recording spool unavailable, recording in memory");
+                None
+            }
+        };
+
+        recorder.start(spool)?;
+        *self.recording_device.lock().map_err(|e| e.to_string())? = recorder.device_meta().cloned();
+        // Never saved: superseded by the new recording
+        *self.last_recording.lock().map_err(|e| e.to_string())? = None;
         self.is_recording.store(true, Ordering::SeqCst);
+        self.is_paused.store(false, Ordering::SeqCst);
         self.current_level.store(0, Ordering::SeqCst);
         Ok(())
     }
 
-    /// Stop recording and return samples (16kHz mono, VAD filtered)
-    pub fn stop(&self) -> Result<Vec<f32>, String> {
+    /// Pause the running recording (audio is dropped until resumed)
+    pub fn pause(&self) -> Result<(), String> {
+        let guard = self.recorder.lock().map_err(|e| e.to_string())?;
+        let recorder = guard.as_ref().ok_or("Recorder not open")?;
+
+        if !self.is_recording() {
+            return Err("Not recording".to_string());
+        }
+        if self.is_paused() {
+            return Err("Recording is already paused".to_string());
+        }
+        recorder.pause()?;
+        self.is_paused.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Resume a paused recording
+    pub fn resume(&self) -> Result<(), String> {
+        let guard = self.recorder.lock().map_err(|e| e.to_string())?;
+        let recorder = guard.as_ref().ok_or("Recorder not open")?;
+
+        if !self.is_paused() {
+            return Err("Recording is not paused".to_string());
+        }
+        recorder.resume()?;
+        self.is_paused.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Stop recording and return it as WAV (16kHz mono, VAD filtered; empty
+    /// without speech)
+    pub fn stop(&self) -> Result<Vec<u8>, String> {
         let guard = self.recorder.lock().map_err(|e| e.to_string())?;
         let recorder = guard.as_ref().ok_or("Recorder not open")?;
 
         self.is_recording.store(false, Ordering::SeqCst);
-        recorder.stop()
+        self.is_paused.store(false, Ordering::SeqCst);
+        let recorded = recorder.stop()?;
+        if recorded.samples.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let wav_bytes = crate::vad::pipeline::samples_to_wav_bytes(&recorded.samples, 16000);
+        let input_device = self
+            .recording_device
+            .lock()
+            .map_err(|e| e.to_string())?
+            .take();
+        *self.last_recording.lock().map_err(|e| e.to_string())? = Some(LastRecording {
+            wav_sha256: Sha256::digest(&wav_bytes).to_vec(),
+            capture: RecordingCapture {
+                input_device,
+                pauses: recorded.pauses,
+            },
+        });
+        Ok(wav_bytes)
     }
 
     /// Close the recorder and release resources
@@ -394,6 +570,7 @@ impl NativeAudioState {
             recorder.close()?;
         }
         self.is_recording.store(false, Ordering::SeqCst);
+        self.is_paused.store(false, Ordering::SeqCst);
         self.current_level.store(0, Ordering::SeqCst);
         tracing::info!("NativeAudioState: Audio device closed");
         Ok(())
@@ -409,6 +586,11 @@ impl NativeAudioState {
         self.is_recording.load(Ordering::SeqCst)
     }
 
+    /// Check if the running recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::SeqCst)
+    }
+
     /// Check if recorder is open
     pub fn is_open(&self) -> bool {
         self.recorder
@@ -416,6 +598,17 @@ impl NativeAudioState {
             .map(|g| g.is_some())
             .unwrap_or(false)
     }
+
+    /// Device and breaks of the last recording (once, for saving it).
+    /// `None` unless `audio` is the WAV [`Self::stop`] returned, so other
+    /// capture paths and imports never get them.
+    pub fn take_recording_capture(&self, audio: &[u8]) -> Option<RecordingCapture> {
+        let mut last = self.last_recording.lock().ok()?;
+        if last.as_ref()?.wav_sha256 != Sha256::digest(audio).as_slice() {
+            return None;
+        }
+        Some(last.take()?.capture)
+    }
 }
 
 /// # Safety
@@ -423,9 +616,14 @@ impl NativeAudioState {
 /// NativeAudioState is safe to share across threads because:
 /// - `recorder: Mutex<Option<NativeAudioRecorder>>` - Mutex provides synchronization
 /// - `is_recording: AtomicBool` - Atomic is inherently thread-safe
+/// - `is_paused: AtomicBool` - Atomic is inherently thread-safe
 /// - `current_level: Arc<AtomicU32>` - Arc provides thread-safe shared ownership
 /// - `shutdown_flag: Arc<AtomicBool>` - Arc provides thread-safe shared ownership
 /// - `vad_model_path: Mutex<Option<String>>` - Mutex provides synchronization
+/// - `recording_device: Arc<Mutex<Option<InputDeviceMeta>>>` - Mutex provides synchronization
+/// - `last_recording: Mutex<Option<LastRecording>>` - Mutex provides synchronization
+/// - `device_lost_cb: Mutex<Option<DeviceLostCallback>>` - Mutex provides synchronization
+/// - `segment_cb: Mutex<Option<SegmentCallback>>` - Mutex provides synchronization
 ///
 /// All fields are either atomics (lock-free) or protected by Mutex (synchronized).
 unsafe impl Send for NativeAudioState {}
@@ -2,20 +2,177 @@
 //!
 //! These commands use the native cpal audio system for production Desktop app.
 //! This is the primary audio path - Web Audio API (audio_legacy.rs) is only a fallback.
+//!
+//! Plugged and unplugged microphones are reported via `audio:device-added`,
+//! `audio:device-removed` and `audio:default-device-changed`; losing the open
+//! microphone via `audio:device-lost`.
+//!
+//! While recording, each completed speech segment is emitted as
+//! `audio:speech-segment`, so transcription and analysis can start before the
+//! recording is stopped.
+
+use std::time::Duration;
 
 use crate::audio::NativeAudioState;
-use crate::native_audio::{list_input_devices as list_devices, CpalDeviceInfo};
-use tauri::State;
+use crate::native_audio::{
+    find_input_device, list_input_devices as list_devices, CpalDeviceInfo, DeviceChange,
+    DeviceLostEvent, DeviceMonitor, InputDeviceRef, SpeechSegment,
+};
+use crate::settings::get_settings_store;
+use serde::Serialize;
+use tauri::{Emitter, State};
 
+use super::settings::apply_settings;
 use super::utils::encode_audio_base64;
 
+/// Event emitted when an input device was plugged in (payload: [`InputDeviceRef`])
+const DEVICE_ADDED_EVENT: &str = "audio:device-added";
+
+/// Event emitted when an input device was unplugged (payload: [`InputDeviceRef`])
+const DEVICE_REMOVED_EVENT: &str = "audio:device-removed";
+
+/// Event emitted when the system default input changed (payload: [`DefaultDeviceChange`])
+const DEFAULT_DEVICE_CHANGED_EVENT: &str = "audio:default-device-changed";
+
+/// Event emitted when the open input device was lost (payload: [`DeviceLostEvent`])
+const DEVICE_LOST_EVENT: &str = "audio:device-lost";
+
+/// Event emitted when a speech segment of the running recording is complete
+/// (payload: [`SpeechSegmentEvent`])
+const SPEECH_SEGMENT_EVENT: &str = "audio:speech-segment";
+
+/// How often the input devices are checked for changes
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// New system default input device
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultDeviceChange {
+    /// `None` if no input device is left
+    pub device: Option<InputDeviceRef>,
+}
+
+/// Completed speech segment of the running recording
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechSegmentEvent {
+    /// Segments of a recording are numbered from 0
+    pub index: u32,
+    /// Position in the recorded audio (as returned by `native_stop_recording`)
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Base64 encoded WAV (16kHz mono)
+    pub audio: String,
+}
+
+/// Watch for plugged and unplugged input devices and report them to the
+/// frontend. Called once on startup; the thread runs for the lifetime of the app.
+pub(crate) fn start_device_monitor(app: &tauri::AppHandle) {
+    let app = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("audio-device-monitor".to_string())
+        .spawn(move || {
+            let mut monitor = DeviceMonitor::new();
+            // Report a failing enumeration once, not on every poll
+            let mut last_error: Option<String> = None;
+            loop {
+                match monitor.poll() {
+                    Ok(changes) => {
+                        last_error = None;
+                        for change in changes {
+                            emit_device_change(&app, change);
+                        }
+                    }
+                    Err(e) => {
+                        if last_error.as_deref() != Some(e.as_str()) {
+                            tracing::warn!(error = %e, "Native audio: Cannot check input devices");
+                        }
+                        last_error = Some(e);
+                    }
+                }
+                std::thread::sleep(DEVICE_POLL_INTERVAL);
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!(error = %e, "Native audio: Device monitoring disabled");
+    }
+}
+
+fn emit_device_change(app: &tauri::AppHandle, change: DeviceChange) {
+    let result = match change {
+        DeviceChange::Added(device) => {
+            tracing::info!(device = %device.name, "Native audio: Input device added");
+            app.emit(DEVICE_ADDED_EVENT, device)
+        }
+        DeviceChange::Removed(device) => {
+            tracing::info!(device = %device.name, "Native audio: Input device removed");
+            app.emit(DEVICE_REMOVED_EVENT, device)
+        }
+        DeviceChange::DefaultChanged(device) => {
+            app.emit(DEFAULT_DEVICE_CHANGED_EVENT, DefaultDeviceChange { device })
+        }
+    };
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "Failed to emit audio device change");
+    }
+}
+
+/// Report a lost input device to the frontend (recorder worker thread)
+pub(crate) fn emit_device_lost(app: &tauri::AppHandle, event: &DeviceLostEvent) {
+    if let Err(e) = app.emit(DEVICE_LOST_EVENT, event) {
+        tracing::warn!(error = %e, "Failed to emit device lost event");
+    }
+}
+
+/// Report a completed speech segment to the frontend (recorder worker thread)
+pub(crate) fn emit_speech_segment(app: &tauri::AppHandle, segment: SpeechSegment) {
+    let wav_bytes = crate::vad::pipeline::samples_to_wav_bytes(&segment.samples, 16000);
+    let payload = SpeechSegmentEvent {
+        index: segment.index,
+        start_ms: segment.start_ms,
+        end_ms: segment.end_ms,
+        audio: encode_audio_base64(&wav_bytes),
+    };
+    if let Err(e) = app.emit(SPEECH_SEGMENT_EVENT, payload) {
+        tracing::warn!(error = %e, "Failed to emit speech segment");
+    }
+}
+
 /// List available audio input devices
 #[tauri::command]
 pub fn list_audio_devices() -> Result<Vec<CpalDeviceInfo>, String> {
     list_devices()
 }
 
-/// Open native audio device (must be called before recording)
+/// Select the input device by its `id` from `list_audio_devices`
+/// (`None` = system default) and remember it in the settings.
+///
+/// Fails if the device is not connected or a recording is running. An open
+/// recorder is reopened with the new device.
+#[tauri::command]
+pub async fn set_audio_input_device(
+    app: tauri::AppHandle,
+    state: State<'_, NativeAudioState>,
+    device_id: Option<String>,
+) -> Result<(), String> {
+    if state.is_recording() {
+        return Err("Cannot switch the input device while recording".to_string());
+    }
+    if let Some(device_id) = &device_id {
+        find_input_device(device_id)?;
+    }
+
+    let mut settings = get_settings_store().get();
+    settings.audio.input_device = device_id;
+    apply_settings(&app, settings)?;
+
+    if state.is_open() {
+        state.open()?;
+    }
+    Ok(())
+}
+
+/// Open the selected native audio device (must be called before recording)
 #[tauri::command]
 pub async fn native_open_audio(state: State<'_, NativeAudioState>) -> Result<(), String> {
     state.open()
@@ -24,30 +181,39 @@ pub async fn native_open_audio(state: State<'_, NativeAudioState>) -> Result<(),
 /// Start native audio recording
 #[tauri::command]
 pub async fn native_start_recording(state: State<'_, NativeAudioState>) -> Result<(), String> {
-    // Auto-open if not already open
-    if !state.is_open() {
-        state.open()?;
-    }
+    // Auto-open (or reopen if another device was selected meanwhile)
+    state.open()?;
     state.start()
 }
 
+/// Pause native audio recording (e.g. to collect one's thoughts).
+///
+/// Audio during the break is not recorded; the break is stored with the
+/// recording (`pauses`) so it is not mistaken for a speech pause.
+#[tauri::command]
+pub async fn native_pause_recording(state: State<'_, NativeAudioState>) -> Result<(), String> {
+    state.pause()
+}
+
+/// Resume a paused native audio recording
+#[tauri::command]
+pub async fn native_resume_recording(state: State<'_, NativeAudioState>) -> Result<(), String> {
+    state.resume()
+}
+
 /// Stop native audio recording and return Base64 encoded WAV
 #[tauri::command]
 pub async fn native_stop_recording(state: State<'_, NativeAudioState>) -> Result<String, String> {
-    let samples = state.stop()?;
+    let wav_bytes = state.stop()?;
 
-    if samples.is_empty() {
+    if wav_bytes.is_empty() {
         tracing::debug!("Native audio: No speech detected in recording");
         return Ok(String::new());
     }
 
-    // Convert samples to WAV bytes
-    let wav_bytes = crate::vad::pipeline::samples_to_wav_bytes(&samples, 16000);
-
     // Encode as Base64
     let base64 = encode_audio_base64(&wav_bytes);
     tracing::debug!(
-        samples = samples.len(),
         wav_bytes = wav_bytes.len(),
         base64_chars = base64.len(),
         "Native audio: Recording converted"
@@ -73,3 +239,9 @@ pub async fn native_close_audio(state: State<'_, NativeAudioState>) -> Result<()
 pub fn native_is_recording(state: State<'_, NativeAudioState>) -> Result<bool, String> {
     Ok(state.is_recording())
 }
+
+/// Check if the native recording is paused
+#[tauri::command]
+pub fn native_is_paused(state: State<'_, NativeAudioState>) -> Result<bool, String> {
+    Ok(state.is_paused())
+}
@@ -0,0 +1,199 @@
+//! Backup Commands
+//!
+//! Incremental snapshots of the active profile's library in a backup directory
+//! (e.g. an external drive), created on demand or on the configured schedule.
+//! Progress, completion and failures are reported as events, so scheduled
+//! backups running in the background are visible to the frontend.
+
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::security::path_validation::validate_backup_path;
+use crate::storage::{get_storage_manager, BackupResult, BackupSnapshot, RestoreResult};
+
+/// Event emitted while a backup or restore runs (payload: [`BackupProgress`])
+const BACKUP_PROGRESS_EVENT: &str = "storage:backup-progress";
+
+/// Event emitted when a backup finished (payload: [`BackupResult`])
+const BACKUP_COMPLETED_EVENT: &str = "storage:backup-completed";
+
+/// Event emitted when a backup or restore failed (payload: [`BackupFailure`])
+const BACKUP_FAILED_EVENT: &str = "storage:backup-failed";
+
+/// Delay before the first scheduled check after startup
+const SCHEDULER_START_DELAY: Duration = Duration::from_secs(120);
+
+/// Interval between checks whether a scheduled backup is due
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Which operation an event belongs to
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackupOperation {
+    Backup,
+    Restore,
+}
+
+/// Progress of a running backup or restore (files for backups, recordings for restores)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupProgress {
+    pub operation: BackupOperation,
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// A failed backup or restore
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFailure {
+    pub operation: BackupOperation,
+    pub error: String,
+    /// Started by the scheduler rather than the user
+    pub scheduled: bool,
+}
+
+fn emit_progress(
+    app: &tauri::AppHandle,
+    operation: BackupOperation,
+    processed: usize,
+    total: usize,
+) {
+    let payload = BackupProgress {
+        operation,
+        processed,
+        total,
+    };
+    if let Err(e) = app.emit(BACKUP_PROGRESS_EVENT, payload) {
+        tracing::warn!(error = %e, "Failed to emit backup progress");
+    }
+}
+
+fn emit_failure(app: &tauri::AppHandle, operation: BackupOperation, error: &str, scheduled: bool) {
+    let payload = BackupFailure {
+        operation,
+        error: error.to_string(),
+        scheduled,
+    };
+    if let Err(e) = app.emit(BACKUP_FAILED_EVENT, payload) {
+        tracing::warn!(error = %e, "Failed to emit backup failure");
+    }
+}
+
+/// Create a snapshot and report progress, completion or failure
+fn run_backup(app: &tauri::AppHandle, scheduled: bool) -> Result<BackupResult, String> {
+    let result = get_storage_manager().create_backup(|processed, total| {
+        emit_progress(app, BackupOperation::Backup, processed, total)
+    });
+    match &result {
+        Ok(backup) => {
+            if let Err(e) = app.emit(BACKUP_COMPLETED_EVENT, backup) {
+                tracing::warn!(error = %e, "Failed to emit backup completion");
+            }
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, scheduled, "Storage: Backup failed");
+            emit_failure(app, BackupOperation::Backup, e, scheduled);
+        }
+    }
+    result
+}
+
+/// Validate a changed backup directory.
+///
+/// Unchanged paths are not checked again, so settings can still be saved while
+/// the backup drive is unplugged.
+pub(crate) fn validate_backup_path_change(
+    path: Option<String>,
+    previous: Option<&str>,
+) -> Result<Option<String>, String> {
+    match path {
+        Some(path) if Some(path.as_str()) != previous => {
+            // Security: Path traversal, symlinks, home directory or external volume
+            validate_backup_path(&path)
+                .map(|p| Some(p.to_string_lossy().to_string()))
+                .map_err(|e| format!("Security validation failed: {}", e))
+        }
+        path => Ok(path),
+    }
+}
+
+/// Check periodically whether a scheduled backup is due and run it.
+/// Called once on startup; the thread runs for the lifetime of the app.
+pub(crate) fn start_backup_scheduler(app: &tauri::AppHandle) {
+    let app = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("backup-scheduler".to_string())
+        .spawn(move || {
+            std::thread::sleep(SCHEDULER_START_DELAY);
+            // Report an unavailable backup directory once, not on every check
+            let mut last_error: Option<String> = None;
+            loop {
+                match get_storage_manager().backup_due(chrono::Utc::now()) {
+                    Ok(true) => {
+                        last_error = None;
+                        let _ = run_backup(&app, true);
+                    }
+                    Ok(false) => last_error = None,
+                    Err(e) => {
+                        if last_error.as_deref() != Some(e.as_str()) {
+                            tracing::warn!(error = %e, "Storage: Scheduled backup not possible");
+                            emit_failure(&app, BackupOperation::Backup, &e, true);
+                        }
+                        last_error = Some(e);
+                    }
+                }
+                std::thread::sleep(SCHEDULER_INTERVAL);
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::warn!(error = %e, "Storage: Scheduled backups disabled");
+    }
+}
+
+/// Back up the library now.
+///
+/// Copies new and changed recordings into a new snapshot and removes
+/// snapshots beyond the configured count. Emits `storage:backup-progress`,
+/// then `storage:backup-completed` or `storage:backup-failed`.
+#[tauri::command]
+pub async fn create_backup(app: tauri::AppHandle) -> Result<BackupResult, String> {
+    tokio::task::spawn_blocking(move || run_backup(&app, false))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// List snapshots in the backup directory (newest first)
+#[tauri::command]
+pub async fn list_backups() -> Result<Vec<BackupSnapshot>, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.list_backups()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Restore the recordings of a snapshot into the library.
+///
+/// The snapshot is verified first; recordings that differ are replaced and the
+/// current version is moved to the trash. Emits `storage:backup-progress`
+/// and `storage:backup-failed`.
+#[tauri::command]
+pub async fn restore_backup(app: tauri::AppHandle, name: String) -> Result<RestoreResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        let result = manager.restore_backup(&name, |processed, total| {
+            emit_progress(&app, BackupOperation::Restore, processed, total)
+        });
+        if let Err(e) = &result {
+            tracing::warn!(error = %e, snapshot = %name, "Storage: Restore failed");
+            emit_failure(&app, BackupOperation::Restore, e, false);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
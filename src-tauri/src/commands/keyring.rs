@@ -46,6 +46,40 @@ fn validate_params(service: &str, user: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Read a password from the OS keyring (blocking).
+///
+/// Shared by the Tauri commands and backend consumers such as the
+/// storage data key. Must be called from a blocking context.
+pub(crate) fn get_password_blocking(service: &str, user: &str) -> Result<Option<String>, String> {
+    let entry = keyring::Entry::new(service, user)
+        .map_err(|e| format!("Keyring entry creation failed for '{}': {}", service, e))?;
+    match entry.get_password() {
+        Ok(pw) => {
+            tracing::debug!(service = %service, "Keyring: password retrieved");
+            Ok(Some(pw))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => {
+            tracing::error!(service = %service, error = %e, "Keyring: get_password failed");
+            Err(format!("Failed to get password for '{}': {}", service, e))
+        }
+    }
+}
+
+/// Store a password in the OS keyring (blocking).
+///
+/// Overwrites any existing entry for the same service/user pair.
+pub(crate) fn set_password_blocking(service: &str, user: &str, password: &str) -> Result<(), String> {
+    let entry = keyring::Entry::new(service, user)
+        .map_err(|e| format!("Keyring entry creation failed for '{}': {}", service, e))?;
+    entry.set_password(password).map_err(|e| {
+        tracing::error!(service = %service, error = %e, "Keyring: set_password failed");
+        format!("Failed to store password for '{}': {}", service, e)
+    })?;
+    tracing::info!(service = %service, "Keyring: password stored");
+    Ok(())
+}
+
 /// Get a password from the OS keyring.
 ///
 /// Returns `Ok(Some(password))` if found, `Ok(None)` if not found,
@@ -54,23 +88,9 @@ fn validate_params(service: &str, user: &str) -> Result<(), String> {
 pub async fn keyring_get_password(service: String, user: String) -> Result<Option<String>, String> {
     validate_params(&service, &user)?;
 
-    tokio::task::spawn_blocking(move || {
-        let entry = keyring::Entry::new(&service, &user)
-            .map_err(|e| format!("Keyring entry creation failed for '{}': {}", service, e))?;
-        match entry.get_password() {
-            Ok(pw) => {
-                tracing::debug!(service = %service, "Keyring: password retrieved");
-                Ok(Some(pw))
-            }
-            Err(keyring::Error::NoEntry) => Ok(None),
-            Err(e) => {
-                tracing::error!(service = %service, error = %e, "Keyring: get_password failed");
-                Err(format!("Failed to get password for '{}': {}", service, e))
-            }
-        }
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?
+    tokio::task::spawn_blocking(move || get_password_blocking(&service, &user))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
 }
 
 /// Store a password in the OS keyring.
@@ -84,18 +104,9 @@ pub async fn keyring_set_password(
 ) -> Result<(), String> {
     validate_params(&service, &user)?;
 
-    tokio::task::spawn_blocking(move || {
-        let entry = keyring::Entry::new(&service, &user)
-            .map_err(|e| format!("Keyring entry creation failed for '{}': {}", service, e))?;
-        entry.set_password(&password).map_err(|e| {
-            tracing::error!(service = %service, error = %e, "Keyring: set_password failed");
-            format!("Failed to store password for '{}': {}", service, e)
-        })?;
-        tracing::info!(service = %service, "Keyring: password stored");
-        Ok(())
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?
+    tokio::task::spawn_blocking(move || set_password_blocking(&service, &user, &password))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
 }
 
 /// Delete a password from the OS keyring.
@@ -6,6 +6,10 @@
 //! - transcription: Whisper/MLX transcription commands
 //! - analysis: Audio emotion/tone analysis commands
 //! - storage: Recording storage commands
+//! - backup: Incremental library backups (scheduled and on demand)
+//! - sync: WebDAV library sync between devices
+//! - profiles: Independent recording libraries
+//! - settings: Persistent backend settings commands
 //! - mlx_llm: MLX-LLM integration commands
 //! - export: Recording export commands
 //! - file_io: File I/O commands (read audio files)
@@ -20,12 +24,16 @@ mod audio_native;
 mod transcription;
 mod analysis;
 mod storage;
+mod backup;
+mod sync;
+mod profiles;
+mod settings;
 mod mlx_llm;
 mod export;
 mod file_io;
 mod window;
 mod system;
-mod keyring;
+pub(crate) mod keyring; // pub(crate) for storage data key access
 pub(crate) mod utils; // pub(crate) for sub-module access
 
 // Re-export all commands and types for frontend
@@ -34,6 +42,10 @@ pub use audio_native::*;
 pub use transcription::*;
 pub use analysis::*;
 pub use storage::*;
+pub use backup::*;
+pub use sync::*;
+pub use profiles::*;
+pub use settings::*;
 pub use mlx_llm::*;
 pub use export::*;
 pub use file_io::*;
@@ -0,0 +1,110 @@
+//! Profile Commands
+//!
+//! Independent recording libraries (e.g. work and private journals). Each
+//! profile has its own storage path, storage settings, baseline and data key;
+//! all storage commands target the active profile.
+//! Changes are saved with the settings and announced via `settings:changed`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::security::path_validation::validate_storage_path;
+use crate::settings::{get_settings_store, StorageProfile};
+use crate::storage::StorageConfig;
+
+use super::settings::apply_settings;
+
+/// All profiles and the active one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub active_profile: String,
+    pub profiles: Vec<StorageProfile>,
+}
+
+/// List all recording profiles
+#[tauri::command]
+pub async fn list_profiles() -> Result<ProfileList, String> {
+    tokio::task::spawn_blocking(|| {
+        let settings = get_settings_store().get();
+        Ok(ProfileList {
+            active_profile: settings.active_profile,
+            profiles: settings.profiles,
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Create a profile with default storage settings.
+///
+/// Without `storage_path` the library is kept in its own directory next to
+/// the default one. The new profile is not activated.
+#[tauri::command]
+pub async fn create_profile(
+    app: tauri::AppHandle,
+    name: String,
+    storage_path: Option<String>,
+) -> Result<StorageProfile, String> {
+    // Security: Validate storage path (path traversal, symlinks, home directory)
+    let storage_path = storage_path
+        .map(|path| {
+            validate_storage_path(&path)
+                .map(|p| p.to_string_lossy().to_string())
+                .map_err(|e| format!("Security validation failed: {}", e))
+        })
+        .transpose()?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut settings = get_settings_store().get();
+        let profile = settings.create_profile(&name, storage_path);
+        apply_settings(&app, settings)?;
+        tracing::info!(profile = %profile.id, "Profiles: Created");
+        Ok(profile)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Make a profile active; returns its storage config
+#[tauri::command]
+pub async fn switch_profile(app: tauri::AppHandle, id: String) -> Result<StorageConfig, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut settings = get_settings_store().get();
+        settings.switch_profile(&id)?;
+        apply_settings(&app, settings).map(|saved| saved.storage)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Rename a profile
+#[tauri::command]
+pub async fn rename_profile(
+    app: tauri::AppHandle,
+    id: String,
+    name: String,
+) -> Result<StorageProfile, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut settings = get_settings_store().get();
+        settings.rename_profile(&id, &name)?;
+        let saved = apply_settings(&app, settings)?;
+        saved.profile(&id).cloned()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Delete an inactive profile.
+/// Its recordings stay on disk and its data key stays in the keyring.
+#[tauri::command]
+pub async fn delete_profile(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let mut settings = get_settings_store().get();
+        let removed = settings.delete_profile(&id)?;
+        apply_settings(&app, settings)?;
+        tracing::info!(profile = %removed.id, "Profiles: Deleted");
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
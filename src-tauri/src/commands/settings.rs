@@ -0,0 +1,109 @@
+//! Settings Commands
+//!
+//! Backend-owned persistent settings (storage, VAD, whisper, analysis, audio input).
+//! Every change is saved to the settings file and announced to the frontend
+//! via the `settings:changed` event.
+
+use tauri::Emitter;
+
+use crate::security::path_validation::validate_storage_path;
+use crate::settings::{get_settings_store, Settings, SETTINGS_CHANGED_EVENT};
+use crate::storage::{set_active_profile, StorageConfig};
+
+use super::backup::validate_backup_path_change;
+use super::storage::restart_storage_watcher;
+use super::utils::reset_vad_pipeline;
+
+/// Persist settings, apply them to running components and emit the change event
+pub(crate) fn apply_settings(app: &tauri::AppHandle, settings: Settings) -> Result<Settings, String> {
+    let store = get_settings_store();
+    let previous = store.get();
+    let saved = store.update(settings)?;
+
+    set_active_profile(&saved.active_profile, saved.storage.clone())?;
+    restart_storage_watcher(app);
+    if saved.vad != previous.vad {
+        // Cached pipeline was built with the old parameters
+        reset_vad_pipeline();
+    }
+
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &saved) {
+        tracing::warn!(error = %e, "Failed to emit settings change event");
+    }
+    tracing::info!("Settings: Updated");
+    Ok(saved)
+}
+
+/// Get the current backend settings
+#[tauri::command]
+pub async fn get_settings() -> Result<Settings, String> {
+    tokio::task::spawn_blocking(|| Ok(get_settings_store().get()))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Validate, persist and apply new backend settings
+#[tauri::command]
+pub async fn update_settings(app: tauri::AppHandle, settings: Settings) -> Result<Settings, String> {
+    // `storage` belongs to the active profile; switching must swap it as well
+    let previous = get_settings_store().get();
+    if settings.active_profile != previous.active_profile {
+        return Err("Use switch_profile to change the active profile".to_string());
+    }
+
+    // Security: Validate storage path (path traversal, symlinks, home directory)
+    let validated_path = validate_storage_path(&settings.storage.storage_path)
+        .map_err(|e| format!("Security validation failed: {}", e))?;
+
+    let mut settings = settings;
+    settings.storage.storage_path = validated_path.to_string_lossy().to_string();
+    settings.storage.backup_path = validate_backup_path_change(
+        settings.storage.backup_path.take(),
+        previous.storage.backup_path.as_deref(),
+    )?;
+
+    // Other profiles' paths: only changed ones (generated paths may not exist yet)
+    for profile in &mut settings.profiles {
+        let unchanged = previous
+            .profile(&profile.id)
+            .is_ok_and(|p| p.storage.storage_path == profile.storage.storage_path);
+        if !unchanged && profile.id != settings.active_profile {
+            let validated = validate_storage_path(&profile.storage.storage_path)
+                .map_err(|e| format!("Security validation failed: {}", e))?;
+            profile.storage.storage_path = validated.to_string_lossy().to_string();
+        }
+        if profile.id != settings.active_profile {
+            let previous_backup = previous
+                .profile(&profile.id)
+                .ok()
+                .and_then(|p| p.storage.backup_path.as_deref());
+            profile.storage.backup_path =
+                validate_backup_path_change(profile.storage.backup_path.take(), previous_backup)?;
+        }
+    }
+
+    tokio::task::spawn_blocking(move || apply_settings(&app, settings))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Reset all backend settings to defaults.
+/// Profiles and the active profile's storage path are kept.
+#[tauri::command]
+pub async fn reset_settings(app: tauri::AppHandle) -> Result<Settings, String> {
+    tokio::task::spawn_blocking(move || {
+        let current = get_settings_store().get();
+        let settings = Settings {
+            storage: StorageConfig {
+                storage_path: current.storage.storage_path,
+                ..StorageConfig::default()
+            },
+            profiles: current.profiles,
+            active_profile: current.active_profile,
+            ..Settings::default()
+        };
+        apply_settings(&app, settings)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
@@ -3,12 +3,32 @@
 //! Recording persistence, metadata management, and baseline calculations.
 //! All commands use spawn_blocking for non-blocking file I/O.
 
-use crate::storage::{get_storage_manager, BaselineResult, RecordingMetadata, StorageConfig, StorageStats};
-use crate::security::path_validation::validate_storage_path;
+use crate::audio::NativeAudioState;
+use crate::storage::{
+    get_storage_manager, AudioCodec, BaselineResult, CleanupPlan, DiffSegment, DuplicateGroup,
+    EmotionTrends, EncryptionConversionResult, IntegrityReport, InterruptedRecording,
+    LibraryExportResult, LibraryImportResult, QuarantinedRecording, RecompressionResult,
+    RecordingMetadata, RecordingMetadataUpdate, RecordingPage, RecordingQuery, RecordingSummary,
+    RepairReport, SchemaUpgradeResult, SearchResult, StorageConfig, StorageStats, StorageWatcher,
+    TranscriptionHistory, TrashedRecording,
+};
+use crate::security::path_validation::{
+    validate_archive_export_path, validate_archive_path, validate_storage_path,
+};
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{Emitter, State};
 
+use crate::settings::get_settings_store;
+
+use super::backup::validate_backup_path_change;
+use super::settings::apply_settings;
 use super::utils::{decode_audio_base64, encode_audio_base64};
 
+/// Window of the baseline used for personalized feedback (recent mood
+/// rather than all history)
+const FEEDBACK_BASELINE_DAYS: u32 = 30;
+
 /// Personalized feedback result for a recording
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +36,8 @@ pub struct PersonalizedFeedback {
     pub baseline_emotion: String,
     pub baseline_confidence: f32,
     pub baseline_sample_count: usize,
+    /// Window the baseline was computed over, `None` for all recordings
+    pub baseline_window_days: Option<u32>,
     pub current_emotion: String,
     pub current_confidence: f32,
     pub confidence_delta: f32,
@@ -24,13 +46,27 @@ pub struct PersonalizedFeedback {
 }
 
 /// Save a recording with metadata
+///
+/// Audio of the last native recording gets its input device and breaks
+/// unless the frontend already set `inputDevice` or `pauses`.
 #[tauri::command]
 pub async fn save_recording(
+    native_audio: State<'_, NativeAudioState>,
     audio_data: String, // Base64 encoded WAV
     metadata: RecordingMetadata,
 ) -> Result<String, String> {
     let audio_bytes = decode_audio_base64(&audio_data)?;
 
+    let mut metadata = metadata;
+    if let Some(capture) = native_audio.take_recording_capture(&audio_bytes) {
+        if metadata.input_device.is_none() {
+            metadata.input_device = capture.input_device;
+        }
+        if metadata.pauses.is_empty() {
+            metadata.pauses = capture.pauses;
+        }
+    }
+
     tokio::task::spawn_blocking(move || {
         let manager = get_storage_manager();
         manager.save_recording(&audio_bytes, &metadata)
@@ -62,6 +98,106 @@ pub async fn get_recording_audio(id: String) -> Result<String, String> {
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// Query recordings with filters, sort order and cursor-based pagination
+///
+/// Pass `nextCursor` of a page as `cursor` to get the next one.
+#[tauri::command]
+pub async fn query_recordings(
+    query: RecordingQuery,
+) -> Result<RecordingPage<RecordingMetadata>, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.query_recordings(&query)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Like `query_recordings`, but returns lightweight summaries (no transcript
+/// or analysis payloads) for list views
+#[tauri::command]
+pub async fn query_recording_summaries(
+    query: RecordingQuery,
+) -> Result<RecordingPage<RecordingSummary>, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.query_recording_summaries(&query)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Full-text search over stored transcriptions and analysis texts
+///
+/// Matches German word forms (stemming, umlaut folding) and returns the best
+/// matches first with highlighted snippets.
+#[tauri::command]
+pub async fn search_recordings(
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.search_recordings(&query, limit)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Update a recording's title, notes and tags
+#[tauri::command]
+pub async fn update_recording_metadata(
+    id: String,
+    update: RecordingMetadataUpdate,
+) -> Result<RecordingMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.update_recording_metadata(&id, &update)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// List all transcription revisions of a recording
+#[tauri::command]
+pub async fn get_transcription_history(id: String) -> Result<TranscriptionHistory, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.get_transcription_history(&id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Make a transcription revision the recording's current transcription
+#[tauri::command]
+pub async fn set_active_transcription_revision(
+    id: String,
+    revision: u32,
+) -> Result<RecordingMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.set_active_transcription_revision(&id, revision)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Word-level diff between two transcription revisions of a recording
+#[tauri::command]
+pub async fn diff_transcription_revisions(
+    id: String,
+    from: u32,
+    to: u32,
+) -> Result<Vec<DiffSegment>, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.diff_transcription_revisions(&id, from, to)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Delete a recording by ID
 #[tauri::command]
 pub async fn delete_recording(id: String) -> Result<(), String> {
@@ -84,6 +220,39 @@ pub async fn clear_all_recordings() -> Result<usize, String> {
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
+/// List recordings in the trash (most recently deleted first)
+#[tauri::command]
+pub async fn list_trash() -> Result<Vec<TrashedRecording>, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.list_trash()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Move a recording from the trash back into the library
+#[tauri::command]
+pub async fn restore_recording(id: String) -> Result<RecordingMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.restore_recording(&id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Permanently delete all recordings in the trash
+#[tauri::command]
+pub async fn empty_trash() -> Result<usize, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.empty_trash()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
 /// Get storage configuration
 #[tauri::command]
 pub async fn get_storage_config() -> Result<StorageConfig, String> {
@@ -107,26 +276,32 @@ pub async fn get_storage_config() -> Result<StorageConfig, String> {
 /// * `config` - New storage configuration
 ///
 /// # Returns
-/// * `Ok(())` if configuration updated successfully
+/// * `Ok(())` if configuration updated successfully (persisted in the settings file)
 ///
 /// # Errors
 /// * Security validation failed (path traversal, symlink, not in home)
 /// * Failed to update configuration
 #[tauri::command]
-pub async fn update_storage_config(config: StorageConfig) -> Result<(), String> {
+pub async fn update_storage_config(app: tauri::AppHandle, config: StorageConfig) -> Result<(), String> {
     // Security: Validate storage path (path traversal, symlinks, home directory)
     let validated_path = validate_storage_path(&config.storage_path)
         .map_err(|e| format!("Security validation failed: {}", e))?;
 
-    // Use validated (canonicalized) path in config
+    let previous = get_settings_store().get().storage;
+    let backup_path =
+        validate_backup_path_change(config.backup_path, previous.backup_path.as_deref())?;
+
+    // Use validated (canonicalized) paths in config
     let config_with_validated_path = StorageConfig {
         storage_path: validated_path.to_string_lossy().to_string(),
+        backup_path,
         ..config
     };
 
     tokio::task::spawn_blocking(move || {
-        let manager = get_storage_manager();
-        manager.update_config(config_with_validated_path)
+        let mut settings = get_settings_store().get();
+        settings.storage = config_with_validated_path;
+        apply_settings(&app, settings).map(|_| ())
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
@@ -143,12 +318,267 @@ pub async fn get_storage_stats() -> Result<StorageStats, String> {
     .map_err(|e| format!("Task join error: {}", e))?
 }
 
-/// Calculate baseline emotion from all recordings
+/// Preview what the automatic cleanup would remove (dry run)
+///
+/// Applies the age, count and quota limits without deleting anything.
 #[tauri::command]
-pub async fn calculate_baseline_emotion() -> Result<Option<BaselineResult>, String> {
+pub async fn preview_storage_cleanup() -> Result<CleanupPlan, String> {
     tokio::task::spawn_blocking(|| {
         let manager = get_storage_manager();
-        manager.calculate_baseline_emotion()
+        manager.preview_cleanup()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Find recordings with the same audio in the library
+///
+/// Groups recordings by content hash and, to tolerate re-encoded copies,
+/// by loudness fingerprint. Reads and decodes all audio.
+#[tauri::command]
+pub async fn find_duplicates() -> Result<Vec<DuplicateGroup>, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.find_duplicates()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Rebuild the recording catalog from the JSON sidecars on disk
+///
+/// Returns the number of indexed recordings.
+#[tauri::command]
+pub async fn rebuild_recording_catalog() -> Result<usize, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.rebuild_catalog()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Convert the whole library to encrypted (`enable = true`) or plaintext storage.
+///
+/// Also switches the format used for new recordings.
+#[tauri::command]
+pub async fn convert_library_encryption(
+    app: tauri::AppHandle,
+    enable: bool,
+) -> Result<EncryptionConversionResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        let result = manager.convert_library_encryption(enable)?;
+
+        // Persist the new format for future recordings
+        let mut settings = get_settings_store().get();
+        settings.storage = manager.get_config()?;
+        apply_settings(&app, settings)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Event emitted while the library is recompressed (payload: [`RecompressionProgress`])
+const RECOMPRESSION_PROGRESS_EVENT: &str = "storage:recompression-progress";
+
+/// Progress of a running library recompression
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecompressionProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// Recompress all stored audio with `codec` and report the space saved.
+///
+/// Runs in the background and emits `storage:recompression-progress` events.
+/// Also switches the codec used for new recordings.
+#[tauri::command]
+pub async fn recompress_library(
+    app: tauri::AppHandle,
+    codec: AudioCodec,
+) -> Result<RecompressionResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        let result = manager.recompress_library(codec, |processed, total| {
+            let payload = RecompressionProgress { processed, total };
+            if let Err(e) = app.emit(RECOMPRESSION_PROGRESS_EVENT, payload) {
+                tracing::warn!(error = %e, "Failed to emit recompression progress");
+            }
+        })?;
+
+        // Persist the codec for future recordings
+        let mut settings = get_settings_store().get();
+        settings.storage = manager.get_config()?;
+        apply_settings(&app, settings)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// List recordings whose metadata could not be read and was quarantined
+#[tauri::command]
+pub async fn list_quarantined_recordings() -> Result<Vec<QuarantinedRecording>, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.list_quarantined()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Move a quarantined recording back into the library (e.g. after an app update)
+#[tauri::command]
+pub async fn restore_quarantined_recording(base_name: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.restore_quarantined(&base_name)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// List recording sessions interrupted by a crash (offered for recovery)
+#[tauri::command]
+pub async fn list_interrupted_recordings() -> Result<Vec<InterruptedRecording>, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.list_interrupted_recordings()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Save an interrupted recording session into the library
+#[tauri::command]
+pub async fn recover_interrupted_recording(id: String) -> Result<RecordingMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.recover_interrupted_recording(&id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Delete an interrupted recording session without saving it
+#[tauri::command]
+pub async fn discard_interrupted_recording(id: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.discard_interrupted_recording(&id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Rewrite metadata sidecars with an outdated schema in the current layout
+#[tauri::command]
+pub async fn upgrade_recording_schemas() -> Result<SchemaUpgradeResult, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.upgrade_sidecars()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Check the storage directory for orphaned, missing or damaged files and
+/// insecure permissions (read-only)
+#[tauri::command]
+pub async fn verify_storage() -> Result<IntegrityReport, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.verify_storage()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Fix what `verify_storage` reports, where possible
+///
+/// Rebuilds metadata for audio without a sidecar, resets file permissions and
+/// quarantines recordings with corrupt audio or metadata. Missing audio and
+/// undecryptable files are returned as unresolved.
+#[tauri::command]
+pub async fn repair_storage() -> Result<RepairReport, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.repair_storage()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Export recordings into a portable `.tar.gz` archive
+///
+/// # Arguments
+/// * `destination_path` - Archive file to write (e.g. from a save dialog)
+/// * `ids` - Recordings to export; `None` exports the whole library
+///
+/// # Security
+/// * Extension validation (only .tar.gz, .tgz allowed)
+/// * Path traversal and symlink protection
+/// * The archive is not encrypted; it is written with owner-only permissions
+#[tauri::command]
+pub async fn export_library(
+    destination_path: String,
+    ids: Option<Vec<String>>,
+) -> Result<LibraryExportResult, String> {
+    let validated_path = validate_archive_export_path(&destination_path)
+        .map_err(|e| format!("Security validation failed: {}", e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.export_library(&validated_path, ids.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Import recordings from a library archive created by `export_library`
+///
+/// # Security
+/// * Extension, path traversal, symlink and size validation (max 4 GB)
+/// * Archive entries are checked against the manifest checksums before import
+#[tauri::command]
+pub async fn import_library(archive_path: String) -> Result<LibraryImportResult, String> {
+    let validated_path = validate_archive_path(&archive_path)
+        .map_err(|e| format!("Security validation failed: {}", e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.import_library(&validated_path)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Calculate baseline emotion from all recordings, or only those with `tag`
+#[tauri::command]
+pub async fn calculate_baseline_emotion(
+    tag: Option<String>,
+) -> Result<Option<BaselineResult>, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        match tag {
+            Some(tag) => manager.calculate_tag_baseline_emotion(&tag),
+            None => manager.calculate_baseline_emotion(),
+        }
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Emotion and tone trends: 7/30/90-day baselines, weekday and time-of-day
+/// distributions, tone averages with variance and notable recent shifts
+#[tauri::command]
+pub async fn get_emotion_trends() -> Result<EmotionTrends, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.get_emotion_trends()
     })
     .await
     .map_err(|e| format!("Task join error: {}", e))?
@@ -157,6 +587,8 @@ pub async fn calculate_baseline_emotion() -> Result<Option<BaselineResult>, Stri
 /// Get personalized feedback for a recording based on baseline comparison
 ///
 /// Returns feedback if current emotion differs significantly from baseline.
+/// The baseline covers the last 30 days, or all recordings while the last
+/// 30 days have too few samples.
 /// Feedback mode depends on sample count:
 /// - 0-4 recordings: "generic" (cold start, no feedback)
 /// - 5-9 recordings: "preliminary" (building baseline)
@@ -168,18 +600,23 @@ pub async fn get_personalized_feedback(
     tokio::task::spawn_blocking(move || {
         let manager = get_storage_manager();
 
-        // Get baseline (already implemented!)
-        let baseline = match manager.calculate_baseline_emotion()? {
-            Some(b) => b,
-            None => return Ok(None), // Cold start - no recordings with emotion
+        // Compare against the recent window; fall back to all recordings
+        // while the window is still too sparse for feedback
+        let recent = manager
+            .calculate_recent_baseline_emotion(FEEDBACK_BASELINE_DAYS)?
+            .filter(|b| b.sample_count >= 5);
+        let (baseline, baseline_window_days) = match recent {
+            Some(b) => (b, Some(FEEDBACK_BASELINE_DAYS)),
+            None => match manager.calculate_baseline_emotion()? {
+                Some(b) => (b, None),
+                None => return Ok(None), // Cold start - no recordings with emotion
+            },
         };
 
-        // Get current recording
-        let recordings = manager.list_recordings()?;
-        let current = recordings
-            .iter()
-            .find(|r| r.id == recording_id)
-            .ok_or_else(|| "Recording not found".to_string())?;
+        // Get current recording (catalog lookup, no directory scan)
+        let current = manager
+            .get_recording(&recording_id)
+            .map_err(|_| "Recording not found".to_string())?;
 
         // Get current emotion (prefer analysis_result, fall back to emotion field)
         let current_emotion = current
@@ -212,6 +649,7 @@ pub async fn get_personalized_feedback(
             baseline_emotion: baseline.emotion,
             baseline_confidence: baseline.confidence,
             baseline_sample_count: sample_count,
+            baseline_window_days,
             current_emotion: current_emotion.primary.clone(),
             current_confidence: current_emotion.confidence,
             confidence_delta,
@@ -222,3 +660,49 @@ pub async fn get_personalized_feedback(
     .await
     .map_err(|e| format!("Task join error: {}", e))?
 }
+
+/// Event emitted when recordings change outside the app (payload: `RecordingChange[]`)
+const RECORDINGS_CHANGED_EVENT: &str = "storage:recordings-changed";
+
+/// Watcher of the current storage directory
+static STORAGE_WATCHER: Mutex<Option<StorageWatcher>> = Mutex::new(None);
+
+/// Watch the configured storage directory for external changes.
+///
+/// Called on startup and after every settings change; restarts the watcher
+/// only when the storage path differs from the watched one.
+pub(crate) fn restart_storage_watcher(app: &tauri::AppHandle) {
+    let manager = get_storage_manager();
+    let storage_dir = match manager.storage_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::warn!(error = %e, "Storage: Cannot watch storage directory");
+            return;
+        }
+    };
+
+    let mut slot = STORAGE_WATCHER.lock().unwrap_or_else(|e| e.into_inner());
+    if slot.as_ref().is_some_and(|w| w.dir() == storage_dir) {
+        return;
+    }
+    // Stop the old watcher before the new one reports anything
+    *slot = None;
+
+    let app = app.clone();
+    let watched_dir = storage_dir.clone();
+    let watcher = StorageWatcher::start(storage_dir, move |base_names| {
+        match get_storage_manager().apply_external_changes(&watched_dir, &base_names) {
+            Ok(changes) if !changes.is_empty() => {
+                if let Err(e) = app.emit(RECORDINGS_CHANGED_EVENT, &changes) {
+                    tracing::warn!(error = %e, "Failed to emit recordings change event");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!(error = %e, "Storage: Failed to apply external changes"),
+        }
+    });
+    match watcher {
+        Ok(watcher) => *slot = Some(watcher),
+        Err(e) => tracing::warn!(error = %e, "Storage: Watching for external changes disabled"),
+    }
+}
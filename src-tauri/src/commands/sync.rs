@@ -0,0 +1,114 @@
+//! Sync Commands
+//!
+//! Keeps the active profile's library in sync with a WebDAV folder shared by
+//! several devices. The WebDAV password and the sync key for client-side
+//! encryption are kept in the OS keyring; the server URL and user name are
+//! part of the storage settings.
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::storage::{get_storage_manager, SyncResult, SyncStatus};
+
+/// Event emitted while a sync runs (payload: [`SyncProgress`])
+const SYNC_PROGRESS_EVENT: &str = "storage:sync-progress";
+
+/// Event emitted when a sync finished (payload: [`SyncResult`])
+const SYNC_COMPLETED_EVENT: &str = "storage:sync-completed";
+
+/// Event emitted when a sync failed (payload: [`SyncFailure`])
+const SYNC_FAILED_EVENT: &str = "storage:sync-failed";
+
+/// Progress of a running sync (recordings)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// A failed sync; the next sync resumes where it stopped
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncFailure {
+    pub error: String,
+}
+
+/// Sync the library with the configured WebDAV server.
+///
+/// Recordings changed on both devices are kept twice: the local version
+/// under its ID, the remote one as a copy (listed in `conflicts`).
+/// Emits `storage:sync-progress`, then `storage:sync-completed` or
+/// `storage:sync-failed`.
+#[tauri::command]
+pub async fn sync_library(app: tauri::AppHandle) -> Result<SyncResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        let result = manager.sync_library(|processed, total| {
+            if let Err(e) = app.emit(SYNC_PROGRESS_EVENT, SyncProgress { processed, total }) {
+                tracing::warn!(error = %e, "Failed to emit sync progress");
+            }
+        });
+        match &result {
+            Ok(synced) => {
+                if let Err(e) = app.emit(SYNC_COMPLETED_EVENT, synced) {
+                    tracing::warn!(error = %e, "Failed to emit sync completion");
+                }
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Storage: Sync failed");
+                let payload = SyncFailure { error: e.clone() };
+                if let Err(e) = app.emit(SYNC_FAILED_EVENT, payload) {
+                    tracing::warn!(error = %e, "Failed to emit sync failure");
+                }
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Sync configuration and state of the active library
+#[tauri::command]
+pub async fn get_sync_status() -> Result<SyncStatus, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.sync_status()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Store the WebDAV password in the keyring (empty removes it)
+#[tauri::command]
+pub async fn set_sync_password(password: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.set_sync_password(&password)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Sync key for client-side encryption (Base64), to enter on the other devices
+#[tauri::command]
+pub async fn export_sync_key() -> Result<String, String> {
+    tokio::task::spawn_blocking(|| {
+        let manager = get_storage_manager();
+        manager.export_sync_key()
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Use the sync key exported on another device
+#[tauri::command]
+pub async fn import_sync_key(key: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.import_sync_key(&key)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
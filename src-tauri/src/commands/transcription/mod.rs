@@ -10,11 +10,13 @@
 mod mlx_whisper;
 mod whisper_cpp;
 
+use crate::storage::{get_storage_manager, NewTranscriptionRevision, TranscriptionRevision};
 use crate::text::filter_transcription_output;
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
 
 use super::mlx_llm::MlxPaths;
-use super::utils::{apply_vad_filter, decode_audio_base64, validate_audio};
+use super::utils::{apply_vad_filter, decode_audio_base64, encode_audio_base64, validate_audio};
 
 // Re-export types from sub-modules
 pub use mlx_whisper::{MlxModelInfo, MlxWhisperStatus};
@@ -53,19 +55,114 @@ pub(crate) const VALID_LANGUAGES: &[&str] = &[
     "de", "en", "fr", "es", "it", "nl", "pt", "ja", "zh", "ko", "auto",
 ];
 
+/// Transcription result plus the backend that actually produced it
+struct TranscriptionRun {
+    result: TranscriptionResult,
+    /// Text before the filler-word filter
+    raw_text: String,
+    provider: String,
+    model: String,
+}
+
 /// Transcribe audio using whisper.cpp or mlx-whisper
 ///
 /// This is the main dispatcher that routes to the appropriate backend
 /// based on the provider parameter. Falls back to whisper.cpp on MLX errors.
+/// Without `model` / `language`, the whisper defaults from the settings are used.
 #[tauri::command]
 pub async fn transcribe_audio(
     audio_data: String, // Base64 encoded audio
-    model: String,
-    language: String,
+    model: Option<String>,
+    language: Option<String>,
     provider: Option<String>,    // "whisper-cpp" or "mlx-whisper"
     mlx_paths: Option<MlxPaths>, // Optional path configuration for MLX-Whisper
     app_handle: tauri::AppHandle,
 ) -> Result<TranscriptionResult, String> {
+    run_transcription(
+        audio_data,
+        model,
+        language,
+        provider,
+        mlx_paths.as_ref(),
+        &app_handle,
+    )
+    .await
+    .map(|run| run.result)
+}
+
+/// Re-transcribe a stored recording
+///
+/// The result is kept as a new transcription revision of the recording and
+/// becomes its active transcription; earlier revisions stay available.
+/// Without `model` / `language`, the whisper defaults from the settings are used.
+#[tauri::command]
+pub async fn retranscribe_recording(
+    id: String,
+    model: Option<String>,
+    language: Option<String>,
+    provider: Option<String>,
+    mlx_paths: Option<MlxPaths>,
+    app_handle: tauri::AppHandle,
+) -> Result<TranscriptionRevision, String> {
+    let audio_id = id.clone();
+    let audio_bytes = tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.get_recording_audio(&audio_id)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    let started = Instant::now();
+    let run = run_transcription(
+        encode_audio_base64(&audio_bytes),
+        model,
+        language,
+        provider,
+        mlx_paths.as_ref(),
+        &app_handle,
+    )
+    .await?;
+
+    if run.raw_text.trim().is_empty() {
+        return Err("No speech detected in recording".to_string());
+    }
+
+    let revision = NewTranscriptionRevision {
+        provider: run.provider,
+        model: run.model,
+        language: run.result.language,
+        filtered_text: filter_transcription_output(&run.raw_text),
+        text: run.raw_text,
+        processing_time_ms: started.elapsed().as_millis() as u64,
+    };
+    tokio::task::spawn_blocking(move || {
+        let manager = get_storage_manager();
+        manager.add_transcription_revision(&id, revision)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Model and language requested by the frontend, else the whisper defaults
+/// from the settings (empty values count as not requested)
+fn resolve_model_and_language(model: Option<String>, language: Option<String>) -> (String, String) {
+    let defaults = crate::settings::get_settings_store().get().whisper;
+    (
+        model.filter(|m| !m.is_empty()).unwrap_or(defaults.model),
+        language.filter(|l| !l.is_empty()).unwrap_or(defaults.language),
+    )
+}
+
+async fn run_transcription(
+    audio_data: String,
+    model: Option<String>,
+    language: Option<String>,
+    provider: Option<String>,
+    mlx_paths: Option<&MlxPaths>,
+    app_handle: &tauri::AppHandle,
+) -> Result<TranscriptionRun, String> {
+    let (model, language) = resolve_model_and_language(model, language);
+
     // Check provider
     let provider = provider.unwrap_or_else(|| "whisper-cpp".to_string());
 
@@ -78,11 +175,18 @@ pub async fn transcribe_audio(
             audio_data.clone(),
             model.clone(),
             language.clone(),
-            mlx_paths.as_ref(),
+            mlx_paths,
         )
         .await
         {
-            Ok(result) => return Ok(result),
+            Ok(result) => {
+                return Ok(TranscriptionRun {
+                    raw_text: result.text.clone(),
+                    result,
+                    provider,
+                    model,
+                })
+            }
             Err(e) => {
                 tracing::warn!(error = %e, "MLX-Whisper failed, falling back to whisper.cpp");
                 // Use "german-turbo" model for whisper.cpp fallback (same as default)
@@ -100,27 +204,37 @@ pub async fn transcribe_audio(
     // Validate audio has sufficient content (not silence)
     if !validate_audio(&audio_bytes) {
         tracing::debug!("Audio validation failed - returning empty result");
-        return Ok(TranscriptionResult {
-            text: String::new(),
-            segments: vec![],
-            language,
-            speech_duration_sec: 0.0,
-            total_duration_sec: 0.0,
+        return Ok(TranscriptionRun {
+            result: TranscriptionResult {
+                text: String::new(),
+                segments: vec![],
+                language,
+                speech_duration_sec: 0.0,
+                total_duration_sec: 0.0,
+            },
+            raw_text: String::new(),
+            provider: "whisper-cpp".to_string(),
+            model: whisper_model,
         });
     }
 
     // Apply VAD filtering to remove non-speech audio
     let (audio_bytes, speech_duration, total_duration) =
-        match apply_vad_filter(&app_handle, &audio_bytes) {
+        match apply_vad_filter(app_handle, &audio_bytes) {
             Ok(filtered) => {
                 if filtered.bytes.is_empty() {
                     tracing::debug!("VAD filtered all audio (no speech detected)");
-                    return Ok(TranscriptionResult {
-                        text: String::new(),
-                        segments: vec![],
-                        language,
-                        speech_duration_sec: filtered.speech_duration_sec,
-                        total_duration_sec: filtered.total_duration_sec,
+                    return Ok(TranscriptionRun {
+                        result: TranscriptionResult {
+                            text: String::new(),
+                            segments: vec![],
+                            language,
+                            speech_duration_sec: filtered.speech_duration_sec,
+                            total_duration_sec: filtered.total_duration_sec,
+                        },
+                        raw_text: String::new(),
+                        provider: "whisper-cpp".to_string(),
+                        model: whisper_model,
                     });
                 }
                 (
@@ -144,7 +258,7 @@ pub async fn transcribe_audio(
         &language,
         speech_duration,
         total_duration,
-        &app_handle,
+        app_handle,
     )
     .await?;
 
@@ -158,12 +272,17 @@ pub async fn transcribe_audio(
         );
     }
 
-    Ok(TranscriptionResult {
-        text: filtered_text,
-        segments: vec![], // whisper.cpp txt output doesn't include segments
-        language,
-        speech_duration_sec: speech_duration,
-        total_duration_sec: total_duration,
+    Ok(TranscriptionRun {
+        result: TranscriptionResult {
+            text: filtered_text,
+            segments: vec![], // whisper.cpp txt output doesn't include segments
+            language,
+            speech_duration_sec: speech_duration,
+            total_duration_sec: total_duration,
+        },
+        raw_text: text,
+        provider: "whisper-cpp".to_string(),
+        model: whisper_model,
     })
 }
 
@@ -15,6 +15,7 @@ use std::os::windows::process::CommandExt;
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 use crate::commands::utils::{find_whisper_paths, get_target_triple, parse_whisper_stdout};
+use crate::settings::MAX_WHISPER_THREADS;
 
 /// Platform-aware setup script hint for error messages
 fn setup_hint() -> &'static str {
@@ -25,14 +26,20 @@ fn setup_hint() -> &'static str {
     }
 }
 
-const MAX_WHISPER_THREADS: usize = 8;
 const DEFAULT_WHISPER_THREADS: usize = 4;
 
-/// Detect available CPU threads for whisper.cpp, capped for optimal performance
+/// Thread count for whisper.cpp: the configured value, otherwise the available
+/// CPU threads, capped for optimal performance
 fn thread_count() -> String {
-    let threads = std::thread::available_parallelism()
-        .map(|n| n.get())
-        .unwrap_or(DEFAULT_WHISPER_THREADS)
+    let threads = crate::settings::get_settings_store()
+        .get()
+        .whisper
+        .threads
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(DEFAULT_WHISPER_THREADS)
+        })
         .clamp(1, MAX_WHISPER_THREADS);
     threads.to_string()
 }
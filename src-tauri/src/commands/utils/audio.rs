@@ -83,8 +83,15 @@ fn get_vad_pipeline(
             let model_path = find_vad_model_path(app_handle)?;
             tracing::info!(path = ?model_path, "Initializing VAD pipeline");
 
-            let pipeline = VadPipeline::new(model_path.to_str().ok_or("Invalid model path")?)
-                .map_err(|e| format!("Failed to initialize VAD: {}", e))?;
+            let vad_settings = crate::settings::get_settings_store().get().vad;
+            let pipeline = VadPipeline::with_params(
+                model_path.to_str().ok_or("Invalid model path")?,
+                vad_settings.threshold,
+                vad_settings.prefill_frames,
+                vad_settings.hangover_frames,
+                vad_settings.onset_frames,
+            )
+            .map_err(|e| format!("Failed to initialize VAD: {}", e))?;
 
             *guard = Some(pipeline);
         }
@@ -93,6 +100,16 @@ fn get_vad_pipeline(
     Ok(mutex)
 }
 
+/// Drop the cached VAD pipeline so the next use picks up changed VAD settings
+pub fn reset_vad_pipeline() {
+    if let Some(mutex) = VAD_PIPELINE.get() {
+        match mutex.lock() {
+            Ok(mut guard) => *guard = None,
+            Err(e) => tracing::warn!(error = %e, "Failed to lock VAD pipeline for reset"),
+        }
+    }
+}
+
 /// Apply VAD filtering to audio bytes, returning filtered audio with timing
 pub fn apply_vad_filter(
     app_handle: &tauri::AppHandle,
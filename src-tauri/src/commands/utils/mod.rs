@@ -13,7 +13,7 @@ pub(crate) mod path;
 
 // Re-export public items used by consumer modules
 // FilteredAudio, VAD_PIPELINE, find_vad_model_path are internal-only
-pub use audio::{apply_vad_filter, validate_audio};
+pub use audio::{apply_vad_filter, reset_vad_pipeline, validate_audio};
 pub use encoding::{decode_audio_base64, encode_audio_base64};
 pub use parsing::parse_whisper_stdout;
 pub use path::{
@@ -7,6 +7,7 @@ pub mod commands;  // Public for integration tests
 mod emotion;
 mod native_audio;
 pub mod security;  // Public for security audit tests
+mod settings;
 pub mod storage;   // Public for security audit tests
 mod text;
 mod types;
@@ -91,6 +92,25 @@ pub fn run() {
 
     app_builder
         .setup(|app| {
+            // Load persisted backend settings before any component reads them
+            let settings_store = settings::get_settings_store();
+            tracing::info!(path = %settings_store.path().display(), "Settings file");
+
+            // Pick up recordings added, edited or removed outside the app
+            commands::restart_storage_watcher(app.handle());
+
+            // Back up the library on the configured schedule
+            commands::start_backup_scheduler(app.handle());
+
+            // Recordings a crash left in the spool are offered for recovery by the UI
+            match storage::get_storage_manager().list_interrupted_recordings() {
+                Ok(sessions) if !sessions.is_empty() => {
+                    tracing::info!(count = sessions.len(), "Interrupted recordings found");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Failed to check for interrupted recordings"),
+            }
+
             // Initialize audio state (Web Audio API based - legacy)
             let audio_state = audio::AudioState::new();
             app.manage(audio_state);
@@ -135,8 +155,21 @@ pub fn run() {
                 }
             }
 
+            // Report a microphone lost while open (with failover, if any)
+            let app_handle = app.handle().clone();
+            native_audio_state.set_device_lost_callback(move |event| {
+                commands::emit_device_lost(&app_handle, event)
+            });
+            let app_handle = app.handle().clone();
+            native_audio_state.set_speech_segment_callback(move |segment| {
+                commands::emit_speech_segment(&app_handle, segment)
+            });
+
             app.manage(native_audio_state);
 
+            // Report plugged and unplugged microphones
+            commands::start_device_monitor(app.handle());
+
             // Build custom application menu
             // Settings menu item (emits event to frontend)
             let settings_item = MenuItemBuilder::new("Einstellungen...")
@@ -269,6 +302,7 @@ pub fn run() {
             commands::start_recording,
             commands::stop_recording,
             commands::transcribe_audio,
+            commands::retranscribe_recording,
             commands::get_audio_level,
             commands::add_audio_samples,
             commands::analyze_audio_emotion,
@@ -279,23 +313,71 @@ pub fn run() {
             commands::list_mlx_whisper_models,
             // Native audio commands (cpal-based)
             commands::list_audio_devices,
+            commands::set_audio_input_device,
             commands::native_open_audio,
             commands::native_start_recording,
+            commands::native_pause_recording,
+            commands::native_resume_recording,
             commands::native_stop_recording,
             commands::native_get_audio_level,
             commands::native_close_audio,
             commands::native_is_recording,
+            commands::native_is_paused,
             // Storage commands
             commands::save_recording,
             commands::list_recordings,
+            commands::query_recordings,
+            commands::query_recording_summaries,
             commands::get_recording_audio,
+            commands::update_recording_metadata,
+            commands::get_transcription_history,
+            commands::set_active_transcription_revision,
+            commands::diff_transcription_revisions,
+            commands::search_recordings,
             commands::delete_recording,
             commands::clear_all_recordings,
+            commands::list_trash,
+            commands::restore_recording,
+            commands::empty_trash,
             commands::get_storage_config,
             commands::update_storage_config,
             commands::get_storage_stats,
+            commands::preview_storage_cleanup,
+            commands::find_duplicates,
+            commands::rebuild_recording_catalog,
+            commands::convert_library_encryption,
+            commands::recompress_library,
+            commands::list_quarantined_recordings,
+            commands::restore_quarantined_recording,
+            commands::list_interrupted_recordings,
+            commands::recover_interrupted_recording,
+            commands::discard_interrupted_recording,
+            commands::upgrade_recording_schemas,
+            commands::verify_storage,
+            commands::repair_storage,
+            commands::export_library,
+            commands::import_library,
+            commands::create_backup,
+            commands::list_backups,
+            commands::restore_backup,
+            commands::sync_library,
+            commands::get_sync_status,
+            commands::set_sync_password,
+            commands::export_sync_key,
+            commands::import_sync_key,
             commands::calculate_baseline_emotion,
+            commands::get_emotion_trends,
             commands::get_personalized_feedback,
+            // Settings commands (persistent backend settings)
+            commands::get_settings,
+            commands::update_settings,
+            commands::reset_settings,
+            // Profile commands (independent recording libraries)
+            commands::list_profiles,
+            commands::create_profile,
+            commands::switch_profile,
+            commands::rename_profile,
+            commands::delete_profile,
             // MLX-LLM commands (Emotion + Fallacy Analysis)
             commands::check_mlx_available,
             commands::analyze_emotion_mlx_cmd,
@@ -5,13 +5,16 @@
 //! Based on [cjpais/handy](https://github.com/cjpais/handy) (MIT License).
 
 use cpal::traits::{DeviceTrait, HostTrait};
+use cpal::Device;
 use serde::{Deserialize, Serialize};
 
 /// Information about an audio device (serializable for frontend)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CpalDeviceInfo {
-    /// Device index (for selection)
+    /// Stable device id (see [`device_id`]), used to select and remember the device
+    pub id: String,
+    /// Position in the host's device list (changes when devices are plugged in)
     pub index: String,
     /// Human-readable device name
     pub name: String,
@@ -34,6 +37,65 @@ pub fn get_cpal_host() -> cpal::Host {
     }
 }
 
+/// Stable identifier of an input device: its name plus its host index, the
+/// position among the host's devices with the same name (`"USB Headset#0"`).
+/// Unlike the position in the device list, it does not change when other
+/// devices are plugged in or removed.
+pub fn device_id(name: &str, host_index: usize) -> String {
+    format!("{}#{}", name, host_index)
+}
+
+/// Split a device id into name and host index
+pub fn parse_device_id(id: &str) -> Option<(&str, usize)> {
+    let (name, host_index) = id.rsplit_once('#')?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, host_index.parse().ok()?))
+}
+
+/// Device ids for device names in host order
+pub(super) fn assign_device_ids(names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| device_id(name, names[..i].iter().filter(|n| *n == name).count()))
+        .collect()
+}
+
+/// Input devices of the host with their names
+pub(super) fn input_devices(host: &cpal::Host) -> Result<Vec<(Device, String)>, String> {
+    Ok(host
+        .input_devices()
+        .map_err(|e| format!("Failed to enumerate input devices: {}", e))?
+        .map(|device| {
+            let name = device.name().unwrap_or_else(|_| "Unknown".into());
+            (device, name)
+        })
+        .collect())
+}
+
+/// Find a connected input device by its id
+pub fn find_input_device(id: &str) -> Result<Device, String> {
+    let (name, _) =
+        parse_device_id(id).ok_or_else(|| format!("Invalid input device id: {}", id))?;
+    let devices = input_devices(&get_cpal_host())?;
+    let names: Vec<String> = devices.iter().map(|(_, name)| name.clone()).collect();
+    let device = devices
+        .into_iter()
+        .zip(assign_device_ids(&names))
+        .find(|(_, candidate)| candidate == id)
+        .map(|((device, _), _)| device);
+
+    match device {
+        Some(device) => Ok(device),
+        None => Err(format!(
+            "Input device \"{}\" is not connected. Connect it or choose another microphone.",
+            name
+        )),
+    }
+}
+
 /// List available input (microphone) devices
 pub fn list_input_devices() -> Result<Vec<CpalDeviceInfo>, String> {
     let host = get_cpal_host();
@@ -41,12 +103,11 @@ pub fn list_input_devices() -> Result<Vec<CpalDeviceInfo>, String> {
 
     let mut out = Vec::new();
 
-    let devices = host
-        .input_devices()
-        .map_err(|e| format!("Failed to enumerate input devices: {}", e))?;
+    let devices = input_devices(&host)?;
+    let names: Vec<String> = devices.iter().map(|(_, name)| name.clone()).collect();
+    let ids = assign_device_ids(&names);
 
-    for (index, device) in devices.enumerate() {
-        let name = device.name().unwrap_or_else(|_| "Unknown".into());
+    for (index, ((device, name), id)) in devices.into_iter().zip(ids).enumerate() {
         let is_default = Some(name.clone()) == default_name;
 
         // Try to get the sample rate
@@ -56,6 +117,7 @@ pub fn list_input_devices() -> Result<Vec<CpalDeviceInfo>, String> {
             .unwrap_or(0);
 
         out.push(CpalDeviceInfo {
+            id,
             index: index.to_string(),
             name,
             is_default,
@@ -65,3 +127,20 @@ pub fn list_input_devices() -> Result<Vec<CpalDeviceInfo>, String> {
 
     Ok(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_ids_distinguish_same_names() {
+        let names = ["USB Headset", "Webcam", "USB Headset"].map(String::from);
+        assert_eq!(
+            assign_device_ids(&names),
+            ["USB Headset#0", "Webcam#0", "USB Headset#1"]
+        );
+        assert_eq!(parse_device_id("Mic #2 (USB)#1"), Some(("Mic #2 (USB)", 1)));
+        assert_eq!(parse_device_id("Webcam"), None);
+        assert_eq!(parse_device_id("#0"), None);
+    }
+}
@@ -6,14 +6,20 @@
 //! Key components:
 //! - `NativeAudioRecorder`: Main audio capture interface
 //! - `FrameResampler`: Resamples audio to 16kHz in 30ms frames
-//! - `CpalDeviceInfo`: Device enumeration
+//! - `CpalDeviceInfo`: Device enumeration and selection by stable id
+//! - `DeviceMonitor`: Hot-plug detection by polling the input devices
+//! - `SegmentTracker`: Live speech segments of a running recording
 //!
 //! Based on [cjpais/handy](https://github.com/cjpais/handy) (MIT License).
 
 pub mod constants;
 pub mod device;
+pub mod monitor;
 pub mod recorder;
 pub mod resampler;
+pub mod segments;
 
-pub use device::{list_input_devices, CpalDeviceInfo};
-pub use recorder::NativeAudioRecorder;
+pub use device::{find_input_device, list_input_devices, CpalDeviceInfo};
+pub use monitor::{DeviceChange, DeviceMonitor, InputDeviceRef};
+pub use recorder::{DeviceLostEvent, NativeAudioRecorder};
+pub use segments::SpeechSegment;
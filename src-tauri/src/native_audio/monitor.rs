@@ -0,0 +1,134 @@
+//! Input Device Monitor
+//!
+//! cpal has no hot-plug notifications, so the input devices are polled and
+//! each snapshot is compared with the previous one: devices added or removed
+//! and a changed system default.
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+
+use super::device::{assign_device_ids, get_cpal_host, input_devices};
+
+/// An input device by stable id (see [`super::device::device_id`]) and name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDeviceRef {
+    pub id: String,
+    pub name: String,
+}
+
+/// A change of the connected input devices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceChange {
+    Added(InputDeviceRef),
+    Removed(InputDeviceRef),
+    /// New system default (`None`: no input device left)
+    DefaultChanged(Option<InputDeviceRef>),
+}
+
+/// Connected input devices at one point in time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DeviceSnapshot {
+    devices: Vec<InputDeviceRef>,
+    default: Option<InputDeviceRef>,
+}
+
+impl DeviceSnapshot {
+    fn capture() -> Result<Self, String> {
+        let host = get_cpal_host();
+        let default_name = host.default_input_device().and_then(|d| d.name().ok());
+        let names: Vec<String> = input_devices(&host)?
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        let devices: Vec<InputDeviceRef> = assign_device_ids(&names)
+            .into_iter()
+            .zip(names)
+            .map(|(id, name)| InputDeviceRef { id, name })
+            .collect();
+        // cpal names the default device only; take the first one of that name
+        let default =
+            default_name.and_then(|name| devices.iter().find(|d| d.name == name).cloned());
+        Ok(Self { devices, default })
+    }
+
+    /// Changes from `self` to `next`
+    fn diff(&self, next: &DeviceSnapshot) -> Vec<DeviceChange> {
+        let mut changes: Vec<DeviceChange> = self
+            .devices
+            .iter()
+            .filter(|d| !next.devices.contains(d))
+            .cloned()
+            .map(DeviceChange::Removed)
+            .collect();
+        changes.extend(
+            next.devices
+                .iter()
+                .filter(|d| !self.devices.contains(d))
+                .cloned()
+                .map(DeviceChange::Added),
+        );
+        if next.default != self.default {
+            changes.push(DeviceChange::DefaultChanged(next.default.clone()));
+        }
+        changes
+    }
+}
+
+/// Polls the input devices and reports changes since the last poll
+#[derive(Debug, Default)]
+pub struct DeviceMonitor {
+    previous: Option<DeviceSnapshot>,
+}
+
+impl DeviceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Changes since the last poll (none on the first poll)
+    pub fn poll(&mut self) -> Result<Vec<DeviceChange>, String> {
+        let next = DeviceSnapshot::capture()?;
+        let changes = self
+            .previous
+            .as_ref()
+            .map(|previous| previous.diff(&next))
+            .unwrap_or_default();
+        self.previous = Some(next);
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(id: &str) -> InputDeviceRef {
+        InputDeviceRef {
+            id: id.to_string(),
+            name: id.split('#').next().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_added_removed_and_default() {
+        let before = DeviceSnapshot {
+            devices: vec![device("Built-in#0"), device("BT Headset#0")],
+            default: Some(device("BT Headset#0")),
+        };
+        let after = DeviceSnapshot {
+            devices: vec![device("Built-in#0"), device("USB Mic#0")],
+            default: Some(device("Built-in#0")),
+        };
+
+        assert_eq!(
+            before.diff(&after),
+            [
+                DeviceChange::Removed(device("BT Headset#0")),
+                DeviceChange::Added(device("USB Mic#0")),
+                DeviceChange::DefaultChanged(Some(device("Built-in#0"))),
+            ]
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+}
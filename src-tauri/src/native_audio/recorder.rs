@@ -1,24 +1,44 @@
 //! Native Audio Recorder
 //!
-//! Captures audio from the default microphone using cpal,
+//! Captures audio from the selected (or default) microphone using cpal,
 //! resamples to 16kHz, and applies VAD filtering.
 //!
+//! The worker thread watches the stream: a device that reports itself gone
+//! or stops delivering audio counts as lost. Samples captured so far are kept,
+//! and the recording can continue on the system default device (failover).
+//!
+//! Recorded samples are written to the storage spool as they are captured,
+//! so a crash does not lose the session. Without a spool they are kept in
+//! memory.
+//!
+//! A recording can be paused and resumed. Audio during the break is dropped,
+//! VAD starts fresh afterwards and the break is reported with the samples.
+//!
+//! Completed speech segments are passed to a callback while recording (see
+//! [`super::segments`]).
+//!
 //! Based on [cjpais/handy](https://github.com/cjpais/handy) (MIT License).
 
 use std::{
     io::Error,
-    sync::{mpsc, Arc, Mutex},
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
     Device, Sample, SizedSample,
 };
+use serde::Serialize;
 
 use super::constants::WHISPER_SAMPLE_RATE;
-use super::device::get_cpal_host;
+use super::device::{find_input_device, get_cpal_host};
 use super::resampler::FrameResampler;
+use super::segments::{SegmentTracker, SpeechSegment};
+use crate::storage::{InputDeviceMeta, RecordingPause, SpoolWriter};
 use crate::vad::{VadFrame, VadPipeline, VoiceActivityDetector};
 
 /// Maximum recording duration to prevent OOM (30 minutes = 1800 seconds)
@@ -29,20 +49,63 @@ const MAX_RECORDING_DURATION_SECS: u64 = 1800;
 const MAX_RECORDING_SAMPLES: usize =
     (MAX_RECORDING_DURATION_SECS as usize) * (WHISPER_SAMPLE_RATE as usize);
 
+/// How long the consumer waits for samples before checking commands
+const SAMPLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A stream without audio for this long counts as a lost device
+/// (not every backend reports a disconnected device as a stream error)
+const STREAM_STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+type LevelCallback = Arc<dyn Fn(f32) + Send + Sync + 'static>;
+type DeviceLostCallback = Arc<dyn Fn(DeviceLostEvent) + Send + Sync + 'static>;
+type SegmentCallback = Arc<dyn Fn(SpeechSegment) + Send + Sync + 'static>;
+
+/// The open input device was lost
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLostEvent {
+    pub device: InputDeviceMeta,
+    pub error: String,
+    /// A recording was running; its samples so far are kept
+    pub recording: bool,
+    /// Device capture continues on (`None`: stopped until reopened)
+    pub failover: Option<InputDeviceMeta>,
+}
+
+/// A finished recording
+#[derive(Debug, Default)]
+pub struct RecordedAudio {
+    /// 16kHz mono, VAD filtered
+    pub samples: Vec<f32>,
+    /// Breaks between pause and resume
+    pub pauses: Vec<RecordingPause>,
+}
+
 /// Commands sent to the worker thread
 enum Cmd {
-    Start,
-    Stop(mpsc::Sender<Vec<f32>>),
+    Start(Option<SpoolWriter>),
+    Pause,
+    Resume,
+    Stop(mpsc::Sender<RecordedAudio>),
     Shutdown,
 }
 
 /// Native audio recorder using cpal
 pub struct NativeAudioRecorder {
     device: Option<Device>,
+    /// Id the device was selected with (`None`: system default)
+    device_id: Option<String>,
+    device_meta: Option<InputDeviceMeta>,
     cmd_tx: Option<mpsc::Sender<Cmd>>,
     worker_handle: Option<std::thread::JoinHandle<()>>,
     vad: Option<Arc<Mutex<VadPipeline>>>,
-    level_cb: Option<Arc<dyn Fn(f32) + Send + Sync + 'static>>,
+    level_cb: Option<LevelCallback>,
+    lost_cb: Option<DeviceLostCallback>,
+    segment_cb: Option<SegmentCallback>,
+    /// Switch to the default device when the open one is lost
+    failover: bool,
+    /// Set by the worker thread when the open device was lost
+    device_lost: Arc<AtomicBool>,
 }
 
 impl NativeAudioRecorder {
@@ -50,10 +113,16 @@ impl NativeAudioRecorder {
     pub fn new() -> Self {
         NativeAudioRecorder {
             device: None,
+            device_id: None,
+            device_meta: None,
             cmd_tx: None,
             worker_handle: None,
             vad: None,
             level_cb: None,
+            lost_cb: None,
+            segment_cb: None,
+            failover: false,
+            device_lost: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -72,8 +141,50 @@ impl NativeAudioRecorder {
         self
     }
 
+    /// Configure device loss callback (called from the worker thread)
+    pub fn with_device_lost_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(DeviceLostEvent) + Send + Sync + 'static,
+    {
+        self.lost_cb = Some(Arc::new(cb));
+        self
+    }
+
+    /// Configure speech segment callback (called from the worker thread
+    /// for each completed segment of a recording)
+    pub fn with_segment_callback<F>(mut self, cb: F) -> Self
+    where
+        F: Fn(SpeechSegment) + Send + Sync + 'static,
+    {
+        self.segment_cb = Some(Arc::new(cb));
+        self
+    }
+
+    /// Continue on the system default device when the open one is lost
+    pub fn with_failover(mut self, failover: bool) -> Self {
+        self.failover = failover;
+        self
+    }
+
+    /// Open the input device with the given id (see [`super::device::device_id`])
+    pub fn open_device(&mut self, device_id: &str) -> Result<(), String> {
+        if self.worker_handle.is_some() {
+            return Ok(()); // already open
+        }
+        let device = find_input_device(device_id)?;
+        self.open_with_id(Some(device), Some(device_id.to_string()))
+    }
+
     /// Open the audio device and start the worker thread
     pub fn open(&mut self, device: Option<Device>) -> Result<(), String> {
+        self.open_with_id(device, None)
+    }
+
+    fn open_with_id(
+        &mut self,
+        device: Option<Device>,
+        device_id: Option<String>,
+    ) -> Result<(), String> {
         if self.worker_handle.is_some() {
             return Ok(()); // already open
         }
@@ -90,88 +201,140 @@ impl NativeAudioRecorder {
                 .map_err(|e| e.to_string())?,
         };
 
+        let config = get_preferred_config(&device).map_err(|e| {
+            let name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+            format!("Input device \"{}\" cannot be used: {}", name, e)
+        })?;
+        let device_meta = InputDeviceMeta {
+            device_id: device_id.clone(),
+            ..input_device_meta(&device, &config)
+        };
+
+        // The worker reports whether the first stream started, so failures reach the caller
         let thread_device = device.clone();
+        let (tx, rx) = mpsc::channel();
         let vad = self.vad.clone();
         let level_cb = self.level_cb.clone();
+        let lost_cb = self.lost_cb.clone();
+        let segment_cb = self.segment_cb.clone();
+        let failover = self.failover;
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let thread_device_lost = Arc::clone(&device_lost);
+        let mut meta = device_meta.clone();
 
         let worker = std::thread::spawn(move || {
-            let config = match get_preferred_config(&thread_device) {
-                Ok(c) => c,
+            let mut stream = match open_stream(&thread_device, &config, &sample_tx) {
+                Ok(stream) => {
+                    let _ = tx.send(Ok(()));
+                    stream
+                }
                 Err(e) => {
-                    tracing::error!(error = %e, "Native audio: Failed to get config");
+                    let _ = tx.send(Err(e));
                     return;
                 }
             };
+            let mut sample_rate = config.sample_rate().0;
+            let mut consumer = Consumer::new(vad, level_cb.clone());
+            consumer.segment_cb = segment_cb;
 
-            let sample_rate = config.sample_rate().0;
-            let channels = config.channels() as usize;
-
-            tracing::info!(
-                device = %thread_device.name().unwrap_or_else(|_| "Unknown".to_string()),
-                sample_rate,
-                channels,
-                format = ?config.sample_format(),
-                "Native audio: Device configured"
-            );
-
-            let stream = match config.sample_format() {
-                cpal::SampleFormat::U8 => {
-                    build_stream::<u8>(&thread_device, &config, sample_tx.clone(), channels)
-                }
-                cpal::SampleFormat::I8 => {
-                    build_stream::<i8>(&thread_device, &config, sample_tx.clone(), channels)
-                }
-                cpal::SampleFormat::I16 => {
-                    build_stream::<i16>(&thread_device, &config, sample_tx.clone(), channels)
-                }
-                cpal::SampleFormat::I32 => {
-                    build_stream::<i32>(&thread_device, &config, sample_tx.clone(), channels)
-                }
-                cpal::SampleFormat::F32 => {
-                    build_stream::<f32>(&thread_device, &config, sample_tx.clone(), channels)
-                }
-                _ => {
-                    tracing::error!("Native audio: Unsupported sample format");
-                    return;
+            loop {
+                let (active_stream, stream_errors) = stream;
+                let error =
+                    match consumer.run(sample_rate, &sample_rx, &cmd_rx, Some(&stream_errors)) {
+                        ConsumerExit::Shutdown => return,
+                        ConsumerExit::DeviceLost(error) => error,
+                    };
+                drop(active_stream);
+                thread_device_lost.store(true, Ordering::SeqCst);
+                if let Some(cb) = &level_cb {
+                    cb(0.0);
                 }
-            };
+                tracing::warn!(device = %meta.name, error = %error, "Native audio: Input device lost");
 
-            let stream = match stream {
-                Ok(s) => s,
-                Err(e) => {
-                    tracing::error!(error = %e, "Native audio: Failed to build stream");
-                    return;
+                let fallback = if failover {
+                    open_default_fallback(&meta.name, &sample_tx)
+                } else {
+                    None
+                };
+                let event = DeviceLostEvent {
+                    device: meta.clone(),
+                    error,
+                    recording: consumer.recording,
+                    failover: fallback.as_ref().map(|(_, fallback_meta)| InputDeviceMeta {
+                        failover_from: Some(meta.name.clone()),
+                        ..fallback_meta.clone()
+                    }),
+                };
+                if let Some(cb) = &lost_cb {
+                    cb(event);
                 }
-            };
 
-            if let Err(e) = stream.play() {
-                tracing::error!(error = %e, "Native audio: Failed to start stream");
-                return;
+                match fallback {
+                    Some((fallback_stream, fallback_meta)) => {
+                        tracing::info!(device = %fallback_meta.name, "Native audio: Failed over to default device");
+                        sample_rate = fallback_meta.sample_rate;
+                        meta = fallback_meta;
+                        stream = fallback_stream;
+                    }
+                    None => {
+                        // Keep serving commands so Stop returns the samples captured so far
+                        consumer.run(sample_rate, &sample_rx, &cmd_rx, None);
+                        return;
+                    }
+                }
             }
-
-            // Run the consumer loop (keeps stream alive)
-            run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb);
-            // Stream is dropped here
         });
 
+        match rx.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                let _ = worker.join();
+                return Err(e);
+            }
+            Err(_) => return Err("Audio worker thread ended unexpectedly".to_string()),
+        }
+
         self.device = Some(device);
+        self.device_id = device_id;
+        self.device_meta = Some(device_meta);
+        self.device_lost = device_lost;
         self.cmd_tx = Some(cmd_tx);
         self.worker_handle = Some(worker);
 
         Ok(())
     }
 
-    /// Start recording (clears buffer, begins collecting samples)
-    pub fn start(&self) -> Result<(), String> {
+    /// Start recording (clears buffer, begins collecting samples).
+    /// With a spool, samples are written to disk while recording.
+    pub fn start(&self, spool: Option<SpoolWriter>) -> Result<(), String> {
         if let Some(tx) = &self.cmd_tx {
-            tx.send(Cmd::Start)
+            tx.send(Cmd::Start(spool))
                 .map_err(|e| format!("Failed to send start command: {}", e))?;
         }
         Ok(())
     }
 
+    /// Pause recording (audio is dropped until resumed)
+    pub fn pause(&self) -> Result<(), String> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Pause)
+                .map_err(|e| format!("Failed to send pause command: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Resume a paused recording
+    pub fn resume(&self) -> Result<(), String> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Resume)
+                .map_err(|e| format!("Failed to send resume command: {}", e))?;
+        }
+        Ok(())
+    }
+
     /// Stop recording and return collected samples (16kHz mono, VAD filtered)
-    pub fn stop(&self) -> Result<Vec<f32>, String> {
+    /// with the breaks of the recording
+    pub fn stop(&self) -> Result<RecordedAudio, String> {
         let (resp_tx, resp_rx) = mpsc::channel();
         if let Some(tx) = &self.cmd_tx {
             tx.send(Cmd::Stop(resp_tx))
@@ -211,6 +374,8 @@ impl NativeAudioRecorder {
         }
 
         self.device = None;
+        self.device_id = None;
+        self.device_meta = None;
         Ok(())
     }
 
@@ -219,6 +384,21 @@ impl NativeAudioRecorder {
     pub fn is_open(&self) -> bool {
         self.worker_handle.is_some()
     }
+
+    /// Id of the open device (`None`: closed or opened as system default)
+    pub fn device_id(&self) -> Option<&str> {
+        self.device_id.as_deref()
+    }
+
+    /// Name and capture config of the open device
+    pub fn device_meta(&self) -> Option<&InputDeviceMeta> {
+        self.device_meta.as_ref()
+    }
+
+    /// Whether the open device was lost (capture stopped or failed over)
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::SeqCst)
+    }
 }
 
 impl Drop for NativeAudioRecorder {
@@ -227,11 +407,91 @@ impl Drop for NativeAudioRecorder {
     }
 }
 
+/// Name and capture config of a device
+fn input_device_meta(device: &Device, config: &cpal::SupportedStreamConfig) -> InputDeviceMeta {
+    InputDeviceMeta {
+        name: device.name().unwrap_or_else(|_| "Unknown".to_string()),
+        device_id: None,
+        sample_rate: config.sample_rate().0,
+        channels: config.channels(),
+        sample_format: format!("{:?}", config.sample_format()).to_lowercase(),
+        failover_from: None,
+    }
+}
+
+/// A playing stream and the receiver for its fatal errors
+type ActiveStream = (cpal::Stream, mpsc::Receiver<String>);
+
+/// Build and start an input stream in the preferred config
+fn open_stream(
+    device: &Device,
+    config: &cpal::SupportedStreamConfig,
+    sample_tx: &mpsc::Sender<Vec<f32>>,
+) -> Result<ActiveStream, String> {
+    let channels = config.channels() as usize;
+
+    tracing::info!(
+        device = %device.name().unwrap_or_else(|_| "Unknown".to_string()),
+        sample_rate = config.sample_rate().0,
+        channels,
+        format = ?config.sample_format(),
+        "Native audio: Device configured"
+    );
+
+    let (error_tx, error_rx) = mpsc::channel();
+    let sample_tx = sample_tx.clone();
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::U8 => build_stream::<u8>(device, config, sample_tx, error_tx, channels),
+        cpal::SampleFormat::I8 => build_stream::<i8>(device, config, sample_tx, error_tx, channels),
+        cpal::SampleFormat::I16 => {
+            build_stream::<i16>(device, config, sample_tx, error_tx, channels)
+        }
+        cpal::SampleFormat::I32 => {
+            build_stream::<i32>(device, config, sample_tx, error_tx, channels)
+        }
+        cpal::SampleFormat::F32 => {
+            build_stream::<f32>(device, config, sample_tx, error_tx, channels)
+        }
+        _ => return Err("Unsupported sample format".to_string()),
+    }
+    .map_err(|e| format!("Failed to build stream: {}", e))?;
+
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start stream: {}", e))?;
+    Ok((stream, error_rx))
+}
+
+/// Open the system default device in place of the lost one
+fn open_default_fallback(
+    lost_name: &str,
+    sample_tx: &mpsc::Sender<Vec<f32>>,
+) -> Option<(ActiveStream, InputDeviceMeta)> {
+    let device = get_cpal_host().default_input_device()?;
+    // Default not updated yet: the lost device again
+    if device.name().is_ok_and(|name| name == lost_name) {
+        tracing::warn!(lost = %lost_name, "Native audio: No other default device to fail over to");
+        return None;
+    }
+    let result = get_preferred_config(&device).and_then(|config| {
+        let stream = open_stream(&device, &config, sample_tx)?;
+        Ok((stream, input_device_meta(&device, &config)))
+    });
+    match result {
+        Ok(fallback) => Some(fallback),
+        Err(e) => {
+            tracing::warn!(lost = %lost_name, error = %e, "Native audio: No default device to fail over to");
+            None
+        }
+    }
+}
+
 /// Build an input stream for any sample type
 fn build_stream<T>(
     device: &cpal::Device,
     config: &cpal::SupportedStreamConfig,
     sample_tx: mpsc::Sender<Vec<f32>>,
+    error_tx: mpsc::Sender<String>,
     channels: usize,
 ) -> Result<cpal::Stream, cpal::BuildStreamError>
 where
@@ -266,12 +526,15 @@ where
         }
     };
 
-    device.build_input_stream(
-        &config.clone().into(),
-        stream_cb,
-        |err| tracing::error!(error = %err, "Native audio: Stream error"),
-        None,
-    )
+    let error_cb = move |err: cpal::StreamError| {
+        tracing::error!(error = %err, "Native audio: Stream error");
+        // Other errors may be transient; a stream that stops delivering is caught by the stall check
+        if matches!(err, cpal::StreamError::DeviceNotAvailable) {
+            let _ = error_tx.send(err.to_string());
+        }
+    };
+
+    device.build_input_stream(&config.clone().into(), stream_cb, error_cb, None)
 }
 
 /// Get preferred audio config (tries to get one supporting 16kHz)
@@ -319,118 +582,314 @@ fn get_preferred_config(
         .map_err(|e| format!("Failed to get default config: {}", e))
 }
 
-/// Consumer loop: processes samples from the audio thread
-fn run_consumer(
-    in_sample_rate: u32,
+/// Why the consumer loop ended
+#[derive(Debug, PartialEq)]
+enum ConsumerExit {
+    Shutdown,
+    DeviceLost(String),
+}
+
+/// Consumer: processes samples from the audio thread.
+///
+/// Outlives a lost device's stream, so a recording continues on the
+/// failover device with the samples captured so far.
+struct Consumer {
     vad: Option<Arc<Mutex<VadPipeline>>>,
-    sample_rx: mpsc::Receiver<Vec<f32>>,
-    cmd_rx: mpsc::Receiver<Cmd>,
-    level_cb: Option<Arc<dyn Fn(f32) + Send + Sync + 'static>>,
-) {
-    // Create resampler (30ms frames for VAD)
-    let mut frame_resampler = FrameResampler::new(
-        in_sample_rate as usize,
-        WHISPER_SAMPLE_RATE as usize,
-        Duration::from_millis(30),
-    );
+    level_cb: Option<LevelCallback>,
+    /// Speech segments are only collected with a callback
+    segment_cb: Option<SegmentCallback>,
+    segments: SegmentTracker,
+    /// Samples not yet written to the spool (all samples without one)
+    processed_samples: Vec<f32>,
+    spool: Option<SpoolWriter>,
+    recording: bool,
+    /// Position in the recording and start of the current break
+    paused: Option<(u64, Instant)>,
+    pauses: Vec<RecordingPause>,
+}
 
-    let mut processed_samples = Vec::<f32>::new();
-    let mut recording = false;
-
-    // Helper to process a single frame through VAD
-    fn handle_frame(
-        samples: &[f32],
-        recording: bool,
-        vad: &Option<Arc<Mutex<VadPipeline>>>,
-        out_buf: &mut Vec<f32>,
-    ) {
-        if !recording {
-            return;
+impl Consumer {
+    fn new(vad: Option<Arc<Mutex<VadPipeline>>>, level_cb: Option<LevelCallback>) -> Self {
+        Self {
+            vad,
+            level_cb,
+            segment_cb: None,
+            segments: SegmentTracker::default(),
+            processed_samples: Vec::new(),
+            spool: None,
+            recording: false,
+            paused: None,
+            pauses: Vec::new(),
         }
+    }
+
+    /// Consumer loop for one stream. Without `stream_errors` (no stream)
+    /// only commands are served.
+    fn run(
+        &mut self,
+        in_sample_rate: u32,
+        sample_rx: &mpsc::Receiver<Vec<f32>>,
+        cmd_rx: &mpsc::Receiver<Cmd>,
+        stream_errors: Option<&mpsc::Receiver<String>>,
+    ) -> ConsumerExit {
+        // Create resampler (30ms frames for VAD)
+        let mut frame_resampler = FrameResampler::new(
+            in_sample_rate as usize,
+            WHISPER_SAMPLE_RATE as usize,
+            Duration::from_millis(30),
+        );
+        let mut last_samples = Instant::now();
 
-        if let Some(vad_arc) = vad {
-            if let Ok(mut det) = vad_arc.lock() {
-                match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
-                    VadFrame::Speech(buf) => out_buf.extend_from_slice(buf),
-                    VadFrame::Noise => {}
+        loop {
+            match sample_rx.recv_timeout(SAMPLE_POLL_INTERVAL) {
+                Ok(raw) => {
+                    last_samples = Instant::now();
+                    self.process(&raw, &mut frame_resampler);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return ConsumerExit::Shutdown, // Stream closed
+            }
+
+            if let Some(errors) = stream_errors {
+                let lost = match errors.try_recv() {
+                    Ok(error) => Some(error),
+                    Err(_) if last_samples.elapsed() >= STREAM_STALL_TIMEOUT => Some(format!(
+                        "No audio from device for {} seconds",
+                        STREAM_STALL_TIMEOUT.as_secs()
+                    )),
+                    Err(_) => None,
+                };
+                if let Some(error) = lost {
+                    // Keep the last partial frame of the lost device
+                    let recording = self.recording;
+                    frame_resampler.finish(&mut |frame: &[f32]| self.handle(frame, recording));
+                    self.flush_spool();
+                    return ConsumerExit::DeviceLost(error);
+                }
+            }
+
+            // Check for commands (non-blocking)
+            loop {
+                let cmd = match cmd_rx.try_recv() {
+                    Ok(cmd) => cmd,
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => return ConsumerExit::Shutdown,
+                };
+                match cmd {
+                    Cmd::Start(spool) => {
+                        self.processed_samples.clear();
+                        self.spool = spool;
+                        self.recording = true;
+                        self.paused = None;
+                        self.pauses.clear();
+                        self.segments.reset();
+                        self.reset_vad();
+                        tracing::info!("Native audio: Recording started");
+                    }
+                    Cmd::Pause => {
+                        if self.recording {
+                            // Keep the last partial frame spoken before the break
+                            frame_resampler.finish(&mut |frame: &[f32]| self.handle(frame, true));
+                            self.close_segment();
+                            self.flush_spool();
+                            self.recording = false;
+                            self.paused = Some((self.recorded_ms(), Instant::now()));
+                            tracing::info!("Native audio: Recording paused");
+                        }
+                    }
+                    Cmd::Resume => {
+                        if let Some((offset_ms, since)) = self.paused.take() {
+                            // Drop audio of the break still in the resampler; speech
+                            // before the break must not carry over through VAD
+                            // pre-roll or hangover
+                            frame_resampler.finish(&mut |_: &[f32]| {});
+                            self.reset_vad();
+                            let pause = RecordingPause {
+                                offset_ms,
+                                duration_ms: since.elapsed().as_millis() as u64,
+                            };
+                            if let Some(spool) = &mut self.spool {
+                                if let Err(e) = spool.add_pause(pause.clone()) {
+                                    tracing::warn!(
+                                        error = %e,
+                                        "Native audio: Failed to add break to spool"
+                                    );
+                                }
+                            }
+                            self.pauses.push(pause);
+                            self.recording = true;
+                            tracing::info!(offset_ms, "Native audio: Recording resumed");
+                        }
+                    }
+                    Cmd::Stop(reply_tx) => {
+                        self.recording = false;
+
+                        // Finish processing remaining samples (none of a break)
+                        let keep = self.paused.take().is_none();
+                        frame_resampler.finish(&mut |frame: &[f32]| self.handle(frame, keep));
+                        self.close_segment();
+
+                        let samples = self.take_samples();
+                        tracing::info!(
+                            samples = samples.len(),
+                            duration_sec = %format!("{:.2}", samples.len() as f32 / WHISPER_SAMPLE_RATE as f32),
+                            "Native audio: Recording stopped"
+                        );
+
+                        let pauses = std::mem::take(&mut self.pauses);
+                        let _ = reply_tx.send(RecordedAudio { samples, pauses });
+                    }
+                    Cmd::Shutdown => {
+                        tracing::debug!("Native audio: Shutting down");
+                        return ConsumerExit::Shutdown;
+                    }
                 }
-            } else {
-                // Lock failed, just keep the samples
-                out_buf.extend_from_slice(samples);
             }
-        } else {
-            // No VAD, keep all samples
-            out_buf.extend_from_slice(samples);
         }
     }
 
-    loop {
-        let raw = match sample_rx.recv() {
-            Ok(s) => s,
-            Err(_) => break, // Stream closed
-        };
-
+    /// Level, resampling, VAD and the recording limit for one chunk from the stream
+    fn process(&mut self, raw: &[f32], frame_resampler: &mut FrameResampler) {
         // Calculate RMS for level callback (before resampling for responsiveness)
-        if let Some(cb) = &level_cb {
+        if let Some(cb) = &self.level_cb {
             let sum: f32 = raw.iter().map(|s| s * s).sum();
             let rms = (sum / raw.len().max(1) as f32).sqrt();
             cb(rms.min(1.0));
         }
 
         // Resample and process through VAD
-        frame_resampler.push(&raw, &mut |frame: &[f32]| {
-            handle_frame(frame, recording, &vad, &mut processed_samples)
-        });
+        let recording = self.recording;
+        frame_resampler.push(raw, &mut |frame: &[f32]| self.handle(frame, recording));
 
-        // OOM Prevention: Stop recording if max duration reached
-        if recording && processed_samples.len() >= MAX_RECORDING_SAMPLES {
+        self.flush_spool();
+
+        // OOM Prevention: Stop recording if max duration reached. Spooled
+        // samples count too: Stop reads the whole recording back into memory.
+        let recorded = self.recorded_samples();
+        if self.recording && recorded >= MAX_RECORDING_SAMPLES {
             tracing::warn!(
                 duration_sec = MAX_RECORDING_DURATION_SECS,
-                samples = processed_samples.len(),
+                samples = recorded,
                 "Max recording duration reached, stopping recording (buffer preserved for next Stop command)"
             );
             // Stop recording but keep samples in buffer
             // User's explicit Stop command will retrieve the samples
-            recording = false;
+            self.recording = false;
+            self.close_segment();
         }
+    }
 
-        // Check for commands (non-blocking)
-        while let Ok(cmd) = cmd_rx.try_recv() {
-            match cmd {
-                Cmd::Start => {
-                    processed_samples.clear();
-                    recording = true;
-                    if let Some(v) = &vad {
-                        if let Ok(mut vad_guard) = v.lock() {
-                            vad_guard.reset();
-                        }
-                    }
-                    tracing::info!("Native audio: Recording started");
-                }
-                Cmd::Stop(reply_tx) => {
-                    recording = false;
-
-                    // Finish processing remaining samples
-                    frame_resampler.finish(&mut |frame: &[f32]| {
-                        handle_frame(frame, true, &vad, &mut processed_samples)
-                    });
-
-                    let samples = std::mem::take(&mut processed_samples);
-                    tracing::info!(
-                        samples = samples.len(),
-                        duration_sec = %format!("{:.2}", samples.len() as f32 / WHISPER_SAMPLE_RATE as f32),
-                        "Native audio: Recording stopped"
-                    );
-
-                    let _ = reply_tx.send(samples);
-                }
-                Cmd::Shutdown => {
-                    tracing::debug!("Native audio: Shutting down");
-                    return;
+    /// One resampled frame: VAD, then segment tracking
+    fn handle(&mut self, frame: &[f32], recording: bool) {
+        let kept = handle_frame(frame, recording, &self.vad, &mut self.processed_samples);
+        if let (Some(kept), Some(cb)) = (kept, &self.segment_cb) {
+            let speech =
+                (kept > 0).then(|| &self.processed_samples[self.processed_samples.len() - kept..]);
+            if let Some(segment) = self.segments.push(speech) {
+                cb(segment);
+            }
+        }
+    }
+
+    /// Report the open speech segment as completed
+    fn close_segment(&mut self) {
+        if let Some(cb) = &self.segment_cb {
+            if let Some(segment) = self.segments.close() {
+                cb(segment);
+            }
+        }
+    }
+
+    fn reset_vad(&self) {
+        if let Some(v) = &self.vad {
+            if let Ok(mut vad_guard) = v.lock() {
+                vad_guard.reset();
+            }
+        }
+    }
+
+    /// Samples of the recording so far, spooled and in memory
+    fn recorded_samples(&self) -> usize {
+        let spooled = self.spool.as_ref().map_or(0, SpoolWriter::sample_count);
+        spooled + self.processed_samples.len()
+    }
+
+    /// Length of the recording so far (milliseconds)
+    fn recorded_ms(&self) -> u64 {
+        self.recorded_samples() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64
+    }
+
+    /// Move processed samples to the spool. If writing fails, the recording
+    /// continues in memory; the part already on disk is kept for recovery.
+    fn flush_spool(&mut self) {
+        let Some(spool) = &mut self.spool else {
+            return;
+        };
+        if self.processed_samples.is_empty() {
+            return;
+        }
+        match spool.append(&self.processed_samples) {
+            Ok(()) => self.processed_samples.clear(),
+            Err(e) => {
+                tracing::error!(error = %e, "Native audio: Spool write failed, recording continues in memory");
+                if let Some(spool) = self.spool.take() {
+                    self.recover_spooled(spool);
                 }
             }
         }
     }
+
+    /// Read spooled samples back in front of the in-memory ones
+    fn recover_spooled(&mut self, spool: SpoolWriter) {
+        match spool.into_samples() {
+            Ok(mut samples) => {
+                samples.append(&mut self.processed_samples);
+                self.processed_samples = samples;
+            }
+            Err(e) => tracing::error!(
+                error = %e,
+                "Native audio: Failed to read spool, earlier audio is kept as interrupted recording"
+            ),
+        }
+    }
+
+    /// All samples of the finished recording (ends the spool session)
+    fn take_samples(&mut self) -> Vec<f32> {
+        if let Some(spool) = self.spool.take() {
+            self.recover_spooled(spool);
+        }
+        std::mem::take(&mut self.processed_samples)
+    }
+}
+
+/// Process a single frame through VAD.
+/// Returns the number of samples kept (`None` while not recording).
+fn handle_frame(
+    samples: &[f32],
+    recording: bool,
+    vad: &Option<Arc<Mutex<VadPipeline>>>,
+    out_buf: &mut Vec<f32>,
+) -> Option<usize> {
+    if !recording {
+        return None;
+    }
+
+    let before = out_buf.len();
+    if let Some(vad_arc) = vad {
+        if let Ok(mut det) = vad_arc.lock() {
+            match det.push_frame(samples).unwrap_or(VadFrame::Speech(samples)) {
+                VadFrame::Speech(buf) => out_buf.extend_from_slice(buf),
+                VadFrame::Noise => {}
+            }
+        } else {
+            // Lock failed, just keep the samples
+            out_buf.extend_from_slice(samples);
+        }
+    } else {
+        // No VAD, keep all samples
+        out_buf.extend_from_slice(samples);
+    }
+    Some(out_buf.len() - before)
 }
 
 #[cfg(test)]
@@ -456,6 +915,142 @@ mod tests {
         assert!(true, "Shutdown timeout is 2000ms (defined in close() method)");
     }
 
+    #[test]
+    fn test_consumer_keeps_samples_across_device_loss() {
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (error_tx, error_rx) = mpsc::channel();
+        let mut consumer = Consumer::new(None, None);
+        consumer.recording = true;
+
+        // One 30ms frame, then the device reports itself gone
+        sample_tx.send(vec![0.1; 480]).unwrap();
+        error_tx.send("device gone".to_string()).unwrap();
+        let exit = consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, Some(&error_rx));
+        assert_eq!(exit, ConsumerExit::DeviceLost("device gone".to_string()));
+        assert!(
+            consumer.recording,
+            "Recording continues on the failover device"
+        );
+
+        // Failover device delivers the next frame
+        let (reply_tx, reply_rx) = mpsc::channel();
+        sample_tx.send(vec![0.2; 480]).unwrap();
+        cmd_tx.send(Cmd::Stop(reply_tx)).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        let exit = consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+        assert_eq!(exit, ConsumerExit::Shutdown);
+
+        let samples = reply_rx.recv().unwrap().samples;
+        assert_eq!(samples.len(), 960);
+        assert_eq!(samples[0], 0.1);
+        assert_eq!(samples[959], 0.2);
+    }
+
+    #[test]
+    fn test_consumer_writes_recording_to_spool() {
+        let dir = tempfile::tempdir().unwrap();
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let mut consumer = Consumer::new(None, None);
+        consumer.spool = Some(SpoolWriter::create(dir.path(), None, None).unwrap());
+        consumer.recording = true;
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        sample_tx
+            .send([vec![0.1; 480], vec![0.2; 480]].concat())
+            .unwrap();
+        cmd_tx.send(Cmd::Stop(reply_tx)).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+
+        let samples = reply_rx.recv().unwrap().samples;
+        assert_eq!(samples.len(), 960);
+        assert!((samples[0] - 0.1).abs() < 1e-4);
+        assert!((samples[959] - 0.2).abs() < 1e-4);
+        assert!(
+            consumer.spool.is_none(),
+            "Spool session ends with the recording"
+        );
+        assert_eq!(
+            std::fs::read_dir(dir.path().join(".spool"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_consumer_drops_audio_during_pause() {
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let mut consumer = Consumer::new(None, None);
+        consumer.recording = true;
+
+        // 1.5 frames, then a break with audio that must not end up in the recording
+        sample_tx.send(vec![0.1; 720]).unwrap();
+        cmd_tx.send(Cmd::Pause).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+        assert!(!consumer.recording);
+
+        sample_tx.send(vec![0.9; 600]).unwrap();
+        cmd_tx.send(Cmd::Resume).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+
+        sample_tx.send(vec![0.2; 480]).unwrap();
+        cmd_tx.send(Cmd::Stop(reply_tx)).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+
+        let recorded = reply_rx.recv().unwrap();
+        assert_eq!(recorded.samples.len(), 1440);
+        assert!(!recorded.samples.contains(&0.9));
+        assert_eq!(
+            recorded.samples[959], 0.0,
+            "Partial frame before the break is padded"
+        );
+        assert_eq!(recorded.samples[960], 0.2);
+        assert_eq!(recorded.pauses.len(), 1);
+        assert_eq!(recorded.pauses[0].offset_ms, 60);
+    }
+
+    #[test]
+    fn test_consumer_reports_speech_segments() {
+        let (sample_tx, sample_rx) = mpsc::channel();
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let segments = Arc::new(Mutex::new(Vec::new()));
+        let reported = Arc::clone(&segments);
+        let mut consumer = Consumer::new(None, None);
+        consumer.segment_cb = Some(Arc::new(move |segment: SpeechSegment| {
+            reported.lock().unwrap().push(segment)
+        }));
+
+        // Without VAD, pause and stop complete the segments
+        cmd_tx.send(Cmd::Start(None)).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+        sample_tx.send(vec![0.1; 960]).unwrap();
+        cmd_tx.send(Cmd::Pause).unwrap();
+        cmd_tx.send(Cmd::Resume).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+        sample_tx.send(vec![0.2; 480]).unwrap();
+        cmd_tx.send(Cmd::Stop(reply_tx)).unwrap();
+        cmd_tx.send(Cmd::Shutdown).unwrap();
+        consumer.run(WHISPER_SAMPLE_RATE, &sample_rx, &cmd_rx, None);
+
+        assert_eq!(reply_rx.recv().unwrap().samples.len(), 1440);
+        let segments = segments.lock().unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (0, 60));
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (60, 90));
+        assert_eq!(segments[1].samples, vec![0.2; 480]);
+    }
+
     #[test]
     fn test_oom_prevention_samples_calculation() {
         // For a 30-minute recording at 16kHz:
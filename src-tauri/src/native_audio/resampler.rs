@@ -89,7 +89,7 @@ impl FrameResampler {
         }
     }
 
-    /// Finish processing and emit any remaining samples
+    /// Finish processing and emit any remaining samples (reusable afterwards)
     pub fn finish(&mut self, mut emit: impl FnMut(&[f32])) {
         // Process any remaining input samples
         if let Some(ref mut resampler) = self.resampler {
@@ -99,7 +99,10 @@ impl FrameResampler {
                 if let Ok(out) = resampler.process(&[&self.in_buf[..]], None) {
                     self.emit_frames(&out[0], &mut emit);
                 }
+                self.in_buf.clear();
             }
+            // Start the next push from silence, not from the audio before
+            resampler.reset();
         }
 
         // Emit any remaining pending frame (padded with zeros)
@@ -0,0 +1,119 @@
+//! Live Speech Segments
+//!
+//! Splits a running recording into speech segments as VAD decides: a segment
+//! opens with the first speech frame (including VAD pre-roll) and closes with
+//! the first noise frame after it, when the recording is paused or stopped,
+//! or when it reaches [`MAX_SEGMENT_DURATION_MS`]. Without VAD every frame is
+//! speech, so segments are cut by length only.
+//!
+//! Times are positions in the recorded (VAD filtered) audio, so segments line
+//! up with the audio `native_stop_recording` returns.
+
+use super::constants::WHISPER_SAMPLE_RATE;
+
+/// Longest segment (one Whisper window); longer speech is split
+pub const MAX_SEGMENT_DURATION_MS: u64 = 30_000;
+
+const MAX_SEGMENT_SAMPLES: usize =
+    (MAX_SEGMENT_DURATION_MS as usize) * (WHISPER_SAMPLE_RATE as usize) / 1000;
+
+/// A completed speech segment of the running recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechSegment {
+    /// Segments of the recording are numbered from 0
+    pub index: u32,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// 16kHz mono
+    pub samples: Vec<f32>,
+}
+
+fn to_ms(samples: usize) -> u64 {
+    samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64
+}
+
+/// Collects the speech of a recording into segments
+#[derive(Debug, Default)]
+pub struct SegmentTracker {
+    next_index: u32,
+    /// Samples recorded before the open segment (or so far, if none is open)
+    position: usize,
+    open: Vec<f32>,
+}
+
+impl SegmentTracker {
+    /// Start over for a new recording
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Feed the result of one VAD frame: the samples kept as speech, or
+    /// `None` for noise. Returns the segment this frame completed.
+    pub fn push(&mut self, speech: Option<&[f32]>) -> Option<SpeechSegment> {
+        let Some(speech) = speech else {
+            return self.close();
+        };
+        self.open.extend_from_slice(speech);
+        if self.open.len() >= MAX_SEGMENT_SAMPLES {
+            return self.close();
+        }
+        None
+    }
+
+    /// Complete the open segment (pause, stop)
+    pub fn close(&mut self) -> Option<SpeechSegment> {
+        if self.open.is_empty() {
+            return None;
+        }
+        let samples = std::mem::take(&mut self.open);
+        let start = self.position;
+        self.position += samples.len();
+        let segment = SpeechSegment {
+            index: self.next_index,
+            start_ms: to_ms(start),
+            end_ms: to_ms(self.position),
+            samples,
+        };
+        self.next_index += 1;
+        Some(segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_close_on_noise_length_and_stop() {
+        let mut tracker = SegmentTracker::default();
+        let frame = [0.1; 480];
+
+        assert_eq!(tracker.push(None), None, "Noise without speech");
+        assert_eq!(tracker.push(Some(&[0.2; 960])), None, "Pre-roll opens");
+        assert_eq!(tracker.push(Some(&frame)), None);
+        let first = tracker.push(None).unwrap();
+        assert_eq!((first.index, first.start_ms, first.end_ms), (0, 0, 90));
+        assert_eq!(first.samples.len(), 1440);
+
+        // Continuous speech is split at the maximum length
+        let mut split = None;
+        for _ in 0..MAX_SEGMENT_SAMPLES / frame.len() {
+            split = split.or(tracker.push(Some(&frame)));
+        }
+        let second = split.unwrap();
+        assert_eq!((second.index, second.start_ms), (1, 90));
+        assert_eq!(second.end_ms, 90 + MAX_SEGMENT_DURATION_MS);
+
+        tracker.push(Some(&frame));
+        let last = tracker.close().unwrap();
+        assert_eq!(
+            (last.index, last.start_ms, last.end_ms),
+            (2, 30_090, 30_120)
+        );
+        assert_eq!(tracker.close(), None);
+
+        tracker.reset();
+        tracker.push(Some(&frame));
+        assert_eq!(tracker.close().unwrap().index, 0);
+    }
+}
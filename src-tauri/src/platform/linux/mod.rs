@@ -11,6 +11,7 @@
 //! - `get_xdg_data_home()`: XDG data directory (`~/.local/share` fallback)
 //! - `get_xdg_config_home()`: XDG config directory (`~/.config` fallback)
 //! - `get_xdg_storage_path()`: XDG-compliant storage path for recordings
+//! - `get_xdg_settings_path()`: XDG-compliant path of the backend settings file
 //!
 //! # Roadmap
 //!
@@ -58,7 +59,6 @@ pub fn get_xdg_data_home() -> Result<PathBuf, String> {
 ///
 /// Returns `$XDG_CONFIG_HOME` if set and absolute, otherwise `~/.config`.
 /// Returns an error if the home directory cannot be determined.
-#[allow(dead_code)]
 pub fn get_xdg_config_home() -> Result<PathBuf, String> {
     if let Ok(xdg) = std::env::var("XDG_CONFIG_HOME") {
         let path = PathBuf::from(&xdg);
@@ -80,6 +80,14 @@ pub fn get_xdg_storage_path() -> Result<PathBuf, String> {
         .map(|p| p.join("hablara").join("recordings"))
 }
 
+/// XDG-compliant settings file path.
+///
+/// Returns `$XDG_CONFIG_HOME/hablara/settings.json` (default: `~/.config/hablara/settings.json`)
+pub fn get_xdg_settings_path() -> Result<PathBuf, String> {
+    get_xdg_config_home()
+        .map(|p| p.join("hablara").join("settings.json"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -154,4 +162,12 @@ mod tests {
         assert_eq!(result.unwrap(), PathBuf::from("/custom/data/hablara/recordings"));
         std::env::remove_var("XDG_DATA_HOME");
     }
+
+    #[test]
+    fn test_get_xdg_settings_path_with_custom_xdg() {
+        std::env::set_var("XDG_CONFIG_HOME", "/custom/config");
+        let result = get_xdg_settings_path();
+        assert_eq!(result.unwrap(), PathBuf::from("/custom/config/hablara/settings.json"));
+        std::env::remove_var("XDG_CONFIG_HOME");
+    }
 }
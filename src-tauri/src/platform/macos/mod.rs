@@ -9,6 +9,7 @@
 //!
 //! - `is_mlx_available()`: Checks if MLX (Apple Silicon ML framework) is available
 //! - `get_app_support_storage_path()`: Application Support storage path for direct distribution
+//! - `get_app_support_settings_path()`: Application Support path of the backend settings file
 
 use std::path::PathBuf;
 
@@ -36,6 +37,16 @@ pub fn get_app_support_storage_path() -> Result<PathBuf, String> {
         .map(|p| p.join("Hablara").join("recordings"))
 }
 
+/// Settings file path.
+///
+/// Returns `~/Library/Application Support/Hablara/settings.json`
+/// (inside the container for sandboxed App Store builds)
+pub fn get_app_support_settings_path() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .ok_or_else(|| "Application Support directory not found".to_string())
+        .map(|p| p.join("Hablara").join("settings.json"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
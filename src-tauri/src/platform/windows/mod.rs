@@ -9,6 +9,7 @@
 //!
 //! - `is_mlx_available()`: Always false (MLX is Apple Silicon only)
 //! - `get_local_app_data_storage_path()`: LocalAppData storage path for direct distribution
+//! - `get_app_data_settings_path()`: Roaming AppData path of the backend settings file
 //!
 //! # Implementation Roadmap
 //!
@@ -31,6 +32,15 @@ pub fn get_local_app_data_storage_path() -> Result<PathBuf, String> {
         .map(|p| p.join("Hablara").join("recordings"))
 }
 
+/// Settings file path.
+///
+/// Returns `%APPDATA%\Hablara\settings.json`
+pub fn get_app_data_settings_path() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .ok_or_else(|| "AppData directory not found".to_string())
+        .map(|p| p.join("Hablara").join("settings.json"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
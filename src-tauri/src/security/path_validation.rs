@@ -35,6 +35,13 @@ const MAX_HTML_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
 /// Allowed HTML file extensions
 const ALLOWED_HTML_EXTENSIONS: &[&str] = &["html", "htm"];
 
+/// Allowed library archive extensions (gzip-compressed tar)
+const ALLOWED_ARCHIVE_EXTENSIONS: &[&str] = &["tar.gz", "tgz"];
+
+/// Maximum library archive size (4 GB)
+/// Archives hold many recordings, but unbounded imports could fill the disk
+const MAX_ARCHIVE_FILE_SIZE: u64 = 4 * 1024 * 1024 * 1024; // 4 GB
+
 /// Validate audio file path for security
 ///
 /// # Security Compliance
@@ -301,3 +308,244 @@ pub fn validate_html_path(path: &str) -> Result<PathBuf, SecurityError> {
 
     Ok(canonical)
 }
+
+/// Check the archive extension (`.tar.gz` is a double extension, so compare
+/// the file name suffix instead of `Path::extension`)
+fn check_archive_extension(path_buf: &Path) -> Result<(), SecurityError> {
+    let file_name = path_buf
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default();
+
+    if ALLOWED_ARCHIVE_EXTENSIONS
+        .iter()
+        .any(|ext| file_name.ends_with(&format!(".{}", ext)))
+    {
+        return Ok(());
+    }
+
+    Err(SecurityError::InvalidExtension {
+        expected: ALLOWED_ARCHIVE_EXTENSIONS.join(", "),
+        got: path_buf
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("none")
+            .to_string(),
+    })
+}
+
+/// Whether a canonical path is inside the user's home or temp directory
+fn is_in_home_or_temp(canonical: &Path) -> Result<bool, SecurityError> {
+    let home_dir = dirs::home_dir().ok_or_else(|| {
+        SecurityError::InvalidStoragePath("Cannot determine home directory".to_string())
+    })?;
+    // Canonicalize home_dir to match canonical path format (Windows adds \\?\ prefix)
+    let home_dir = home_dir.canonicalize().unwrap_or(home_dir);
+    let tmp_dir = std::env::temp_dir();
+    // Canonicalize tmp_dir to handle macOS /private/var/folders symlink
+    let canonical_tmp = tmp_dir.canonicalize().unwrap_or(tmp_dir.clone());
+
+    Ok(canonical.starts_with(&home_dir) || canonical.starts_with(&canonical_tmp))
+}
+
+/// Validate a library archive path for import
+///
+/// # Security Compliance
+/// * CWE-22: Path Traversal protection
+/// * CWE-59: Symlink following protection
+/// * CWE-400: Resource exhaustion protection (file size limits)
+///
+/// # Checks
+/// 1. Extension is .tar.gz or .tgz
+/// 2. No path traversal
+/// 3. File exists
+/// 4. Not a symlink (checked BEFORE canonicalize to prevent TOCTOU)
+/// 5. File size limit (max 4 GB)
+/// 6. Path canonicalization
+/// 7. Within allowed directories (user's home or temp)
+#[must_use = "validation result must be checked to ensure security"]
+pub fn validate_archive_path(path: &str) -> Result<PathBuf, SecurityError> {
+    // 1. Extension check
+    let path_buf = Path::new(path);
+    check_archive_extension(path_buf)?;
+
+    // 2. Reject path traversal
+    if path.contains("..") {
+        return Err(SecurityError::PathTraversal(path.to_string()));
+    }
+
+    // 3. Verify file exists
+    if !path_buf.exists() {
+        return Err(SecurityError::PathNotFound(path.to_string()));
+    }
+
+    // 4. Symlink check BEFORE canonicalize (prevents TOCTOU)
+    let metadata = std::fs::symlink_metadata(path_buf)?;
+    if metadata.is_symlink() {
+        return Err(SecurityError::SymlinkNotAllowed(path.to_string()));
+    }
+    if !metadata.is_file() {
+        return Err(SecurityError::PathNotFound(format!("{} is not a file", path)));
+    }
+
+    // 5. File size check
+    if metadata.len() > MAX_ARCHIVE_FILE_SIZE {
+        return Err(SecurityError::FileTooLarge {
+            size: metadata.len(),
+            limit: MAX_ARCHIVE_FILE_SIZE,
+        });
+    }
+
+    // 6. Canonicalize (safe now, we know it's not a symlink)
+    let canonical = path_buf.canonicalize()?;
+
+    // 7. Verify within allowed directories
+    if !is_in_home_or_temp(&canonical)? {
+        return Err(SecurityError::NotInAllowedDir(canonical.display().to_string()));
+    }
+
+    Ok(canonical)
+}
+
+/// Validate a library archive path for export (file may not exist yet)
+///
+/// # Security Compliance
+/// * CWE-22: Path Traversal protection
+/// * CWE-59: Symlink following protection
+///
+/// # Checks
+/// 1. Extension is .tar.gz or .tgz
+/// 2. No path traversal
+/// 3. Parent directory exists and is within the user's home or temp directory
+/// 4. An existing target is a regular file, not a symlink or directory
+#[must_use = "validation result must be checked to ensure security"]
+pub fn validate_archive_export_path(path: &str) -> Result<PathBuf, SecurityError> {
+    // 1. Extension check
+    let path_buf = Path::new(path);
+    check_archive_extension(path_buf)?;
+
+    // 2. Reject path traversal
+    if path.contains("..") {
+        return Err(SecurityError::PathTraversal(path.to_string()));
+    }
+
+    // 3. Canonicalize parent (the archive itself may not exist yet)
+    let parent = path_buf
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .ok_or_else(|| SecurityError::InvalidStoragePath("Path has no parent directory".to_string()))?;
+    if !parent.is_dir() {
+        return Err(SecurityError::PathNotFound(format!(
+            "Parent directory does not exist: {}",
+            parent.display()
+        )));
+    }
+    let file_name = path_buf
+        .file_name()
+        .ok_or_else(|| SecurityError::InvalidStoragePath("Invalid path".to_string()))?;
+    let canonical = parent.canonicalize()?.join(file_name);
+
+    if !is_in_home_or_temp(&canonical)? {
+        return Err(SecurityError::NotInAllowedDir(canonical.display().to_string()));
+    }
+
+    // 4. Overwriting is allowed (save dialog confirms), but never through a symlink
+    if let Ok(metadata) = std::fs::symlink_metadata(&canonical) {
+        if metadata.is_symlink() {
+            return Err(SecurityError::SymlinkNotAllowed(path.to_string()));
+        }
+        if !metadata.is_file() {
+            return Err(SecurityError::InvalidStoragePath(format!(
+                "Path exists but is not a file: {}",
+                canonical.display()
+            )));
+        }
+    }
+
+    Ok(canonical)
+}
+
+/// Mount points of removable and external volumes
+#[cfg(target_os = "macos")]
+const EXTERNAL_VOLUME_ROOTS: &[&str] = &["/Volumes"];
+#[cfg(target_os = "linux")]
+const EXTERNAL_VOLUME_ROOTS: &[&str] = &["/media", "/run/media", "/mnt"];
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+const EXTERNAL_VOLUME_ROOTS: &[&str] = &[];
+
+/// Whether a canonical path is on an external volume (below a mount root,
+/// or on Windows any drive other than the system drive)
+fn is_on_external_volume(canonical: &Path) -> bool {
+    if EXTERNAL_VOLUME_ROOTS
+        .iter()
+        .any(|root| canonical.starts_with(root) && canonical != Path::new(root))
+    {
+        return true;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::path::{Component, Prefix};
+        let system_drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+        if let Some(Component::Prefix(prefix)) = canonical.components().next() {
+            let drive = match prefix.kind() {
+                Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => letter,
+                _ => return false,
+            };
+            return !system_drive
+                .as_bytes()
+                .first()
+                .is_some_and(|system| system.eq_ignore_ascii_case(&drive));
+        }
+    }
+
+    false
+}
+
+/// Validate a backup directory
+///
+/// # Security Compliance
+/// * CWE-22: Path Traversal protection
+/// * CWE-59: Symlink following protection
+/// * OWASP A01:2021: Broken Access Control
+///
+/// # Checks
+/// 1. No path traversal
+/// 2. Directory exists (backups never create their target, so an unplugged
+///    drive is not replaced by a directory on the system disk)
+/// 3. Not a symlink (checked BEFORE canonicalize to prevent TOCTOU)
+/// 4. Within the user's home or temp directory, or on an external volume
+#[must_use = "validation result must be checked to ensure security"]
+pub fn validate_backup_path(path: &str) -> Result<PathBuf, SecurityError> {
+    // 1. Reject path traversal
+    if path.contains("..") {
+        return Err(SecurityError::PathTraversal(path.to_string()));
+    }
+
+    // 2. Verify directory exists
+    let path_buf = Path::new(path);
+    if !path_buf.exists() {
+        return Err(SecurityError::PathNotFound(path.to_string()));
+    }
+
+    // 3. Symlink check BEFORE canonicalize (prevents TOCTOU)
+    let metadata = std::fs::symlink_metadata(path_buf)?;
+    if metadata.is_symlink() {
+        return Err(SecurityError::SymlinkNotAllowed(path.to_string()));
+    }
+    if !metadata.is_dir() {
+        return Err(SecurityError::InvalidStoragePath(format!(
+            "Path exists but is not a directory: {}",
+            path
+        )));
+    }
+
+    // 4. Verify within allowed directories
+    let canonical = path_buf.canonicalize()?;
+    if !is_in_home_or_temp(&canonical)? && !is_on_external_volume(&canonical) {
+        return Err(SecurityError::NotInAllowedDir(canonical.display().to_string()));
+    }
+
+    Ok(canonical)
+}
@@ -0,0 +1,1258 @@
+//! Persistent Backend Settings
+//!
+//! Backend-owned settings file in the platform config directory:
+//! - macOS: `~/Library/Application Support/Hablara/settings.json`
+//! - Linux: `$XDG_CONFIG_HOME/hablara/settings.json` (`~/.config/...`)
+//! - Windows: `%APPDATA%\Hablara\settings.json`
+//!
+//! The file is versioned. Older files are upgraded on load by a chain of
+//! migration steps on the raw JSON; missing sections and fields fall back to
+//! defaults. A file that cannot be parsed is moved aside and replaced by
+//! defaults; invalid values in a readable file are reset to their defaults
+//! one by one, keeping all profiles. A file from a newer app version is read
+//! but never overwritten.
+//!
+//! Guidelines: docs/reference/guidelines/RUST.md
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::storage::{atomic, profile_storage_path, StorageConfig, DEFAULT_PROFILE_ID};
+use crate::vad::pipeline::{DEFAULT_HANGOVER, DEFAULT_ONSET, DEFAULT_PREFILL, DEFAULT_THRESHOLD};
+
+/// Settings file version written by this build
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Event emitted to the frontend after settings changed (payload: [`Settings`])
+pub const SETTINGS_CHANGED_EVENT: &str = "settings:changed";
+
+/// Upper bound for whisper.cpp threads (more threads stop helping)
+pub const MAX_WHISPER_THREADS: usize = 8;
+
+/// Upper bound for VAD smoothing windows (100 frames = 3s)
+const MAX_VAD_FRAMES: usize = 100;
+
+/// Upper bound for trash retention (one year)
+const MAX_TRASH_RETENTION_DAYS: u32 = 365;
+
+/// Upper bound for recording retention (ten years)
+const MAX_RETENTION_DAYS: u32 = 3650;
+
+/// Upper bound for the backup interval (30 days)
+const MAX_BACKUP_INTERVAL_HOURS: u32 = 720;
+
+/// Upper bound for kept backup snapshots
+const MAX_BACKUP_KEEP: usize = 100;
+
+/// Upper bound for input device ids (characters)
+const MAX_INPUT_DEVICE_ID_LEN: usize = 256;
+
+/// Hosts that may use plain HTTP for sync (local test servers)
+const LOOPBACK_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// Name of the profile created for existing installations
+const DEFAULT_PROFILE_NAME: &str = "Standard";
+
+/// Upper bound for profile names (characters)
+const MAX_PROFILE_NAME_LEN: usize = 50;
+
+/// A migration step upgrading the settings file from version `n` to `n + 1`
+type MigrationStep = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// Migration chain, indexed by source version (first entry upgrades v1 → v2)
+const MIGRATIONS: &[MigrationStep] = &[migrate_v1_to_v2];
+
+/// Voice activity detection parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VadSettings {
+    /// Speech probability threshold (0.0-1.0)
+    pub threshold: f32,
+    /// Frames kept before speech onset (30ms each)
+    pub prefill_frames: usize,
+    /// Frames kept after speech ends
+    pub hangover_frames: usize,
+    /// Consecutive speech frames required to start a segment
+    pub onset_frames: usize,
+}
+
+impl Default for VadSettings {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_THRESHOLD,
+            prefill_frames: DEFAULT_PREFILL,
+            hangover_frames: DEFAULT_HANGOVER,
+            onset_frames: DEFAULT_ONSET,
+        }
+    }
+}
+
+/// Whisper transcription defaults
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WhisperSettings {
+    /// Model name (`ggml-<model>.bin`), used when a transcription names none
+    pub model: String,
+    /// Transcription language (ISO 639-1 or "auto"), used when a transcription
+    /// names none
+    pub language: String,
+    /// whisper.cpp threads (`None` = detect from CPU count)
+    pub threads: Option<usize>,
+}
+
+impl Default for WhisperSettings {
+    fn default() -> Self {
+        Self {
+            model: "german-turbo".to_string(),
+            language: "de".to_string(),
+            threads: None,
+        }
+    }
+}
+
+/// Analysis feature toggles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalysisSettings {
+    pub emotion_analysis_enabled: bool,
+    pub fallacy_detection_enabled: bool,
+    pub tone_analysis_enabled: bool,
+    pub topic_classification_enabled: bool,
+}
+
+impl Default for AnalysisSettings {
+    fn default() -> Self {
+        Self {
+            emotion_analysis_enabled: true,
+            fallacy_detection_enabled: true,
+            tone_analysis_enabled: true,
+            topic_classification_enabled: true,
+        }
+    }
+}
+
+/// Native audio capture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioSettings {
+    /// Selected microphone (`name#index`, `None` = system default)
+    pub input_device: Option<String>,
+    /// Continue on the system default device when the microphone is lost
+    pub failover_to_default: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            input_device: None,
+            failover_to_default: true,
+        }
+    }
+}
+
+/// A named recording library with its own storage config and data key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub storage: StorageConfig,
+}
+
+fn default_profile_id() -> String {
+    DEFAULT_PROFILE_ID.to_string()
+}
+
+/// Complete backend settings (the settings file layout)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    /// File format version (see [`SETTINGS_VERSION`])
+    #[serde(default)]
+    pub version: u32,
+    /// Storage config of the active profile (mirrored into its `profiles` entry)
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// All recording libraries
+    #[serde(default)]
+    pub profiles: Vec<StorageProfile>,
+    #[serde(default = "default_profile_id")]
+    pub active_profile: String,
+    #[serde(default)]
+    pub vad: VadSettings,
+    #[serde(default)]
+    pub whisper: WhisperSettings,
+    #[serde(default)]
+    pub analysis: AnalysisSettings,
+    #[serde(default)]
+    pub audio: AudioSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let storage = StorageConfig::default();
+        Self {
+            version: SETTINGS_VERSION,
+            profiles: vec![StorageProfile {
+                id: default_profile_id(),
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                storage: storage.clone(),
+            }],
+            active_profile: default_profile_id(),
+            storage,
+            vad: VadSettings::default(),
+            whisper: WhisperSettings::default(),
+            analysis: AnalysisSettings::default(),
+            audio: AudioSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Check all values are in range and safe to use
+    pub fn validate(&self) -> Result<(), String> {
+        validate_storage(&self.storage)?;
+        self.validate_profiles()?;
+
+        validate_vad(&self.vad)?;
+        validate_whisper(&self.whisper)?;
+        validate_audio(&self.audio)?;
+        Ok(())
+    }
+
+    /// Check profile ids, names and storage paths are valid and unique
+    fn validate_profiles(&self) -> Result<(), String> {
+        if !self.profiles.iter().any(|p| p.id == self.active_profile) {
+            return Err(format!("Active profile not found: {}", self.active_profile));
+        }
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if !is_valid_profile_id(&profile.id) {
+                return Err(format!("Invalid profile id: {}", profile.id));
+            }
+            validate_profile_name(&profile.name)?;
+            validate_storage(&profile.storage)?;
+
+            for other in &self.profiles[..i] {
+                if other.id == profile.id {
+                    return Err(format!("Duplicate profile id: {}", profile.id));
+                }
+                if other.name.to_lowercase() == profile.name.to_lowercase() {
+                    return Err(format!("A profile named \"{}\" already exists", profile.name));
+                }
+                if other.storage.storage_path == profile.storage.storage_path {
+                    return Err(format!(
+                        "Profiles \"{}\" and \"{}\" use the same storage path",
+                        other.name, profile.name
+                    ));
+                }
+                // Snapshots of two libraries would rotate each other out
+                if other.storage.backup_path.is_some()
+                    && other.storage.backup_path == profile.storage.backup_path
+                {
+                    return Err(format!(
+                        "Profiles \"{}\" and \"{}\" use the same backup path",
+                        other.name, profile.name
+                    ));
+                }
+                // Each library would mirror the other's recordings
+                if other.storage.sync_url.is_some()
+                    && other.storage.sync_url == profile.storage.sync_url
+                {
+                    return Err(format!(
+                        "Profiles \"{}\" and \"{}\" sync with the same server folder",
+                        other.name, profile.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reset invalid values of a loaded file to their defaults instead of
+    /// discarding it. Profiles are never dropped.
+    fn repair(&mut self) {
+        self.repair_profiles();
+        if let Some(active) = self.profiles.iter().find(|p| p.id == self.active_profile) {
+            self.storage = active.storage.clone();
+        }
+        reset_invalid_fields("vad", &mut self.vad, validate_vad, VAD_RESETS);
+        reset_invalid_fields(
+            "whisper",
+            &mut self.whisper,
+            validate_whisper,
+            WHISPER_RESETS,
+        );
+        reset_invalid_fields("audio", &mut self.audio, validate_audio, AUDIO_RESETS);
+    }
+
+    /// Make the profile list valid while keeping every entry: a missing
+    /// active profile is added back, invalid storage values are reset, and
+    /// ids, names, paths and sync folders clashing with an earlier profile
+    /// are replaced
+    fn repair_profiles(&mut self) {
+        if !self.profiles.iter().any(|p| p.id == self.active_profile) {
+            tracing::warn!(id = %self.active_profile, "Settings: Active profile missing, adding it");
+            self.profiles.push(StorageProfile {
+                id: self.active_profile.clone(),
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                storage: self.storage.clone(),
+            });
+        }
+
+        for i in 0..self.profiles.len() {
+            let (earlier, rest) = self.profiles.split_at_mut(i);
+            let profile = &mut rest[0];
+
+            let duplicate_id = earlier.iter().any(|p| p.id == profile.id);
+            if duplicate_id || !is_valid_profile_id(&profile.id) {
+                let id = new_profile_id();
+                tracing::warn!(old = %profile.id, new = %id, "Settings: Invalid profile id replaced");
+                if !duplicate_id && profile.id == self.active_profile {
+                    self.active_profile = id.clone();
+                }
+                profile.id = id;
+            }
+
+            let name_taken = |name: &str| {
+                earlier
+                    .iter()
+                    .any(|p| p.name.to_lowercase() == name.to_lowercase())
+            };
+            if validate_profile_name(&profile.name).is_err() || name_taken(&profile.name) {
+                let base: String = if validate_profile_name(&profile.name).is_ok() {
+                    profile
+                        .name
+                        .trim()
+                        .chars()
+                        .take(MAX_PROFILE_NAME_LEN - 5)
+                        .collect()
+                } else {
+                    DEFAULT_PROFILE_NAME.to_string()
+                };
+                let name = std::iter::once(base.clone())
+                    .chain((2..).map(|n| format!("{} ({})", base, n)))
+                    .find(|name| !name_taken(name))
+                    .unwrap_or(base);
+                tracing::warn!(id = %profile.id, name = %name, "Settings: Invalid profile name replaced");
+                profile.name = name;
+            }
+
+            let storage = &mut profile.storage;
+            if storage.storage_path.trim().is_empty()
+                || earlier
+                    .iter()
+                    .any(|p| p.storage.storage_path == storage.storage_path)
+            {
+                tracing::warn!(id = %profile.id, "Settings: Invalid profile storage path, using default");
+                storage.storage_path = profile_storage_path(&profile.id)
+                    .to_string_lossy()
+                    .to_string();
+            }
+            if storage.backup_path.is_some()
+                && earlier
+                    .iter()
+                    .any(|p| p.storage.backup_path == storage.backup_path)
+            {
+                tracing::warn!(id = %profile.id, "Settings: Backup path shared with another profile, disabling backups");
+                storage.backup_path = None;
+            }
+            if storage.sync_url.is_some()
+                && earlier
+                    .iter()
+                    .any(|p| p.storage.sync_url == storage.sync_url)
+            {
+                tracing::warn!(id = %profile.id, "Settings: Sync folder shared with another profile, disabling sync");
+                storage.sync_url = None;
+            }
+            let section = format!("profiles.{}.storage", profile.id);
+            reset_invalid_fields(&section, storage, validate_storage, STORAGE_RESETS);
+        }
+    }
+
+    /// Copy `storage` into the active profile's entry.
+    /// Files without profiles get the default profile.
+    fn sync_active_profile(&mut self) {
+        if let Some(profile) = self.profiles.iter_mut().find(|p| p.id == self.active_profile) {
+            profile.storage = self.storage.clone();
+        } else if self.profiles.is_empty() {
+            self.profiles.push(StorageProfile {
+                id: self.active_profile.clone(),
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                storage: self.storage.clone(),
+            });
+        }
+        // An unknown active profile is rejected by validate()
+    }
+
+    /// Look up a profile by id
+    pub fn profile(&self, id: &str) -> Result<&StorageProfile, String> {
+        self.profiles
+            .iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Profile not found: {}", id))
+    }
+
+    /// Add a profile with default storage settings and its own directory
+    /// (`storage_path` overrides the directory)
+    pub fn create_profile(
+        &mut self,
+        name: &str,
+        storage_path: Option<String>,
+    ) -> StorageProfile {
+        let id = new_profile_id();
+        let profile = StorageProfile {
+            storage: StorageConfig {
+                storage_path: storage_path.unwrap_or_else(|| {
+                    profile_storage_path(&id).to_string_lossy().to_string()
+                }),
+                ..StorageConfig::default()
+            },
+            id,
+            name: name.trim().to_string(),
+        };
+        self.profiles.push(profile.clone());
+        profile
+    }
+
+    /// Give a profile a new name
+    pub fn rename_profile(&mut self, id: &str, name: &str) -> Result<(), String> {
+        let profile = self
+            .profiles
+            .iter_mut()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+        profile.name = name.trim().to_string();
+        Ok(())
+    }
+
+    /// Remove a profile from the list (its recordings stay on disk).
+    /// The active profile cannot be deleted.
+    pub fn delete_profile(&mut self, id: &str) -> Result<StorageProfile, String> {
+        if id == self.active_profile {
+            return Err("The active profile cannot be deleted".to_string());
+        }
+        let index = self
+            .profiles
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+        Ok(self.profiles.remove(index))
+    }
+
+    /// Make another profile active; its storage config becomes `storage`
+    pub fn switch_profile(&mut self, id: &str) -> Result<(), String> {
+        self.sync_active_profile();
+        self.storage = self.profile(id)?.storage.clone();
+        self.active_profile = id.to_string();
+        Ok(())
+    }
+}
+
+/// Security: The id becomes part of a directory and keyring entry name
+fn is_valid_profile_id(id: &str) -> bool {
+    (1..=32).contains(&id.len())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Random short profile id
+fn new_profile_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..8].to_string()
+}
+
+/// Check a profile name is non-empty, single-line and not too long
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Profile name must not be empty".to_string());
+    }
+    if trimmed.chars().count() > MAX_PROFILE_NAME_LEN {
+        return Err(format!(
+            "Profile name must be at most {} characters",
+            MAX_PROFILE_NAME_LEN
+        ));
+    }
+    if trimmed.chars().any(char::is_control) {
+        return Err("Profile name must not contain control characters".to_string());
+    }
+    Ok(())
+}
+
+/// Check VAD parameters are in range
+fn validate_vad(vad: &VadSettings) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&vad.threshold) {
+        return Err(format!(
+            "VAD threshold must be between 0.0 and 1.0, got {}",
+            vad.threshold
+        ));
+    }
+    for (name, frames) in [
+        ("prefill", vad.prefill_frames),
+        ("hangover", vad.hangover_frames),
+        ("onset", vad.onset_frames),
+    ] {
+        if frames > MAX_VAD_FRAMES {
+            return Err(format!(
+                "VAD {} frames must be at most {}, got {}",
+                name, MAX_VAD_FRAMES, frames
+            ));
+        }
+    }
+    if vad.onset_frames == 0 {
+        return Err("VAD onset frames must be at least 1".to_string());
+    }
+    Ok(())
+}
+
+/// Check the whisper model name is safe and language and threads are valid
+fn validate_whisper(whisper: &WhisperSettings) -> Result<(), String> {
+    // Security: Model name becomes part of a file path (ggml-<model>.bin)
+    let model = &whisper.model;
+    if model.is_empty()
+        || !model
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        || model.contains("..")
+    {
+        return Err(format!("Invalid whisper model name: {}", model));
+    }
+    let language = &whisper.language;
+    let valid_language = language == "auto"
+        || ((2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase()));
+    if !valid_language {
+        return Err(format!("Invalid transcription language: {}", language));
+    }
+    if let Some(threads) = whisper.threads {
+        if !(1..=MAX_WHISPER_THREADS).contains(&threads) {
+            return Err(format!(
+                "Whisper threads must be between 1 and {}, got {}",
+                MAX_WHISPER_THREADS, threads
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check the input device id has the `name#index` form
+fn validate_audio(audio: &AudioSettings) -> Result<(), String> {
+    if let Some(device_id) = &audio.input_device {
+        let valid_device_id = device_id.chars().count() <= MAX_INPUT_DEVICE_ID_LEN
+            && device_id
+                .rsplit_once('#')
+                .is_some_and(|(name, index)| !name.is_empty() && index.parse::<usize>().is_ok());
+        if !valid_device_id {
+            return Err(format!("Invalid input device id: {}", device_id));
+        }
+    }
+    Ok(())
+}
+
+/// Check a storage config is in range and safe to use
+fn validate_storage(storage: &StorageConfig) -> Result<(), String> {
+    if storage.storage_path.trim().is_empty() {
+        return Err("Storage path must not be empty".to_string());
+    }
+    if storage.max_recordings == 0 {
+        return Err("Maximum recordings must be at least 1".to_string());
+    }
+    if !storage.audio_codec.is_available() {
+        return Err(format!(
+            "Audio codec {} is not available in this build",
+            storage.audio_codec.extension()
+        ));
+    }
+    if storage.trash_retention_days > MAX_TRASH_RETENTION_DAYS {
+        return Err(format!(
+            "Trash retention must be at most {} days, got {}",
+            MAX_TRASH_RETENTION_DAYS, storage.trash_retention_days
+        ));
+    }
+    if storage.retention_days > MAX_RETENTION_DAYS {
+        return Err(format!(
+            "Recording retention must be at most {} days, got {}",
+            MAX_RETENTION_DAYS, storage.retention_days
+        ));
+    }
+    if let Some(backup_path) = &storage.backup_path {
+        if backup_path.trim().is_empty() {
+            return Err("Backup path must not be empty".to_string());
+        }
+        if *backup_path == storage.storage_path {
+            return Err("Backup path must differ from the storage path".to_string());
+        }
+    }
+    if storage.backup_interval_hours > MAX_BACKUP_INTERVAL_HOURS {
+        return Err(format!(
+            "Backup interval must be at most {} hours, got {}",
+            MAX_BACKUP_INTERVAL_HOURS, storage.backup_interval_hours
+        ));
+    }
+    if !(1..=MAX_BACKUP_KEEP).contains(&storage.backup_keep) {
+        return Err(format!(
+            "Kept backups must be between 1 and {}, got {}",
+            MAX_BACKUP_KEEP, storage.backup_keep
+        ));
+    }
+    if let Some(sync_url) = &storage.sync_url {
+        validate_sync_url(sync_url)?;
+        if storage.sync_username.trim().is_empty() {
+            return Err("Sync user name must not be empty".to_string());
+        }
+        // Basic auth separates user and password with a colon
+        if storage.sync_username.contains(':') {
+            return Err("Sync user name must not contain ':'".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// WebDAV folder URL: HTTPS (plain HTTP only on this machine), without
+/// embedded credentials, query or fragment
+fn validate_sync_url(sync_url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(sync_url).map_err(|e| format!("Invalid sync URL: {}", e))?;
+    let host = url.host_str().unwrap_or_default();
+    match url.scheme() {
+        "https" => {}
+        "http" if LOOPBACK_HOSTS.contains(&host) => {}
+        _ => return Err("Sync URL must use https".to_string()),
+    }
+    if host.is_empty() {
+        return Err("Sync URL must name a server".to_string());
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("Sync URL must not contain credentials".to_string());
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err("Sync URL must not contain a query or fragment".to_string());
+    }
+    Ok(())
+}
+
+/// A settings field (file name) and how to put it back to its default
+type FieldReset<T> = (&'static str, fn(&mut T));
+
+/// Storage fields reset on their own when invalid. The storage path is never
+/// reset here: profiles get their own default path (see `repair_profiles`).
+const STORAGE_RESETS: &[FieldReset<StorageConfig>] = &[
+    ("maxRecordings", |s| {
+        s.max_recordings = StorageConfig::default().max_recordings
+    }),
+    ("audioCodec", |s| {
+        s.audio_codec = StorageConfig::default().audio_codec
+    }),
+    ("trashRetentionDays", |s| {
+        s.trash_retention_days = StorageConfig::default().trash_retention_days
+    }),
+    ("retentionDays", |s| {
+        s.retention_days = StorageConfig::default().retention_days
+    }),
+    ("backupPath", |s| s.backup_path = None),
+    ("backupIntervalHours", |s| {
+        s.backup_interval_hours = StorageConfig::default().backup_interval_hours
+    }),
+    ("backupKeep", |s| {
+        s.backup_keep = StorageConfig::default().backup_keep
+    }),
+    ("syncUrl", |s| s.sync_url = None),
+];
+
+const VAD_RESETS: &[FieldReset<VadSettings>] = &[
+    ("threshold", |v| v.threshold = DEFAULT_THRESHOLD),
+    ("prefillFrames", |v| v.prefill_frames = DEFAULT_PREFILL),
+    ("hangoverFrames", |v| v.hangover_frames = DEFAULT_HANGOVER),
+    ("onsetFrames", |v| v.onset_frames = DEFAULT_ONSET),
+];
+
+const WHISPER_RESETS: &[FieldReset<WhisperSettings>] = &[
+    ("model", |w| w.model = WhisperSettings::default().model),
+    ("language", |w| {
+        w.language = WhisperSettings::default().language
+    }),
+    ("threads", |w| w.threads = None),
+];
+
+const AUDIO_RESETS: &[FieldReset<AudioSettings>] = &[("inputDevice", |a| a.input_device = None)];
+
+/// Reset invalid fields of a loaded settings section to their defaults until
+/// `validate` accepts it. A field is reset when that clears the reported
+/// problem; if no single field does, the whole section falls back to defaults.
+fn reset_invalid_fields<T: Clone + Default>(
+    section: &str,
+    value: &mut T,
+    validate: fn(&T) -> Result<(), String>,
+    resets: &[FieldReset<T>],
+) {
+    while let Err(error) = validate(value) {
+        let fixed = resets.iter().find_map(|(field, reset)| {
+            let mut candidate = value.clone();
+            reset(&mut candidate);
+            (validate(&candidate).err().as_ref() != Some(&error)).then_some((*field, candidate))
+        });
+        match fixed {
+            Some((field, candidate)) => {
+                tracing::warn!(section, field, error = %error, "Settings: Invalid value, using default");
+                *value = candidate;
+            }
+            None => {
+                tracing::warn!(section, error = %error, "Settings: Invalid section, using defaults");
+                *value = T::default();
+            }
+        }
+    }
+}
+
+/// Platform-specific settings file path
+pub fn settings_path() -> Result<PathBuf, String> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::get_app_support_settings_path()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::linux::get_xdg_settings_path()
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        crate::platform::windows::get_app_data_settings_path()
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        Ok(std::env::temp_dir().join("hablara").join("settings.json"))
+    }
+}
+
+/// Upgrade a raw settings file to [`SETTINGS_VERSION`] using `steps`.
+/// Files without a version are treated as version 1.
+fn apply_migrations(mut value: Value, steps: &[MigrationStep]) -> Result<Value, String> {
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(1)
+        .max(1);
+    let target = steps.len() as u32 + 1;
+
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| "Settings file is not a JSON object".to_string())?;
+
+    for from in version..target {
+        steps[(from - 1) as usize](object)
+            .map_err(|e| format!("Settings migration v{} -> v{} failed: {}", from, from + 1, e))?;
+        object.insert("version".to_string(), Value::from(from + 1));
+    }
+    Ok(value)
+}
+
+/// v1 → v2: Recording libraries (profiles).
+///
+/// The existing storage section becomes the default profile, keeping its
+/// storage path and data key.
+fn migrate_v1_to_v2(object: &mut Map<String, Value>) -> Result<(), String> {
+    let storage = object
+        .get("storage")
+        .cloned()
+        .unwrap_or_else(|| Value::Object(Map::new()));
+    if !storage.is_object() {
+        return Err("storage is not an object".to_string());
+    }
+    object.insert(
+        "profiles".to_string(),
+        serde_json::json!([{
+            "id": DEFAULT_PROFILE_ID,
+            "name": DEFAULT_PROFILE_NAME,
+            "storage": storage,
+        }]),
+    );
+    object.insert("activeProfile".to_string(), Value::from(DEFAULT_PROFILE_ID));
+    Ok(())
+}
+
+/// Version of a raw settings file (files without a version are version 1)
+fn file_version(value: &Value) -> u32 {
+    value
+        .get("version")
+        .and_then(Value::as_u64)
+        .map(|v| v.min(u32::MAX as u64) as u32)
+        .unwrap_or(1)
+}
+
+/// Parse raw settings, migrating older versions.
+/// Files from a newer version are read as far as understood.
+fn parse_settings(value: Value) -> Result<Settings, String> {
+    let value = if file_version(&value) > SETTINGS_VERSION {
+        value
+    } else {
+        apply_migrations(value, MIGRATIONS)?
+    };
+
+    let mut settings: Settings =
+        serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))?;
+    settings.version = SETTINGS_VERSION;
+    settings.sync_active_profile();
+    Ok(settings)
+}
+
+/// Thread-safe settings with file persistence
+pub struct SettingsStore {
+    path: PathBuf,
+    current: RwLock<Settings>,
+    /// Set when the file was written by a newer app version (never overwritten)
+    read_only: bool,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, falling back to defaults.
+    ///
+    /// - Missing file: defaults (written on first save)
+    /// - Unparseable file: moved to `settings.json.corrupt`, defaults used
+    /// - Invalid values: reset to their defaults field by field, keeping
+    ///   all profiles
+    /// - File from a newer app version: loaded as far as understood (defaults
+    ///   if it cannot be used), read-only and left in place
+    pub fn load(path: PathBuf) -> Self {
+        let mut read_only = false;
+
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let value = serde_json::from_str::<Value>(&content)
+                    .map_err(|e| format!("Invalid settings JSON: {}", e));
+                // Checked before validating: newer files may hold values this
+                // version rejects, and must never be replaced
+                let newer_version = value
+                    .as_ref()
+                    .ok()
+                    .map(file_version)
+                    .filter(|v| *v > SETTINGS_VERSION);
+                let parsed = value.and_then(parse_settings).map(|mut settings| {
+                    settings.repair();
+                    settings
+                });
+
+                match (parsed, newer_version) {
+                    (parsed, Some(file_version)) => {
+                        read_only = true;
+                        tracing::warn!(
+                            file_version,
+                            supported = SETTINGS_VERSION,
+                            "Settings: File written by a newer app version, not overwriting"
+                        );
+                        parsed.unwrap_or_else(|e| {
+                            tracing::warn!(
+                                error = %e,
+                                "Settings: Newer settings not usable, using defaults"
+                            );
+                            Settings::default()
+                        })
+                    }
+                    (Ok(settings), None) => {
+                        tracing::info!(path = %path.display(), "Settings: Loaded");
+                        settings
+                    }
+                    (Err(e), None) => {
+                        let backup = path.with_extension("json.corrupt");
+                        tracing::warn!(
+                            error = %e,
+                            backup = %backup.display(),
+                            "Settings: Unparseable settings file, using defaults"
+                        );
+                        if let Err(e) = std::fs::rename(&path, &backup) {
+                            tracing::warn!(
+                                error = %e,
+                                "Settings: Failed to move unparseable file aside"
+                            );
+                        }
+                        Settings::default()
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => {
+                tracing::warn!(error = %e, "Settings: Failed to read settings file, using defaults");
+                Settings::default()
+            }
+        };
+
+        Self {
+            path,
+            current: RwLock::new(settings),
+            read_only,
+        }
+    }
+
+    /// Settings file location
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Current settings
+    pub fn get(&self) -> Settings {
+        match self.current.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Validate, persist and apply new settings
+    pub fn update(&self, mut settings: Settings) -> Result<Settings, String> {
+        settings.version = SETTINGS_VERSION;
+        settings.sync_active_profile();
+        settings.validate()?;
+        self.save(&settings)?;
+
+        let mut guard = self
+            .current
+            .write()
+            .map_err(|e| format!("Failed to lock settings: {}", e))?;
+        *guard = settings.clone();
+        Ok(settings)
+    }
+
+    /// Write settings atomically with owner-only permissions
+    fn save(&self, settings: &Settings) -> Result<(), String> {
+        if self.read_only {
+            return Err(
+                "Settings were written by a newer app version and cannot be changed".to_string(),
+            );
+        }
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        // Storage path and preferences are private
+        atomic::write_file(&self.path, content.as_bytes())?;
+
+        tracing::debug!(path = %self.path.display(), "Settings: Saved");
+        Ok(())
+    }
+}
+
+/// Global settings store (loaded on first access)
+static SETTINGS_STORE: std::sync::OnceLock<SettingsStore> = std::sync::OnceLock::new();
+
+/// Get or load the global settings store
+pub fn get_settings_store() -> &'static SettingsStore {
+    SETTINGS_STORE.get_or_init(|| match settings_path() {
+        Ok(path) => SettingsStore::load(path),
+        Err(e) => {
+            tracing::warn!(error = %e, "Settings: No config directory, using temp dir");
+            SettingsStore::load(std::env::temp_dir().join("hablara").join("settings.json"))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_in(dir: &Path) -> SettingsStore {
+        SettingsStore::load(dir.join("settings.json"))
+    }
+
+    fn modify(store: &SettingsStore, f: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut settings = store.get();
+        f(&mut settings);
+        store.update(settings)
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert!(Settings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults_and_update_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store_in(dir.path());
+        assert_eq!(store.get().vad, VadSettings::default());
+
+        modify(&store, |s| {
+            s.vad.threshold = 0.5;
+            s.whisper.threads = Some(2);
+            s.audio.input_device = Some("USB Headset#1".to_string());
+        })
+        .unwrap();
+
+        let reloaded = store_in(dir.path()).get();
+        assert_eq!(reloaded.vad.threshold, 0.5);
+        assert_eq!(reloaded.whisper.threads, Some(2));
+        assert_eq!(reloaded.audio.input_device.as_deref(), Some("USB Headset#1"));
+        assert_eq!(reloaded.version, SETTINGS_VERSION);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = std::fs::metadata(dir.path().join("settings.json")).unwrap();
+            assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_validation_rejects_bad_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store_in(dir.path());
+
+        assert!(modify(&store, |s| s.vad.threshold = 1.5).is_err());
+        assert!(modify(&store, |s| s.whisper.model = "../../evil".to_string()).is_err());
+        assert!(modify(&store, |s| s.whisper.threads = Some(0)).is_err());
+        assert!(modify(&store, |s| s.whisper.language = "Deutsch".to_string()).is_err());
+        assert!(modify(&store, |s| s.audio.input_device = Some("USB Headset".to_string())).is_err());
+        assert!(modify(&store, |s| s.storage.trash_retention_days = 10_000).is_err());
+        assert!(modify(&store, |s| s.storage.retention_days = 10_000).is_err());
+        assert!(modify(&store, |s| s.storage.backup_keep = 0).is_err());
+        assert!(modify(&store, |s| s.storage.backup_interval_hours = 10_000).is_err());
+        assert!(modify(&store, |s| s.storage.backup_path = Some(s.storage.storage_path.clone()))
+            .is_err());
+        assert!(modify(&store, |s| {
+            s.storage.sync_url = Some("http://dav.example.com/hablara".to_string());
+            s.storage.sync_username = "journal".to_string();
+        })
+        .is_err());
+        assert!(modify(&store, |s| {
+            s.storage.sync_url = Some("https://user:pw@dav.example.com/hablara".to_string());
+            s.storage.sync_username = "journal".to_string();
+        })
+        .is_err());
+        assert!(modify(&store, |s| s.storage.sync_url = Some("https://dav.example.com/".to_string()))
+            .is_err());
+        assert!(!dir.path().join("settings.json").exists(), "Invalid settings must not be saved");
+    }
+
+    #[test]
+    fn test_partial_file_filled_with_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{ "version": 1, "vad": { "threshold": 0.4 } }"#,
+        )
+        .unwrap();
+
+        let settings = store_in(dir.path()).get();
+        assert_eq!(settings.vad.threshold, 0.4);
+        assert_eq!(settings.vad.onset_frames, DEFAULT_ONSET);
+        assert_eq!(settings.whisper, WhisperSettings::default());
+        assert!(settings.audio.failover_to_default);
+    }
+
+    #[test]
+    fn test_corrupt_file_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("settings.json"), "{ not json").unwrap();
+
+        let store = store_in(dir.path());
+        assert_eq!(store.get().vad, VadSettings::default());
+        assert!(dir.path().join("settings.json.corrupt").exists());
+        assert!(!dir.path().join("settings.json").exists());
+    }
+
+    #[test]
+    fn test_invalid_values_reset_one_by_one() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{
+                "version": 2,
+                "activeProfile": "default",
+                "storage": { "storagePath": "/data/journal", "retentionDays": 99999, "maxRecordings": 42 },
+                "profiles": [
+                    { "id": "default", "name": "Standard", "storage": { "storagePath": "/data/journal" } },
+                    { "id": "work", "name": "Arbeit", "storage": { "storagePath": "/data/work", "backupKeep": 0 } },
+                    { "id": "work", "name": "arbeit", "storage": { "storagePath": "/data/work" } }
+                ],
+                "vad": { "threshold": 1.5, "onsetFrames": 4 },
+                "whisper": { "model": "../../evil", "language": "en" }
+            }"#,
+        )
+        .unwrap();
+
+        let settings = store_in(dir.path()).get();
+        assert!(settings.validate().is_ok());
+        assert!(!dir.path().join("settings.json.corrupt").exists());
+
+        // Only the invalid fields fell back to their defaults
+        assert_eq!(settings.storage.storage_path, "/data/journal");
+        assert_eq!(settings.storage.max_recordings, 42);
+        assert_eq!(
+            settings.storage.retention_days,
+            StorageConfig::default().retention_days
+        );
+        assert_eq!(settings.vad.threshold, DEFAULT_THRESHOLD);
+        assert_eq!(settings.vad.onset_frames, 4);
+        assert_eq!(settings.whisper.model, WhisperSettings::default().model);
+        assert_eq!(settings.whisper.language, "en");
+
+        // All profiles are kept; the clashing copy gets its own id, name and path
+        assert_eq!(settings.active_profile, "default");
+        assert_eq!(settings.profiles.len(), 3);
+        let work = settings.profile("work").unwrap();
+        assert_eq!(work.name, "Arbeit");
+        assert_eq!(work.storage.storage_path, "/data/work");
+        assert_eq!(
+            work.storage.backup_keep,
+            StorageConfig::default().backup_keep
+        );
+        let copy = &settings.profiles[2];
+        assert_ne!(copy.id, "work");
+        assert_eq!(copy.name, "arbeit (2)");
+        assert_ne!(copy.storage.storage_path, "/data/work");
+    }
+
+    #[test]
+    fn test_newer_version_is_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = format!(
+            r#"{{ "version": {}, "vad": {{ "threshold": 0.6 }}, "futureSection": {{}} }}"#,
+            SETTINGS_VERSION + 1
+        );
+        std::fs::write(dir.path().join("settings.json"), &content).unwrap();
+
+        let store = store_in(dir.path());
+        assert_eq!(store.get().vad.threshold, 0.6);
+        assert!(modify(&store, |s| s.vad.threshold = 0.3).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("settings.json")).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_newer_version_with_unknown_values_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = format!(
+            r#"{{ "version": {}, "storage": {{ "audioCodec": "future-codec" }}, "vad": {{ "threshold": 2.0 }} }}"#,
+            SETTINGS_VERSION + 1
+        );
+        std::fs::write(dir.path().join("settings.json"), &content).unwrap();
+
+        let store = store_in(dir.path());
+        assert_eq!(store.get().vad, VadSettings::default());
+        assert!(modify(&store, |s| s.vad.threshold = 0.3).is_err());
+        assert!(!dir.path().join("settings.json.corrupt").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("settings.json")).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_v1_storage_becomes_default_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("settings.json"),
+            r#"{ "version": 1, "storage": { "storagePath": "/data/hablara", "encryptionEnabled": true } }"#,
+        )
+        .unwrap();
+
+        let settings = store_in(dir.path()).get();
+        assert_eq!(settings.active_profile, DEFAULT_PROFILE_ID);
+        assert_eq!(settings.profiles.len(), 1);
+        assert_eq!(settings.profiles[0].name, DEFAULT_PROFILE_NAME);
+        assert_eq!(settings.profiles[0].storage.storage_path, "/data/hablara");
+        assert!(settings.profiles[0].storage.encryption_enabled);
+    }
+
+    #[test]
+    fn test_profiles_create_switch_rename_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store_in(dir.path());
+
+        let mut settings = store.get();
+        let work = settings.create_profile(" Arbeit ", Some("/data/arbeit".to_string()));
+        assert_eq!(work.name, "Arbeit");
+        settings.storage.max_recordings = 42;
+        settings.switch_profile(&work.id).unwrap();
+        let settings = store.update(settings).unwrap();
+
+        // Each profile keeps its own storage config
+        assert_eq!(settings.active_profile, work.id);
+        assert_eq!(settings.storage.storage_path, "/data/arbeit");
+        assert_eq!(settings.profile(DEFAULT_PROFILE_ID).unwrap().storage.max_recordings, 42);
+
+        let mut settings = store_in(dir.path()).get();
+        assert_eq!(settings.active_profile, work.id);
+        assert!(settings.delete_profile(&work.id).is_err(), "Active profile is kept");
+        settings.rename_profile(&work.id, "Job").unwrap();
+        settings.switch_profile(DEFAULT_PROFILE_ID).unwrap();
+        assert_eq!(settings.storage.max_recordings, 42);
+        assert_eq!(settings.delete_profile(&work.id).unwrap().name, "Job");
+        assert!(settings.switch_profile(&work.id).is_err());
+        store.update(settings).unwrap();
+    }
+
+    #[test]
+    fn test_profile_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store_in(dir.path());
+
+        // Same name (case-insensitive) or same storage path as the default profile
+        assert!(modify(&store, |s| {
+            s.create_profile("standard", Some("/data/other".to_string()));
+        })
+        .is_err());
+        let default_path = store.get().storage.storage_path;
+        assert!(modify(&store, |s| {
+            s.create_profile("Privat", Some(default_path));
+        })
+        .is_err());
+        assert!(modify(&store, |s| {
+            s.create_profile("  ", None);
+        })
+        .is_err());
+        assert!(modify(&store, |s| s.active_profile = "missing".to_string()).is_err());
+        assert!(modify(&store, |s| s.profiles[0].id = "../x".to_string()).is_err());
+
+        let settings = modify(&store, |s| {
+            s.create_profile("Privat", None);
+        })
+        .unwrap();
+        assert_eq!(settings.profiles.len(), 2);
+        assert!(settings.profiles[1]
+            .storage
+            .storage_path
+            .ends_with(&settings.profiles[1].id));
+
+        // Two libraries must not mirror into the same server folder
+        assert!(modify(&store, |s| {
+            for storage in [&mut s.storage, &mut s.profiles[1].storage] {
+                storage.sync_url = Some("https://dav.example.com/hablara".to_string());
+                storage.sync_username = "journal".to_string();
+            }
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_migration_chain_applied_in_order() {
+        fn rename_threshold(object: &mut Map<String, Value>) -> Result<(), String> {
+            let threshold = object.remove("vadThreshold").unwrap_or(Value::from(0.3));
+            object.insert("vad".to_string(), serde_json::json!({ "threshold": threshold }));
+            Ok(())
+        }
+        fn add_marker(object: &mut Map<String, Value>) -> Result<(), String> {
+            object.insert("marker".to_string(), Value::from(true));
+            Ok(())
+        }
+
+        let old = serde_json::json!({ "vadThreshold": 0.45 });
+        let upgraded = apply_migrations(old, &[rename_threshold, add_marker]).unwrap();
+        assert_eq!(upgraded["version"], 3);
+        assert_eq!(upgraded["vad"]["threshold"], 0.45);
+        assert_eq!(upgraded["marker"], true);
+
+        // Already at the target version: untouched
+        let current = serde_json::json!({ "version": 3, "vadThreshold": 0.1 });
+        let same = apply_migrations(current.clone(), &[rename_threshold, add_marker]).unwrap();
+        assert_eq!(same, current);
+    }
+}
@@ -0,0 +1,515 @@
+//! Emotion Trend Analytics
+//!
+//! Aggregates the stored `EmotionData` and `ToneData` of all recordings:
+//! rolling-window baselines (7/30/90 days), emotion distributions per weekday
+//! and time of day, tone-dimension averages with variance, and notable shifts
+//! of the last week against the personal baseline before it.
+//!
+//! Weekday and time of day are taken in the user's local time zone.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{BaselineResult, EmotionData, RecordingMetadata, ToneData};
+
+/// Rolling windows reported by [`trends`], in days
+pub const TREND_WINDOWS: [u32; 3] = [7, 30, 90];
+
+/// Recent period compared against the personal baseline
+const SHIFT_RECENT_DAYS: i64 = 7;
+
+/// Baseline period for shift detection (ending where the recent period starts)
+const SHIFT_BASELINE_DAYS: i64 = 90;
+
+/// Minimum samples in each period before shifts are reported
+const MIN_RECENT_SAMPLES: usize = 3;
+const MIN_BASELINE_SAMPLES: usize = 5;
+
+/// Change in an emotion's share (0.0-1.0) that counts as notable
+const EMOTION_SHARE_SHIFT: f32 = 0.25;
+
+/// Change of a tone dimension (1-5 scale) that counts as notable, if it is
+/// also at least one standard deviation of the baseline
+const TONE_SHIFT: f32 = 0.75;
+
+/// Share of one emotion among the recordings of a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionShare {
+    pub emotion: String,
+    pub count: usize,
+    /// Fraction of the group's recordings (0.0-1.0)
+    pub share: f32,
+}
+
+/// Mean and variance of one tone dimension
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DimensionStats {
+    pub mean: f32,
+    pub variance: f32,
+}
+
+/// Tone-dimension statistics of a group of recordings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToneStats {
+    pub sample_count: usize,
+    pub formality: DimensionStats,
+    pub professionalism: DimensionStats,
+    pub directness: DimensionStats,
+    pub energy: DimensionStats,
+    pub seriousness: DimensionStats,
+}
+
+/// Emotion and tone baseline over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowBaseline {
+    /// Length of the window in days, `None` for all time
+    pub days: Option<u32>,
+    /// Recordings with emotion data in the window
+    pub sample_count: usize,
+    pub dominant_emotion: Option<String>,
+    pub average_confidence: f32,
+    /// Most frequent emotion first
+    pub emotions: Vec<EmotionShare>,
+    pub tone: Option<ToneStats>,
+}
+
+/// Emotion distribution of one weekday or time of day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketDistribution {
+    /// Weekday ("monday".."sunday") or time of day ("night", "morning",
+    /// "afternoon", "evening")
+    pub bucket: String,
+    pub sample_count: usize,
+    pub emotions: Vec<EmotionShare>,
+}
+
+/// What changed in a [`TrendShift`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShiftKind {
+    /// Share of an emotion (values 0.0-1.0)
+    EmotionShare,
+    /// Mean of a tone dimension (values 1-5)
+    ToneDimension,
+}
+
+/// Notable difference between the last week and the personal baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrendShift {
+    pub kind: ShiftKind,
+    /// Emotion or tone dimension name
+    pub subject: String,
+    pub baseline: f32,
+    pub recent: f32,
+    pub delta: f32,
+}
+
+/// Emotion and tone trends across the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionTrends {
+    /// Baselines over the last 7, 30 and 90 days
+    pub windows: Vec<WindowBaseline>,
+    pub all_time: WindowBaseline,
+    /// Monday first
+    pub by_weekday: Vec<BucketDistribution>,
+    /// Night (0-6h), morning (6-12h), afternoon (12-18h), evening (18-24h)
+    pub by_time_of_day: Vec<BucketDistribution>,
+    /// Largest change first
+    pub shifts: Vec<TrendShift>,
+}
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+const TIMES_OF_DAY: [&str; 4] = ["night", "morning", "afternoon", "evening"];
+
+/// Emotion of a recording (the combined analysis result takes precedence,
+/// as in personalized feedback)
+pub fn emotion_of(recording: &RecordingMetadata) -> Option<&EmotionData> {
+    recording
+        .analysis_result
+        .as_ref()
+        .and_then(|a| a.emotion.as_ref())
+        .or(recording.emotion.as_ref())
+}
+
+fn created_at(recording: &RecordingMetadata) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&recording.created_at)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Recordings created within the last `days` days
+pub fn within_days(
+    recordings: &[RecordingMetadata],
+    now: DateTime<Utc>,
+    days: u32,
+) -> Vec<&RecordingMetadata> {
+    let since = now - Duration::days(days as i64);
+    recordings
+        .iter()
+        .filter(|r| created_at(r).is_some_and(|t| t >= since && t <= now))
+        .collect()
+}
+
+/// Most frequent emotion (`emotion` field only), ties broken by the most
+/// recent recording. Confidence is that of the most recent recording with the
+/// winning emotion; `sample_count` counts recordings with that emotion.
+pub fn baseline<'a>(
+    recordings: impl IntoIterator<Item = &'a RecordingMetadata>,
+) -> Option<BaselineResult> {
+    // Group by emotion, tracking most recent for each
+    let mut emotion_map: HashMap<String, (usize, String, f32)> = HashMap::new();
+
+    for recording in recordings {
+        if let Some(ref emotion) = recording.emotion {
+            let entry =
+                emotion_map
+                    .entry(emotion.primary.clone())
+                    .or_insert((0, String::new(), 0.0));
+
+            entry.0 += 1; // count
+
+            // Update if this is more recent (RFC3339 strings are sortable)
+            if recording.created_at > entry.1 {
+                entry.1 = recording.created_at.clone();
+                entry.2 = emotion.confidence;
+            }
+        }
+    }
+
+    // Find most frequent, tie-break by most recent
+    emotion_map
+        .into_iter()
+        .max_by(|a, b| {
+            // Primary: count, Secondary: timestamp (most recent)
+            match a.1 .0.cmp(&b.1 .0) {
+                std::cmp::Ordering::Equal => a.1 .1.cmp(&b.1 .1),
+                other => other,
+            }
+        })
+        .map(|(emotion, (count, _, confidence))| BaselineResult {
+            emotion,
+            confidence,
+            sample_count: count,
+        })
+}
+
+/// Emotion shares of a group, most frequent first (ties by name)
+fn emotion_shares(emotions: &[&EmotionData]) -> Vec<EmotionShare> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for emotion in emotions {
+        *counts.entry(emotion.primary.as_str()).or_default() += 1;
+    }
+
+    let mut shares: Vec<EmotionShare> = counts
+        .into_iter()
+        .map(|(emotion, count)| EmotionShare {
+            emotion: emotion.to_string(),
+            count,
+            share: count as f32 / emotions.len() as f32,
+        })
+        .collect();
+    shares.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.emotion.cmp(&b.emotion))
+    });
+    shares
+}
+
+fn dimension_stats(values: impl Iterator<Item = f32> + Clone) -> DimensionStats {
+    let n = values.clone().count();
+    if n == 0 {
+        return DimensionStats::default();
+    }
+    let mean = values.clone().sum::<f32>() / n as f32;
+    let variance = values.map(|v| (v - mean).powi(2)).sum::<f32>() / n as f32;
+    DimensionStats { mean, variance }
+}
+
+/// Tone dimensions by name, in [`ToneStats`] field order
+fn tone_dimensions(tone: &ToneData) -> [(&'static str, f32); 5] {
+    [
+        ("formality", tone.formality as f32),
+        ("professionalism", tone.professionalism as f32),
+        ("directness", tone.directness as f32),
+        ("energy", tone.energy as f32),
+        ("seriousness", tone.seriousness as f32),
+    ]
+}
+
+fn tone_stats(tones: &[&ToneData]) -> Option<ToneStats> {
+    if tones.is_empty() {
+        return None;
+    }
+    let stats = |i: usize| dimension_stats(tones.iter().map(move |t| tone_dimensions(t)[i].1));
+    Some(ToneStats {
+        sample_count: tones.len(),
+        formality: stats(0),
+        professionalism: stats(1),
+        directness: stats(2),
+        energy: stats(3),
+        seriousness: stats(4),
+    })
+}
+
+fn window_baseline(recordings: &[&RecordingMetadata], days: Option<u32>) -> WindowBaseline {
+    let emotions: Vec<&EmotionData> = recordings.iter().filter_map(|r| emotion_of(r)).collect();
+    let tones: Vec<&ToneData> = recordings.iter().filter_map(|r| r.tone.as_ref()).collect();
+    let shares = emotion_shares(&emotions);
+
+    WindowBaseline {
+        days,
+        sample_count: emotions.len(),
+        dominant_emotion: shares.first().map(|s| s.emotion.clone()),
+        average_confidence: if emotions.is_empty() {
+            0.0
+        } else {
+            emotions.iter().map(|e| e.confidence).sum::<f32>() / emotions.len() as f32
+        },
+        emotions: shares,
+        tone: tone_stats(&tones),
+    }
+}
+
+/// Emotion distribution per bucket, for recordings with emotion data
+fn bucket_distributions<Tz: TimeZone>(
+    recordings: &[RecordingMetadata],
+    tz: &Tz,
+    names: &[&str],
+    bucket_of: impl Fn(&DateTime<Tz>) -> usize,
+) -> Vec<BucketDistribution> {
+    let mut buckets: Vec<Vec<&EmotionData>> = vec![Vec::new(); names.len()];
+    for recording in recordings {
+        if let (Some(time), Some(emotion)) = (created_at(recording), emotion_of(recording)) {
+            buckets[bucket_of(&time.with_timezone(tz))].push(emotion);
+        }
+    }
+
+    names
+        .iter()
+        .zip(buckets)
+        .map(|(name, emotions)| BucketDistribution {
+            bucket: name.to_string(),
+            sample_count: emotions.len(),
+            emotions: emotion_shares(&emotions),
+        })
+        .collect()
+}
+
+/// Notable changes of the last week against the 90 days before it
+fn detect_shifts(recordings: &[RecordingMetadata], now: DateTime<Utc>) -> Vec<TrendShift> {
+    let recent_start = now - Duration::days(SHIFT_RECENT_DAYS);
+    let baseline_start = recent_start - Duration::days(SHIFT_BASELINE_DAYS);
+    let (mut recent, mut baseline) = (Vec::new(), Vec::new());
+    for recording in recordings {
+        match created_at(recording) {
+            Some(t) if t >= recent_start && t <= now => recent.push(recording),
+            Some(t) if t >= baseline_start && t < recent_start => baseline.push(recording),
+            _ => {}
+        }
+    }
+
+    let mut shifts = Vec::new();
+
+    let recent_emotions: Vec<&EmotionData> = recent.iter().filter_map(|r| emotion_of(r)).collect();
+    let baseline_emotions: Vec<&EmotionData> =
+        baseline.iter().filter_map(|r| emotion_of(r)).collect();
+    if recent_emotions.len() >= MIN_RECENT_SAMPLES
+        && baseline_emotions.len() >= MIN_BASELINE_SAMPLES
+    {
+        let recent_shares = emotion_shares(&recent_emotions);
+        let baseline_shares = emotion_shares(&baseline_emotions);
+        let share_of = |shares: &[EmotionShare], emotion: &str| {
+            shares
+                .iter()
+                .find(|s| s.emotion == emotion)
+                .map(|s| s.share)
+                .unwrap_or(0.0)
+        };
+
+        let mut names: Vec<&str> = recent_shares
+            .iter()
+            .chain(&baseline_shares)
+            .map(|s| s.emotion.as_str())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        for emotion in names {
+            let before = share_of(&baseline_shares, emotion);
+            let after = share_of(&recent_shares, emotion);
+            if (after - before).abs() >= EMOTION_SHARE_SHIFT {
+                shifts.push(TrendShift {
+                    kind: ShiftKind::EmotionShare,
+                    subject: emotion.to_string(),
+                    baseline: before,
+                    recent: after,
+                    delta: after - before,
+                });
+            }
+        }
+    }
+
+    let recent_tones: Vec<&ToneData> = recent.iter().filter_map(|r| r.tone.as_ref()).collect();
+    let baseline_tones: Vec<&ToneData> = baseline.iter().filter_map(|r| r.tone.as_ref()).collect();
+    if recent_tones.len() >= MIN_RECENT_SAMPLES && baseline_tones.len() >= MIN_BASELINE_SAMPLES {
+        for i in 0..5 {
+            let name = tone_dimensions(recent_tones[0])[i].0;
+            let before = dimension_stats(baseline_tones.iter().map(|t| tone_dimensions(t)[i].1));
+            let after = dimension_stats(recent_tones.iter().map(|t| tone_dimensions(t)[i].1));
+            let delta = after.mean - before.mean;
+            if delta.abs() >= TONE_SHIFT && delta.abs() >= before.variance.sqrt() {
+                shifts.push(TrendShift {
+                    kind: ShiftKind::ToneDimension,
+                    subject: name.to_string(),
+                    baseline: before.mean,
+                    recent: after.mean,
+                    delta,
+                });
+            }
+        }
+    }
+
+    shifts.sort_by(|a, b| b.delta.abs().total_cmp(&a.delta.abs()));
+    shifts
+}
+
+/// Compute emotion and tone trends as of `now`, bucketing by weekday and time
+/// of day in `tz`
+pub fn trends<Tz: TimeZone>(
+    recordings: &[RecordingMetadata],
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> EmotionTrends {
+    let all: Vec<&RecordingMetadata> = recordings.iter().collect();
+
+    EmotionTrends {
+        windows: TREND_WINDOWS
+            .iter()
+            .map(|&days| window_baseline(&within_days(recordings, now, days), Some(days)))
+            .collect(),
+        all_time: window_baseline(&all, None),
+        by_weekday: bucket_distributions(recordings, tz, &WEEKDAYS, |t| {
+            t.weekday().num_days_from_monday() as usize
+        }),
+        by_time_of_day: bucket_distributions(recordings, tz, &TIMES_OF_DAY, |t| {
+            t.hour() as usize / 6
+        }),
+        shifts: detect_shifts(recordings, now),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::AudioValidationMeta;
+
+    fn recording(created_at: DateTime<Utc>, emotion: &str, energy: u8) -> RecordingMetadata {
+        let mut m = RecordingMetadata::new(
+            1000,
+            16000,
+            32044,
+            AudioValidationMeta {
+                rms_energy: 0.05,
+                duration_ms: 1000,
+                sample_count: 16000,
+                passed: true,
+            },
+            "whisper-cpp".to_string(),
+            "german-turbo".to_string(),
+        );
+        m.created_at = created_at.to_rfc3339();
+        m.emotion = Some(EmotionData {
+            primary: emotion.to_string(),
+            confidence: 0.8,
+            secondary: None,
+        });
+        m.tone = Some(ToneData {
+            formality: 3,
+            professionalism: 3,
+            directness: 3,
+            energy,
+            seriousness: 3,
+            confidence: 0.9,
+        });
+        m
+    }
+
+    #[test]
+    fn test_windows_buckets_and_tone_stats() {
+        // Wednesday 2026-03-04, 20:00 UTC
+        let now = Utc.with_ymd_and_hms(2026, 3, 4, 20, 0, 0).unwrap();
+        let recordings = vec![
+            recording(now - Duration::hours(1), "joy", 4),
+            recording(now - Duration::days(1) - Duration::hours(12), "joy", 2),
+            recording(now - Duration::days(20) - Duration::hours(18), "stress", 3),
+            recording(now - Duration::days(200) - Duration::hours(15), "stress", 3),
+        ];
+
+        let trends = trends(&recordings, now, &Utc);
+        let counts: Vec<_> = trends.windows.iter().map(|w| w.sample_count).collect();
+        assert_eq!(counts, [2, 3, 3]);
+        assert_eq!(trends.all_time.sample_count, 4);
+        assert_eq!(trends.windows[0].dominant_emotion.as_deref(), Some("joy"));
+
+        let week_tone = trends.windows[0].tone.as_ref().unwrap();
+        assert_eq!(
+            week_tone.energy,
+            DimensionStats {
+                mean: 3.0,
+                variance: 1.0
+            }
+        );
+        assert_eq!(week_tone.formality.variance, 0.0);
+
+        // Wednesday 19:00, Tuesday 08:00, Thursday 02:00, Saturday 05:00
+        let weekdays: Vec<_> = trends.by_weekday.iter().map(|b| b.sample_count).collect();
+        assert_eq!(weekdays, [0, 1, 1, 1, 0, 1, 0]);
+        assert_eq!(trends.by_weekday[1].emotions[0].emotion, "joy");
+        let times: Vec<_> = trends
+            .by_time_of_day
+            .iter()
+            .map(|b| b.sample_count)
+            .collect();
+        assert_eq!(times, [2, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_shift_against_previous_baseline() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 4, 20, 0, 0).unwrap();
+        let mut recordings: Vec<_> = (10..16)
+            .map(|d| recording(now - Duration::days(d), "calm", 2))
+            .collect();
+        assert!(trends(&recordings, now, &Utc).shifts.is_empty());
+
+        recordings.extend((0..3).map(|d| recording(now - Duration::days(d), "stress", 4)));
+        let shifts = trends(&recordings, now, &Utc).shifts;
+        let subjects: Vec<_> = shifts
+            .iter()
+            .map(|s| (s.kind, s.subject.as_str()))
+            .collect();
+        assert!(subjects.contains(&(ShiftKind::EmotionShare, "stress")));
+        assert!(subjects.contains(&(ShiftKind::EmotionShare, "calm")));
+        assert!(subjects.contains(&(ShiftKind::ToneDimension, "energy")));
+        assert_eq!(subjects.len(), 3);
+
+        let stress = shifts.iter().find(|s| s.subject == "stress").unwrap();
+        assert_eq!((stress.baseline, stress.recent), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,222 @@
+//! User Annotations
+//!
+//! Title, notes, tags and pinning the user adds to a saved recording. Updates are
+//! validated and normalized here; the storage manager writes them into the
+//! sidecar (atomically, keeping its encryption format) and stamps `editedAt`.
+//!
+//! Tags are stored normalized (trimmed, lowercase, unique, sorted) so filters
+//! and per-tag baselines can compare them directly.
+
+use serde::{Deserialize, Serialize};
+
+use super::RecordingMetadata;
+
+/// Maximum title length in characters
+pub const MAX_TITLE_LENGTH: usize = 200;
+
+/// Maximum notes length in characters
+pub const MAX_NOTES_LENGTH: usize = 10_000;
+
+/// Maximum number of tags per recording
+pub const MAX_TAGS: usize = 20;
+
+/// Maximum tag length in characters
+pub const MAX_TAG_LENGTH: usize = 40;
+
+/// Changes to a recording's user fields (absent fields are left unchanged)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingMetadataUpdate {
+    /// New title; an empty string removes it
+    pub title: Option<String>,
+    /// New notes; an empty string removes them
+    pub notes: Option<String>,
+    /// Replaces all tags
+    pub tags: Option<Vec<String>>,
+    /// Pin (keep during automatic cleanup) or unpin
+    pub pinned: Option<bool>,
+}
+
+/// Normalize a tag for storage and comparison
+pub fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn validate_tag(tag: &str) -> Result<(), String> {
+    if tag.is_empty() {
+        return Err("Tags must not be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(format!(
+            "Tag '{}' too long (max {} characters)",
+            tag, MAX_TAG_LENGTH
+        ));
+    }
+    if !tag
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+    {
+        return Err(format!(
+            "Tag '{}' may only contain letters, digits, spaces, '-' and '_'",
+            tag
+        ));
+    }
+    Ok(())
+}
+
+/// Trimmed text, `None` if empty; rejects overlong text and control characters
+fn validate_text(
+    value: &str,
+    name: &str,
+    max_length: usize,
+    allow_newlines: bool,
+) -> Result<Option<String>, String> {
+    let value = value.trim();
+    if value.chars().count() > max_length {
+        return Err(format!("{} too long (max {} characters)", name, max_length));
+    }
+    if value
+        .chars()
+        .any(|c| c.is_control() && !(allow_newlines && matches!(c, '\n' | '\t')))
+    {
+        return Err(format!("{} contains control characters", name));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+impl RecordingMetadataUpdate {
+    /// Validate the update and apply it to `metadata`.
+    /// Nothing is changed if validation fails.
+    pub fn apply_to(&self, metadata: &mut RecordingMetadata) -> Result<(), String> {
+        let title = self
+            .title
+            .as_deref()
+            .map(|t| validate_text(t, "Title", MAX_TITLE_LENGTH, false))
+            .transpose()?;
+        let notes = self
+            .notes
+            .as_deref()
+            .map(|n| validate_text(n, "Notes", MAX_NOTES_LENGTH, true))
+            .transpose()?;
+        let tags = self
+            .tags
+            .as_ref()
+            .map(|tags| {
+                let mut normalized: Vec<String> = tags.iter().map(|t| normalize_tag(t)).collect();
+                normalized.iter().try_for_each(|t| validate_tag(t))?;
+                normalized.sort();
+                normalized.dedup();
+                if normalized.len() > MAX_TAGS {
+                    return Err(format!("Too many tags (max {})", MAX_TAGS));
+                }
+                Ok(normalized)
+            })
+            .transpose()?;
+
+        if let Some(title) = title {
+            metadata.title = title;
+        }
+        if let Some(notes) = notes {
+            metadata.notes = notes;
+        }
+        if let Some(tags) = tags {
+            metadata.tags = tags;
+        }
+        if let Some(pinned) = self.pinned {
+            metadata.pinned = pinned;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::AudioValidationMeta;
+
+    fn metadata() -> RecordingMetadata {
+        RecordingMetadata::new(
+            1000,
+            16000,
+            32044,
+            AudioValidationMeta {
+                rms_energy: 0.05,
+                duration_ms: 1000,
+                sample_count: 16000,
+                passed: true,
+            },
+            "whisper-cpp".to_string(),
+            "german-turbo".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_update_normalizes_and_clears() {
+        let mut m = metadata();
+        RecordingMetadataUpdate {
+            title: Some("  Gespräch mit Anna ".to_string()),
+            notes: Some("Zeile 1\nZeile 2".to_string()),
+            tags: Some(vec![
+                "Arbeit".to_string(),
+                " familie  urlaub ".to_string(),
+                "arbeit".to_string(),
+            ]),
+            pinned: Some(true),
+        }
+        .apply_to(&mut m)
+        .unwrap();
+        assert_eq!(m.title.as_deref(), Some("Gespräch mit Anna"));
+        assert_eq!(m.notes.as_deref(), Some("Zeile 1\nZeile 2"));
+        assert_eq!(m.tags, ["arbeit", "familie urlaub"]);
+
+        // Absent fields stay, empty ones are removed
+        RecordingMetadataUpdate {
+            title: Some(String::new()),
+            ..Default::default()
+        }
+        .apply_to(&mut m)
+        .unwrap();
+        assert_eq!(m.title, None);
+        assert!(m.notes.is_some());
+        assert!(m.pinned);
+        assert_eq!(m.tags.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_update_changes_nothing() {
+        let mut m = metadata();
+        let invalid = [
+            RecordingMetadataUpdate {
+                title: Some("a".repeat(MAX_TITLE_LENGTH + 1)),
+                ..Default::default()
+            },
+            RecordingMetadataUpdate {
+                title: Some("Zeile\nUmbruch".to_string()),
+                ..Default::default()
+            },
+            RecordingMetadataUpdate {
+                tags: Some(vec!["work/../x".to_string()]),
+                ..Default::default()
+            },
+            RecordingMetadataUpdate {
+                tags: Some(vec!["   ".to_string()]),
+                ..Default::default()
+            },
+            RecordingMetadataUpdate {
+                notes: Some("ok".to_string()),
+                tags: Some((0..=MAX_TAGS).map(|i| format!("tag{}", i)).collect()),
+                pinned: Some(true),
+                title: None,
+            },
+        ];
+        for update in invalid {
+            assert!(update.apply_to(&mut m).is_err());
+        }
+        assert_eq!(m.notes, None);
+        assert!(m.tags.is_empty());
+        assert!(!m.pinned);
+    }
+}
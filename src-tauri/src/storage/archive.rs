@@ -0,0 +1,501 @@
+//! Portable Library Archives
+//!
+//! Export packs recordings into a gzip-compressed tar archive:
+//!
+//! ```text
+//! recordings/<base_name>.json   # Metadata (current schema, plaintext)
+//! recordings/<base_name>.wav    # Audio (plaintext WAV, if present)
+//! manifest.json                 # App/schema version, SHA-256 per file
+//! ```
+//!
+//! Files are always written decrypted, because the data key never leaves the
+//! machine's keyring, and audio as WAV, because the target build may lack a
+//! codec. Import re-encodes and re-encrypts according to the target library's
+//! settings.
+//!
+//! Import never extracts to paths taken from the archive: entries are checked
+//! against a strict name pattern and staged in a hidden directory inside the
+//! storage directory, where they are verified against the manifest before any
+//! recording is added to the library.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::schema::CURRENT_SCHEMA_VERSION;
+use super::set_secure_permissions;
+
+/// Archive layout version written by this build
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest entry
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Directory of recording files inside the archive
+const RECORDINGS_DIR: &str = "recordings";
+
+/// Maximum size of the manifest (guards against memory exhaustion)
+const MAX_MANIFEST_SIZE: u64 = 16 * 1024 * 1024; // 16 MB
+
+/// Maximum size of a single recording file inside an archive
+const MAX_ENTRY_SIZE: u64 = 500 * 1024 * 1024; // 500 MB
+
+/// Prefix of the staging directory used during import (hidden, inside the storage directory)
+const STAGING_DIR_PREFIX: &str = ".import-";
+
+/// Archive manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// Hablará version that wrote the archive
+    pub app_version: String,
+    /// Metadata schema version of the contained sidecars
+    pub schema_version: u32,
+    pub created_at: String,
+    pub recordings: Vec<ArchiveRecording>,
+}
+
+/// A recording listed in the manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveRecording {
+    pub id: String,
+    pub base_name: String,
+    pub files: Vec<ArchiveFile>,
+}
+
+/// A file inside the archive with its checksum
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveFile {
+    /// Path inside the archive (`recordings/<base_name>.<ext>`)
+    pub name: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256
+    pub sha256: String,
+}
+
+/// Lowercase hex SHA-256 of `bytes`
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+/// Lowercase hex encoding of a digest
+pub(super) fn hex(digest: &[u8]) -> String {
+    let mut out = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+/// Archive path of a recording file
+pub fn entry_name(base_name: &str, ext: &str) -> String {
+    format!("{}/{}.{}", RECORDINGS_DIR, base_name, ext)
+}
+
+/// Whether `name` is an entry this format may contain.
+///
+/// Security: Only `manifest.json` and `recordings/<stem>.{json,wav}` with a
+/// plain file stem are accepted, so no entry can point outside the staging
+/// directory (no `..`, separators, absolute or drive paths).
+fn is_allowed_entry(name: &str) -> bool {
+    if name == MANIFEST_NAME {
+        return true;
+    }
+    let Some(file) = name
+        .strip_prefix(RECORDINGS_DIR)
+        .and_then(|n| n.strip_prefix('/'))
+    else {
+        return false;
+    };
+    let Some((stem, ext)) = file.rsplit_once('.') else {
+        return false;
+    };
+    matches!(ext, "json" | "wav")
+        && !stem.is_empty()
+        && stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+/// Streaming writer for a library archive.
+///
+/// Writes to `<dest>.partial` and renames into place on [`ArchiveWriter::finish`],
+/// so an aborted export never leaves a truncated archive behind.
+pub struct ArchiveWriter {
+    /// Taken by `finish`
+    builder: Option<tar::Builder<GzEncoder<std::fs::File>>>,
+    dest: PathBuf,
+    partial: PathBuf,
+    recordings: Vec<ArchiveRecording>,
+}
+
+impl ArchiveWriter {
+    pub fn create(dest: &Path) -> Result<Self, String> {
+        let partial = dest.with_extension("partial");
+        let file = std::fs::File::create(&partial)
+            .map_err(|e| format!("Failed to create archive: {}", e))?;
+        set_secure_permissions(&partial)?;
+
+        Ok(Self {
+            builder: Some(tar::Builder::new(GzEncoder::new(
+                file,
+                Compression::default(),
+            ))),
+            dest: dest.to_path_buf(),
+            partial,
+            recordings: Vec::new(),
+        })
+    }
+
+    /// Add one recording (plaintext metadata JSON and optional audio)
+    pub fn add_recording(
+        &mut self,
+        id: &str,
+        base_name: &str,
+        metadata_json: &[u8],
+        audio: Option<&[u8]>,
+    ) -> Result<(), String> {
+        let mut files = vec![self.append(&entry_name(base_name, "json"), metadata_json)?];
+        if let Some(audio) = audio {
+            files.push(self.append(&entry_name(base_name, "wav"), audio)?);
+        }
+
+        self.recordings.push(ArchiveRecording {
+            id: id.to_string(),
+            base_name: base_name.to_string(),
+            files,
+        });
+        Ok(())
+    }
+
+    fn append(&mut self, name: &str, content: &[u8]) -> Result<ArchiveFile, String> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        header.set_cksum();
+
+        self.builder
+            .as_mut()
+            .ok_or_else(|| "Archive already finished".to_string())?
+            .append_data(&mut header, name, content)
+            .map_err(|e| format!("Failed to write archive entry: {}", e))?;
+
+        Ok(ArchiveFile {
+            name: name.to_string(),
+            size: content.len() as u64,
+            sha256: sha256_hex(content),
+        })
+    }
+
+    /// Append the manifest and move the archive into place
+    pub fn finish(mut self) -> Result<ArchiveManifest, String> {
+        let manifest = ArchiveManifest {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: CURRENT_SCHEMA_VERSION,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            recordings: std::mem::take(&mut self.recordings),
+        };
+        let content = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        self.append(MANIFEST_NAME, &content)?;
+
+        let encoder = self
+            .builder
+            .take()
+            .ok_or_else(|| "Archive already finished".to_string())?
+            .into_inner()
+            .map_err(|e| format!("Failed to finish archive: {}", e))?;
+        let file = encoder
+            .finish()
+            .map_err(|e| format!("Failed to finish archive: {}", e))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to flush archive: {}", e))?;
+
+        std::fs::rename(&self.partial, &self.dest)
+            .map_err(|e| format!("Failed to move archive into place: {}", e))?;
+        Ok(manifest)
+    }
+}
+
+impl Drop for ArchiveWriter {
+    fn drop(&mut self) {
+        // No-op after a successful rename; removes the partial file otherwise
+        let _ = std::fs::remove_file(&self.partial);
+    }
+}
+
+/// Writer that hashes everything passing through it
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Staging directory, removed on drop
+struct StagingDir(PathBuf);
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.0) {
+            tracing::warn!(
+                path = %self.0.display(),
+                error = %e,
+                "Storage: Failed to remove import staging directory"
+            );
+        }
+    }
+}
+
+/// An archive extracted into a staging directory and verified against its manifest.
+/// The staging directory is removed on drop.
+pub struct StagedArchive {
+    dir: StagingDir,
+    pub manifest: ArchiveManifest,
+}
+
+impl StagedArchive {
+    /// Extract `archive` into a staging directory below `storage_dir` and
+    /// verify every file against the manifest.
+    pub fn extract(archive: &Path, storage_dir: &Path) -> Result<Self, String> {
+        let dir =
+            StagingDir(storage_dir.join(format!("{}{}", STAGING_DIR_PREFIX, uuid::Uuid::new_v4())));
+        std::fs::create_dir_all(dir.0.join(RECORDINGS_DIR))
+            .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+
+        let extracted = extract_entries(archive, &dir.0)?;
+        let manifest = verify_manifest(extracted)?;
+        Ok(Self { dir, manifest })
+    }
+
+    /// Read a staged recording file, if the archive contained it
+    pub fn read(&self, base_name: &str, ext: &str) -> Result<Option<Vec<u8>>, String> {
+        let path = self.dir.0.join(entry_name(base_name, ext));
+        if !path.exists() {
+            return Ok(None);
+        }
+        std::fs::read(&path)
+            .map(Some)
+            .map_err(|e| format!("Failed to read staged file: {}", e))
+    }
+}
+
+/// Stream all entries of `archive` into `staging_dir`.
+/// Returns the manifest bytes and the checksum of every staged file.
+fn extract_entries(archive: &Path, staging_dir: &Path) -> Result<ExtractedEntries, String> {
+    let file =
+        std::fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+
+    let mut manifest = None;
+    let mut files = HashMap::new();
+
+    for entry in tar
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?
+    {
+        let entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let name = entry
+            .path()
+            .map_err(|e| format!("Invalid archive entry name: {}", e))?
+            .to_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Archive entry name is not valid UTF-8".to_string())?;
+
+        // Security: Only regular files with known names (no links, devices, traversal)
+        if !entry.header().entry_type().is_file() || !is_allowed_entry(&name) {
+            return Err(format!("Unexpected archive entry: {}", name));
+        }
+        if files.contains_key(&name) || (name == MANIFEST_NAME && manifest.is_some()) {
+            return Err(format!("Duplicate archive entry: {}", name));
+        }
+
+        let limit = if name == MANIFEST_NAME {
+            MAX_MANIFEST_SIZE
+        } else {
+            MAX_ENTRY_SIZE
+        };
+        if entry.size() > limit {
+            return Err(format!("Archive entry too large: {}", name));
+        }
+        let mut limited = entry.take(limit);
+
+        if name == MANIFEST_NAME {
+            let mut content = Vec::new();
+            limited
+                .read_to_end(&mut content)
+                .map_err(|e| format!("Failed to read manifest: {}", e))?;
+            manifest = Some(content);
+            continue;
+        }
+
+        let path = staging_dir.join(&name);
+        let out = std::fs::File::create(&path)
+            .map_err(|e| format!("Failed to stage archive entry: {}", e))?;
+        set_secure_permissions(&path)?;
+        let mut writer = HashingWriter {
+            inner: out,
+            hasher: Sha256::new(),
+            size: 0,
+        };
+        std::io::copy(&mut limited, &mut writer)
+            .map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+
+        files.insert(
+            name.clone(),
+            ArchiveFile {
+                name,
+                size: writer.size,
+                sha256: hex(&writer.hasher.finalize()),
+            },
+        );
+    }
+
+    Ok(ExtractedEntries {
+        manifest: manifest.ok_or_else(|| "Archive has no manifest".to_string())?,
+        files,
+    })
+}
+
+struct ExtractedEntries {
+    manifest: Vec<u8>,
+    files: HashMap<String, ArchiveFile>,
+}
+
+/// Check the manifest is supported and matches the extracted files exactly
+fn verify_manifest(extracted: ExtractedEntries) -> Result<ArchiveManifest, String> {
+    let manifest: ArchiveManifest = serde_json::from_slice(&extracted.manifest)
+        .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+
+    if manifest.format_version == 0 || manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported archive format version {} (supported: {})",
+            manifest.format_version, ARCHIVE_FORMAT_VERSION
+        ));
+    }
+    if manifest.schema_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Archive was created by a newer app version ({}, schema version {})",
+            manifest.app_version, manifest.schema_version
+        ));
+    }
+
+    let mut listed = 0;
+    for recording in &manifest.recordings {
+        let has_metadata = recording
+            .files
+            .iter()
+            .any(|f| f.name == entry_name(&recording.base_name, "json"));
+        if !has_metadata {
+            return Err(format!(
+                "Archive recording {} has no metadata",
+                recording.id
+            ));
+        }
+
+        for file in &recording.files {
+            let expected_name = [
+                entry_name(&recording.base_name, "json"),
+                entry_name(&recording.base_name, "wav"),
+            ];
+            if !expected_name.contains(&file.name) {
+                return Err(format!("Unexpected file in manifest: {}", file.name));
+            }
+            match extracted.files.get(&file.name) {
+                Some(actual) if actual == file => listed += 1,
+                Some(_) => return Err(format!("Checksum mismatch: {}", file.name)),
+                None => return Err(format!("Missing archive file: {}", file.name)),
+            }
+        }
+    }
+
+    if listed != extracted.files.len() {
+        return Err("Archive contains files not listed in the manifest".to_string());
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_names_restricted() {
+        assert!(is_allowed_entry("manifest.json"));
+        assert!(is_allowed_entry(
+            "recordings/2026-01-01_10-00-00_abcd1234.wav"
+        ));
+
+        assert!(!is_allowed_entry("recordings/../../evil.json"));
+        assert!(!is_allowed_entry("/etc/passwd"));
+        assert!(!is_allowed_entry("recordings/sub/x.json"));
+        assert!(!is_allowed_entry("recordings/.json"));
+        assert!(!is_allowed_entry("recordings/x.sh"));
+        assert!(!is_allowed_entry("other/x.json"));
+    }
+
+    #[test]
+    fn test_archive_roundtrip_and_tamper_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("library.tar.gz");
+
+        let mut writer = ArchiveWriter::create(&archive).unwrap();
+        writer
+            .add_recording("id-1", "base_1", b"{}", Some(&[1, 2, 3]))
+            .unwrap();
+        writer.add_recording("id-2", "base_2", b"{}", None).unwrap();
+        let manifest = writer.finish().unwrap();
+        assert_eq!(manifest.recordings.len(), 2);
+        assert!(!archive.with_extension("partial").exists());
+
+        let staged = StagedArchive::extract(&archive, dir.path()).unwrap();
+        assert_eq!(
+            staged.manifest.recordings[0].files,
+            manifest.recordings[0].files
+        );
+        assert_eq!(staged.read("base_1", "wav").unwrap(), Some(vec![1, 2, 3]));
+        assert_eq!(staged.read("base_2", "wav").unwrap(), None);
+        let staging_dir = staged.dir.0.clone();
+        drop(staged);
+        assert!(!staging_dir.exists());
+
+        // Manifest checksum no longer matches the file content
+        let mut tampered = manifest.clone();
+        tampered.recordings[0].files[1].sha256 = sha256_hex(b"other");
+        let extracted = ExtractedEntries {
+            manifest: serde_json::to_vec(&tampered).unwrap(),
+            files: manifest
+                .recordings
+                .iter()
+                .flat_map(|r| r.files.clone())
+                .map(|f| (f.name.clone(), f))
+                .collect(),
+        };
+        assert!(verify_manifest(extracted)
+            .unwrap_err()
+            .contains("Checksum mismatch"));
+    }
+}
@@ -0,0 +1,338 @@
+//! Crash-Safe Writes
+//!
+//! Files are written to a temporary file next to the target, flushed to disk
+//! and renamed over it, so a crash or full disk never leaves a half-written
+//! file behind.
+//!
+//! A recording consists of two files (audio and JSON sidecar). Saving one is
+//! a small transaction: a journal listing the files is written first and
+//! removed on commit. [`recover`] runs when a storage directory is opened and
+//! completes saves whose files were all written, and rolls back the others.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::set_secure_permissions;
+
+/// Extension of save journals (`<base name>.pending`)
+pub const JOURNAL_EXTENSION: &str = "pending";
+
+/// Suffix of temporary files (`<file name>.tmp`)
+pub const TEMP_SUFFIX: &str = ".tmp";
+
+/// Temporary file used while writing `path`
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(TEMP_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// Flush a directory entry change (create, rename, remove) to disk
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::File::open(dir)?.sync_all()
+}
+
+/// Windows cannot open directories for syncing; NTFS journals renames itself
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn sync_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+        _ => Ok(()),
+    }
+}
+
+/// Write `content` to `path` atomically with owner-only permissions.
+///
+/// Readers see either the previous file or the complete new one.
+pub(crate) fn write_file(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp_path = temp_path(path);
+    // Leftover from an earlier crash
+    let _ = std::fs::remove_file(&tmp_path);
+
+    let result = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .map_err(|e| e.to_string())
+        .and_then(|mut file| {
+            // Restrict before any content is written
+            set_secure_permissions(&tmp_path)?;
+            file.write_all(content)
+                .and_then(|()| file.sync_all())
+                .map_err(|e| e.to_string())
+        })
+        .and_then(|()| std::fs::rename(&tmp_path, path).map_err(|e| e.to_string()))
+        .and_then(|()| sync_parent(path).map_err(|e| e.to_string()));
+
+    result.map_err(|e| {
+        let _ = std::fs::remove_file(&tmp_path);
+        format!("Failed to write {}: {}", path.display(), e)
+    })
+}
+
+/// A file belonging to a save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalFile {
+    name: String,
+    /// Existed before the save; never removed by a rollback
+    existed: bool,
+}
+
+/// Contents of a save journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Journal {
+    base_name: String,
+    files: Vec<JournalFile>,
+}
+
+impl Journal {
+    /// Remove the files this save created
+    fn roll_back(&self, dir: &Path) {
+        for file in self.files.iter().filter(|f| !f.existed) {
+            let path = dir.join(&file.name);
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "Storage: Failed to roll back interrupted save"
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The files of one recording, written as a unit.
+///
+/// Dropping an uncommitted transaction rolls it back right away; after a
+/// crash, [`recover`] does the same on the next start.
+pub struct SaveTransaction {
+    dir: PathBuf,
+    journal_path: PathBuf,
+    journal: Journal,
+    committed: bool,
+}
+
+impl SaveTransaction {
+    /// Start a save of `files` (names within `dir`) by writing its journal
+    pub fn begin(dir: &Path, base_name: &str, files: &[String]) -> Result<Self, String> {
+        let journal = Journal {
+            base_name: base_name.to_string(),
+            files: files
+                .iter()
+                .map(|name| JournalFile {
+                    name: name.clone(),
+                    existed: dir.join(name).exists(),
+                })
+                .collect(),
+        };
+        let journal_path = dir.join(format!("{}.{}", base_name, JOURNAL_EXTENSION));
+        let content = serde_json::to_vec_pretty(&journal)
+            .map_err(|e| format!("Failed to serialize save journal: {}", e))?;
+        write_file(&journal_path, &content)?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            journal_path,
+            journal,
+            committed: false,
+        })
+    }
+
+    /// Write one of the transaction's files
+    pub fn write(&mut self, name: &str, content: &[u8]) -> Result<(), String> {
+        if !self.journal.files.iter().any(|f| f.name == name) {
+            return Err(format!("File not part of the save: {}", name));
+        }
+        write_file(&self.dir.join(name), content)
+    }
+
+    /// Finish the save; all files must have been written
+    pub fn commit(mut self) -> Result<(), String> {
+        std::fs::remove_file(&self.journal_path)
+            .and_then(|()| sync_dir(&self.dir))
+            .map_err(|e| format!("Failed to commit save: {}", e))?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for SaveTransaction {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.journal.roll_back(&self.dir);
+            let _ = std::fs::remove_file(&self.journal_path);
+        }
+    }
+}
+
+/// Outcome of [`recover`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Interrupted saves whose files were all written
+    pub completed: usize,
+    /// Interrupted saves whose written files were removed
+    pub rolled_back: usize,
+    /// Leftover temporary files removed
+    pub temp_files_removed: usize,
+}
+
+/// Finish or roll back saves interrupted by a crash and remove leftover
+/// temporary files
+pub fn recover(dir: &Path) -> Result<RecoveryReport, String> {
+    let mut report = RecoveryReport::default();
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read storage directory: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if name.ends_with(TEMP_SUFFIX) {
+            if std::fs::remove_file(&path).is_ok() {
+                report.temp_files_removed += 1;
+            }
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != JOURNAL_EXTENSION) {
+            continue;
+        }
+
+        let journal = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| serde_json::from_slice::<Journal>(&bytes).map_err(|e| e.to_string()));
+        match journal {
+            // Only plain file names; a journal never points outside the directory
+            Ok(journal)
+                if journal
+                    .files
+                    .iter()
+                    .all(|f| Path::new(&f.name).file_name() == Some(f.name.as_ref())) =>
+            {
+                if journal.files.iter().all(|f| dir.join(&f.name).exists()) {
+                    report.completed += 1;
+                } else {
+                    journal.roll_back(dir);
+                    report.rolled_back += 1;
+                }
+                tracing::info!(
+                    name = %journal.base_name,
+                    "Storage: Resolved interrupted save"
+                );
+            }
+            Ok(_) => {
+                tracing::warn!(path = %path.display(), "Storage: Ignoring invalid save journal");
+            }
+            Err(e) => {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "Storage: Ignoring unreadable save journal"
+                );
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    sync_dir(dir).map_err(|e| format!("Failed to sync storage directory: {}", e))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_file_replaces_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.json");
+
+        write_file(&path, b"first").unwrap();
+        write_file(&path, b"second").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        assert!(!temp_path(&path).exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_failed_transaction_rolls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = ["rec.wav".to_string(), "rec.json".to_string()];
+
+        let mut transaction = SaveTransaction::begin(dir.path(), "rec", &files).unwrap();
+        transaction.write("rec.wav", b"RIFF").unwrap();
+        assert!(transaction.write("other.json", b"{}").is_err());
+        drop(transaction);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        let mut transaction = SaveTransaction::begin(dir.path(), "rec", &files).unwrap();
+        transaction.write("rec.wav", b"RIFF").unwrap();
+        transaction.write("rec.json", b"{}").unwrap();
+        transaction.commit().unwrap();
+        assert!(!dir.path().join("rec.pending").exists());
+        assert!(dir.path().join("rec.json").exists());
+    }
+
+    #[test]
+    fn test_recover_interrupted_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = |base: &str| [format!("{}.wav", base), format!("{}.json", base)];
+
+        // Crashed after the audio file: rolled back
+        let mut partial = SaveTransaction::begin(dir.path(), "partial", &files("partial")).unwrap();
+        partial.write("partial.wav", b"RIFF").unwrap();
+        std::mem::forget(partial);
+
+        // Crashed before removing the journal: completed
+        let mut written = SaveTransaction::begin(dir.path(), "written", &files("written")).unwrap();
+        written.write("written.wav", b"RIFF").unwrap();
+        written.write("written.json", b"{}").unwrap();
+        std::mem::forget(written);
+
+        // Interrupted overwrite keeps the previous file
+        std::fs::write(dir.path().join("kept.json"), b"{}").unwrap();
+        let kept = SaveTransaction::begin(dir.path(), "kept", &files("kept")).unwrap();
+        std::mem::forget(kept);
+
+        std::fs::write(dir.path().join("x.json.tmp"), b"{").unwrap();
+        std::fs::write(
+            dir.path().join("evil.pending"),
+            br#"{"baseName":"evil","files":[{"name":"../outside","existed":false}]}"#,
+        )
+        .unwrap();
+
+        let report = recover(dir.path()).unwrap();
+        assert_eq!(
+            report,
+            RecoveryReport {
+                completed: 1,
+                rolled_back: 2,
+                temp_files_removed: 1,
+            }
+        );
+
+        let mut remaining: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, ["kept.json", "written.json", "written.wav"]);
+    }
+}
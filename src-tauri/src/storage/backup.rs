@@ -0,0 +1,506 @@
+//! Incremental Backups
+//!
+//! Snapshots of the storage directory in a second directory, e.g. on an
+//! external drive:
+//!
+//! ```text
+//! <backup dir>/
+//!   2026-03-01_10-00-00/
+//!     backup-manifest.json      # Files with size, modification time, SHA-256
+//!     <base_name>.json          # Recording files as stored
+//!     <base_name>.wav           #   (encrypted files stay encrypted)
+//! ```
+//!
+//! Every snapshot is complete on its own, but files unchanged since the
+//! previous snapshot are hard links to it, so only new or changed files are
+//! copied and take space. File systems without hard links (FAT, exFAT) get
+//! plain copies. A snapshot is written to a hidden directory and renamed when
+//! complete; snapshots beyond the configured count are removed, oldest first.
+//!
+//! Copied files are read back and checked against their checksum. A restore
+//! verifies the whole snapshot before it touches the library.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::archive::hex;
+use super::atomic;
+use super::watcher::recording_base_name;
+
+/// Snapshot layout version written by this build
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Manifest inside every snapshot directory
+const MANIFEST_NAME: &str = "backup-manifest.json";
+
+/// Prefix of a snapshot that is still being written (hidden)
+const IN_PROGRESS_PREFIX: &str = ".in-progress-";
+
+/// Snapshot directory names (UTC creation time)
+const SNAPSHOT_NAME_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+
+/// Snapshot manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// Hablará version that wrote the snapshot
+    pub app_version: String,
+    pub created_at: String,
+    pub files: Vec<BackupFile>,
+}
+
+/// A file in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupFile {
+    /// File name (same as in the storage directory)
+    pub name: String,
+    pub size: u64,
+    /// Modification time of the source file (ms since epoch) when it was backed up
+    pub modified_ms: Option<i64>,
+    /// Lowercase hex SHA-256
+    pub sha256: String,
+}
+
+/// A snapshot in the backup directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSnapshot {
+    /// Directory name, used to restore it
+    pub name: String,
+    pub created_at: String,
+    pub recording_count: usize,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+impl BackupSnapshot {
+    fn from_manifest(name: &str, manifest: &BackupManifest) -> Self {
+        let recordings: Vec<&str> = manifest
+            .files
+            .iter()
+            .filter_map(|f| f.name.strip_suffix(".json"))
+            .collect();
+        Self {
+            name: name.to_string(),
+            created_at: manifest.created_at.clone(),
+            recording_count: recordings.len(),
+            file_count: manifest.files.len(),
+            total_bytes: manifest.files.iter().map(|f| f.size).sum(),
+        }
+    }
+}
+
+/// Result of creating a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub snapshot: BackupSnapshot,
+    /// New or changed files copied into the snapshot
+    pub copied_files: usize,
+    pub copied_bytes: u64,
+    /// Unchanged files linked from the previous snapshot
+    pub linked_files: usize,
+    /// Old snapshots removed by rotation
+    pub removed_snapshots: Vec<String>,
+}
+
+/// Result of restoring a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    /// Recordings missing from the library, copied back
+    pub restored: usize,
+    /// Recordings that differed; the library version was moved to the trash
+    pub replaced: usize,
+    /// Recordings identical to the snapshot
+    pub unchanged: usize,
+}
+
+impl BackupManifest {
+    /// Files per recording, keyed by base name
+    pub fn recordings(&self) -> BTreeMap<String, Vec<&BackupFile>> {
+        let mut recordings: BTreeMap<String, Vec<&BackupFile>> = BTreeMap::new();
+        for file in &self.files {
+            if let Some((base_name, _)) = file.name.rsplit_once('.') {
+                recordings
+                    .entry(base_name.to_string())
+                    .or_default()
+                    .push(file);
+            }
+        }
+        recordings
+    }
+}
+
+/// Lowercase hex SHA-256 of a file
+pub fn file_sha256(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Modification time in ms since epoch (`None` if the file system has none)
+pub(super) fn modified_ms(metadata: &std::fs::Metadata) -> Option<i64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as i64)
+}
+
+/// Copy `src` to `dst`, then read `dst` back and compare checksums.
+/// Returns the size and checksum.
+fn copy_verified(src: &Path, dst: &Path) -> Result<(u64, String), String> {
+    let mut input =
+        std::fs::File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+    let mut output = std::fs::File::create(dst)
+        .map_err(|e| format!("Failed to create {}: {}", dst.display(), e))?;
+
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = input
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        output
+            .write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write {}: {}", dst.display(), e))?;
+        size += read as u64;
+    }
+    output
+        .sync_all()
+        .map_err(|e| format!("Failed to write {}: {}", dst.display(), e))?;
+
+    let sha256 = hex(&hasher.finalize());
+    if file_sha256(dst)? != sha256 {
+        return Err(format!("Checksum mismatch after copying {}", dst.display()));
+    }
+    Ok((size, sha256))
+}
+
+/// Path of a snapshot, rejecting names that are not snapshot names
+pub fn snapshot_dir(backup_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    if NaiveDateTime::parse_from_str(name, SNAPSHOT_NAME_FORMAT).is_err() {
+        return Err(format!("Invalid backup name: {}", name));
+    }
+    let dir = backup_dir.join(name);
+    if !dir.join(MANIFEST_NAME).is_file() {
+        return Err(format!("Backup not found: {}", name));
+    }
+    Ok(dir)
+}
+
+fn read_manifest(snapshot_dir: &Path) -> Result<BackupManifest, String> {
+    let content = std::fs::read(snapshot_dir.join(MANIFEST_NAME))
+        .map_err(|e| format!("Failed to read backup manifest: {}", e))?;
+    let manifest: BackupManifest =
+        serde_json::from_slice(&content).map_err(|e| format!("Invalid backup manifest: {}", e))?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format {} is newer than supported format {}",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        ));
+    }
+    // Only plain file names; a manifest never points outside its snapshot
+    if let Some(file) = manifest
+        .files
+        .iter()
+        .find(|f| recording_base_name(snapshot_dir, &snapshot_dir.join(&f.name)).is_none())
+    {
+        return Err(format!("Invalid file in backup manifest: {}", file.name));
+    }
+    Ok(manifest)
+}
+
+/// Complete snapshots in the backup directory with their manifests, newest first
+fn snapshots(backup_dir: &Path) -> Result<Vec<(String, BackupManifest)>, String> {
+    let entries = std::fs::read_dir(backup_dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?;
+
+    let mut snapshots = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(dir) = snapshot_dir(backup_dir, &name) else {
+            continue;
+        };
+        match read_manifest(&dir) {
+            Ok(manifest) => snapshots.push((name, manifest)),
+            Err(e) => tracing::warn!(snapshot = %name, error = %e, "Backup: Skipping snapshot"),
+        }
+    }
+    // Names sort chronologically
+    snapshots.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(snapshots)
+}
+
+/// Snapshots in the backup directory, newest first
+pub fn list_snapshots(backup_dir: &Path) -> Result<Vec<BackupSnapshot>, String> {
+    Ok(snapshots(backup_dir)?
+        .iter()
+        .map(|(name, manifest)| BackupSnapshot::from_manifest(name, manifest))
+        .collect())
+}
+
+/// Creation time of the newest snapshot
+pub fn latest_snapshot_time(backup_dir: &Path) -> Result<Option<DateTime<Utc>>, String> {
+    Ok(snapshots(backup_dir)?.first().and_then(|(name, _)| {
+        NaiveDateTime::parse_from_str(name, SNAPSHOT_NAME_FORMAT)
+            .ok()
+            .map(|t| t.and_utc())
+    }))
+}
+
+/// Remove an incomplete snapshot directory, if one is left over
+fn remove_dir(dir: &Path) {
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        tracing::warn!(path = %dir.display(), error = %e, "Backup: Failed to remove directory");
+    }
+}
+
+/// Write a snapshot of the recording files in `storage_dir` and keep the
+/// newest `keep` snapshots. `progress` receives (processed, total) files.
+pub fn create_snapshot(
+    storage_dir: &Path,
+    backup_dir: &Path,
+    keep: usize,
+    now: DateTime<Utc>,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<BackupResult, String> {
+    // Leftovers of interrupted backups
+    for entry in std::fs::read_dir(backup_dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?
+        .flatten()
+    {
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with(IN_PROGRESS_PREFIX)
+        {
+            remove_dir(&entry.path());
+        }
+    }
+
+    let mut sources: Vec<(String, std::fs::Metadata)> = std::fs::read_dir(storage_dir)
+        .map_err(|e| format!("Failed to read storage directory: {}", e))?
+        .flatten()
+        .filter(|e| recording_base_name(storage_dir, &e.path()).is_some())
+        .filter_map(|e| {
+            let metadata = e.metadata().ok().filter(|m| m.is_file())?;
+            Some((e.file_name().to_string_lossy().to_string(), metadata))
+        })
+        .collect();
+    sources.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let previous = snapshots(backup_dir)?.into_iter().next();
+    let previous_files: HashMap<&str, &BackupFile> = previous
+        .iter()
+        .flat_map(|(_, manifest)| manifest.files.iter().map(|f| (f.name.as_str(), f)))
+        .collect();
+    let previous_dir = previous.as_ref().map(|(name, _)| backup_dir.join(name));
+
+    let name = now.format(SNAPSHOT_NAME_FORMAT).to_string();
+    let final_dir = backup_dir.join(&name);
+    if final_dir.exists() {
+        return Err(format!("Backup {} already exists", name));
+    }
+    let staging = backup_dir.join(format!("{}{}", IN_PROGRESS_PREFIX, name));
+    std::fs::create_dir(&staging)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let result = (|| {
+        let mut files = Vec::with_capacity(sources.len());
+        let (mut copied_files, mut copied_bytes, mut linked_files) = (0, 0, 0);
+
+        for (i, (file_name, metadata)) in sources.iter().enumerate() {
+            let modified = modified_ms(metadata);
+            let target = staging.join(file_name);
+            let unchanged = previous_files.get(file_name.as_str()).filter(|f| {
+                f.size == metadata.len() && modified.is_some() && f.modified_ms == modified
+            });
+            let linked = match (unchanged, &previous_dir) {
+                (Some(file), Some(dir)) => std::fs::hard_link(dir.join(file_name), &target)
+                    .ok()
+                    .map(|()| (*file).clone()),
+                _ => None,
+            };
+
+            match linked {
+                Some(file) => {
+                    linked_files += 1;
+                    files.push(file);
+                }
+                None => {
+                    let (size, sha256) = copy_verified(&storage_dir.join(file_name), &target)?;
+                    copied_files += 1;
+                    copied_bytes += size;
+                    files.push(BackupFile {
+                        name: file_name.clone(),
+                        size,
+                        modified_ms: modified,
+                        sha256,
+                    });
+                }
+            }
+            progress(i + 1, sources.len());
+        }
+
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: now.to_rfc3339(),
+            files,
+        };
+        let content = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+        atomic::write_file(&staging.join(MANIFEST_NAME), &content)?;
+        std::fs::rename(&staging, &final_dir)
+            .map_err(|e| format!("Failed to finish backup: {}", e))?;
+
+        Ok(BackupResult {
+            snapshot: BackupSnapshot::from_manifest(&name, &manifest),
+            copied_files,
+            copied_bytes,
+            linked_files,
+            removed_snapshots: Vec::new(),
+        })
+    })();
+
+    let mut result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            remove_dir(&staging);
+            return Err(e);
+        }
+    };
+
+    // Rotation: hard links keep files alive that newer snapshots still use
+    for (old, _) in snapshots(backup_dir)?.into_iter().skip(keep.max(1)) {
+        remove_dir(&backup_dir.join(&old));
+        result.removed_snapshots.push(old);
+    }
+    Ok(result)
+}
+
+/// Check every file of a snapshot against its manifest
+pub fn verify_snapshot(snapshot_dir: &Path) -> Result<BackupManifest, String> {
+    let manifest = read_manifest(snapshot_dir)?;
+    let damaged: Vec<&str> = manifest
+        .files
+        .iter()
+        .filter(|f| {
+            let path = snapshot_dir.join(&f.name);
+            std::fs::metadata(&path).map(|m| m.len()).ok() != Some(f.size)
+                || file_sha256(&path).ok().as_deref() != Some(f.sha256.as_str())
+        })
+        .map(|f| f.name.as_str())
+        .collect();
+
+    if let Some(first) = damaged.first() {
+        return Err(format!(
+            "Backup is damaged: {} of {} files missing or altered (e.g. {})",
+            damaged.len(),
+            manifest.files.len(),
+            first
+        ));
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn names(backup_dir: &Path) -> Vec<String> {
+        list_snapshots(backup_dir)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_snapshots_with_rotation() {
+        let storage = tempfile::tempdir().unwrap();
+        let backup = tempfile::tempdir().unwrap();
+        std::fs::write(storage.path().join("a.json"), b"{}").unwrap();
+        std::fs::write(storage.path().join("a.wav"), b"RIFF-a").unwrap();
+        std::fs::write(storage.path().join(".hablara-catalog.sqlite3"), b"db").unwrap();
+
+        let start = Utc::now();
+        let first = create_snapshot(storage.path(), backup.path(), 2, start, |_, _| {}).unwrap();
+        assert_eq!(first.copied_files, 2);
+        assert_eq!(first.linked_files, 0);
+        assert_eq!(first.snapshot.recording_count, 1);
+
+        std::fs::write(storage.path().join("b.json"), b"{\"b\":1}").unwrap();
+        let mut calls = Vec::new();
+        let second = create_snapshot(
+            storage.path(),
+            backup.path(),
+            2,
+            start + Duration::seconds(1),
+            |done, total| calls.push((done, total)),
+        )
+        .unwrap();
+        // Only the new file is copied
+        assert_eq!((second.copied_files, second.linked_files), (1, 2));
+        assert_eq!(calls.last(), Some(&(3, 3)));
+        assert!(verify_snapshot(&backup.path().join(&second.snapshot.name)).is_ok());
+
+        let third = create_snapshot(
+            storage.path(),
+            backup.path(),
+            2,
+            start + Duration::seconds(2),
+            |_, _| {},
+        )
+        .unwrap();
+        assert_eq!(third.removed_snapshots, [first.snapshot.name]);
+        assert_eq!(
+            names(backup.path()),
+            [third.snapshot.name, second.snapshot.name]
+        );
+        assert!(latest_snapshot_time(backup.path()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_verify_detects_damage_and_rejects_bad_names() {
+        let storage = tempfile::tempdir().unwrap();
+        let backup = tempfile::tempdir().unwrap();
+        std::fs::write(storage.path().join("a.json"), b"{}").unwrap();
+        let result =
+            create_snapshot(storage.path(), backup.path(), 5, Utc::now(), |_, _| {}).unwrap();
+
+        let dir = snapshot_dir(backup.path(), &result.snapshot.name).unwrap();
+        std::fs::write(dir.join("a.json"), b"{ }").unwrap();
+        assert!(verify_snapshot(&dir).unwrap_err().contains("damaged"));
+
+        assert!(snapshot_dir(backup.path(), "../outside").is_err());
+        assert!(snapshot_dir(backup.path(), "2020-01-01_00-00-00").is_err());
+    }
+}
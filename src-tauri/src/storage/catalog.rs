@@ -0,0 +1,771 @@
+//! Recording Catalog
+//!
+//! Embedded SQLite index stored next to the recordings.
+//! Serves ID lookups and sorted listings without re-reading every JSON sidecar.
+//!
+//! The JSON sidecars remain the source of truth: the catalog is rebuilt from
+//! them whenever it is missing, has an outdated schema, or is out of sync.
+//!
+//! When encryption at rest is enabled, the metadata column is encrypted with
+//! the library's data key, just like the sidecars.
+//!
+//! A companion FTS5 table (`recordings_fts`) holds the stemmed search terms of
+//! each recording. Terms of encrypted recordings are blinded with a keyed hash.
+//! The audio content hash column (duplicate lookups) is blinded the same way.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::crypto::{blind_term, DataKey, KeyStore, AAD_METADATA};
+use super::query::{PageCursor, RecordingSort};
+use super::retention::RetentionRow;
+use super::schema;
+use super::search::{self, SearchField};
+use super::watcher::recording_base_name;
+use super::{set_secure_permissions, RecordingMetadata};
+
+/// Catalog database file name (hidden, lives inside the storage directory)
+pub const CATALOG_FILE_NAME: &str = ".hablara-catalog.sqlite3";
+
+/// Catalog schema version (stored in `PRAGMA user_version`).
+/// Bump when the table layout changes - the catalog is then rebuilt from sidecars.
+const CATALOG_SCHEMA_VERSION: i32 = 5;
+
+/// A catalog row: metadata plus the on-disk base name of its files
+#[derive(Debug, Clone)]
+pub struct CatalogEntry {
+    /// File stem shared by `<base_name>.wav` and `<base_name>.json`
+    pub base_name: String,
+    pub metadata: RecordingMetadata,
+}
+
+/// Encoded catalog row, ready to be written
+struct PreparedRow {
+    metadata: Vec<u8>,
+    /// Space-separated index terms per [`SearchField`]
+    terms: Vec<String>,
+    /// Audio content hash (blinded like the terms)
+    content_hash: Option<String>,
+    blinded: bool,
+}
+
+/// SQLite-backed index over the recordings in one storage directory
+pub struct RecordingCatalog {
+    conn: Connection,
+    storage_dir: PathBuf,
+    keys: Arc<KeyStore>,
+    /// Encrypt metadata of newly written rows
+    encrypt: bool,
+}
+
+impl RecordingCatalog {
+    /// Open (or create) the catalog for a storage directory.
+    ///
+    /// Returns the catalog and whether it must be rebuilt from sidecars
+    /// (freshly created or schema changed).
+    pub fn open(storage_dir: &Path, keys: Arc<KeyStore>) -> Result<(Self, bool), String> {
+        let db_path = storage_dir.join(CATALOG_FILE_NAME);
+        let conn = Connection::open(&db_path)
+            .map_err(|e| format!("Failed to open recording catalog: {}", e))?;
+
+        // Security: Catalog contains the same data as the metadata sidecars
+        set_secure_permissions(&db_path)?;
+
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read catalog version: {}", e))?;
+
+        let needs_rebuild = version != CATALOG_SCHEMA_VERSION;
+        if needs_rebuild {
+            if version != 0 {
+                tracing::info!(
+                    from = version,
+                    to = CATALOG_SCHEMA_VERSION,
+                    "Catalog: Schema changed, recreating"
+                );
+            }
+            Self::create_schema(&conn)?;
+        }
+
+        Ok((
+            Self {
+                conn,
+                storage_dir: storage_dir.to_path_buf(),
+                keys,
+                encrypt: false,
+            },
+            needs_rebuild,
+        ))
+    }
+
+    /// Drop and recreate all catalog tables
+    fn create_schema(conn: &Connection) -> Result<(), String> {
+        conn.execute_batch(&format!(
+            "DROP TABLE IF EXISTS recordings;
+             CREATE TABLE recordings (
+                 id TEXT PRIMARY KEY NOT NULL,
+                 base_name TEXT NOT NULL,
+                 created_at TEXT NOT NULL,
+                 duration_ms INTEGER NOT NULL,
+                 file_size INTEGER NOT NULL,
+                 pinned INTEGER NOT NULL,
+                 content_hash TEXT,
+                 metadata BLOB NOT NULL
+             );
+             CREATE INDEX idx_recordings_created_at ON recordings(created_at);
+             CREATE INDEX idx_recordings_content_hash ON recordings(content_hash);
+             DROP TABLE IF EXISTS recordings_fts;
+             CREATE VIRTUAL TABLE recordings_fts USING fts5(
+                 id UNINDEXED,
+                 transcription,
+                 original_text,
+                 enrichment,
+                 fallacy_quotes,
+                 blinded UNINDEXED
+             );
+             PRAGMA user_version = {};",
+            CATALOG_SCHEMA_VERSION
+        ))
+        .map_err(|e| format!("Failed to create catalog schema: {}", e))
+    }
+
+    /// Storage directory this catalog belongs to
+    pub fn storage_dir(&self) -> &Path {
+        &self.storage_dir
+    }
+
+    /// Set whether newly written rows are encrypted (existing rows are read either way)
+    pub fn set_encrypt(&mut self, encrypt: bool) {
+        self.encrypt = encrypt;
+    }
+
+    /// Serialize (and optionally encrypt) metadata and extract its search terms
+    fn prepare_row(&self, metadata: &RecordingMetadata) -> Result<PreparedRow, String> {
+        let json = serde_json::to_vec(metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        let sealed = self.keys.seal(&json, AAD_METADATA, self.encrypt)?;
+
+        let key = if self.encrypt {
+            Some(self.keys.get_or_create()?)
+        } else {
+            None
+        };
+        let terms = SearchField::ALL
+            .iter()
+            .map(|field| {
+                let terms = search::index_terms(&field.text(metadata));
+                match &key {
+                    Some(key) => terms.iter().map(|t| blind_term(key, t)).collect::<Vec<_>>(),
+                    None => terms,
+                }
+                .join(" ")
+            })
+            .collect();
+
+        let content_hash = metadata.content_hash.as_ref().map(|hash| match &key {
+            Some(key) => blind_term(key, hash),
+            None => hash.clone(),
+        });
+
+        Ok(PreparedRow {
+            metadata: sealed,
+            terms,
+            content_hash,
+            blinded: key.is_some(),
+        })
+    }
+
+    /// Write a prepared row to the recordings and search tables
+    fn write_row(
+        conn: &Connection,
+        base_name: &str,
+        metadata: &RecordingMetadata,
+        row: &PreparedRow,
+    ) -> Result<(), String> {
+        conn.execute(
+            "INSERT OR REPLACE INTO recordings
+                 (id, base_name, created_at, duration_ms, file_size, pinned, content_hash, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                metadata.id,
+                base_name,
+                metadata.created_at,
+                metadata.duration_ms as i64,
+                metadata.file_size as i64,
+                metadata.pinned,
+                row.content_hash,
+                row.metadata
+            ],
+        )
+        .map_err(|e| format!("Failed to update catalog: {}", e))?;
+
+        conn.execute("DELETE FROM recordings_fts WHERE id = ?1", params![metadata.id])
+            .map_err(|e| format!("Failed to update search index: {}", e))?;
+        conn.execute(
+            "INSERT INTO recordings_fts
+                 (id, transcription, original_text, enrichment, fallacy_quotes, blinded)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                metadata.id,
+                row.terms[0],
+                row.terms[1],
+                row.terms[2],
+                row.terms[3],
+                row.blinded
+            ],
+        )
+        .map_err(|e| format!("Failed to update search index: {}", e))?;
+        Ok(())
+    }
+
+    /// Insert or replace a recording
+    pub fn upsert(&mut self, base_name: &str, metadata: &RecordingMetadata) -> Result<(), String> {
+        let row = self.prepare_row(metadata)?;
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|e| format!("Failed to start catalog transaction: {}", e))?;
+        Self::write_row(&tx, base_name, metadata, &row)?;
+        tx.commit()
+            .map_err(|e| format!("Failed to commit catalog: {}", e))
+    }
+
+    /// Remove a recording (no-op if not present)
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM recordings WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to update catalog: {}", e))?;
+        self.conn
+            .execute("DELETE FROM recordings_fts WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to update search index: {}", e))?;
+        Ok(())
+    }
+
+    /// Full-text search for recordings containing all `terms` (index terms
+    /// from [`search::query_terms`]). Returns entries with their bm25 rank,
+    /// best match first (lower rank is better).
+    pub fn search(&self, terms: &[String], limit: usize) -> Result<Vec<(CatalogEntry, f64)>, String> {
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let key = self.blinding_key()?;
+
+        let match_expr = terms
+            .iter()
+            .map(|term| match &key {
+                Some(key) => format!("(\"{}\" OR \"{}\")", term, blind_term(key, term)),
+                None => format!("\"{}\"", term),
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        // Column weights in table order: id, 4 search fields, blinded
+        let weights = SearchField::ALL
+            .iter()
+            .map(|f| f.weight().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "SELECT r.base_name, r.metadata, bm25(recordings_fts, 0.0, {}, 0.0) AS rank
+             FROM recordings_fts JOIN recordings r ON r.id = recordings_fts.id
+             WHERE recordings_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+            weights
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to query search index: {}", e))?;
+        let rows = stmt
+            .query_map(params![match_expr, limit as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, f64>(2)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to query search index: {}", e))?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (base_name, sealed, rank) =
+                row.map_err(|e| format!("Failed to read search result: {}", e))?;
+            results.push((self.parse_entry(base_name, &sealed)?, rank));
+        }
+        Ok(results)
+    }
+
+    /// Look up a single recording by ID
+    pub fn find(&self, id: &str) -> Result<Option<CatalogEntry>, String> {
+        self.conn
+            .query_row(
+                "SELECT base_name, metadata FROM recordings WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query catalog: {}", e))?
+            .map(|(base_name, json)| self.parse_entry(base_name, &json))
+            .transpose()
+    }
+
+    /// Data key for matching blinded rows, `None` if there are none.
+    ///
+    /// Blinded rows only exist if the library was (partly) encrypted; only
+    /// then is the data key needed to query them.
+    fn blinding_key(&self) -> Result<Option<DataKey>, String> {
+        let has_blinded: bool = self
+            .conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM recordings_fts WHERE blinded = 1)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to query search index: {}", e))?;
+        if has_blinded {
+            self.keys.get()
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Oldest recording whose audio has `content_hash`
+    pub fn find_by_content_hash(&self, content_hash: &str) -> Result<Option<CatalogEntry>, String> {
+        let blinded = self
+            .blinding_key()?
+            .map(|key| blind_term(&key, content_hash))
+            .unwrap_or_else(|| content_hash.to_string());
+        self.conn
+            .query_row(
+                "SELECT base_name, metadata FROM recordings WHERE content_hash IN (?1, ?2)
+                 ORDER BY created_at ASC, id ASC LIMIT 1",
+                params![content_hash, blinded],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query catalog: {}", e))?
+            .map(|(base_name, json)| self.parse_entry(base_name, &json))
+            .transpose()
+    }
+
+    /// Look up a recording by the file stem of its sidecar
+    pub fn find_by_base_name(&self, base_name: &str) -> Result<Option<CatalogEntry>, String> {
+        self.conn
+            .query_row(
+                "SELECT base_name, metadata FROM recordings WHERE base_name = ?1",
+                params![base_name],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query catalog: {}", e))?
+            .map(|(base_name, json)| self.parse_entry(base_name, &json))
+            .transpose()
+    }
+
+    /// All recordings, newest first
+    pub fn list(&self) -> Result<Vec<CatalogEntry>, String> {
+        self.query_entries(
+            "SELECT base_name, metadata FROM recordings ORDER BY created_at DESC, id DESC",
+            [],
+        )
+    }
+
+    /// Retention data of all recordings, oldest first (no metadata decoding)
+    pub fn retention_rows(&self) -> Result<Vec<RetentionRow>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, base_name, created_at, pinned FROM recordings
+                 ORDER BY created_at ASC, id ASC",
+            )
+            .map_err(|e| format!("Failed to query catalog: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(RetentionRow {
+                    id: row.get(0)?,
+                    base_name: row.get(1)?,
+                    created_at: row.get(2)?,
+                    pinned: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query catalog: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read catalog row: {}", e))
+    }
+
+    /// Visit recordings in `sort` order, starting after `after` (keyset
+    /// pagination). Rows are decoded one at a time; the scan stops as soon as
+    /// `visit` returns `false`.
+    pub fn scan(
+        &self,
+        sort: RecordingSort,
+        after: Option<&PageCursor>,
+        mut visit: impl FnMut(CatalogEntry) -> bool,
+    ) -> Result<(), String> {
+        let (column, descending) = sort.column();
+        let (order, cmp) = if descending { ("DESC", "<") } else { ("ASC", ">") };
+        let sql = format!(
+            "SELECT base_name, metadata FROM recordings {} ORDER BY {col} {order}, id {order}",
+            if after.is_some() {
+                format!("WHERE ({}, id) {} (?1, ?2)", column, cmp)
+            } else {
+                String::new()
+            },
+            col = column,
+            order = order
+        );
+
+        let key: Vec<rusqlite::types::Value> = match after {
+            Some(cursor) => vec![
+                if column == "created_at" {
+                    cursor.created_at.clone().into()
+                } else {
+                    (cursor.duration_ms as i64).into()
+                },
+                cursor.id.clone().into(),
+            ],
+            None => Vec::new(),
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to query catalog: {}", e))?;
+        let mut rows = stmt
+            .query(rusqlite::params_from_iter(key))
+            .map_err(|e| format!("Failed to query catalog: {}", e))?;
+
+        while let Some(row) = rows
+            .next()
+            .map_err(|e| format!("Failed to read catalog row: {}", e))?
+        {
+            let base_name: String = row
+                .get(0)
+                .map_err(|e| format!("Failed to read catalog row: {}", e))?;
+            let sealed: Vec<u8> = row
+                .get(1)
+                .map_err(|e| format!("Failed to read catalog row: {}", e))?;
+            if !visit(self.parse_entry(base_name, &sealed)?) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of recordings in the catalog
+    pub fn count(&self) -> Result<usize, String> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM recordings", [], |row| row.get::<_, i64>(0))
+            .map(|n| n as usize)
+            .map_err(|e| format!("Failed to query catalog: {}", e))
+    }
+
+    /// Aggregate totals: (count, total file size, total duration)
+    pub fn totals(&self) -> Result<(usize, usize, u64), String> {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*), COALESCE(SUM(file_size), 0), COALESCE(SUM(duration_ms), 0)
+                 FROM recordings",
+                [],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)? as usize,
+                        row.get::<_, i64>(1)? as usize,
+                        row.get::<_, i64>(2)? as u64,
+                    ))
+                },
+            )
+            .map_err(|e| format!("Failed to query catalog: {}", e))
+    }
+
+    /// Replace the entire catalog contents in a single transaction
+    pub fn replace_all(&mut self, entries: &[CatalogEntry]) -> Result<(), String> {
+        let rows = entries
+            .iter()
+            .map(|entry| self.prepare_row(&entry.metadata))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|e| format!("Failed to start catalog transaction: {}", e))?;
+        tx.execute_batch("DELETE FROM recordings; DELETE FROM recordings_fts;")
+            .map_err(|e| format!("Failed to clear catalog: {}", e))?;
+        for (entry, row) in entries.iter().zip(&rows) {
+            Self::write_row(&tx, &entry.base_name, &entry.metadata, row)?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit catalog: {}", e))
+    }
+
+    fn query_entries<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<CatalogEntry>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed to query catalog: {}", e))?;
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .map_err(|e| format!("Failed to query catalog: {}", e))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (base_name, json) = row.map_err(|e| format!("Failed to read catalog row: {}", e))?;
+            entries.push(self.parse_entry(base_name, &json)?);
+        }
+        Ok(entries)
+    }
+
+    fn parse_entry(&self, base_name: String, sealed: &[u8]) -> Result<CatalogEntry, String> {
+        let json = self.keys.open(sealed, AAD_METADATA)?;
+        // Rows may predate a metadata schema bump - upgrade like sidecars
+        let (metadata, _) = schema::parse_metadata(&json)
+            .map_err(|e| format!("Corrupt catalog entry '{}': {}", base_name, e))?;
+        Ok(CatalogEntry { base_name, metadata })
+    }
+}
+
+/// Count metadata sidecars in a directory without parsing them.
+/// Used to detect a catalog that is out of sync with the files on disk.
+pub fn count_sidecars(storage_dir: &Path) -> usize {
+    std::fs::read_dir(storage_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+                // Hidden files (sync state) are not sidecars
+                .filter(|path| recording_base_name(storage_dir, path).is_some())
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::crypto::{is_encrypted, DataKey};
+    use crate::storage::AudioValidationMeta;
+
+    fn test_keys() -> Arc<KeyStore> {
+        Arc::new(KeyStore::with_key("test", DataKey::generate()))
+    }
+
+    fn metadata(created_at: &str) -> RecordingMetadata {
+        let mut m = RecordingMetadata::new(
+            1000,
+            16000,
+            32044,
+            AudioValidationMeta {
+                rms_energy: 0.05,
+                duration_ms: 1000,
+                sample_count: 16000,
+                passed: true,
+            },
+            "whisper-cpp".to_string(),
+            "german-turbo".to_string(),
+        );
+        m.created_at = created_at.to_string();
+        m
+    }
+
+    #[test]
+    fn test_open_creates_schema_and_requests_rebuild() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, needs_rebuild) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        assert!(needs_rebuild, "New catalog must be populated from sidecars");
+
+        let (_, needs_rebuild) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        assert!(!needs_rebuild, "Existing catalog should be reused");
+    }
+
+    #[test]
+    fn test_upsert_find_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut catalog, _) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        let m = metadata("2026-01-01T10:00:00+00:00");
+
+        catalog.upsert("base", &m).unwrap();
+        let found = catalog.find(&m.id).unwrap().expect("entry should exist");
+        assert_eq!(found.base_name, "base");
+        assert_eq!(found.metadata.id, m.id);
+        let by_name = catalog.find_by_base_name("base").unwrap().expect("entry should exist");
+        assert_eq!(by_name.metadata.id, m.id);
+
+        catalog.remove(&m.id).unwrap();
+        assert!(catalog.find(&m.id).unwrap().is_none());
+        assert!(catalog.find_by_base_name("base").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_list_sorted_newest_first_and_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut catalog, _) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        let old = metadata("2026-01-01T10:00:00+00:00");
+        let new = metadata("2026-02-01T10:00:00+00:00");
+        catalog.upsert("old", &old).unwrap();
+        catalog.upsert("new", &new).unwrap();
+
+        let listed = catalog.list().unwrap();
+        assert_eq!(listed[0].metadata.id, new.id);
+        assert_eq!(listed[1].metadata.id, old.id);
+
+        let rows = catalog.retention_rows().unwrap();
+        let row_ids: Vec<_> = rows.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(row_ids, [old.id.clone(), new.id.clone()]);
+        assert_eq!(rows[0].base_name, "old");
+        assert!(!rows[0].pinned);
+
+        let (count, size, duration) = catalog.totals().unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(size, 2 * 32044);
+        assert_eq!(duration, 2000);
+    }
+
+    #[test]
+    fn test_scan_pages_with_keyset() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut catalog, _) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        for (i, day) in ["01", "02", "03", "04"].iter().enumerate() {
+            let mut m = metadata(&format!("2026-01-{}T10:00:00+00:00", day));
+            m.duration_ms = [3000, 1000, 4000, 2000][i];
+            catalog.upsert(day, &m).unwrap();
+        }
+
+        let page = |sort: RecordingSort, after: Option<&PageCursor>| {
+            let mut entries = Vec::new();
+            catalog
+                .scan(sort, after, |e| {
+                    entries.push(e);
+                    entries.len() < 2
+                })
+                .unwrap();
+            entries
+        };
+
+        let first = page(RecordingSort::NewestFirst, None);
+        let names: Vec<_> = first.iter().map(|e| e.base_name.as_str()).collect();
+        assert_eq!(names, ["04", "03"]);
+        let cursor = PageCursor::after(RecordingSort::NewestFirst, &first[1].metadata);
+        let second = page(RecordingSort::NewestFirst, Some(&cursor));
+        let names: Vec<_> = second.iter().map(|e| e.base_name.as_str()).collect();
+        assert_eq!(names, ["02", "01"]);
+
+        let shortest = page(RecordingSort::ShortestFirst, None);
+        let cursor = PageCursor::after(RecordingSort::ShortestFirst, &shortest[1].metadata);
+        let rest = page(RecordingSort::ShortestFirst, Some(&cursor));
+        let durations: Vec<_> = shortest
+            .iter()
+            .chain(&rest)
+            .map(|e| e.metadata.duration_ms)
+            .collect();
+        assert_eq!(durations, [1000, 2000, 3000, 4000]);
+    }
+
+    #[test]
+    fn test_encrypted_rows_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut catalog, _) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        let plain = metadata("2026-01-01T10:00:00+00:00");
+        let secret = metadata("2026-02-01T10:00:00+00:00");
+
+        catalog.upsert("plain", &plain).unwrap();
+        catalog.set_encrypt(true);
+        catalog.upsert("secret", &secret).unwrap();
+
+        let raw: Vec<u8> = catalog
+            .conn
+            .query_row(
+                "SELECT metadata FROM recordings WHERE id = ?1",
+                params![secret.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(is_encrypted(&raw), "Metadata column must be encrypted");
+
+        // Mixed rows are both readable
+        assert_eq!(catalog.list().unwrap().len(), 2);
+        assert_eq!(catalog.find(&secret.id).unwrap().unwrap().base_name, "secret");
+    }
+
+    #[test]
+    fn test_find_by_content_hash_in_plain_and_blinded_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut catalog, _) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+        let mut plain = metadata("2026-01-01T10:00:00+00:00");
+        plain.content_hash = Some("aaaa".to_string());
+        let mut secret = metadata("2026-02-01T10:00:00+00:00");
+        secret.content_hash = Some("bbbb".to_string());
+
+        catalog.upsert("plain", &plain).unwrap();
+        catalog.set_encrypt(true);
+        catalog.upsert("secret", &secret).unwrap();
+
+        let raw: Option<String> = catalog
+            .conn
+            .query_row(
+                "SELECT content_hash FROM recordings WHERE id = ?1",
+                params![secret.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_ne!(raw.as_deref(), Some("bbbb"), "Hash of encrypted rows must be blinded");
+
+        let find = |hash: &str| catalog.find_by_content_hash(hash).unwrap().map(|e| e.metadata.id);
+        assert_eq!(find("aaaa"), Some(plain.id.clone()));
+        assert_eq!(find("bbbb"), Some(secret.id.clone()));
+        assert_eq!(find("cccc"), None);
+    }
+
+    #[test]
+    fn test_search_plain_and_blinded_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut catalog, _) = RecordingCatalog::open(dir.path(), test_keys()).unwrap();
+
+        let mut plain = metadata("2026-01-01T10:00:00+00:00");
+        plain.transcription = Some(crate::storage::TranscriptionMeta {
+            text: "Das Gespräch mit meiner Chefin war anstrengend".to_string(),
+            provider: "whisper-cpp".to_string(),
+            model: "german-turbo".to_string(),
+            language: "de".to_string(),
+            processing_time_ms: 100,
+        });
+        let mut secret = plain.clone();
+        secret.id = "secret".to_string();
+        secret.transcription.as_mut().unwrap().text = "Keine Pause heute".to_string();
+
+        catalog.upsert("plain", &plain).unwrap();
+        catalog.set_encrypt(true);
+        catalog.upsert("secret", &secret).unwrap();
+
+        let raw: String = catalog
+            .conn
+            .query_row(
+                "SELECT transcription FROM recordings_fts WHERE id = 'secret'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!raw.contains("paus"), "Encrypted rows must not store plaintext terms");
+
+        let hits = catalog.search(&search::query_terms("gespräche"), 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.metadata.id, plain.id);
+
+        let hits = catalog.search(&search::query_terms("Pause"), 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.metadata.id, "secret");
+
+        catalog.remove("secret").unwrap();
+        assert!(catalog.search(&search::query_terms("Pause"), 10).unwrap().is_empty());
+    }
+}
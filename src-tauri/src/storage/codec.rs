@@ -0,0 +1,497 @@
+//! Audio Storage Codecs
+//!
+//! Recordings arrive as WAV and can be stored as WAV, lossless FLAC or lossy
+//! Opus. The stored format is detected from the file content on read, and
+//! [`decode_to_wav`] always hands WAV back, so callers never see the codec.
+//!
+//! Opus needs libopus and is only available with the `opus-codec` feature.
+//! Opus files use a small in-house container (no Ogg):
+//! `MAGIC (8) | sample rate u32 | channels u8 | pre-skip u16 | sample count u64 | packets`,
+//! each packet prefixed with its length as u16 (all little endian).
+
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+
+use super::flac;
+
+/// File extensions of stored audio, one per codec
+pub const AUDIO_EXTENSIONS: [&str; 3] = ["wav", "flac", "opus"];
+
+/// Header identifying an Opus recording (container version 1)
+const OPUS_MAGIC: &[u8; 8] = b"HBLROPS1";
+
+/// Opus target bitrate (speech stays intelligible well below this)
+#[cfg(feature = "opus-codec")]
+const OPUS_BITRATE: i32 = 24_000;
+
+/// Opus frame length in milliseconds
+#[cfg(feature = "opus-codec")]
+const OPUS_FRAME_MS: u32 = 20;
+
+/// Codec used for stored recordings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    /// Uncompressed 16 kHz 16-bit PCM (as recorded)
+    #[default]
+    Wav,
+    /// Lossless, roughly half the size of WAV for speech
+    Flac,
+    /// Lossy speech codec, about 5% of the WAV size (`opus-codec` feature)
+    Opus,
+}
+
+impl AudioCodec {
+    /// File extension for this codec
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioCodec::Wav => "wav",
+            AudioCodec::Flac => "flac",
+            AudioCodec::Opus => "opus",
+        }
+    }
+
+    /// Whether this build can encode and decode the codec
+    pub fn is_available(self) -> bool {
+        self != AudioCodec::Opus || cfg!(feature = "opus-codec")
+    }
+
+    /// Codec of stored (decrypted) audio bytes, detected from the header
+    pub fn detect(audio: &[u8]) -> AudioCodec {
+        if audio.starts_with(b"fLaC") {
+            AudioCodec::Flac
+        } else if audio.starts_with(OPUS_MAGIC) {
+            AudioCodec::Opus
+        } else {
+            AudioCodec::Wav
+        }
+    }
+}
+
+/// Decoded PCM from a WAV file
+struct Pcm {
+    samples: Vec<i32>,
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+fn read_wav(wav: &[u8]) -> Result<Pcm, String> {
+    let mut reader =
+        hound::WavReader::new(Cursor::new(wav)).map_err(|e| format!("Invalid WAV: {}", e))?;
+    let spec = reader.spec();
+    if spec.sample_format != hound::SampleFormat::Int || !matches!(spec.bits_per_sample, 8 | 16 | 24)
+    {
+        return Err(format!(
+            "Unsupported WAV format: {:?} {} bit",
+            spec.sample_format, spec.bits_per_sample
+        ));
+    }
+
+    let samples = reader
+        .samples::<i32>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid WAV samples: {}", e))?;
+
+    Ok(Pcm {
+        samples,
+        channels: spec.channels,
+        sample_rate: spec.sample_rate,
+        bits_per_sample: spec.bits_per_sample,
+    })
+}
+
+fn write_wav(pcm: &Pcm) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: pcm.channels,
+        sample_rate: pcm.sample_rate,
+        bits_per_sample: pcm.bits_per_sample,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer =
+        hound::WavWriter::new(&mut cursor, spec).map_err(|e| format!("Failed to write WAV: {}", e))?;
+    for &sample in &pcm.samples {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("Failed to write WAV: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to write WAV: {}", e))?;
+
+    Ok(cursor.into_inner())
+}
+
+/// Encode WAV bytes with `codec`.
+///
+/// Returns the encoded bytes and the codec actually used: input that the codec
+/// cannot represent (e.g. not PCM WAV, or stereo for Opus) is stored with the
+/// next best codec instead of failing the save.
+pub fn encode(wav: &[u8], codec: AudioCodec) -> (Vec<u8>, AudioCodec) {
+    if codec == AudioCodec::Wav {
+        return (wav.to_vec(), AudioCodec::Wav);
+    }
+
+    let pcm = match read_wav(wav) {
+        Ok(pcm) => pcm,
+        Err(e) => {
+            tracing::debug!(error = %e, "Storage: Audio kept as-is, not encodable");
+            return (wav.to_vec(), AudioCodec::Wav);
+        }
+    };
+
+    if codec == AudioCodec::Opus {
+        match encode_opus(&pcm) {
+            Ok(opus) => return (opus, AudioCodec::Opus),
+            Err(e) => tracing::warn!(error = %e, "Storage: Opus encoding failed, using FLAC"),
+        }
+    }
+
+    match encode_flac(&pcm) {
+        Ok(flac) => (flac, AudioCodec::Flac),
+        Err(e) => {
+            tracing::warn!(error = %e, "Storage: FLAC encoding failed, keeping WAV");
+            (wav.to_vec(), AudioCodec::Wav)
+        }
+    }
+}
+
+/// Decode stored audio of any supported codec back to WAV
+pub fn decode_to_wav(audio: &[u8]) -> Result<Vec<u8>, String> {
+    match AudioCodec::detect(audio) {
+        AudioCodec::Wav => Ok(audio.to_vec()),
+        AudioCodec::Flac => write_wav(&decode_flac(audio)?),
+        AudioCodec::Opus => write_wav(&decode_opus(audio)?),
+    }
+}
+
+/// Decoded audio samples, independent of codec and bit depth
+#[derive(Debug, Clone, PartialEq)]
+pub struct Samples {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Interleaved, normalized to -1.0..=1.0
+    pub samples: Vec<f32>,
+}
+
+/// Decode stored audio of any supported codec to normalized samples.
+/// The same audio gives the same samples regardless of container or bit depth.
+pub fn decode_samples(audio: &[u8]) -> Result<Samples, String> {
+    let pcm = match AudioCodec::detect(audio) {
+        AudioCodec::Wav => {
+            let mut reader = hound::WavReader::new(Cursor::new(audio))
+                .map_err(|e| format!("Invalid WAV: {}", e))?;
+            let spec = reader.spec();
+            let samples: Result<Vec<f32>, _> = match spec.sample_format {
+                hound::SampleFormat::Float => reader.samples::<f32>().collect(),
+                hound::SampleFormat::Int => {
+                    let scale = (1i64 << (spec.bits_per_sample.max(1) - 1)) as f32;
+                    reader
+                        .samples::<i32>()
+                        .map(|s| s.map(|s| s as f32 / scale))
+                        .collect()
+                }
+            };
+            let samples = samples.map_err(|e| format!("Invalid WAV samples: {}", e))?;
+            return Ok(Samples {
+                sample_rate: spec.sample_rate,
+                channels: spec.channels,
+                samples,
+            });
+        }
+        AudioCodec::Flac => decode_flac(audio)?,
+        AudioCodec::Opus => decode_opus(audio)?,
+    };
+
+    let scale = (1i64 << (pcm.bits_per_sample.max(1) - 1)) as f32;
+    Ok(Samples {
+        sample_rate: pcm.sample_rate,
+        channels: pcm.channels,
+        samples: pcm.samples.iter().map(|&s| s as f32 / scale).collect(),
+    })
+}
+
+/// What a complete decode of stored audio revealed about it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AudioInfo {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    /// Samples per channel
+    pub frames: u64,
+    /// RMS over all samples, normalized to 0.0..=1.0
+    pub rms_energy: f32,
+}
+
+impl AudioInfo {
+    pub fn duration_ms(&self) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        self.frames * 1000 / self.sample_rate as u64
+    }
+
+    /// Size of the audio as WAV (what `fileSize` in the metadata describes)
+    pub fn wav_size(&self) -> usize {
+        44 + (self.frames * self.channels as u64 * (self.bits_per_sample as u64 / 8)) as usize
+    }
+}
+
+/// Decode stored audio completely and describe it.
+///
+/// Fails for damaged audio, e.g. a WAV whose header is cut off or whose data
+/// chunk is shorter than the header claims.
+pub fn probe(audio: &[u8]) -> Result<AudioInfo, String> {
+    let pcm = match AudioCodec::detect(audio) {
+        AudioCodec::Wav => return probe_wav(audio),
+        AudioCodec::Flac => decode_flac(audio)?,
+        AudioCodec::Opus => decode_opus(audio)?,
+    };
+
+    let scale = (1i64 << (pcm.bits_per_sample.max(1) - 1)) as f64;
+    let sum_squares: f64 = pcm
+        .samples
+        .iter()
+        .map(|&s| (s as f64 / scale).powi(2))
+        .sum();
+    Ok(AudioInfo {
+        sample_rate: pcm.sample_rate,
+        channels: pcm.channels,
+        bits_per_sample: pcm.bits_per_sample,
+        frames: (pcm.samples.len() / pcm.channels.max(1) as usize) as u64,
+        rms_energy: rms(sum_squares, pcm.samples.len()),
+    })
+}
+
+/// WAV is probed without converting, so float WAVs are accepted too
+fn probe_wav(wav: &[u8]) -> Result<AudioInfo, String> {
+    let mut reader =
+        hound::WavReader::new(Cursor::new(wav)).map_err(|e| format!("Invalid WAV: {}", e))?;
+    let spec = reader.spec();
+
+    let (sum_squares, count) = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().try_fold((0.0, 0), |(sum, n), s| {
+            s.map(|s| (sum + (s as f64).powi(2), n + 1))
+        }),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f64;
+            reader.samples::<i32>().try_fold((0.0, 0), |(sum, n), s| {
+                s.map(|s| (sum + (s as f64 / scale).powi(2), n + 1))
+            })
+        }
+    }
+    .map_err(|e| format!("Invalid WAV samples: {}", e))?;
+
+    Ok(AudioInfo {
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+        bits_per_sample: spec.bits_per_sample,
+        frames: (count / spec.channels.max(1) as usize) as u64,
+        rms_energy: rms(sum_squares, count),
+    })
+}
+
+fn rms(sum_squares: f64, count: usize) -> f32 {
+    if count == 0 {
+        0.0
+    } else {
+        (sum_squares / count as f64).sqrt() as f32
+    }
+}
+
+fn encode_flac(pcm: &Pcm) -> Result<Vec<u8>, String> {
+    flac::encode(&flac::PcmAudio {
+        samples: &pcm.samples,
+        channels: pcm.channels,
+        sample_rate: pcm.sample_rate,
+        bits_per_sample: pcm.bits_per_sample,
+    })
+}
+
+fn decode_flac(audio: &[u8]) -> Result<Pcm, String> {
+    let mut reader = claxon::FlacReader::new(Cursor::new(audio))
+        .map_err(|e| format!("Invalid FLAC: {}", e))?;
+    let info = reader.streaminfo();
+    let samples = reader
+        .samples()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid FLAC samples: {}", e))?;
+    if let Some(total) = info.samples {
+        if samples.len() as u64 != total * info.channels as u64 {
+            return Err("Truncated FLAC: fewer samples than the stream header declares".to_string());
+        }
+    }
+
+    Ok(Pcm {
+        samples,
+        channels: info.channels as u16,
+        sample_rate: info.sample_rate,
+        bits_per_sample: info.bits_per_sample as u16,
+    })
+}
+
+#[cfg(feature = "opus-codec")]
+fn encode_opus(pcm: &Pcm) -> Result<Vec<u8>, String> {
+    if pcm.channels != 1 || pcm.bits_per_sample != 16 {
+        return Err("Opus storage supports 16-bit mono only".to_string());
+    }
+    if !matches!(pcm.sample_rate, 8_000 | 12_000 | 16_000 | 24_000 | 48_000) {
+        return Err(format!("Unsupported Opus sample rate: {}", pcm.sample_rate));
+    }
+
+    let mut encoder =
+        opus::Encoder::new(pcm.sample_rate, opus::Channels::Mono, opus::Application::Voip)
+            .map_err(|e| format!("Failed to create Opus encoder: {}", e))?;
+    encoder
+        .set_bitrate(opus::Bitrate::Bits(OPUS_BITRATE))
+        .map_err(|e| format!("Failed to set Opus bitrate: {}", e))?;
+    let pre_skip = encoder
+        .get_lookahead()
+        .map_err(|e| format!("Failed to query Opus lookahead: {}", e))? as usize;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(OPUS_MAGIC);
+    out.extend_from_slice(&pcm.sample_rate.to_le_bytes());
+    out.push(1);
+    out.extend_from_slice(&(pre_skip as u16).to_le_bytes());
+    out.extend_from_slice(&(pcm.samples.len() as u64).to_le_bytes());
+
+    // Pad with the encoder delay so the tail is flushed, then to whole frames
+    let frame_len = (pcm.sample_rate * OPUS_FRAME_MS / 1000) as usize;
+    let mut samples: Vec<i16> = pcm.samples.iter().map(|&s| s as i16).collect();
+    samples.resize(samples.len() + pre_skip, 0);
+    samples.resize(samples.len().div_ceil(frame_len) * frame_len, 0);
+
+    let mut packet = vec![0u8; 4000];
+    for frame in samples.chunks(frame_len) {
+        let len = encoder
+            .encode(frame, &mut packet)
+            .map_err(|e| format!("Opus encoding failed: {}", e))?;
+        out.extend_from_slice(&(len as u16).to_le_bytes());
+        out.extend_from_slice(&packet[..len]);
+    }
+
+    Ok(out)
+}
+
+#[cfg(not(feature = "opus-codec"))]
+fn encode_opus(_pcm: &Pcm) -> Result<Vec<u8>, String> {
+    Err("Opus support is not included in this build".to_string())
+}
+
+#[cfg(feature = "opus-codec")]
+fn decode_opus(audio: &[u8]) -> Result<Pcm, String> {
+    const HEADER_LEN: usize = 8 + 4 + 1 + 2 + 8;
+    if audio.len() < HEADER_LEN {
+        return Err("Truncated Opus header".to_string());
+    }
+    let sample_rate = u32::from_le_bytes(audio[8..12].try_into().expect("4 bytes"));
+    let pre_skip = u16::from_le_bytes(audio[13..15].try_into().expect("2 bytes")) as usize;
+    let sample_count = u64::from_le_bytes(audio[15..23].try_into().expect("8 bytes")) as usize;
+
+    let mut decoder = opus::Decoder::new(sample_rate, opus::Channels::Mono)
+        .map_err(|e| format!("Failed to create Opus decoder: {}", e))?;
+
+    let mut samples: Vec<i16> = Vec::with_capacity(sample_count + pre_skip);
+    // Largest Opus frame is 120 ms
+    let mut frame = vec![0i16; (sample_rate as usize * 120) / 1000];
+    let mut pos = HEADER_LEN;
+    while pos < audio.len() {
+        if pos + 2 > audio.len() {
+            return Err("Truncated Opus packet header".to_string());
+        }
+        let len = u16::from_le_bytes([audio[pos], audio[pos + 1]]) as usize;
+        pos += 2;
+        let packet = audio
+            .get(pos..pos + len)
+            .ok_or_else(|| "Truncated Opus packet".to_string())?;
+        pos += len;
+
+        let decoded = decoder
+            .decode(packet, &mut frame, false)
+            .map_err(|e| format!("Opus decoding failed: {}", e))?;
+        samples.extend_from_slice(&frame[..decoded]);
+    }
+
+    let samples = samples
+        .into_iter()
+        .skip(pre_skip)
+        .take(sample_count)
+        .map(|s| s as i32)
+        .collect();
+
+    Ok(Pcm {
+        samples,
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+    })
+}
+
+#[cfg(not(feature = "opus-codec"))]
+fn decode_opus(_audio: &[u8]) -> Result<Pcm, String> {
+    Err("This recording is stored as Opus, which this build cannot decode".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_wav(samples: &[i16]) -> Vec<u8> {
+        let pcm = Pcm {
+            samples: samples.iter().map(|&s| s as i32).collect(),
+            channels: 1,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+        };
+        write_wav(&pcm).unwrap()
+    }
+
+    #[test]
+    fn test_flac_roundtrip_returns_identical_wav() {
+        let samples: Vec<i16> = (0..16_000).map(|i| ((i as f32 * 0.03).sin() * 9_000.0) as i16).collect();
+        let wav = test_wav(&samples);
+
+        let (flac, codec) = encode(&wav, AudioCodec::Flac);
+        assert_eq!(codec, AudioCodec::Flac);
+        assert_eq!(AudioCodec::detect(&flac), AudioCodec::Flac);
+        assert!(flac.len() < wav.len() / 2);
+
+        assert_eq!(decode_to_wav(&flac).unwrap(), wav);
+    }
+
+    #[test]
+    fn test_non_wav_input_kept_as_is() {
+        let (stored, codec) = encode(&[1, 2, 3], AudioCodec::Flac);
+        assert_eq!(codec, AudioCodec::Wav);
+        assert_eq!(stored, vec![1, 2, 3]);
+        assert_eq!(decode_to_wav(&stored).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_probe_detects_truncated_audio() {
+        let wav = test_wav(&vec![1_000i16; 8_000]);
+        let info = probe(&wav).unwrap();
+        assert_eq!(info.duration_ms(), 500);
+        assert_eq!(info.wav_size(), wav.len());
+        assert!((info.rms_energy - 1_000.0 / 32_768.0).abs() < 1e-4);
+
+        let (flac, _) = encode(&wav, AudioCodec::Flac);
+        assert_eq!(probe(&flac).unwrap(), info);
+
+        assert!(probe(&wav[..wav.len() - 100]).is_err());
+        assert!(probe(&wav[..20]).is_err());
+        assert!(probe(&flac[..flac.len() / 2]).is_err());
+    }
+
+    #[cfg(not(feature = "opus-codec"))]
+    #[test]
+    fn test_opus_unavailable_falls_back_to_flac() {
+        assert!(!AudioCodec::Opus.is_available());
+        let (_, codec) = encode(&test_wav(&[0, 100, -100, 50]), AudioCodec::Opus);
+        assert_eq!(codec, AudioCodec::Flac);
+        assert!(decode_to_wav(OPUS_MAGIC).is_err());
+    }
+}
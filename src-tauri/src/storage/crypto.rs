@@ -0,0 +1,308 @@
+//! Encryption at Rest
+//!
+//! AES-256-GCM authenticated encryption for recording files (WAV + metadata JSON)
+//! and catalog rows. The 256-bit data key is generated once per library and
+//! stored in the OS keyring through the keyring command backend.
+//!
+//! Encrypted file layout: `MAGIC (8) | nonce (12) | ciphertext + tag (16)`
+//!
+//! Plaintext files carry no magic header, so encrypted and plaintext recordings
+//! can live side by side and are detected per file on read.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Mutex;
+
+use crate::commands::keyring::{get_password_blocking, set_password_blocking};
+
+/// Header identifying an encrypted file (format version 1)
+pub const ENCRYPTION_MAGIC: &[u8; 8] = b"HBLRENC1";
+
+/// AES-GCM nonce length (96 bit)
+const NONCE_LEN: usize = 12;
+
+/// AES-GCM authentication tag length (128 bit)
+const TAG_LEN: usize = 16;
+
+/// Keyring service for storage secrets (shared with the frontend's API keys)
+pub const KEYRING_SERVICE: &str = "hablara-vip";
+
+/// Keyring user holding the data key of the default library
+pub const DEFAULT_DATA_KEY_USER: &str = "storage-data-key";
+
+/// Keyring user holding the data key of a profile's library.
+/// The default profile keeps the original entry.
+pub fn data_key_user(profile_id: &str) -> String {
+    if profile_id == super::DEFAULT_PROFILE_ID {
+        DEFAULT_DATA_KEY_USER.to_string()
+    } else {
+        format!("{}-{}", DEFAULT_DATA_KEY_USER, profile_id)
+    }
+}
+
+/// Associated data binding ciphertext to its content type
+pub const AAD_AUDIO: &[u8] = b"hablara:audio";
+pub const AAD_METADATA: &[u8] = b"hablara:metadata";
+pub const AAD_SPOOL: &[u8] = b"hablara:spool";
+
+/// Domain separator for blinded search terms
+const SEARCH_TERM_DOMAIN: &[u8] = b"hablara:search-term:";
+
+/// Bytes of the keyed hash kept per blinded search term
+const BLINDED_TERM_LEN: usize = 10;
+
+/// 256-bit data key for one recording library
+#[derive(Clone)]
+pub struct DataKey([u8; 32]);
+
+impl DataKey {
+    /// Generate a new random key
+    pub fn generate() -> Self {
+        let key = Aes256Gcm::generate_key(OsRng);
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&key);
+        Self(bytes)
+    }
+
+    /// Decode a key from its Base64 keyring representation
+    pub fn from_base64(encoded: &str) -> Result<Self, String> {
+        let bytes = BASE64
+            .decode(encoded.trim())
+            .map_err(|e| format!("Invalid data key encoding: {}", e))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| "Invalid data key length (expected 32 bytes)".to_string())?;
+        Ok(Self(bytes))
+    }
+
+    /// Encode the key for keyring storage
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(self.0)
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.0))
+    }
+}
+
+impl std::fmt::Debug for DataKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DataKey(<redacted>)")
+    }
+}
+
+/// Check whether bytes carry the encryption header
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTION_MAGIC)
+}
+
+/// Encrypt bytes with a fresh random nonce
+pub fn encrypt(key: &DataKey, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = key
+        .cipher()
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut out = Vec::with_capacity(ENCRYPTION_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(ENCRYPTION_MAGIC);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt and authenticate bytes produced by [`encrypt`]
+pub fn decrypt(key: &DataKey, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let header_len = ENCRYPTION_MAGIC.len() + NONCE_LEN;
+    if !is_encrypted(data) || data.len() < header_len + TAG_LEN {
+        return Err("Not a valid encrypted file".to_string());
+    }
+
+    let nonce = Nonce::from_slice(&data[ENCRYPTION_MAGIC.len()..header_len]);
+    key.cipher()
+        .decrypt(
+            nonce,
+            Payload {
+                msg: &data[header_len..],
+                aad,
+            },
+        )
+        .map_err(|_| "Decryption failed (wrong key or corrupted file)".to_string())
+}
+
+/// Keyed hash of a search term (HMAC-SHA256, truncated, hex).
+///
+/// Lets encrypted libraries be searched through the catalog's full-text index
+/// without storing plaintext terms. Equal terms map to equal tokens.
+pub fn blind_term(key: &DataKey, term: &str) -> String {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(&key.0).expect("HMAC accepts keys of any length");
+    mac.update(SEARCH_TERM_DOMAIN);
+    mac.update(term.as_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let mut token = String::with_capacity(1 + 2 * BLINDED_TERM_LEN);
+    token.push('h');
+    for byte in &digest[..BLINDED_TERM_LEN] {
+        token.push_str(&format!("{:02x}", byte));
+    }
+    token
+}
+
+/// Lazily loaded data key of one library.
+///
+/// The keyring is only touched when encrypted data is read or written,
+/// and the result is cached for the lifetime of the store.
+pub struct KeyStore {
+    keyring_user: String,
+    /// `None` = not loaded yet, `Some(None)` = no key in keyring
+    cached: Mutex<Option<Option<DataKey>>>,
+}
+
+impl KeyStore {
+    /// Key store backed by the OS keyring entry `hablara-vip/<keyring_user>`
+    pub fn new(keyring_user: &str) -> Self {
+        Self {
+            keyring_user: keyring_user.to_string(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Key store with a preloaded key (no keyring access)
+    pub fn with_key(keyring_user: &str, key: DataKey) -> Self {
+        Self {
+            keyring_user: keyring_user.to_string(),
+            cached: Mutex::new(Some(Some(key))),
+        }
+    }
+
+    /// Keyring user name of this store
+    pub fn keyring_user(&self) -> &str {
+        &self.keyring_user
+    }
+
+    /// Get the existing key, if any (loads from keyring on first call)
+    pub fn get(&self) -> Result<Option<DataKey>, String> {
+        let mut guard = self
+            .cached
+            .lock()
+            .map_err(|e| format!("Failed to lock key store: {}", e))?;
+        if let Some(cached) = guard.as_ref() {
+            return Ok(cached.clone());
+        }
+
+        let key = get_password_blocking(KEYRING_SERVICE, &self.keyring_user)?
+            .map(|encoded| DataKey::from_base64(&encoded))
+            .transpose()?;
+        *guard = Some(key.clone());
+        Ok(key)
+    }
+
+    /// Get the key, generating and storing a new one if none exists
+    pub fn get_or_create(&self) -> Result<DataKey, String> {
+        if let Some(key) = self.get()? {
+            return Ok(key);
+        }
+
+        let key = DataKey::generate();
+        set_password_blocking(KEYRING_SERVICE, &self.keyring_user, &key.to_base64())?;
+        tracing::info!(user = %self.keyring_user, "Storage: Created data key in keyring");
+
+        let mut guard = self
+            .cached
+            .lock()
+            .map_err(|e| format!("Failed to lock key store: {}", e))?;
+        *guard = Some(Some(key.clone()));
+        Ok(key)
+    }
+
+    /// Store `key` in the keyring, replacing an existing one
+    pub fn set(&self, key: DataKey) -> Result<(), String> {
+        set_password_blocking(KEYRING_SERVICE, &self.keyring_user, &key.to_base64())?;
+        let mut guard = self
+            .cached
+            .lock()
+            .map_err(|e| format!("Failed to lock key store: {}", e))?;
+        *guard = Some(Some(key));
+        Ok(())
+    }
+
+    /// Encrypt `data` if `encrypt_data` is set, otherwise return it unchanged
+    pub fn seal(&self, data: &[u8], aad: &[u8], encrypt_data: bool) -> Result<Vec<u8>, String> {
+        if !encrypt_data {
+            return Ok(data.to_vec());
+        }
+        encrypt(&self.get_or_create()?, data, aad)
+    }
+
+    /// Decrypt `data` if it is encrypted, otherwise return it unchanged
+    pub fn open(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+        if !is_encrypted(data) {
+            return Ok(data.to_vec());
+        }
+        let key = self
+            .get()?
+            .ok_or_else(|| "Recording is encrypted but no data key was found in the keyring".to_string())?;
+        decrypt(&key, data, aad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let key = DataKey::generate();
+        let sealed = encrypt(&key, b"Tagebuch", AAD_METADATA).unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(decrypt(&key, &sealed, AAD_METADATA).unwrap(), b"Tagebuch");
+    }
+
+    #[test]
+    fn test_wrong_key_or_aad_rejected() {
+        let key = DataKey::generate();
+        let sealed = encrypt(&key, b"audio", AAD_AUDIO).unwrap();
+        assert!(decrypt(&DataKey::generate(), &sealed, AAD_AUDIO).is_err());
+        assert!(decrypt(&key, &sealed, AAD_METADATA).is_err());
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let key = DataKey::generate();
+        let mut sealed = encrypt(&key, b"audio", AAD_AUDIO).unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+        assert!(decrypt(&key, &sealed, AAD_AUDIO).is_err());
+    }
+
+    #[test]
+    fn test_key_base64_roundtrip() {
+        let key = DataKey::generate();
+        let decoded = DataKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(decoded.0, key.0);
+        assert!(DataKey::from_base64("c2hvcnQ=").is_err());
+    }
+
+    #[test]
+    fn test_blind_term_is_keyed_and_deterministic() {
+        let key = DataKey::generate();
+        assert_eq!(blind_term(&key, "gesprach"), blind_term(&key, "gesprach"));
+        assert_ne!(blind_term(&key, "gesprach"), blind_term(&key, "arbeit"));
+        assert_ne!(blind_term(&key, "gesprach"), blind_term(&DataKey::generate(), "gesprach"));
+    }
+
+    #[test]
+    fn test_key_store_passes_plaintext_through() {
+        let store = KeyStore::with_key("test", DataKey::generate());
+        assert_eq!(store.open(b"plain", AAD_AUDIO).unwrap(), b"plain");
+        assert_eq!(store.seal(b"plain", AAD_AUDIO, false).unwrap(), b"plain");
+
+        let sealed = store.seal(b"secret", AAD_AUDIO, true).unwrap();
+        assert_ne!(sealed, b"secret");
+        assert_eq!(store.open(&sealed, AAD_AUDIO).unwrap(), b"secret");
+    }
+}
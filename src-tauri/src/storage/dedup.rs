@@ -0,0 +1,352 @@
+//! Duplicate Detection
+//!
+//! Recognizes the same audio saved more than once, e.g. a voice memo imported
+//! twice, so it is not counted twice in baselines and trends.
+//!
+//! - Content hash: SHA-256 over the decoded samples, sample rate and channel
+//!   count. Stored in the metadata at save time; container, header chunks,
+//!   bit depth and lossless codecs do not change it.
+//! - Fingerprint: loudness envelope in 100 ms windows. The library scan uses
+//!   it to also find near-identical audio (resampled, lossy re-encodes).
+//!
+//! What happens when a saved recording matches an existing one is set by the
+//! [`DuplicatePolicy`].
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use super::annotations::normalize_tag;
+use super::archive::hex;
+use super::codec::{self, Samples};
+use super::query::RecordingSummary;
+use super::RecordingMetadata;
+
+/// Envelope window length
+const WINDOW_MS: u64 = 100;
+
+/// Envelopes below this peak loudness count as silence
+const SILENCE_RMS: f32 = 1e-3;
+
+/// Largest mean difference of normalized envelopes still considered the same audio
+const MAX_ENVELOPE_DIFFERENCE: f32 = 0.05;
+
+/// Smallest envelope correlation still considered the same audio
+const MIN_ENVELOPE_CORRELATION: f32 = 0.95;
+
+/// What saving a recording whose audio is already in the library does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Save it, marked as `duplicateOf` the existing recording (not counted in baselines)
+    #[default]
+    Warn,
+    /// Refuse to save it
+    Reject,
+    /// Keep only the existing recording and add the new title, notes and tags to it
+    Merge,
+}
+
+/// Content hash of audio in any supported codec.
+/// Audio that cannot be decoded is hashed as stored.
+pub fn content_hash(audio: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    match codec::decode_samples(audio) {
+        Ok(decoded) => {
+            hasher.update(b"pcm");
+            hasher.update(decoded.sample_rate.to_le_bytes());
+            hasher.update(decoded.channels.to_le_bytes());
+            for sample in &decoded.samples {
+                hasher.update(sample.to_le_bytes());
+            }
+        }
+        Err(_) => {
+            hasher.update(b"raw");
+            hasher.update(audio);
+        }
+    }
+    hex(&hasher.finalize())
+}
+
+/// Loudness envelope of a recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fingerprint {
+    pub duration_ms: u64,
+    /// RMS per [`WINDOW_MS`] window, channels mixed down
+    envelope: Vec<f32>,
+}
+
+impl Fingerprint {
+    pub fn of(decoded: &Samples) -> Self {
+        let channels = decoded.channels.max(1) as usize;
+        let frames = decoded.samples.len() / channels;
+        let window = (decoded.sample_rate as u64 * WINDOW_MS / 1000).max(1) as usize;
+
+        let envelope = decoded
+            .samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect::<Vec<_>>()
+            .chunks_exact(window)
+            .map(|w| (w.iter().map(|s| s * s).sum::<f32>() / w.len() as f32).sqrt())
+            .collect();
+
+        Self {
+            duration_ms: if decoded.sample_rate == 0 {
+                0
+            } else {
+                frames as u64 * 1000 / decoded.sample_rate as u64
+            },
+            envelope,
+        }
+    }
+
+    /// Duration difference still considered the same recording
+    fn duration_tolerance_ms(duration_ms: u64) -> u64 {
+        (duration_ms / 50).max(250)
+    }
+
+    /// Whether both fingerprints describe the same audio
+    pub fn matches(&self, other: &Fingerprint) -> bool {
+        let longer = self.duration_ms.max(other.duration_ms);
+        if longer - self.duration_ms.min(other.duration_ms) > Self::duration_tolerance_ms(longer) {
+            return false;
+        }
+        let len = self.envelope.len().min(other.envelope.len());
+        if len == 0 {
+            return false;
+        }
+
+        let peak = |e: &[f32]| e.iter().copied().fold(0.0f32, f32::max);
+        let (a, b) = (&self.envelope[..len], &other.envelope[..len]);
+        let (peak_a, peak_b) = (peak(a), peak(b));
+        match (peak_a < SILENCE_RMS, peak_b < SILENCE_RMS) {
+            (true, true) => return true,
+            (false, false) => {}
+            _ => return false,
+        }
+        let a: Vec<f32> = a.iter().map(|v| v / peak_a).collect();
+        let b: Vec<f32> = b.iter().map(|v| v / peak_b).collect();
+
+        let difference = a.iter().zip(&b).map(|(x, y)| (x - y).abs()).sum::<f32>() / len as f32;
+        difference <= MAX_ENVELOPE_DIFFERENCE
+            && correlation(&a, &b).is_none_or(|c| c >= MIN_ENVELOPE_CORRELATION)
+    }
+}
+
+/// Pearson correlation (`None` if either side is constant)
+fn correlation(a: &[f32], b: &[f32]) -> Option<f32> {
+    let n = a.len() as f32;
+    let (mean_a, mean_b) = (a.iter().sum::<f32>() / n, b.iter().sum::<f32>() / n);
+    let (mut covariance, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    (var_a > f32::EPSILON && var_b > f32::EPSILON).then(|| covariance / (var_a * var_b).sqrt())
+}
+
+/// A recording examined by the library scan
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub content_hash: Option<String>,
+    pub fingerprint: Option<Fingerprint>,
+}
+
+/// Recordings with the same audio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// All recordings have the same content hash
+    pub exact: bool,
+    /// Oldest first
+    pub recordings: Vec<RecordingSummary>,
+}
+
+/// Representative of `i`'s set (union-find with path halving)
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Merge the sets of `a` and `b`; the lower index stays representative
+fn join(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (root(parent, a), root(parent, b));
+    parent[a.max(b)] = a.min(b);
+}
+
+/// Group candidates with the same content hash or matching fingerprints.
+/// Returns index groups of two or more, in candidate order.
+pub fn group(candidates: &[Candidate]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+
+    let mut by_hash: HashMap<&str, usize> = HashMap::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        if let Some(hash) = &candidate.content_hash {
+            match by_hash.get(hash.as_str()) {
+                Some(&first) => join(&mut parent, first, i),
+                None => {
+                    by_hash.insert(hash, i);
+                }
+            }
+        }
+    }
+
+    // Sorted by duration, only neighbors within the duration tolerance are compared
+    let mut by_duration: Vec<(usize, &Fingerprint)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| c.fingerprint.as_ref().map(|f| (i, f)))
+        .collect();
+    by_duration.sort_by_key(|(_, f)| f.duration_ms);
+    for (pos, (i, fingerprint)) in by_duration.iter().enumerate() {
+        for (j, other) in &by_duration[pos + 1..] {
+            let tolerance = Fingerprint::duration_tolerance_ms(other.duration_ms);
+            if other.duration_ms - fingerprint.duration_ms > tolerance {
+                break;
+            }
+            if fingerprint.matches(other) {
+                join(&mut parent, *i, *j);
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    for i in 0..candidates.len() {
+        let r = root(&mut parent, i);
+        match group_of.get(&r) {
+            Some(&g) => groups[g].push(i),
+            None => {
+                group_of.insert(r, groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+    groups.retain(|g| g.len() > 1);
+    groups
+}
+
+/// Fold a duplicate's title, notes, tags and pinning into the existing recording
+pub fn merge_into(existing: &mut RecordingMetadata, duplicate: &RecordingMetadata) {
+    if existing.title.is_none() {
+        existing.title = duplicate.title.clone();
+    }
+    if existing.notes.is_none() {
+        existing.notes = duplicate.notes.clone();
+    }
+    for tag in &duplicate.tags {
+        let tag = normalize_tag(tag);
+        if !tag.is_empty() && !existing.tags.contains(&tag) {
+            existing.tags.push(tag);
+        }
+    }
+    existing.tags.sort();
+    existing.pinned |= duplicate.pinned;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Speech-like test signal: a tone whose loudness follows `shape`
+    fn signal(sample_rate: u32, seconds: f32, shape: fn(f32) -> f32) -> Vec<f32> {
+        let count = (sample_rate as f32 * seconds) as usize;
+        (0..count)
+            .map(|n| {
+                let t = n as f32 / sample_rate as f32;
+                shape(t) * (t * 220.0 * std::f32::consts::TAU).sin()
+            })
+            .collect()
+    }
+
+    fn wav(samples: &[f32], sample_rate: u32, bits: u16) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: bits,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        let scale = (1i32 << (bits - 1)) as f32 - 1.0;
+        for s in samples {
+            writer.write_sample((s * scale) as i32).unwrap();
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    fn speech(t: f32) -> f32 {
+        0.5 + 0.4 * (t * 3.0).sin()
+    }
+
+    fn other_speech(t: f32) -> f32 {
+        0.5 + 0.4 * (t * 7.0).cos()
+    }
+
+    fn fingerprint(audio: &[u8]) -> Fingerprint {
+        Fingerprint::of(&codec::decode_samples(audio).unwrap())
+    }
+
+    #[test]
+    fn test_content_hash_ignores_container() {
+        let samples = signal(16_000, 1.0, speech);
+        let original = wav(&samples, 16_000, 16);
+        let (flac, _) = codec::encode(&original, codec::AudioCodec::Flac);
+        // Extra header chunk: same samples, different bytes
+        let mut with_chunk = original.clone();
+        with_chunk.splice(36..36, *b"LIST\x04\x00\x00\x00INFO");
+        let size = (with_chunk.len() - 8) as u32;
+        with_chunk[4..8].copy_from_slice(&size.to_le_bytes());
+
+        let hash = content_hash(&original);
+        assert_eq!(content_hash(&flac), hash);
+        assert_eq!(content_hash(&with_chunk), hash);
+        assert_ne!(
+            content_hash(&wav(&signal(16_000, 1.0, other_speech), 16_000, 16)),
+            hash
+        );
+        assert_ne!(content_hash(b"not audio"), hash);
+    }
+
+    #[test]
+    fn test_fingerprint_tolerates_resampling() {
+        let original = fingerprint(&wav(&signal(16_000, 3.0, speech), 16_000, 16));
+        let resampled = fingerprint(&wav(&signal(8_000, 3.0, speech), 8_000, 16));
+        let different = fingerprint(&wav(&signal(16_000, 3.0, other_speech), 16_000, 16));
+        let shorter = fingerprint(&wav(&signal(16_000, 2.0, speech), 16_000, 16));
+
+        assert!(original.matches(&resampled));
+        assert!(!original.matches(&different));
+        assert!(!original.matches(&shorter));
+    }
+
+    #[test]
+    fn test_group_by_hash_and_fingerprint() {
+        let a = wav(&signal(16_000, 2.0, speech), 16_000, 16);
+        let b = wav(&signal(16_000, 2.0, other_speech), 16_000, 16);
+        let a_resampled = wav(&signal(8_000, 2.0, speech), 8_000, 16);
+        let candidate = |audio: &[u8]| Candidate {
+            content_hash: Some(content_hash(audio)),
+            fingerprint: Some(fingerprint(audio)),
+        };
+        let text_only = Candidate {
+            content_hash: None,
+            fingerprint: None,
+        };
+
+        let candidates = [
+            candidate(&a),
+            candidate(&b),
+            text_only,
+            candidate(&a_resampled),
+            candidate(&b),
+        ];
+        assert_eq!(group(&candidates), [vec![0, 3], vec![1, 4]]);
+    }
+}
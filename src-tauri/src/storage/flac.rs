@@ -0,0 +1,477 @@
+//! FLAC Encoder
+//!
+//! Minimal lossless FLAC encoder for stored recordings. Decoding uses claxon.
+//!
+//! Uses only the fixed predictors (order 0-4) with partitioned Rice coding and
+//! falls back to constant/verbatim subframes. That captures most of the gain on
+//! speech at 16 kHz while staying small enough to maintain in-tree.
+//!
+//! Reference: https://xiph.org/flac/format.html
+
+/// Samples per frame (FLAC default for 16 kHz and above)
+const BLOCK_SIZE: usize = 4096;
+
+/// Highest fixed predictor order defined by the format
+const MAX_FIXED_ORDER: usize = 4;
+
+/// Highest Rice partition order tried (4096 / 2^6 = 64 samples per partition)
+const MAX_PARTITION_ORDER: u32 = 6;
+
+/// Largest Rice parameter of the 4-bit coding method (15 is the escape code)
+const MAX_RICE_PARAM: u32 = 14;
+
+/// PCM input for the encoder
+pub struct PcmAudio<'a> {
+    /// Interleaved samples
+    pub samples: &'a [i32],
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// 8, 16 or 24
+    pub bits_per_sample: u16,
+}
+
+/// Encode PCM samples as a FLAC stream
+pub fn encode(audio: &PcmAudio) -> Result<Vec<u8>, String> {
+    let channels = audio.channels as usize;
+    if !(1..=8).contains(&channels) {
+        return Err(format!("Unsupported channel count: {}", channels));
+    }
+    if !matches!(audio.bits_per_sample, 8 | 16 | 24) {
+        return Err(format!("Unsupported bit depth: {}", audio.bits_per_sample));
+    }
+    if audio.sample_rate == 0 || audio.sample_rate >= 1 << 20 {
+        return Err(format!("Unsupported sample rate: {}", audio.sample_rate));
+    }
+    if !audio.samples.len().is_multiple_of(channels) {
+        return Err("Sample count is not a multiple of the channel count".to_string());
+    }
+
+    let total_frames = audio.samples.len() / channels;
+    let mut out = BitWriter::default();
+    out.write_bytes(b"fLaC");
+    write_streaminfo(&mut out, audio, total_frames as u64);
+
+    let mut channel_buf = vec![Vec::with_capacity(BLOCK_SIZE); channels];
+    for (frame_number, block) in audio.samples.chunks(BLOCK_SIZE * channels).enumerate() {
+        for (ch, buf) in channel_buf.iter_mut().enumerate() {
+            buf.clear();
+            buf.extend(block.iter().skip(ch).step_by(channels));
+        }
+        write_frame(&mut out, audio, frame_number as u64, &channel_buf);
+    }
+
+    Ok(out.into_bytes())
+}
+
+fn write_streaminfo(out: &mut BitWriter, audio: &PcmAudio, total_frames: u64) {
+    // Metadata block header: last block, type 0 (STREAMINFO), 34 bytes
+    out.write(1, 1);
+    out.write(0, 7);
+    out.write(34, 24);
+
+    let block_size = BLOCK_SIZE.min(total_frames.max(16) as usize) as u64;
+    out.write(block_size, 16); // min block size
+    out.write(block_size, 16); // max block size
+    out.write(0, 24); // min frame size (unknown)
+    out.write(0, 24); // max frame size (unknown)
+    out.write(audio.sample_rate as u64, 20);
+    out.write(audio.channels as u64 - 1, 3);
+    out.write(audio.bits_per_sample as u64 - 1, 5);
+    out.write(total_frames, 36);
+    out.write_bytes(&[0; 16]); // MD5 of the audio (not computed)
+}
+
+fn write_frame(out: &mut BitWriter, audio: &PcmAudio, frame_number: u64, channels: &[Vec<i32>]) {
+    let frame_start = out.len_bytes();
+    let block_len = channels[0].len();
+
+    out.write(0b11_1111_1111_1110, 14); // sync code
+    out.write(0, 1); // reserved
+    out.write(0, 1); // fixed block size stream
+    out.write(0b0111, 4); // block size: 16 bit (n - 1) at end of header
+    let (rate_code, rate_trailer) = sample_rate_code(audio.sample_rate);
+    out.write(rate_code, 4);
+    out.write(channels.len() as u64 - 1, 4); // independent channels
+    out.write(sample_size_code(audio.bits_per_sample), 3);
+    out.write(0, 1); // reserved
+    write_utf8_number(out, frame_number);
+    out.write(block_len as u64 - 1, 16);
+    if let Some((value, bits)) = rate_trailer {
+        out.write(value, bits);
+    }
+    let crc = crc8(&out.bytes()[frame_start..]);
+    out.write(crc as u64, 8);
+
+    for samples in channels {
+        write_subframe(out, samples, audio.bits_per_sample as u32);
+    }
+
+    out.align();
+    let crc = crc16(&out.bytes()[frame_start..]);
+    out.write(crc as u64, 16);
+}
+
+/// Frame header sample rate code, plus the explicit value for uncommon rates.
+///
+/// Decoders such as claxon require the rate in every frame header instead of
+/// referring back to STREAMINFO.
+fn sample_rate_code(sample_rate: u32) -> (u64, Option<(u64, u32)>) {
+    match sample_rate {
+        88_200 => (0b0001, None),
+        176_400 => (0b0010, None),
+        192_000 => (0b0011, None),
+        8_000 => (0b0100, None),
+        16_000 => (0b0101, None),
+        22_050 => (0b0110, None),
+        24_000 => (0b0111, None),
+        32_000 => (0b1000, None),
+        44_100 => (0b1001, None),
+        48_000 => (0b1010, None),
+        96_000 => (0b1011, None),
+        rate if rate % 1000 == 0 && rate / 1000 < 256 => (0b1100, Some((rate as u64 / 1000, 8))),
+        rate if rate < 65_536 => (0b1101, Some((rate as u64, 16))),
+        rate if rate % 10 == 0 && rate / 10 < 65_536 => (0b1110, Some((rate as u64 / 10, 16))),
+        _ => (0b0000, None), // from STREAMINFO
+    }
+}
+
+/// Frame header sample size code (bit depth is validated in `encode`)
+fn sample_size_code(bits_per_sample: u16) -> u64 {
+    match bits_per_sample {
+        8 => 0b001,
+        16 => 0b100,
+        _ => 0b110, // 24
+    }
+}
+
+/// Frame numbers use the UTF-8 style variable length coding
+fn write_utf8_number(out: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        out.write(value, 8);
+        return;
+    }
+
+    let mut continuation_bytes = 1;
+    while value >= 1u64 << (6 + 5 * continuation_bytes) {
+        continuation_bytes += 1;
+    }
+    let lead_marker = (0xFF00u64 >> (continuation_bytes + 1)) & 0xFF;
+    out.write(lead_marker | (value >> (6 * continuation_bytes)), 8);
+    for i in (0..continuation_bytes).rev() {
+        out.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+/// Best fixed-predictor encoding of one channel
+struct FixedChoice {
+    order: usize,
+    partition_order: u32,
+    residuals: Vec<i32>,
+    params: Vec<u32>,
+    bits: u64,
+}
+
+fn write_subframe(out: &mut BitWriter, samples: &[i32], bps: u32) {
+    if samples.iter().all(|&s| s == samples[0]) {
+        out.write(0x00, 8); // CONSTANT, no wasted bits
+        write_signed(out, samples[0], bps);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * bps as u64;
+    let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .filter_map(|order| fixed_choice(samples, order, bps))
+        .min_by_key(|c| c.bits);
+
+    match best {
+        Some(choice) if choice.bits < verbatim_bits => {
+            out.write(0, 1);
+            out.write(0b001000 | choice.order as u64, 6); // FIXED, order
+            out.write(0, 1); // no wasted bits
+            for &s in &samples[..choice.order] {
+                write_signed(out, s, bps);
+            }
+            write_residual(out, &choice, samples.len());
+        }
+        _ => {
+            out.write(0x02, 8); // VERBATIM, no wasted bits
+            for &s in samples {
+                write_signed(out, s, bps);
+            }
+        }
+    }
+}
+
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    let s = |i: usize| samples[i] as i64;
+    (order..samples.len())
+        .map(|i| {
+            let r = match order {
+                0 => s(i),
+                1 => s(i) - s(i - 1),
+                2 => s(i) - 2 * s(i - 1) + s(i - 2),
+                3 => s(i) - 3 * s(i - 1) + 3 * s(i - 2) - s(i - 3),
+                _ => s(i) - 4 * s(i - 1) + 6 * s(i - 2) - 4 * s(i - 3) + s(i - 4),
+            };
+            r as i32
+        })
+        .collect()
+}
+
+fn zigzag(r: i32) -> u64 {
+    ((r << 1) ^ (r >> 31)) as u32 as u64
+}
+
+/// Rice parameter estimate for `count` values summing to `sum`
+fn rice_param(sum: u64, count: u64) -> u32 {
+    let mut k = 0;
+    while k < MAX_RICE_PARAM && (count << (k + 1)) < sum {
+        k += 1;
+    }
+    k
+}
+
+/// Choose partition order and Rice parameters for a predictor order
+fn fixed_choice(samples: &[i32], order: usize, bps: u32) -> Option<FixedChoice> {
+    let residuals = fixed_residuals(samples, order);
+    let block_len = samples.len();
+
+    // Partition sums at the finest usable order, merged pairwise for coarser ones
+    let mut max_order = 0;
+    while max_order < MAX_PARTITION_ORDER
+        && block_len.is_multiple_of(1 << (max_order + 1))
+        && (block_len >> (max_order + 1)) > order
+    {
+        max_order += 1;
+    }
+
+    let partition_len = block_len >> max_order;
+    let mut sums: Vec<u64> = (0..1usize << max_order)
+        .map(|p| {
+            let start = (p * partition_len).saturating_sub(order);
+            let end = (p + 1) * partition_len - order;
+            residuals[start..end].iter().map(|&r| zigzag(r)).sum()
+        })
+        .collect();
+
+    let mut best: Option<(u64, u32, Vec<u32>)> = None;
+    for partition_order in (0..=max_order).rev() {
+        let partition_len = (block_len >> partition_order) as u64;
+        let mut bits = 2 + 4; // coding method + partition order
+        let mut params = Vec::with_capacity(sums.len());
+        for (p, &sum) in sums.iter().enumerate() {
+            let count = if p == 0 { partition_len - order as u64 } else { partition_len };
+            let k = rice_param(sum, count);
+            bits += 4 + count * (k as u64 + 1) + (sum >> k);
+            params.push(k);
+        }
+        if best.as_ref().map(|b| bits < b.0).unwrap_or(true) {
+            best = Some((bits, partition_order, params));
+        }
+        if partition_order > 0 {
+            sums = sums.chunks(2).map(|pair| pair[0] + pair[1]).collect();
+        }
+    }
+
+    let (residual_bits, partition_order, params) = best?;
+    Some(FixedChoice {
+        order,
+        partition_order,
+        residuals,
+        params,
+        bits: 8 + order as u64 * bps as u64 + residual_bits,
+    })
+}
+
+fn write_residual(out: &mut BitWriter, choice: &FixedChoice, block_len: usize) {
+    out.write(0b00, 2); // Rice coding with 4-bit parameters
+    out.write(choice.partition_order as u64, 4);
+
+    let partition_len = block_len >> choice.partition_order;
+    let mut offset = 0;
+    for (p, &k) in choice.params.iter().enumerate() {
+        let count = if p == 0 { partition_len - choice.order } else { partition_len };
+        out.write(k as u64, 4);
+        for &r in &choice.residuals[offset..offset + count] {
+            let u = zigzag(r);
+            out.write_unary(u >> k);
+            if k > 0 {
+                out.write(u & ((1 << k) - 1), k);
+            }
+        }
+        offset += count;
+    }
+}
+
+fn write_signed(out: &mut BitWriter, value: i32, bits: u32) {
+    out.write((value as u32 as u64) & ((1u64 << bits) - 1), bits);
+}
+
+/// MSB-first bit writer
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    acc_bits: u32,
+}
+
+impl BitWriter {
+    /// Write the low `bits` bits of `value` (at most 56)
+    fn write(&mut self, value: u64, bits: u32) {
+        debug_assert!(bits <= 56);
+        if bits == 0 {
+            return;
+        }
+        self.acc = (self.acc << bits) | (value & ((1u64 << bits) - 1));
+        self.acc_bits += bits;
+        while self.acc_bits >= 8 {
+            self.acc_bits -= 8;
+            self.bytes.push((self.acc >> self.acc_bits) as u8);
+        }
+    }
+
+    /// `count` zero bits followed by a one bit
+    fn write_unary(&mut self, mut count: u64) {
+        while count >= 32 {
+            self.write(0, 32);
+            count -= 32;
+        }
+        self.write(1, count as u32 + 1);
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write(b as u64, 8);
+        }
+    }
+
+    /// Pad with zero bits to the next byte boundary
+    fn align(&mut self) {
+        if self.acc_bits > 0 {
+            self.write(0, 8 - self.acc_bits);
+        }
+    }
+
+    /// Completed bytes (excludes bits not yet flushed)
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn len_bytes(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+/// CRC-8, polynomial x^8 + x^2 + x + 1 (frame header)
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// CRC-16, polynomial x^16 + x^15 + x^2 + 1 (whole frame)
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(flac: &[u8]) -> (claxon::metadata::StreamInfo, Vec<i32>) {
+        let mut reader = claxon::FlacReader::new(std::io::Cursor::new(flac)).unwrap();
+        let info = reader.streaminfo();
+        let samples = reader.samples().map(|s| s.unwrap()).collect();
+        (info, samples)
+    }
+
+    #[test]
+    fn test_roundtrip_speech_like_signal() {
+        // Tone with noise and a silent tail, longer than one block
+        let mut seed = 1u32;
+        let samples: Vec<i32> = (0..10_000)
+            .map(|i| {
+                if i > 9_000 {
+                    return 0;
+                }
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let noise = ((seed >> 16) % 200) as i32 - 100;
+                let tone = ((i as f64 * 0.05).sin() * 8_000.0) as i32;
+                tone + noise
+            })
+            .collect();
+
+        let flac = encode(&PcmAudio {
+            samples: &samples,
+            channels: 1,
+            sample_rate: 16_000,
+            bits_per_sample: 16,
+        })
+        .unwrap();
+
+        let (info, decoded) = decode(&flac);
+        assert_eq!(info.sample_rate, 16_000);
+        assert_eq!(info.samples, Some(10_000));
+        assert_eq!(decoded, samples);
+        assert!(flac.len() < samples.len() * 2, "FLAC should be smaller than PCM");
+    }
+
+    #[test]
+    fn test_roundtrip_stereo_extremes() {
+        // Full-scale values and white noise force verbatim/high-order paths
+        let mut seed = 7u32;
+        let samples: Vec<i32> = (0..2 * 5_000)
+            .map(|i| match i % 3 {
+                0 => i16::MAX as i32,
+                1 => i16::MIN as i32,
+                _ => {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (seed >> 16) as i16 as i32
+                }
+            })
+            .collect();
+
+        let flac = encode(&PcmAudio {
+            samples: &samples,
+            channels: 2,
+            sample_rate: 44_100,
+            bits_per_sample: 16,
+        })
+        .unwrap();
+
+        let (info, decoded) = decode(&flac);
+        assert_eq!(info.channels, 2);
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn test_tiny_and_empty_inputs() {
+        for samples in [vec![], vec![5], vec![1, -1, 3]] {
+            let flac = encode(&PcmAudio {
+                samples: &samples,
+                channels: 1,
+                sample_rate: 16_000,
+                bits_per_sample: 16,
+            })
+            .unwrap();
+            assert_eq!(decode(&flac).1, samples);
+        }
+    }
+}
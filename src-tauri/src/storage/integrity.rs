@@ -0,0 +1,423 @@
+//! Storage Integrity Check
+//!
+//! The catalog only indexes readable sidecars, so it cannot see audio without a
+//! sidecar, sidecars whose audio is gone, audio that no longer decodes (e.g. a
+//! truncated WAV header) or files that other users can read. [`verify`] scans
+//! the storage directory for these; [`repair`] fixes what it can and reports
+//! the rest.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::codec::{self, AudioCodec, AudioInfo, AUDIO_EXTENSIONS};
+use super::crypto::{self, KeyStore, AAD_AUDIO, AAD_METADATA};
+use super::{
+    atomic, quarantine, schema, set_secure_permissions, AudioValidationMeta, RecordingMetadata,
+};
+
+/// Kind of problem found by the integrity check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrityIssueKind {
+    /// Audio file without a JSON sidecar
+    OrphanedAudio,
+    /// Sidecar of a recording with audio, but no audio file
+    MissingAudio,
+    /// Audio that cannot be decoded (truncated or damaged)
+    CorruptAudio,
+    /// Sidecar that cannot be parsed
+    CorruptMetadata,
+    /// Encrypted file that cannot be decrypted (data key missing or different)
+    Unreadable,
+    /// Sidecar written by a newer app version (left out of listings until updated)
+    NewerSchema,
+    /// File accessible by other users (Unix permissions other than owner-only)
+    InsecurePermissions,
+}
+
+impl IntegrityIssueKind {
+    /// Whether `repair` can fix a file with this problem
+    fn is_repairable(self) -> bool {
+        !matches!(
+            self,
+            IntegrityIssueKind::MissingAudio
+                | IntegrityIssueKind::Unreadable
+                | IntegrityIssueKind::NewerSchema
+        )
+    }
+}
+
+/// A single problem found by the integrity check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    /// File stem shared by the recording's audio and sidecar
+    pub base_name: String,
+    /// Name of the affected file inside the storage directory
+    pub file: String,
+    pub detail: String,
+    /// Whether `repair` can fix it
+    pub repairable: bool,
+}
+
+/// Result of scanning the storage directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// Recordings (distinct base names) examined
+    pub checked_recordings: usize,
+    pub checked_files: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+/// Result of repairing the storage directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    /// Issues fixed in place (sidecar rebuilt, permissions reset)
+    pub repaired: usize,
+    /// Recordings moved to quarantine
+    pub quarantined: usize,
+    /// Issues left for the user (missing audio, missing data key, failed fixes)
+    pub unresolved: Vec<IntegrityIssue>,
+}
+
+/// Files of one recording in the storage directory
+#[derive(Default)]
+struct RecordingFiles {
+    sidecar: Option<PathBuf>,
+    audio: Vec<PathBuf>,
+}
+
+/// Group the recording files in the storage directory by base name.
+/// Hidden files and directories (catalog, trash, quarantine) are skipped.
+fn group_files(storage_dir: &Path) -> Result<BTreeMap<String, RecordingFiles>, String> {
+    let mut groups: BTreeMap<String, RecordingFiles> = BTreeMap::new();
+
+    for entry in std::fs::read_dir(storage_dir)
+        .map_err(|e| format!("Failed to read storage directory: {}", e))?
+        .flatten()
+    {
+        if !entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            continue;
+        }
+        let path = entry.path();
+        let (Some(base_name), Some(ext)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            path.extension().and_then(|s| s.to_str()),
+        ) else {
+            continue;
+        };
+        if base_name.starts_with('.') {
+            continue;
+        }
+
+        let files = groups.entry(base_name.to_string()).or_default();
+        if ext == "json" {
+            files.sidecar = Some(path);
+        } else if AUDIO_EXTENSIONS.contains(&ext) {
+            files.audio.push(path);
+        }
+    }
+
+    groups.retain(|_, files| files.sidecar.is_some() || !files.audio.is_empty());
+    Ok(groups)
+}
+
+#[cfg(unix)]
+fn has_secure_permissions(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o077 == 0)
+        .unwrap_or(true)
+}
+
+#[cfg(not(unix))]
+fn has_secure_permissions(_path: &Path) -> bool {
+    // Windows uses ACLs, not Unix-style permissions
+    true
+}
+
+/// Parse a sidecar for the integrity check
+fn check_sidecar(
+    path: &Path,
+    keys: &KeyStore,
+) -> Result<RecordingMetadata, (IntegrityIssueKind, String)> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        (
+            IntegrityIssueKind::Unreadable,
+            format!("Failed to read: {}", e),
+        )
+    })?;
+    let content = keys
+        .open(&bytes, AAD_METADATA)
+        .map_err(|e| (IntegrityIssueKind::Unreadable, e))?;
+
+    match schema::parse_metadata(&content) {
+        Ok((metadata, _)) => Ok(metadata),
+        Err(e @ schema::SchemaError::TooNew(_)) => {
+            Err((IntegrityIssueKind::NewerSchema, e.to_string()))
+        }
+        Err(schema::SchemaError::Invalid(reason)) => {
+            Err((IntegrityIssueKind::CorruptMetadata, reason))
+        }
+    }
+}
+
+/// Decoded audio properties, `None` if this build cannot decode the codec
+fn check_audio(
+    path: &Path,
+    keys: &KeyStore,
+) -> Result<Option<AudioInfo>, (IntegrityIssueKind, String)> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        (
+            IntegrityIssueKind::Unreadable,
+            format!("Failed to read: {}", e),
+        )
+    })?;
+    let audio = keys
+        .open(&bytes, AAD_AUDIO)
+        .map_err(|e| (IntegrityIssueKind::Unreadable, e))?;
+
+    if !AudioCodec::detect(&audio).is_available() {
+        return Ok(None);
+    }
+    codec::probe(&audio)
+        .map(Some)
+        .map_err(|e| (IntegrityIssueKind::CorruptAudio, e))
+}
+
+/// Scan the storage directory for orphaned, missing and damaged files and
+/// insecure permissions. Nothing is changed.
+pub fn verify(storage_dir: &Path, keys: &KeyStore) -> Result<IntegrityReport, String> {
+    let groups = group_files(storage_dir)?;
+    let mut report = IntegrityReport {
+        checked_recordings: groups.len(),
+        ..Default::default()
+    };
+
+    for (base_name, files) in &groups {
+        let mut push = |kind: IntegrityIssueKind, path: &Path, detail: &str, repairable: bool| {
+            report.issues.push(IntegrityIssue {
+                kind,
+                base_name: base_name.clone(),
+                file: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                detail: detail.to_string(),
+                repairable,
+            });
+        };
+
+        for path in files.sidecar.iter().chain(&files.audio) {
+            report.checked_files += 1;
+            if !has_secure_permissions(path) {
+                push(
+                    IntegrityIssueKind::InsecurePermissions,
+                    path,
+                    "Readable by other users",
+                    true,
+                );
+            }
+        }
+
+        let mut expects_audio = false;
+        if let Some(path) = &files.sidecar {
+            match check_sidecar(path, keys) {
+                // Text imports are saved without audio and with fileSize 0
+                Ok(metadata) => expects_audio = metadata.file_size > 0,
+                Err((kind, detail)) => push(kind, path, &detail, kind.is_repairable()),
+            }
+        }
+
+        // First intact audio file, and whether it can be decoded to rebuild a sidecar
+        let mut intact_audio: Option<(&PathBuf, bool)> = None;
+        for path in &files.audio {
+            match check_audio(path, keys) {
+                Ok(info) => {
+                    intact_audio.get_or_insert((path, info.is_some()));
+                }
+                Err((kind, detail)) => push(kind, path, &detail, kind.is_repairable()),
+            }
+        }
+
+        match (&files.sidecar, intact_audio) {
+            (None, Some((path, true))) => push(
+                IntegrityIssueKind::OrphanedAudio,
+                path,
+                "No metadata file",
+                true,
+            ),
+            (None, Some((path, false))) => push(
+                IntegrityIssueKind::OrphanedAudio,
+                path,
+                "No metadata file; this build cannot decode the audio to rebuild it",
+                false,
+            ),
+            (Some(path), _) if expects_audio && files.audio.is_empty() => push(
+                IntegrityIssueKind::MissingAudio,
+                path,
+                "Audio file is missing",
+                false,
+            ),
+            _ => {}
+        }
+    }
+
+    tracing::info!(
+        recordings = report.checked_recordings,
+        issues = report.issues.len(),
+        path = %storage_dir.display(),
+        "Storage: Integrity check finished"
+    );
+    Ok(report)
+}
+
+/// Fix the issues `verify` finds, where possible:
+/// - insecure permissions are reset to owner-only
+/// - orphaned audio gets a sidecar rebuilt from the decoded audio
+/// - recordings with corrupt audio or metadata are quarantined
+///
+/// The caller is responsible for re-indexing the storage directory.
+pub fn repair(storage_dir: &Path, keys: &KeyStore) -> Result<RepairReport, String> {
+    let report = verify(storage_dir, keys)?;
+    let mut result = RepairReport::default();
+    let mut quarantined = BTreeSet::new();
+
+    // Issues of a recording are listed permissions first, so files are fixed
+    // before they may be moved to quarantine
+    for issue in report.issues {
+        if !issue.repairable {
+            result.unresolved.push(issue);
+            continue;
+        }
+        if quarantined.contains(&issue.base_name) {
+            continue;
+        }
+
+        let path = storage_dir.join(&issue.file);
+        let outcome = match issue.kind {
+            IntegrityIssueKind::InsecurePermissions => set_secure_permissions(&path),
+            IntegrityIssueKind::OrphanedAudio => {
+                rebuild_sidecar(storage_dir, &issue.base_name, &path, keys)
+            }
+            IntegrityIssueKind::CorruptAudio | IntegrityIssueKind::CorruptMetadata => {
+                quarantine::quarantine(storage_dir, &issue.base_name, &issue.detail).map(|()| {
+                    quarantined.insert(issue.base_name.clone());
+                })
+            }
+            // Never repairable, already listed as unresolved
+            IntegrityIssueKind::MissingAudio
+            | IntegrityIssueKind::Unreadable
+            | IntegrityIssueKind::NewerSchema => continue,
+        };
+
+        match outcome {
+            Ok(()) if quarantined.contains(&issue.base_name) => result.quarantined += 1,
+            Ok(()) => result.repaired += 1,
+            Err(e) => {
+                tracing::warn!(file = %issue.file, error = %e, "Storage: Repair failed");
+                result.unresolved.push(IntegrityIssue {
+                    detail: format!("{} (repair failed: {})", issue.detail, e),
+                    ..issue
+                });
+            }
+        }
+    }
+
+    tracing::info!(
+        repaired = result.repaired,
+        quarantined = result.quarantined,
+        unresolved = result.unresolved.len(),
+        "Storage: Repair finished"
+    );
+    Ok(result)
+}
+
+/// Write a sidecar for orphaned audio, with what the audio itself reveals.
+/// The sidecar is encrypted if the audio is.
+fn rebuild_sidecar(
+    storage_dir: &Path,
+    base_name: &str,
+    audio_path: &Path,
+    keys: &KeyStore,
+) -> Result<(), String> {
+    let stored = std::fs::read(audio_path).map_err(|e| format!("Failed to read audio: {}", e))?;
+    let info = codec::probe(&keys.open(&stored, AAD_AUDIO)?)?;
+
+    let mut metadata = RecordingMetadata::new(
+        info.duration_ms(),
+        info.sample_rate,
+        info.wav_size(),
+        AudioValidationMeta {
+            rms_energy: info.rms_energy,
+            duration_ms: info.duration_ms(),
+            sample_count: info.frames as usize,
+            passed: info.frames > 0,
+        },
+        "unknown".to_string(),
+        "unknown".to_string(),
+    );
+    metadata.id = id_from_name(base_name);
+    metadata.created_at = created_at_from_name(base_name)
+        .or_else(|| {
+            std::fs::metadata(audio_path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(|t| DateTime::<Utc>::from(t).to_rfc3339())
+        })
+        .unwrap_or_else(|| Utc::now().to_rfc3339());
+
+    let json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    let content = keys.seal(json.as_bytes(), AAD_METADATA, crypto::is_encrypted(&stored))?;
+    atomic::write_file(&storage_dir.join(format!("{}.json", base_name)), &content)?;
+
+    tracing::info!(name = %base_name, "Storage: Metadata rebuilt from audio");
+    Ok(())
+}
+
+/// New recording ID that keeps the 8-character ID prefix from the file name
+/// (`<timestamp>_<id8>`), so the name still matches the metadata
+fn id_from_name(base_name: &str) -> String {
+    let id = Uuid::new_v4().to_string();
+    match base_name.rsplit_once('_') {
+        Some((_, prefix)) if prefix.len() == 8 && prefix.chars().all(|c| c.is_ascii_hexdigit()) => {
+            format!("{}{}", prefix.to_ascii_lowercase(), &id[8..])
+        }
+        _ => id,
+    }
+}
+
+/// Recording time from the `<YYYY-MM-DD_HH-MM-SS>_<id8>` file name
+fn created_at_from_name(base_name: &str) -> Option<String> {
+    let timestamp = base_name.get(..19)?;
+    NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d_%H-%M-%S")
+        .ok()
+        .map(|dt| dt.and_utc().to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_parsing() {
+        let base = "2026-01-25_20-30-45_a1b2c3d4";
+        assert_eq!(
+            created_at_from_name(base).as_deref(),
+            Some("2026-01-25T20:30:45+00:00")
+        );
+        let id = id_from_name(base);
+        assert!(id.starts_with("a1b2c3d4-"));
+        assert!(Uuid::parse_str(&id).is_ok());
+
+        assert_eq!(created_at_from_name("recording"), None);
+        assert!(Uuid::parse_str(&id_from_name("recording")).is_ok());
+    }
+}
@@ -0,0 +1,3886 @@
+//! Persistent Audio Storage Module
+//! Handles saving, listing, and managing audio recordings.
+//! Guidelines: docs/reference/guidelines/RUST.md
+//!
+//! Submodules:
+//! - analytics: Emotion/tone trends (rolling baselines, distributions, shifts)
+//! - annotations: User title, notes and tags (validation, normalization)
+//! - atomic: Crash-safe writes (temp file + rename, save journal and recovery)
+//! - archive: Portable tar.gz export/import with checksummed manifest
+//! - backup: Incremental snapshots in a backup directory (rotation, verified restore)
+//! - dedup: Audio content hash and fingerprint (duplicate check and library scan)
+//! - codec: Stored audio format (WAV, FLAC, Opus), decoded back to WAV on read
+//! - flac: Lossless FLAC encoder
+//! - integrity: Storage directory check (orphans, damaged audio, permissions) and repair
+//! - catalog: SQLite index over the JSON sidecars (lookups, sorted listings)
+//! - crypto: Optional AES-256-GCM encryption at rest (data key in OS keyring)
+//! - query: Filtered, paginated listings and lightweight summaries
+//! - search: Full-text search analysis (German stemming, snippets)
+//! - schema: Metadata schema versions and migration chain
+//! - quarantine: Unreadable sidecars moved aside instead of dropped
+//! - retention: Cleanup plan (age, count and storage quota; pinned recordings kept)
+//! - revisions: Transcription history per recording and word-level diffs
+//! - spool: Native recordings written to disk while captured (crash recovery)
+//! - sync: WebDAV library sync (per-recording conflicts, client-side encryption)
+//! - trash: Deleted recordings kept for restore until their retention expires
+//! - watcher: Debounced file system watcher for changes made outside the app
+//! - webdav: Minimal WebDAV client for the sync collection
+
+mod analytics;
+mod annotations;
+pub(crate) mod atomic;
+mod archive;
+mod backup;
+mod catalog;
+mod codec;
+mod crypto;
+mod dedup;
+mod flac;
+mod integrity;
+mod quarantine;
+mod retention;
+mod revisions;
+mod query;
+mod schema;
+mod search;
+mod spool;
+mod sync;
+mod trash;
+mod watcher;
+mod webdav;
+
+pub use analytics::EmotionTrends;
+pub use backup::{BackupResult, BackupSnapshot, RestoreResult};
+pub use annotations::RecordingMetadataUpdate;
+pub use codec::AudioCodec;
+pub use dedup::{DuplicateGroup, DuplicatePolicy};
+pub use integrity::{IntegrityIssue, IntegrityIssueKind, IntegrityReport, RepairReport};
+pub use quarantine::QuarantinedRecording;
+pub use retention::{CleanupPlan, EvictionReason, PlannedEviction};
+pub use revisions::{
+    DiffKind, DiffSegment, NewTranscriptionRevision, TranscriptionHistory, TranscriptionRevision,
+};
+pub use query::{AnalysisKind, RecordingPage, RecordingQuery, RecordingSort, RecordingSummary};
+pub use schema::CURRENT_SCHEMA_VERSION;
+pub use search::{SearchField, SearchResult, SearchSnippet, SnippetPart};
+pub use spool::{InterruptedRecording, SpoolWriter};
+pub use sync::{SyncConflict, SyncResult, SyncStatus};
+pub use trash::{TrashReason, TrashedRecording};
+pub use watcher::{RecordingChange, RecordingChangeKind, StorageWatcher};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::commands::keyring::{get_password_blocking, set_password_blocking};
+use crate::types::psychological::{CognitiveDistortionResult, FourSidesAnalysis, GfkAnalysis};
+
+use catalog::{CatalogEntry, RecordingCatalog};
+use crypto::{KeyStore, AAD_AUDIO, AAD_METADATA, DEFAULT_DATA_KEY_USER};
+
+/// Audio validation metadata captured during recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioValidationMeta {
+    pub rms_energy: f32,
+    pub duration_ms: u64,
+    pub sample_count: usize,
+    pub passed: bool,
+}
+
+/// Input device a recording was captured with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputDeviceMeta {
+    pub name: String,
+    /// Stable device id (`name#index`) the device was selected with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    /// Capture config before resampling to 16 kHz mono
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sample_format: String,
+    /// Device the recording started on before it was lost mid-recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failover_from: Option<String>,
+}
+
+/// Deliberate break in a native recording (paused and resumed by the user).
+/// The break is not part of the audio; it lies between `offset_ms` and the
+/// following sample.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPause {
+    /// Position in the recorded audio
+    pub offset_ms: u64,
+    /// Length of the break (wall clock)
+    pub duration_ms: u64,
+}
+
+/// VAD (Voice Activity Detection) statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VadStatsMeta {
+    pub original_samples: usize,
+    pub filtered_samples: usize,
+    pub speech_ratio: f32,
+    pub frames_processed: usize,
+    pub speech_frames: usize,
+}
+
+/// Transcription metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionMeta {
+    pub text: String,
+    pub provider: String,
+    pub model: String,
+    pub language: String,
+    pub processing_time_ms: u64,
+}
+
+/// Text filter metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFilterMeta {
+    pub original_text: String,
+    pub filtered_text: String,
+    pub filler_words_removed: usize,
+    pub hallucinations_detected: bool,
+}
+
+/// Emotion analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionData {
+    pub primary: String,
+    pub confidence: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secondary: Option<String>,
+}
+
+/// Tone analysis result (5-dimensional communication style)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToneData {
+    pub formality: u8,       // 1-5
+    pub professionalism: u8, // 1-5
+    pub directness: u8,      // 1-5
+    pub energy: u8,          // 1-5
+    pub seriousness: u8,     // 1-5
+    pub confidence: f32,     // 0.0-1.0
+}
+
+/// Baseline emotion result from all recordings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineResult {
+    pub emotion: String,
+    pub confidence: f32,
+    pub sample_count: usize,
+}
+
+/// Fallacy data for storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FallacyData {
+    #[serde(rename = "type")]
+    pub fallacy_type: String,
+    pub confidence: f32,
+    pub quote: String,
+    pub explanation: String,
+    pub suggestion: String,
+}
+
+/// Topic classification result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicData {
+    pub topic: String,
+    pub confidence: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+/// Analysis result stored with recording (emotion + fallacies + enrichment + topic)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisResultData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emotion: Option<EmotionData>,
+    pub fallacies: Vec<FallacyData>,
+    pub enrichment: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<TopicData>,
+}
+
+/// Complete recording metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingMetadata {
+    /// Sidecar layout version (see `schema` module), set by the backend on save
+    #[serde(default)]
+    pub schema_version: u32,
+    pub id: String,
+    pub created_at: String,
+    pub duration_ms: u64,
+    pub sample_rate: u32,
+    pub file_size: usize,
+    pub audio_validation: AudioValidationMeta,
+    pub vad_stats: Option<VadStatsMeta>,
+    pub transcription: Option<TranscriptionMeta>,
+    pub text_filter: Option<TextFilterMeta>,
+    pub provider: String,
+    pub model: String,
+    pub app_version: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emotion: Option<EmotionData>,
+
+    /// Full analysis result (emotion + fallacies + enrichment)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analysis_result: Option<AnalysisResultData>,
+
+    /// Tone analysis result (5-dimensional communication style)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tone: Option<ToneData>,
+
+    /// Input source (recording/text/file) - optional for backward compatibility
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    /// GFK (Gewaltfreie Kommunikation) analysis result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gfk: Option<GfkAnalysis>,
+
+    /// Cognitive Distortion analysis result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cognitive: Option<CognitiveDistortionResult>,
+
+    /// Four-Sides Communication Model analysis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub four_sides: Option<FourSidesAnalysis>,
+
+    /// User-given title (see `annotations` module)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// User notes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+
+    /// User tags (normalized: lowercase, unique, sorted)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Pinned by the user; never removed by automatic cleanup
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+
+    /// When title, notes, tags or pinning were last edited (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<String>,
+
+    /// All transcriptions of this recording (see `revisions` module)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transcription_revisions: Vec<TranscriptionRevision>,
+
+    /// Revision mirrored into `transcription`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_revision: Option<u32>,
+
+    /// SHA-256 of the decoded audio (see `dedup` module), set at save time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+
+    /// Existing recording with the same audio when this one was saved;
+    /// duplicates are left out of baselines and trends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+
+    /// Microphone and capture config of native recordings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_device: Option<InputDeviceMeta>,
+
+    /// Breaks of native recordings; not speech pauses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pauses: Vec<RecordingPause>,
+}
+
+impl RecordingMetadata {
+    /// Create new recording metadata with generated ID and timestamp
+    #[allow(dead_code)]
+    pub fn new(
+        duration_ms: u64,
+        sample_rate: u32,
+        file_size: usize,
+        audio_validation: AudioValidationMeta,
+        provider: String,
+        model: String,
+    ) -> Self {
+        Self {
+            schema_version: schema::CURRENT_SCHEMA_VERSION,
+            id: Uuid::new_v4().to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            duration_ms,
+            sample_rate,
+            file_size,
+            audio_validation,
+            vad_stats: None,
+            transcription: None,
+            text_filter: None,
+            provider,
+            model,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            emotion: None,
+            analysis_result: None,
+            tone: None,
+            source: None,
+            gfk: None,
+            cognitive: None,
+            four_sides: None,
+            title: None,
+            notes: None,
+            tags: Vec::new(),
+            pinned: false,
+            edited_at: None,
+            transcription_revisions: Vec::new(),
+            active_revision: None,
+            content_hash: None,
+            duplicate_of: None,
+            input_device: None,
+            pauses: Vec::new(),
+        }
+    }
+}
+
+/// Storage configuration (missing fields fall back to defaults)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StorageConfig {
+    pub storage_enabled: bool,
+    pub user_mode_enabled: bool,
+    pub max_recordings: usize,
+    /// Storage quota for recordings on disk (0 = unlimited)
+    pub max_user_storage_mb: usize,
+    /// Remove recordings older than this many days (0 = keep regardless of age)
+    pub retention_days: u32,
+    pub storage_path: String,
+    /// Encrypt newly saved recordings (existing files keep their format until converted)
+    pub encryption_enabled: bool,
+    /// Days deleted recordings stay in the trash (0 = delete immediately)
+    pub trash_retention_days: u32,
+    /// Format for newly saved audio (existing files keep theirs until recompressed)
+    pub audio_codec: AudioCodec,
+    /// What saving audio that is already in the library does
+    pub duplicate_policy: DuplicatePolicy,
+    /// Directory for backup snapshots (None = backups disabled)
+    pub backup_path: Option<String>,
+    /// Hours between scheduled backups (0 = manual backups only)
+    pub backup_interval_hours: u32,
+    /// Snapshots kept in the backup directory (oldest removed first)
+    pub backup_keep: usize,
+    /// WebDAV collection the library is synced with (None = sync disabled)
+    pub sync_url: Option<String>,
+    /// WebDAV user name (the password is kept in the keyring)
+    pub sync_username: String,
+    /// Encrypt files before upload with the sync key shared by all devices
+    pub sync_encrypt: bool,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            storage_enabled: true,
+            user_mode_enabled: false,
+            max_recordings: 100,
+            max_user_storage_mb: 500,
+            retention_days: 0,
+            storage_path: get_default_storage_path()
+                .to_string_lossy()
+                .to_string(),
+            encryption_enabled: false,
+            trash_retention_days: 30,
+            audio_codec: AudioCodec::default(),
+            duplicate_policy: DuplicatePolicy::default(),
+            backup_path: None,
+            backup_interval_hours: 24,
+            backup_keep: 7,
+            sync_url: None,
+            sync_username: String::new(),
+            sync_encrypt: true,
+        }
+    }
+}
+
+/// Profile that owns the default storage path and the original data key
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// Default storage path of a profile: the platform path for the default
+/// profile, `profiles/<id>` next to it for all others
+pub fn profile_storage_path(profile_id: &str) -> PathBuf {
+    let default_path = get_default_storage_path();
+    if profile_id == DEFAULT_PROFILE_ID {
+        return default_path;
+    }
+    default_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("profiles")
+        .join(profile_id)
+}
+
+/// Set secure file permissions (owner-only read/write)
+///
+/// On Unix: Sets 0o600 (rw-------)
+/// On Windows: No-op (Windows uses ACLs, not Unix permissions)
+#[cfg(unix)]
+pub(crate) fn set_secure_permissions(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to set permissions on {}: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn set_secure_permissions(_path: &std::path::Path) -> Result<(), String> {
+    // Windows uses ACLs, not Unix-style permissions
+    // File inherits directory permissions by default
+    Ok(())
+}
+
+/// Get default storage path based on build configuration.
+///
+/// # App Store Build (`--features app-store`)
+/// Uses `~/Documents/Hablara/recordings/` on all platforms.
+/// This path is user-accessible and complies with Apple Guideline 2.4.5(i).
+///
+/// # Direct Distribution Build (default)
+/// Uses platform-native paths:
+/// - macOS: `~/Library/Application Support/Hablara/recordings/`
+/// - Linux: `~/.local/share/hablara/recordings/` (XDG)
+/// - Windows: `%LOCALAPPDATA%\Hablara\recordings\`
+fn get_default_storage_path() -> PathBuf {
+    #[cfg(feature = "app-store")]
+    {
+        get_app_store_storage_path()
+    }
+
+    #[cfg(not(feature = "app-store"))]
+    {
+        get_direct_distribution_storage_path()
+    }
+}
+
+/// Storage path for App Store builds: ~/Documents/Hablara/recordings/
+///
+/// # Panics
+/// Panics if Documents directory is unavailable in sandboxed App Store build.
+/// This indicates a broken sandbox configuration that must be fixed.
+#[cfg(feature = "app-store")]
+fn get_app_store_storage_path() -> PathBuf {
+    dirs::document_dir()
+        .or_else(|| dirs::home_dir().map(|h| h.join("Documents")))
+        .expect("FATAL: Documents directory not available - App Store sandbox may be misconfigured")
+        .join("Hablara")
+        .join("recordings")
+}
+
+/// Storage path for direct distribution builds (platform-native paths)
+#[cfg(not(feature = "app-store"))]
+fn get_direct_distribution_storage_path() -> PathBuf {
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::get_app_support_storage_path()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to get Application Support path: {e}");
+                std::env::temp_dir().join("hablara_recordings")
+            })
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::linux::get_xdg_storage_path()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to get XDG data path: {e}");
+                std::env::temp_dir().join("hablara_recordings")
+            })
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        crate::platform::windows::get_local_app_data_storage_path()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to get LocalAppData path: {e}");
+                std::env::temp_dir().join("hablara_recordings")
+            })
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        std::env::temp_dir().join("hablara_recordings")
+    }
+}
+
+/// Migrate recordings from legacy path (~/Hablara/recordings/) to new location.
+///
+/// This function is called on app startup to migrate existing recordings
+/// from the old home directory path to the new platform-native location.
+///
+/// The migration uses a safe two-phase approach:
+/// 1. Attempt atomic rename (fast, same filesystem)
+/// 2. Fall back to copy with integrity verification, then delete source
+///
+/// Returns the number of files migrated.
+pub fn migrate_legacy_storage() -> Result<usize, String> {
+    let legacy_path = dirs::home_dir()
+        .ok_or("Home directory not found")?
+        .join("Hablara")
+        .join("recordings");
+
+    if !legacy_path.exists() {
+        return Ok(0);
+    }
+
+    let current_path = get_default_storage_path();
+    if legacy_path == current_path {
+        return Ok(0);
+    }
+
+    // Read directory entries
+    let files: Vec<_> = std::fs::read_dir(&legacy_path)
+        .map_err(|e| format!("Failed to read legacy directory: {e}"))?
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            matches!(
+                e.path().extension().and_then(|s| s.to_str()),
+                Some("wav") | Some("json")
+            )
+        })
+        .collect();
+
+    if files.is_empty() {
+        return Ok(0);
+    }
+
+    // Create target directory
+    std::fs::create_dir_all(&current_path)
+        .map_err(|e| format!("Failed to create target directory: {e}"))?;
+
+    let mut migrated = 0;
+    for entry in files {
+        let src = entry.path();
+        let dest = current_path.join(src.file_name().unwrap());
+        if !dest.exists() {
+            // Try rename first (fast, atomic, same filesystem)
+            if std::fs::rename(&src, &dest).is_ok() {
+                // Set secure permissions on renamed file
+                if let Err(e) = set_secure_permissions(&dest) {
+                    tracing::warn!("Failed to set permissions after rename: {e}");
+                }
+                migrated += 1;
+                continue;
+            }
+
+            // Fall back to copy with integrity verification
+            let src_size = std::fs::metadata(&src)
+                .map_err(|e| format!("Failed to read source metadata: {e}"))?
+                .len();
+
+            let bytes_copied = std::fs::copy(&src, &dest)
+                .map_err(|e| format!("Failed to copy file: {e}"))?;
+
+            // Verify integrity: file size must match
+            if bytes_copied != src_size {
+                // Remove partial copy and continue with next file (don't delete source)
+                let _ = std::fs::remove_file(&dest);
+                tracing::warn!(
+                    src = %src.display(),
+                    expected = src_size,
+                    actual = bytes_copied,
+                    "Migration: incomplete copy, skipping file"
+                );
+                continue;
+            }
+
+            // Set secure permissions on copied file
+            if let Err(e) = set_secure_permissions(&dest) {
+                tracing::warn!("Failed to set permissions after copy: {e}");
+            }
+
+            // Only delete source after successful, verified copy
+            if let Err(e) = std::fs::remove_file(&src) {
+                tracing::warn!(src = %src.display(), error = %e, "Failed to remove source after migration");
+            }
+            migrated += 1;
+        }
+    }
+
+    // Cleanup empty legacy directories
+    if std::fs::read_dir(&legacy_path)
+        .map(|mut d| d.next().is_none())
+        .unwrap_or(false)
+    {
+        let _ = std::fs::remove_dir(&legacy_path);
+        if let Some(parent) = legacy_path.parent() {
+            let _ = std::fs::remove_dir(parent);
+        }
+    }
+
+    if migrated > 0 {
+        tracing::info!(migrated, "Migrated recordings from legacy storage");
+
+        // Keep the catalog at the target location in sync with the moved sidecars.
+        // Files are moved as-is, so encrypted sidecars are decrypted while indexing.
+        let keys = Arc::new(KeyStore::new(DEFAULT_DATA_KEY_USER));
+        match RecordingCatalog::open(&current_path, keys.clone()) {
+            Ok((mut catalog, _)) => {
+                if let Err(e) = rebuild_from_sidecars(&mut catalog, &keys) {
+                    tracing::warn!("Failed to rebuild catalog after migration: {e}");
+                }
+            }
+            Err(e) => tracing::warn!("Failed to open catalog after migration: {e}"),
+        }
+
+        // Moved files are not checked individually; report incomplete or damaged ones
+        match integrity::verify(&current_path, &keys) {
+            Ok(report) if !report.issues.is_empty() => tracing::warn!(
+                issues = report.issues.len(),
+                "Storage: Integrity issues after migration, run repair_storage"
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Integrity check after migration failed: {e}"),
+        }
+    }
+    Ok(migrated)
+}
+
+/// Parse all metadata sidecars in a storage directory (plaintext or encrypted).
+///
+/// Older schema versions are upgraded in memory. Unparseable sidecars are moved
+/// to quarantine once they have settled (see [`quarantine::SETTLE_DELAY`]);
+/// until then, and for unreadable ones (I/O, missing key) and sidecars from a
+/// newer app version, they are logged and left in place. The integrity check
+/// reports all of them.
+fn scan_sidecars(storage_dir: &Path, keys: &KeyStore) -> Result<Vec<CatalogEntry>, String> {
+    let mut entries = Vec::new();
+
+    let dir = std::fs::read_dir(storage_dir)
+        .map_err(|e| format!("Failed to read storage directory: {}", e))?;
+
+    for entry in dir.flatten() {
+        let path = entry.path();
+        if !path.extension().map(|e| e == "json").unwrap_or(false) {
+            continue;
+        }
+        // Hidden files (sync state) are not sidecars
+        let Some(base_name) = watcher::recording_base_name(storage_dir, &path) else {
+            continue;
+        };
+
+        let content = std::fs::read(&path)
+            .map_err(|e| format!("Failed to read metadata: {}", e))
+            .and_then(|bytes| keys.open(&bytes, AAD_METADATA));
+
+        match content {
+            Ok(content) => match schema::parse_metadata(&content) {
+                Ok((metadata, _)) => entries.push(CatalogEntry {
+                    base_name,
+                    metadata,
+                }),
+                Err(schema::SchemaError::TooNew(version)) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        version,
+                        "Storage: Metadata written by a newer app version, skipping"
+                    );
+                }
+                Err(schema::SchemaError::Invalid(reason)) if !quarantine::is_settled(&path) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        reason = %reason,
+                        "Storage: Unparseable metadata, skipping until it settles"
+                    );
+                }
+                Err(schema::SchemaError::Invalid(reason)) => {
+                    if let Err(e) = quarantine::quarantine(storage_dir, &base_name, &reason) {
+                        tracing::warn!(
+                            path = %path.display(),
+                            error = %e,
+                            "Storage: Failed to quarantine unparseable metadata"
+                        );
+                    }
+                }
+            },
+            Err(e) => {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "Storage: Failed to read metadata"
+                );
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Replace the catalog contents with the sidecars currently on disk
+fn rebuild_from_sidecars(catalog: &mut RecordingCatalog, keys: &KeyStore) -> Result<usize, String> {
+    let entries = scan_sidecars(catalog.storage_dir(), keys)?;
+    catalog.replace_all(&entries)?;
+    tracing::info!(
+        recordings = entries.len(),
+        path = %catalog.storage_dir().display(),
+        "Catalog: Rebuilt from sidecars"
+    );
+    Ok(entries.len())
+}
+
+/// Complete or roll back saves interrupted by a crash in a storage directory
+/// that is about to be used (see the `atomic` module)
+fn recover_storage_dir(storage_path: &str) {
+    let dir = crate::commands::utils::path::to_long_path(Path::new(storage_path));
+    if !dir.is_dir() {
+        return;
+    }
+    match atomic::recover(&dir) {
+        Ok(report) if report != atomic::RecoveryReport::default() => tracing::warn!(
+            completed = report.completed,
+            rolled_back = report.rolled_back,
+            temp_files = report.temp_files_removed,
+            "Storage: Recovered interrupted writes"
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "Storage: Recovery of interrupted writes failed"),
+    }
+}
+
+/// Read and parse a single sidecar (plaintext or encrypted, any supported schema)
+fn read_sidecar(path: &Path, keys: &KeyStore) -> Result<RecordingMetadata, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    let content = keys.open(&bytes, AAD_METADATA)?;
+    schema::parse_metadata(&content)
+        .map(|(metadata, _)| metadata)
+        .map_err(|e| e.to_string())
+}
+
+/// Path of a recording's audio file in whichever codec it is stored
+fn find_audio_file(storage_dir: &Path, base_name: &str) -> Option<PathBuf> {
+    codec::AUDIO_EXTENSIONS
+        .iter()
+        .map(|ext| storage_dir.join(format!("{}.{}", base_name, ext)))
+        .find(|path| path.exists())
+}
+
+/// Remove the audio and JSON files of a recording (missing files are ignored)
+fn delete_recording_files(storage_dir: &Path, base_name: &str) -> Result<(), String> {
+    // Delete audio file (any codec)
+    for ext in codec::AUDIO_EXTENSIONS {
+        let audio_path = storage_dir.join(format!("{}.{}", base_name, ext));
+        if audio_path.exists() {
+            std::fs::remove_file(&audio_path)
+                .map_err(|e| format!("Failed to delete audio file: {}", e))?;
+        }
+    }
+
+    // Delete JSON file
+    let json_path = storage_dir.join(format!("{}.json", base_name));
+    if json_path.exists() {
+        std::fs::remove_file(&json_path)
+            .map_err(|e| format!("Failed to delete metadata file: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Remove a recording from the storage directory: into the trash, or for good
+/// if trash retention is disabled
+fn discard_recording(
+    storage_dir: &Path,
+    id: &str,
+    base_name: &str,
+    reason: TrashReason,
+    retention_days: u32,
+) -> Result<(), String> {
+    if retention_days == 0 {
+        delete_recording_files(storage_dir, base_name)
+    } else {
+        trash::move_to_trash(storage_dir, id, base_name, reason)
+    }
+}
+
+/// Bytes a recording occupies on disk (audio in any codec plus sidecar)
+fn recording_disk_size(storage_dir: &Path, base_name: &str) -> u64 {
+    codec::AUDIO_EXTENSIONS
+        .iter()
+        .chain(std::iter::once(&"json"))
+        .filter_map(|ext| std::fs::metadata(storage_dir.join(format!("{}.{}", base_name, ext))).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Result of converting a library between plaintext and encrypted storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptionConversionResult {
+    /// Files rewritten in the target format
+    pub converted: usize,
+    /// Files already in the target format
+    pub skipped: usize,
+    /// Files that could not be converted (left untouched)
+    pub failed: usize,
+}
+
+/// Result of rewriting sidecars in the current schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaUpgradeResult {
+    /// Sidecars rewritten in the current schema
+    pub upgraded: usize,
+    /// Sidecars already current
+    pub current: usize,
+    /// Sidecars that could not be upgraded (left untouched)
+    pub failed: usize,
+}
+
+/// Result of recompressing the library's audio files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecompressionResult {
+    /// Audio files rewritten in the target codec
+    pub recompressed: usize,
+    /// Files already in the target codec, or not representable in it
+    pub skipped: usize,
+    /// Files that could not be recompressed (left untouched)
+    pub failed: usize,
+    /// Size of the recompressed files before
+    pub bytes_before: u64,
+    /// Size of the recompressed files after
+    pub bytes_after: u64,
+    /// Space saved on disk (negative when converting to a larger codec)
+    pub bytes_saved: i64,
+}
+
+/// Result of exporting recordings into a library archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryExportResult {
+    /// Recordings written to the archive
+    pub recordings: usize,
+    /// Archive size in bytes
+    pub bytes: u64,
+}
+
+/// Result of importing a library archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryImportResult {
+    /// Recordings added under their original ID
+    pub imported: usize,
+    /// Recordings added under a new ID (ID already used by a different recording)
+    pub renamed: usize,
+    /// Recordings already in the library (same ID and timestamp)
+    pub duplicates: usize,
+    /// Recordings that could not be imported
+    pub failed: usize,
+}
+
+/// How a single archive recording was imported
+enum ImportOutcome {
+    Imported,
+    Renamed,
+    Duplicate,
+}
+
+/// Storage manager for handling recording persistence
+pub struct StorageManager {
+    config: Mutex<StorageConfig>,
+    /// Catalog for the current storage directory (opened lazily)
+    catalog: Mutex<Option<RecordingCatalog>>,
+    /// Data key for encrypted recordings (loaded from keyring on demand)
+    keys: Arc<KeyStore>,
+    /// Held while a backup or restore runs
+    backup_lock: Mutex<()>,
+    /// Key shared by all synced devices (client-side encryption)
+    sync_keys: Arc<KeyStore>,
+    /// Keyring user holding the WebDAV password
+    sync_password_user: String,
+    /// WebDAV password used instead of the keyring (tests, tooling)
+    fixed_sync_password: Option<String>,
+    /// Held while a sync runs
+    sync_lock: Mutex<()>,
+}
+
+/// Flag to ensure migration only runs once per process
+static MIGRATION_DONE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+impl StorageManager {
+    /// Create new storage manager with default config
+    pub fn new() -> Self {
+        // Attempt legacy storage migration on first initialization only
+        if !MIGRATION_DONE.swap(true, std::sync::atomic::Ordering::SeqCst) {
+            if let Err(e) = migrate_legacy_storage() {
+                tracing::warn!("Legacy storage migration failed: {e}");
+            }
+        }
+        let config = StorageConfig::default();
+        recover_storage_dir(&config.storage_path);
+        Self {
+            config: Mutex::new(config),
+            catalog: Mutex::new(None),
+            keys: Arc::new(KeyStore::new(DEFAULT_DATA_KEY_USER)),
+            backup_lock: Mutex::new(()),
+            sync_keys: Arc::new(KeyStore::new(&sync::sync_key_user(DEFAULT_PROFILE_ID))),
+            sync_password_user: sync::password_user(DEFAULT_PROFILE_ID),
+            fixed_sync_password: None,
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    /// Create storage manager with custom config
+    #[allow(dead_code)]
+    pub fn with_config(config: StorageConfig) -> Self {
+        recover_storage_dir(&config.storage_path);
+        Self {
+            config: Mutex::new(config),
+            catalog: Mutex::new(None),
+            keys: Arc::new(KeyStore::new(DEFAULT_DATA_KEY_USER)),
+            backup_lock: Mutex::new(()),
+            sync_keys: Arc::new(KeyStore::new(&sync::sync_key_user(DEFAULT_PROFILE_ID))),
+            sync_password_user: sync::password_user(DEFAULT_PROFILE_ID),
+            fixed_sync_password: None,
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    /// Use the data key of another keyring entry (one per profile)
+    pub fn with_keyring_user(mut self, keyring_user: &str) -> Self {
+        self.keys = Arc::new(KeyStore::new(keyring_user));
+        self
+    }
+
+    /// Use the sync key and WebDAV password of a profile
+    pub fn with_sync_profile(mut self, profile_id: &str) -> Self {
+        self.sync_keys = Arc::new(KeyStore::new(&sync::sync_key_user(profile_id)));
+        self.sync_password_user = sync::password_user(profile_id);
+        self
+    }
+
+    /// Use a fixed data key instead of the keyring (tests, tooling)
+    #[allow(dead_code)]
+    pub fn with_data_key(mut self, key: crypto::DataKey) -> Self {
+        self.keys = Arc::new(KeyStore::with_key(DEFAULT_DATA_KEY_USER, key));
+        self
+    }
+
+    /// Use a fixed WebDAV password instead of the keyring (tests, tooling)
+    #[allow(dead_code)]
+    pub fn with_sync_password(mut self, password: &str) -> Self {
+        self.fixed_sync_password = Some(password.to_string());
+        self
+    }
+
+    /// Get current storage configuration
+    pub fn get_config(&self) -> Result<StorageConfig, String> {
+        self.config
+            .lock()
+            .map_err(|e| format!("Failed to lock config: {}", e))
+            .map(|g| g.clone())
+    }
+
+    /// Update storage configuration
+    pub fn update_config(&self, config: StorageConfig) -> Result<(), String> {
+        let mut guard = self
+            .config
+            .lock()
+            .map_err(|e| format!("Failed to lock config: {}", e))?;
+        if guard.storage_path != config.storage_path {
+            recover_storage_dir(&config.storage_path);
+        }
+        *guard = config;
+        Ok(())
+    }
+
+    /// Ensure storage directory exists
+    fn ensure_storage_dir(&self) -> Result<PathBuf, String> {
+        let config = self.get_config()?;
+        let path = PathBuf::from(&config.storage_path);
+        // Apply Windows Long Path prefix if path exceeds 260 chars
+        let path = crate::commands::utils::path::to_long_path(&path);
+
+        if !path.exists() {
+            std::fs::create_dir_all(&path)
+                .map_err(|e| format!("Failed to create storage directory: {}", e))?;
+        }
+
+        Ok(path)
+    }
+
+    /// Generate filename from metadata
+    fn generate_filename(metadata: &RecordingMetadata) -> String {
+        // Parse ISO8601 timestamp and format for filename
+        let timestamp = DateTime::parse_from_rfc3339(&metadata.created_at)
+            .map(|dt| dt.format("%Y-%m-%d_%H-%M-%S").to_string())
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        format!("{}_{}", timestamp, &metadata.id[..8])
+    }
+
+    /// Run `f` against the catalog of the current storage directory.
+    ///
+    /// The catalog is opened lazily and reopened when the storage path changes.
+    /// It is rebuilt from the sidecars when it is new, has an outdated schema,
+    /// or its row count no longer matches the sidecars on disk.
+    fn with_catalog<T>(
+        &self,
+        f: impl FnOnce(&mut RecordingCatalog) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let encrypt = self.get_config()?.encryption_enabled;
+        let mut guard = self
+            .catalog
+            .lock()
+            .map_err(|e| format!("Failed to lock catalog: {}", e))?;
+
+        let is_current = guard
+            .as_ref()
+            .map(|c| c.storage_dir() == storage_dir)
+            .unwrap_or(false);
+
+        if !is_current {
+            let (mut catalog, needs_rebuild) =
+                RecordingCatalog::open(&storage_dir, self.keys.clone())?;
+            catalog.set_encrypt(encrypt);
+            if needs_rebuild || catalog.count()? != catalog::count_sidecars(&storage_dir) {
+                rebuild_from_sidecars(&mut catalog, &self.keys)?;
+            }
+            *guard = Some(catalog);
+        }
+
+        let catalog = guard.as_mut().expect("catalog initialized above");
+        catalog.set_encrypt(encrypt);
+        f(catalog)
+    }
+
+    /// Rebuild the catalog from the JSON sidecars on disk.
+    /// Returns the number of indexed recordings.
+    pub fn rebuild_catalog(&self) -> Result<usize, String> {
+        self.with_catalog(|c| rebuild_from_sidecars(c, &self.keys))
+    }
+
+    /// Storage directory of the current config (created if missing)
+    pub fn storage_dir(&self) -> Result<PathBuf, String> {
+        self.ensure_storage_dir()
+    }
+
+    /// Bring the catalog in line with recordings changed outside the app.
+    ///
+    /// `base_names` are the recordings the [`StorageWatcher`] on `storage_dir`
+    /// saw touched. Each is compared with its catalog row: new sidecars are
+    /// indexed, sidecars that differ are re-indexed and missing ones removed.
+    /// The app's own writes are already in the catalog and report nothing.
+    /// Sidecars that cannot be read yet (half-synced, being edited) are skipped
+    /// until their next change.
+    pub fn apply_external_changes(
+        &self,
+        storage_dir: &Path,
+        base_names: &[String],
+    ) -> Result<Vec<RecordingChange>, String> {
+        self.with_catalog(|c| {
+            // Storage path changed since the event was queued
+            if c.storage_dir() != storage_dir {
+                return Ok(Vec::new());
+            }
+
+            let mut changes = Vec::new();
+            for base_name in base_names {
+                // Save in progress or interrupted; recovery decides on the next start
+                if storage_dir
+                    .join(format!("{}.{}", base_name, atomic::JOURNAL_EXTENSION))
+                    .exists()
+                {
+                    continue;
+                }
+
+                let existing = c.find_by_base_name(base_name)?;
+                let sidecar_path = storage_dir.join(format!("{}.json", base_name));
+                if !sidecar_path.exists() {
+                    if let Some(entry) = existing {
+                        c.remove(&entry.metadata.id)?;
+                        changes.push(RecordingChange {
+                            kind: RecordingChangeKind::Removed,
+                            id: entry.metadata.id,
+                            base_name: base_name.clone(),
+                        });
+                    }
+                    continue;
+                }
+
+                let metadata = match read_sidecar(&sidecar_path, &self.keys) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        tracing::warn!(
+                            name = %base_name,
+                            error = %e,
+                            "Storage: Skipping externally changed metadata"
+                        );
+                        continue;
+                    }
+                };
+
+                let kind = match existing {
+                    None => RecordingChangeKind::Added,
+                    Some(entry)
+                        if serde_json::to_value(&entry.metadata).ok()
+                            == serde_json::to_value(&metadata).ok() =>
+                    {
+                        continue;
+                    }
+                    Some(entry) => {
+                        if entry.metadata.id != metadata.id {
+                            c.remove(&entry.metadata.id)?;
+                        }
+                        RecordingChangeKind::Modified
+                    }
+                };
+                c.upsert(base_name, &metadata)?;
+                changes.push(RecordingChange {
+                    kind,
+                    id: metadata.id,
+                    base_name: base_name.clone(),
+                });
+            }
+
+            if !changes.is_empty() {
+                tracing::info!(changes = changes.len(), "Storage: Applied external changes");
+            }
+            Ok(changes)
+        })
+    }
+
+    /// Save recording with metadata
+    pub fn save_recording(
+        &self,
+        audio_bytes: &[u8],
+        metadata: &RecordingMetadata,
+    ) -> Result<String, String> {
+        let config = self.get_config()?;
+
+        if !config.storage_enabled {
+            return Err("Storage is disabled".to_string());
+        }
+
+        // Enrich metadata with generated fields
+        let mut enriched_metadata = metadata.clone();
+
+        // Generate ID if empty
+        if enriched_metadata.id.is_empty() {
+            enriched_metadata.id = uuid::Uuid::new_v4().to_string();
+        }
+
+        // Set createdAt if empty
+        if enriched_metadata.created_at.is_empty() {
+            enriched_metadata.created_at = chrono::Utc::now().to_rfc3339();
+        }
+
+        // Set appVersion if empty
+        if enriched_metadata.app_version.is_empty() {
+            enriched_metadata.app_version = env!("CARGO_PKG_VERSION").to_string();
+        }
+
+        // Keep the initial transcription as revision 1
+        revisions::seed(&mut enriched_metadata);
+
+        enriched_metadata.duplicate_of = None;
+        if !audio_bytes.is_empty() {
+            let content_hash = dedup::content_hash(audio_bytes);
+            if let Some(existing) = self.with_catalog(|c| c.find_by_content_hash(&content_hash))? {
+                let existing_id = existing.metadata.id;
+                match config.duplicate_policy {
+                    DuplicatePolicy::Reject => {
+                        return Err(format!("Duplicate of recording {}", existing_id));
+                    }
+                    DuplicatePolicy::Merge => {
+                        self.modify_sidecar(&existing_id, |m| {
+                            dedup::merge_into(m, &enriched_metadata);
+                            Ok(())
+                        })?;
+                        tracing::info!(
+                            id = %existing_id,
+                            "Storage: Duplicate merged into existing recording"
+                        );
+                        return Ok(existing_id);
+                    }
+                    DuplicatePolicy::Warn => {
+                        tracing::warn!(
+                            existing = %existing_id,
+                            "Storage: Saving duplicate recording"
+                        );
+                        enriched_metadata.duplicate_of = Some(existing_id);
+                    }
+                }
+            }
+            enriched_metadata.content_hash = Some(content_hash);
+        }
+
+        self.write_recording(audio_bytes, &mut enriched_metadata)?;
+
+        // Trigger cleanup if needed
+        self.cleanup_old_recordings()?;
+
+        Ok(enriched_metadata.id.clone())
+    }
+
+    /// Write a recording's files (in the configured format) and index it
+    fn write_recording(
+        &self,
+        audio_bytes: &[u8],
+        enriched_metadata: &mut RecordingMetadata,
+    ) -> Result<(), String> {
+        let config = self.get_config()?;
+
+        // Always written in the current layout
+        enriched_metadata.schema_version = schema::CURRENT_SCHEMA_VERSION;
+        if enriched_metadata.content_hash.is_none() && !audio_bytes.is_empty() {
+            enriched_metadata.content_hash = Some(dedup::content_hash(audio_bytes));
+        }
+
+        let storage_dir = self.ensure_storage_dir()?;
+        let base_name = Self::generate_filename(enriched_metadata);
+
+        // Audio in the configured codec (none for text imports)
+        let audio = if audio_bytes.is_empty() {
+            None
+        } else {
+            let (encoded, codec) = codec::encode(audio_bytes, config.audio_codec);
+            let audio_content = self
+                .keys
+                .seal(&encoded, AAD_AUDIO, config.encryption_enabled)?;
+            Some((format!("{}.{}", base_name, codec.extension()), audio_content))
+        };
+
+        // Metadata JSON (use enriched metadata)
+        let json_name = format!("{}.json", base_name);
+        let json_content = serde_json::to_string_pretty(enriched_metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        let json_content =
+            self.keys
+                .seal(json_content.as_bytes(), AAD_METADATA, config.encryption_enabled)?;
+
+        // Both files or neither: written atomically with owner-only permissions,
+        // the sidecar last so listings never show a recording without its audio
+        let mut files: Vec<String> = audio.iter().map(|(name, _)| name.clone()).collect();
+        files.push(json_name.clone());
+        let mut transaction = atomic::SaveTransaction::begin(&storage_dir, &base_name, &files)?;
+        if let Some((audio_name, audio_content)) = &audio {
+            transaction.write(audio_name, audio_content)?;
+        }
+        transaction.write(&json_name, &json_content)?;
+        transaction.commit()?;
+
+        self.with_catalog(|c| c.upsert(&base_name, enriched_metadata))?;
+
+        tracing::info!(
+            name = %base_name,
+            bytes = audio_bytes.len(),
+            "Storage: Recording saved"
+        );
+
+        Ok(())
+    }
+
+    /// List all recordings (newest first)
+    pub fn list_recordings(&self) -> Result<Vec<RecordingMetadata>, String> {
+        let config = self.get_config()?;
+        let storage_path = PathBuf::from(&config.storage_path);
+
+        if !storage_path.exists() {
+            return Ok(vec![]);
+        }
+
+        let entries = self.with_catalog(|c| c.list())?;
+        Ok(entries.into_iter().map(|e| e.metadata).collect())
+    }
+
+    /// Query recordings with filters, sort order and cursor-based pagination
+    pub fn query_recordings(
+        &self,
+        query: &RecordingQuery,
+    ) -> Result<RecordingPage<RecordingMetadata>, String> {
+        let filter = query.filter()?;
+        let cursor = query.page_cursor()?;
+        let limit = query.page_size();
+
+        let config = self.get_config()?;
+        if !PathBuf::from(&config.storage_path).exists() {
+            return Ok(RecordingPage {
+                items: vec![],
+                next_cursor: None,
+            });
+        }
+
+        let mut items = Vec::new();
+        let mut has_more = false;
+        self.with_catalog(|c| {
+            c.scan(query.sort, cursor.as_ref(), |entry| {
+                if !filter.matches(&entry.metadata) {
+                    return true;
+                }
+                if items.len() == limit {
+                    has_more = true;
+                    return false;
+                }
+                items.push(entry.metadata);
+                true
+            })
+        })?;
+
+        let next_cursor = if has_more {
+            items
+                .last()
+                .map(|last| query::PageCursor::after(query.sort, last).encode())
+        } else {
+            None
+        };
+        Ok(RecordingPage { items, next_cursor })
+    }
+
+    /// Like [`Self::query_recordings`], but returns summaries without
+    /// transcripts and analysis payloads
+    pub fn query_recording_summaries(
+        &self,
+        query: &RecordingQuery,
+    ) -> Result<RecordingPage<RecordingSummary>, String> {
+        self.query_recordings(query)
+            .map(|page| page.map(RecordingSummary::from))
+    }
+
+    /// Look up a catalog entry by recording ID
+    fn find_entry(&self, id: &str) -> Result<CatalogEntry, String> {
+        self.with_catalog(|c| c.find(id))?
+            .ok_or_else(|| format!("Recording not found: {}", id))
+    }
+
+    /// Get recording metadata by ID
+    pub fn get_recording(&self, id: &str) -> Result<RecordingMetadata, String> {
+        self.find_entry(id).map(|e| e.metadata)
+    }
+
+    /// Read, modify and write back a recording's sidecar.
+    ///
+    /// The sidecar on disk is replaced atomically in its current encryption
+    /// format and re-indexed. Nothing is written if `modify` fails.
+    fn modify_sidecar<T>(
+        &self,
+        id: &str,
+        modify: impl FnOnce(&mut RecordingMetadata) -> Result<T, String>,
+    ) -> Result<(RecordingMetadata, T), String> {
+        let entry = self.find_entry(id)?;
+        let json_path = self
+            .ensure_storage_dir()?
+            .join(format!("{}.json", entry.base_name));
+
+        let content =
+            std::fs::read(&json_path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let encrypted = crypto::is_encrypted(&content);
+        let (mut metadata, _) = schema::parse_metadata(&self.keys.open(&content, AAD_METADATA)?)
+            .map_err(|e| e.to_string())?;
+
+        let result = modify(&mut metadata)?;
+
+        let json = serde_json::to_string_pretty(&metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        let sealed = self.keys.seal(json.as_bytes(), AAD_METADATA, encrypted)?;
+        atomic::write_file(&json_path, &sealed)?;
+        self.with_catalog(|c| c.upsert(&entry.base_name, &metadata))?;
+
+        Ok((metadata, result))
+    }
+
+    /// Update a recording's title, notes and tags; `editedAt` is set to now
+    pub fn update_recording_metadata(
+        &self,
+        id: &str,
+        update: &RecordingMetadataUpdate,
+    ) -> Result<RecordingMetadata, String> {
+        let (metadata, _) = self.modify_sidecar(id, |metadata| {
+            update.apply_to(metadata)?;
+            metadata.edited_at = Some(Utc::now().to_rfc3339());
+            Ok(())
+        })?;
+
+        tracing::info!(id = %id, "Storage: Recording metadata updated");
+        Ok(metadata)
+    }
+
+    /// Store a new transcription of a recording as its next revision and
+    /// make it active
+    pub fn add_transcription_revision(
+        &self,
+        id: &str,
+        new: NewTranscriptionRevision,
+    ) -> Result<TranscriptionRevision, String> {
+        let (_, revision) = self.modify_sidecar(id, |metadata| {
+            Ok(revisions::add(metadata, new, Utc::now().to_rfc3339()))
+        })?;
+
+        tracing::info!(
+            id = %id,
+            revision = revision.revision,
+            provider = %revision.provider,
+            model = %revision.model,
+            "Storage: Transcription revision added"
+        );
+        Ok(revision)
+    }
+
+    /// All transcription revisions of a recording
+    pub fn get_transcription_history(&self, id: &str) -> Result<TranscriptionHistory, String> {
+        let mut metadata = self.get_recording(id)?;
+        revisions::seed(&mut metadata);
+        Ok(TranscriptionHistory::from(&metadata))
+    }
+
+    /// Make a transcription revision the recording's current transcription
+    pub fn set_active_transcription_revision(
+        &self,
+        id: &str,
+        revision: u32,
+    ) -> Result<RecordingMetadata, String> {
+        let (metadata, _) = self.modify_sidecar(id, |metadata| {
+            revisions::seed(metadata);
+            revisions::activate(metadata, revision)
+        })?;
+
+        tracing::info!(id = %id, revision, "Storage: Active transcription revision changed");
+        Ok(metadata)
+    }
+
+    /// Word-level diff of the filtered text from revision `from` to `to`
+    pub fn diff_transcription_revisions(
+        &self,
+        id: &str,
+        from: u32,
+        to: u32,
+    ) -> Result<Vec<DiffSegment>, String> {
+        let mut metadata = self.get_recording(id)?;
+        revisions::seed(&mut metadata);
+        revisions::word_diff(
+            &revisions::get(&metadata, from)?.filtered_text,
+            &revisions::get(&metadata, to)?.filtered_text,
+        )
+    }
+
+    /// Full-text search over transcriptions, original text, enrichment and
+    /// fallacy quotes. Returns the best matches first, with highlighted snippets.
+    pub fn search_recordings(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<SearchResult>, String> {
+        if query.chars().count() > search::MAX_QUERY_LENGTH {
+            return Err(format!(
+                "Search query too long (max {} characters)",
+                search::MAX_QUERY_LENGTH
+            ));
+        }
+
+        let terms = search::query_terms(query);
+        if terms.is_empty() {
+            return Ok(vec![]);
+        }
+        let limit = limit
+            .unwrap_or(search::DEFAULT_SEARCH_LIMIT)
+            .clamp(1, search::MAX_SEARCH_LIMIT);
+
+        let hits = self.with_catalog(|c| c.search(&terms, limit))?;
+        let term_set: std::collections::HashSet<String> = terms.into_iter().collect();
+
+        Ok(hits
+            .into_iter()
+            .map(|(entry, rank)| {
+                let snippets = SearchField::ALL
+                    .iter()
+                    .filter_map(|&field| {
+                        search::build_snippet(&field.text(&entry.metadata), &term_set)
+                            .map(|parts| SearchSnippet { field, parts })
+                    })
+                    .collect();
+                SearchResult {
+                    recording_id: entry.metadata.id,
+                    created_at: entry.metadata.created_at,
+                    // bm25 ranks are negative, lower is better
+                    score: -rank,
+                    snippets,
+                }
+            })
+            .collect())
+    }
+
+    /// Get recording audio by ID (always WAV, whatever the stored codec)
+    pub fn get_recording_audio(&self, id: &str) -> Result<Vec<u8>, String> {
+        let entry = self.find_entry(id)?;
+        self.read_audio(&entry.base_name)?
+            .ok_or_else(|| format!("Audio file not found for recording: {}", id))
+    }
+
+    /// Delete a recording by ID (moved to the trash unless retention is 0)
+    pub fn delete_recording(&self, id: &str) -> Result<(), String> {
+        let retention_days = self.get_config()?.trash_retention_days;
+        self.with_catalog(|c| {
+            let entry = c
+                .find(id)?
+                .ok_or_else(|| format!("Recording not found: {}", id))?;
+            discard_recording(
+                c.storage_dir(),
+                id,
+                &entry.base_name,
+                TrashReason::Deleted,
+                retention_days,
+            )?;
+            c.remove(id)
+        })?;
+
+        tracing::debug!(id = %id, "Storage: Recording deleted");
+        Ok(())
+    }
+
+    /// Plan a cleanup with the configured retention limits
+    fn plan_cleanup(&self, c: &RecordingCatalog, config: &StorageConfig) -> Result<CleanupPlan, String> {
+        let storage_dir = c.storage_dir();
+        Ok(retention::plan(
+            c.retention_rows()?,
+            |row| recording_disk_size(storage_dir, &row.base_name),
+            retention::RetentionPolicy::from(config),
+            Utc::now(),
+        ))
+    }
+
+    /// Preview what the automatic cleanup would remove right now (dry run)
+    pub fn preview_cleanup(&self) -> Result<CleanupPlan, String> {
+        let config = self.get_config()?;
+        if !PathBuf::from(&config.storage_path).exists() {
+            return Ok(CleanupPlan::default());
+        }
+        self.with_catalog(|c| self.plan_cleanup(c, &config))
+    }
+
+    /// Remove recordings beyond the retention limits (age, count, storage
+    /// quota), oldest first; pinned recordings are kept.
+    ///
+    /// The trash counts toward the quota: its oldest entries are purged
+    /// before any recording is evicted. Recordings still over the quota then
+    /// move to the trash like all other evictions and are the first to go at
+    /// the next cleanup.
+    fn cleanup_old_recordings(&self) -> Result<usize, String> {
+        let config = self.get_config()?;
+        self.purge_expired_trash()?;
+
+        let (deleted, freed_bytes) = self.with_catalog(|c| {
+            let plan = self.plan_cleanup(c, &config)?;
+
+            if let Some(quota) = retention::RetentionPolicy::from(&config).quota_bytes {
+                let library_bytes = plan.remaining_bytes + plan.freed_bytes;
+                let (purged, purged_bytes) =
+                    trash::purge_over_quota(c.storage_dir(), library_bytes, quota)?;
+                if purged > 0 {
+                    tracing::info!(purged, purged_bytes, "Storage: Purged trash over the quota");
+                }
+            }
+
+            let mut deleted = 0;
+            let mut freed_bytes = 0;
+            for eviction in plan.evictions {
+                let discarded = discard_recording(
+                    c.storage_dir(),
+                    &eviction.id,
+                    &eviction.base_name,
+                    TrashReason::Cleanup,
+                    config.trash_retention_days,
+                );
+                if discarded.is_ok() {
+                    c.remove(&eviction.id)?;
+                    tracing::debug!(
+                        id = %eviction.id,
+                        reason = ?eviction.reason,
+                        "Storage: Evicted old recording"
+                    );
+                    deleted += 1;
+                    freed_bytes += eviction.size_bytes;
+                }
+            }
+            Ok((deleted, freed_bytes))
+        })?;
+
+        if deleted > 0 {
+            tracing::info!(
+                deleted,
+                freed_bytes,
+                max = config.max_recordings,
+                quota_mb = config.max_user_storage_mb,
+                retention_days = config.retention_days,
+                "Storage: Cleaned up old recordings"
+            );
+        }
+
+        Ok(deleted)
+    }
+
+    /// Delete all recordings (moved to the trash unless retention is 0)
+    pub fn clear_all_recordings(&self) -> Result<usize, String> {
+        let retention_days = self.get_config()?.trash_retention_days;
+        let count = self.with_catalog(|c| {
+            let entries = c.list()?;
+            for entry in &entries {
+                let _ = discard_recording(
+                    c.storage_dir(),
+                    &entry.metadata.id,
+                    &entry.base_name,
+                    TrashReason::Cleared,
+                    retention_days,
+                );
+            }
+            c.replace_all(&[])?;
+            Ok(entries.len())
+        })?;
+
+        tracing::info!(count, "Storage: Cleared all recordings");
+        Ok(count)
+    }
+
+    /// List recordings in the trash (most recently deleted first).
+    /// Expired entries are purged first.
+    pub fn list_trash(&self) -> Result<Vec<TrashedRecording>, String> {
+        self.purge_expired_trash()?;
+        let storage_dir = self.ensure_storage_dir()?;
+        let retention_days = self.get_config()?.trash_retention_days;
+        let trash_dir = trash::trash_dir(&storage_dir);
+
+        let mut recordings = Vec::new();
+        for note in trash::list_notes(&storage_dir)? {
+            let path = trash_dir.join(format!("{}.json", note.base_name));
+            match read_sidecar(&path, &self.keys) {
+                Ok(metadata) => {
+                    let expires_at = DateTime::parse_from_rfc3339(&note.deleted_at)
+                        .map(|d| (d + chrono::Duration::days(retention_days as i64)).to_rfc3339())
+                        .unwrap_or_default();
+                    recordings.push(TrashedRecording {
+                        metadata,
+                        deleted_at: note.deleted_at,
+                        reason: note.reason,
+                        expires_at,
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "Storage: Failed to read trashed metadata"
+                    );
+                }
+            }
+        }
+
+        Ok(recordings)
+    }
+
+    /// Move a recording out of the trash and back into the library
+    pub fn restore_recording(&self, id: &str) -> Result<RecordingMetadata, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let note = trash::list_notes(&storage_dir)?
+            .into_iter()
+            .find(|n| n.id == id)
+            .ok_or_else(|| format!("Recording not in trash: {}", id))?;
+
+        trash::restore_files(&storage_dir, &note)?;
+        let metadata = read_sidecar(&storage_dir.join(format!("{}.json", note.base_name)), &self.keys)?;
+        self.with_catalog(|c| c.upsert(&note.base_name, &metadata))?;
+
+        tracing::info!(id = %id, "Storage: Recording restored from trash");
+        Ok(metadata)
+    }
+
+    /// Permanently delete everything in the trash.
+    /// Returns the number of removed recordings.
+    pub fn empty_trash(&self) -> Result<usize, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let mut removed = 0;
+        for note in trash::list_notes(&storage_dir)? {
+            trash::purge(&storage_dir, &note)?;
+            removed += 1;
+        }
+
+        tracing::info!(removed, "Storage: Trash emptied");
+        Ok(removed)
+    }
+
+    /// Permanently delete trashed recordings older than the retention period
+    fn purge_expired_trash(&self) -> Result<usize, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let retention_days = self.get_config()?.trash_retention_days;
+        let now = Utc::now();
+
+        let mut purged = 0;
+        for note in trash::list_notes(&storage_dir)? {
+            if note.is_expired(retention_days, now) {
+                trash::purge(&storage_dir, &note)?;
+                purged += 1;
+            }
+        }
+
+        if purged > 0 {
+            tracing::info!(purged, retention_days, "Storage: Purged expired trash");
+        }
+        Ok(purged)
+    }
+
+    /// Convert every recording in the library to encrypted (`enable = true`)
+    /// or plaintext storage, and make that the format for new recordings.
+    ///
+    /// Each file is rewritten through a temporary file and renamed into place,
+    /// so an interrupted conversion leaves a mixed but readable library.
+    pub fn convert_library_encryption(
+        &self,
+        enable: bool,
+    ) -> Result<EncryptionConversionResult, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+
+        // Create the data key up front so a keyring failure aborts before any file is touched
+        if enable {
+            self.keys.get_or_create()?;
+        }
+
+        let mut result = EncryptionConversionResult {
+            converted: 0,
+            skipped: 0,
+            failed: 0,
+        };
+
+        // Hidden files (sync state, catalog) are not recordings
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&storage_dir)
+            .map_err(|e| format!("Failed to read storage directory: {}", e))?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| watcher::recording_base_name(&storage_dir, p).is_some())
+            .collect();
+
+        // Trashed recordings can still be restored, so they follow the library format
+        if let Ok(dir) = std::fs::read_dir(trash::trash_dir(&storage_dir)) {
+            paths.extend(
+                dir.flatten()
+                    .map(|e| e.path())
+                    .filter(|p| !trash::is_note(p)),
+            );
+        }
+
+        for path in paths {
+            let aad = match path.extension().and_then(|e| e.to_str()) {
+                Some("wav" | "flac" | "opus") => AAD_AUDIO,
+                Some("json") => AAD_METADATA,
+                _ => continue,
+            };
+
+            match self.convert_file(&path, aad, enable) {
+                Ok(true) => result.converted += 1,
+                Ok(false) => result.skipped += 1,
+                Err(e) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "Storage: Failed to convert file"
+                    );
+                    result.failed += 1;
+                }
+            }
+        }
+
+        let mut config = self.get_config()?;
+        config.encryption_enabled = enable;
+        self.update_config(config)?;
+
+        // Re-seal catalog rows in the new format
+        self.rebuild_catalog()?;
+
+        tracing::info!(
+            enable,
+            converted = result.converted,
+            skipped = result.skipped,
+            failed = result.failed,
+            "Storage: Library encryption converted"
+        );
+
+        Ok(result)
+    }
+
+    /// Rewrite a single file in the target format.
+    /// Returns `false` if the file already was in that format.
+    fn convert_file(&self, path: &Path, aad: &[u8], encrypt: bool) -> Result<bool, String> {
+        let content =
+            std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        if crypto::is_encrypted(&content) == encrypt {
+            return Ok(false);
+        }
+
+        let plaintext = self.keys.open(&content, aad)?;
+        let converted = self.keys.seal(&plaintext, aad, encrypt)?;
+        atomic::write_file(path, &converted)?;
+
+        Ok(true)
+    }
+
+    /// List recordings whose metadata could not be read and was quarantined
+    pub fn list_quarantined(&self) -> Result<Vec<QuarantinedRecording>, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        quarantine::list(&storage_dir)
+    }
+
+    /// Move a quarantined recording back and re-index the library.
+    /// If it still cannot be read, it is quarantined again.
+    pub fn restore_quarantined(&self, base_name: &str) -> Result<(), String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        quarantine::restore(&storage_dir, base_name)?;
+        self.rebuild_catalog()?;
+        Ok(())
+    }
+
+    /// Start spooling a native recording to disk (sealed with the data key
+    /// when encryption at rest is enabled). `None` while storage is disabled:
+    /// the recording then stays in memory only.
+    pub fn create_spool(
+        &self,
+        input_device: Option<InputDeviceMeta>,
+    ) -> Result<Option<SpoolWriter>, String> {
+        let config = self.get_config()?;
+        if !config.storage_enabled {
+            return Ok(None);
+        }
+        let storage_dir = self.ensure_storage_dir()?;
+        let key = if config.encryption_enabled {
+            Some(self.keys.get_or_create()?)
+        } else {
+            None
+        };
+        SpoolWriter::create(&storage_dir, key, input_device).map(Some)
+    }
+
+    /// Recording sessions interrupted by a crash, to offer for recovery
+    pub fn list_interrupted_recordings(&self) -> Result<Vec<InterruptedRecording>, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        spool::list_interrupted(&storage_dir)
+    }
+
+    /// Save an interrupted recording session into the library and remove it
+    /// from the spool. It keeps its start time; transcription and analysis
+    /// are left to the user.
+    pub fn recover_interrupted_recording(&self, id: &str) -> Result<RecordingMetadata, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let key = self.keys.get()?;
+        let (wav, session) = spool::read_interrupted(&storage_dir, id, key.as_ref())?;
+        let info = codec::probe(&wav)?;
+
+        let mut metadata = RecordingMetadata::new(
+            info.duration_ms(),
+            info.sample_rate,
+            info.wav_size(),
+            AudioValidationMeta {
+                rms_energy: info.rms_energy,
+                duration_ms: info.duration_ms(),
+                sample_count: info.frames as usize,
+                passed: info.frames > 0,
+            },
+            "unknown".to_string(),
+            "unknown".to_string(),
+        );
+        metadata.created_at = session.started_at;
+        metadata.source = Some("recording".to_string());
+        metadata.input_device = session.input_device;
+        metadata.pauses = session.pauses;
+
+        let saved_id = self.save_recording(&wav, &metadata)?;
+        spool::discard(&storage_dir, id)?;
+        tracing::info!(id = %saved_id, duration_ms = session.duration_ms, "Storage: Interrupted recording recovered");
+        self.get_recording(&saved_id)
+    }
+
+    /// Remove an interrupted recording session without saving it
+    pub fn discard_interrupted_recording(&self, id: &str) -> Result<(), String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        spool::discard(&storage_dir, id)
+    }
+
+    /// Check the storage directory for audio without metadata, missing or
+    /// damaged audio, corrupt or undecryptable sidecars and insecure permissions
+    pub fn verify_storage(&self) -> Result<IntegrityReport, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        integrity::verify(&storage_dir, &self.keys)
+    }
+
+    /// Fix what `verify_storage` finds where possible (rebuild metadata from
+    /// audio, reset permissions, quarantine corrupt recordings) and re-index
+    pub fn repair_storage(&self) -> Result<RepairReport, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let report = integrity::repair(&storage_dir, &self.keys)?;
+        if report.repaired > 0 || report.quarantined > 0 {
+            self.rebuild_catalog()?;
+        }
+        Ok(report)
+    }
+
+    /// Rewrite all sidecars with an outdated schema in the current layout.
+    ///
+    /// Reading already upgrades in memory; this makes it permanent so old
+    /// files no longer need migrating. Each file keeps its encryption format.
+    pub fn upgrade_sidecars(&self) -> Result<SchemaUpgradeResult, String> {
+        let storage_dir = self.ensure_storage_dir()?;
+        let mut result = SchemaUpgradeResult {
+            upgraded: 0,
+            current: 0,
+            failed: 0,
+        };
+
+        let dir = std::fs::read_dir(&storage_dir)
+            .map_err(|e| format!("Failed to read storage directory: {}", e))?;
+
+        for entry in dir.flatten() {
+            let path = entry.path();
+            if !path.extension().map(|e| e == "json").unwrap_or(false)
+                || watcher::recording_base_name(&storage_dir, &path).is_none()
+            {
+                continue;
+            }
+
+            match self.upgrade_sidecar(&path) {
+                Ok(true) => result.upgraded += 1,
+                Ok(false) => result.current += 1,
+                Err(e) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "Storage: Failed to upgrade metadata"
+                    );
+                    result.failed += 1;
+                }
+            }
+        }
+
+        tracing::info!(
+            upgraded = result.upgraded,
+            current = result.current,
+            failed = result.failed,
+            version = schema::CURRENT_SCHEMA_VERSION,
+            "Storage: Metadata schema upgrade finished"
+        );
+
+        Ok(result)
+    }
+
+    /// Rewrite a single sidecar if it has an outdated schema.
+    /// Returns `false` if it already was current.
+    fn upgrade_sidecar(&self, path: &Path) -> Result<bool, String> {
+        let content =
+            std::fs::read(path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let encrypted = crypto::is_encrypted(&content);
+        let plaintext = self.keys.open(&content, AAD_METADATA)?;
+
+        let (metadata, upgraded) = schema::parse_metadata(&plaintext).map_err(|e| e.to_string())?;
+        if !upgraded {
+            return Ok(false);
+        }
+
+        let json = serde_json::to_string_pretty(&metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        let sealed = self.keys.seal(json.as_bytes(), AAD_METADATA, encrypted)?;
+        atomic::write_file(path, &sealed)?;
+
+        Ok(true)
+    }
+
+    /// Rewrite all audio files in `codec` and make it the codec for new recordings.
+    ///
+    /// `progress` is called with (processed, total) after each recording. Each
+    /// file keeps its encryption format and is replaced via a temporary file,
+    /// so an interrupted run leaves a mixed but readable library.
+    pub fn recompress_library(
+        &self,
+        codec: AudioCodec,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<RecompressionResult, String> {
+        if !codec.is_available() {
+            return Err(format!(
+                "Audio codec {} is not available in this build",
+                codec.extension()
+            ));
+        }
+
+        // Switch first so recordings saved meanwhile already use the new codec
+        let mut config = self.get_config()?;
+        config.audio_codec = codec;
+        self.update_config(config)?;
+
+        let storage_dir = self.ensure_storage_dir()?;
+        let entries = self.with_catalog(|c| c.list())?;
+
+        let mut result = RecompressionResult {
+            recompressed: 0,
+            skipped: 0,
+            failed: 0,
+            bytes_before: 0,
+            bytes_after: 0,
+            bytes_saved: 0,
+        };
+
+        for (index, entry) in entries.iter().enumerate() {
+            if let Some(path) = find_audio_file(&storage_dir, &entry.base_name) {
+                match self.recompress_file(&path, codec) {
+                    Ok(Some((before, after))) => {
+                        result.recompressed += 1;
+                        result.bytes_before += before;
+                        result.bytes_after += after;
+                    }
+                    Ok(None) => result.skipped += 1,
+                    Err(e) => {
+                        tracing::warn!(
+                            path = %path.display(),
+                            error = %e,
+                            "Storage: Failed to recompress audio"
+                        );
+                        result.failed += 1;
+                    }
+                }
+            }
+            progress(index + 1, entries.len());
+        }
+
+        result.bytes_saved = result.bytes_before as i64 - result.bytes_after as i64;
+
+        tracing::info!(
+            codec = codec.extension(),
+            recompressed = result.recompressed,
+            skipped = result.skipped,
+            failed = result.failed,
+            bytes_saved = result.bytes_saved,
+            "Storage: Library recompressed"
+        );
+
+        Ok(result)
+    }
+
+    /// Rewrite a single audio file in `codec`.
+    /// Returns the file size before and after, or `None` if nothing changed.
+    fn recompress_file(&self, path: &Path, codec: AudioCodec) -> Result<Option<(u64, u64)>, String> {
+        let content =
+            std::fs::read(path).map_err(|e| format!("Failed to read audio file: {}", e))?;
+        let audio = self.keys.open(&content, AAD_AUDIO)?;
+        let current = AudioCodec::detect(&audio);
+        if current == codec {
+            return Ok(None);
+        }
+
+        let wav = codec::decode_to_wav(&audio)?;
+        let (encoded, actual) = codec::encode(&wav, codec);
+        if actual == current {
+            return Ok(None);
+        }
+
+        let sealed = self
+            .keys
+            .seal(&encoded, AAD_AUDIO, crypto::is_encrypted(&content))?;
+        let new_path = path.with_extension(actual.extension());
+        atomic::write_file(&new_path, &sealed)?;
+        if new_path != path {
+            std::fs::remove_file(path)
+                .map_err(|e| format!("Failed to remove old audio file: {}", e))?;
+        }
+
+        Ok(Some((content.len() as u64, sealed.len() as u64)))
+    }
+
+    /// Export recordings (all, or the given IDs) into a portable archive at `dest`.
+    ///
+    /// The archive contains decrypted files, so it can be imported on another
+    /// machine with a different data key.
+    pub fn export_library(
+        &self,
+        dest: &Path,
+        ids: Option<&[String]>,
+    ) -> Result<LibraryExportResult, String> {
+        let entries = match ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| self.find_entry(id))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => self.with_catalog(|c| c.list())?,
+        };
+
+        let mut writer = archive::ArchiveWriter::create(dest)?;
+        for entry in &entries {
+            let json = serde_json::to_vec_pretty(&entry.metadata)
+                .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+            let audio = self.read_audio(&entry.base_name)?;
+            writer.add_recording(&entry.metadata.id, &entry.base_name, &json, audio.as_deref())?;
+        }
+        writer.finish()?;
+
+        let bytes = std::fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+        tracing::info!(
+            recordings = entries.len(),
+            bytes,
+            "Storage: Library exported"
+        );
+
+        Ok(LibraryExportResult {
+            recordings: entries.len(),
+            bytes,
+        })
+    }
+
+    /// Import all recordings from a library archive.
+    ///
+    /// The archive is verified against its manifest before anything is added.
+    /// Recordings already in the library are skipped; recordings whose ID is
+    /// taken by a different recording are imported under a new ID.
+    pub fn import_library(&self, archive_path: &Path) -> Result<LibraryImportResult, String> {
+        if !self.get_config()?.storage_enabled {
+            return Err("Storage is disabled".to_string());
+        }
+
+        let storage_dir = self.ensure_storage_dir()?;
+        let staged = archive::StagedArchive::extract(archive_path, &storage_dir)?;
+
+        let mut result = LibraryImportResult {
+            imported: 0,
+            renamed: 0,
+            duplicates: 0,
+            failed: 0,
+        };
+
+        for recording in &staged.manifest.recordings {
+            match self.import_recording(&staged, &recording.base_name) {
+                Ok(ImportOutcome::Imported) => result.imported += 1,
+                Ok(ImportOutcome::Renamed) => result.renamed += 1,
+                Ok(ImportOutcome::Duplicate) => result.duplicates += 1,
+                Err(e) => {
+                    tracing::warn!(
+                        id = %recording.id,
+                        error = %e,
+                        "Storage: Failed to import recording"
+                    );
+                    result.failed += 1;
+                }
+            }
+        }
+        drop(staged);
+
+        self.cleanup_old_recordings()?;
+
+        tracing::info!(
+            imported = result.imported,
+            renamed = result.renamed,
+            duplicates = result.duplicates,
+            failed = result.failed,
+            "Storage: Library imported"
+        );
+
+        Ok(result)
+    }
+
+    fn import_recording(
+        &self,
+        staged: &archive::StagedArchive,
+        base_name: &str,
+    ) -> Result<ImportOutcome, String> {
+        let json = staged
+            .read(base_name, "json")?
+            .ok_or_else(|| "Metadata missing from archive".to_string())?;
+        let (mut metadata, _) = schema::parse_metadata(&json).map_err(|e| e.to_string())?;
+        let audio = staged.read(base_name, "wav")?.unwrap_or_default();
+
+        let mut outcome = ImportOutcome::Imported;
+        if Uuid::parse_str(&metadata.id).is_err() {
+            // IDs become part of file names - never trust a foreign format
+            outcome = ImportOutcome::Renamed;
+        } else if let Some(existing) = self.with_catalog(|c| c.find(&metadata.id))? {
+            // Random v4 IDs only repeat for the same recording; audio is not
+            // compared because lossy storage codecs change it
+            if existing.metadata.created_at == metadata.created_at {
+                return Ok(ImportOutcome::Duplicate);
+            }
+            outcome = ImportOutcome::Renamed;
+        }
+
+        if matches!(outcome, ImportOutcome::Renamed) {
+            metadata.id = Uuid::new_v4().to_string();
+        }
+        if metadata.created_at.is_empty() {
+            metadata.created_at = chrono::Utc::now().to_rfc3339();
+        }
+
+        self.write_recording(&audio, &mut metadata)?;
+        Ok(outcome)
+    }
+
+    /// Backup directory of the current config. It must exist and must not
+    /// overlap the storage directory.
+    fn backup_dir(&self, config: &StorageConfig) -> Result<PathBuf, String> {
+        let path = config
+            .backup_path
+            .as_deref()
+            .ok_or_else(|| "No backup directory configured".to_string())?;
+        let backup_dir = PathBuf::from(path)
+            .canonicalize()
+            .map_err(|e| format!("Backup directory not available: {}", e))?;
+        if !backup_dir.is_dir() {
+            return Err("Backup path is not a directory".to_string());
+        }
+        let storage_dir = self
+            .ensure_storage_dir()?
+            .canonicalize()
+            .map_err(|e| format!("Failed to resolve storage directory: {}", e))?;
+        if backup_dir.starts_with(&storage_dir) || storage_dir.starts_with(&backup_dir) {
+            return Err("Backup directory must not overlap the storage directory".to_string());
+        }
+        Ok(backup_dir)
+    }
+
+    fn lock_backup(&self) -> Result<std::sync::MutexGuard<'_, ()>, String> {
+        match self.backup_lock.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                Err("A backup or restore is already running".to_string())
+            }
+            Err(std::sync::TryLockError::Poisoned(e)) => Ok(e.into_inner()),
+        }
+    }
+
+    /// Write a new backup snapshot and rotate old ones.
+    /// `progress` receives (processed, total) files.
+    pub fn create_backup(
+        &self,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<BackupResult, String> {
+        let _guard = self.lock_backup()?;
+        let config = self.get_config()?;
+        if !config.storage_enabled {
+            return Err("Storage is disabled".to_string());
+        }
+        let backup_dir = self.backup_dir(&config)?;
+        let storage_dir = self.ensure_storage_dir()?;
+
+        let result = backup::create_snapshot(
+            &storage_dir,
+            &backup_dir,
+            config.backup_keep,
+            Utc::now(),
+            progress,
+        )?;
+        tracing::info!(
+            snapshot = %result.snapshot.name,
+            copied = result.copied_files,
+            linked = result.linked_files,
+            removed = result.removed_snapshots.len(),
+            "Storage: Backup created"
+        );
+        Ok(result)
+    }
+
+    /// Snapshots in the backup directory, newest first
+    /// (empty if no backup directory is configured)
+    pub fn list_backups(&self) -> Result<Vec<BackupSnapshot>, String> {
+        let config = self.get_config()?;
+        if config.backup_path.is_none() {
+            return Ok(Vec::new());
+        }
+        backup::list_snapshots(&self.backup_dir(&config)?)
+    }
+
+    /// Whether a scheduled backup is due at `now`
+    pub fn backup_due(&self, now: DateTime<Utc>) -> Result<bool, String> {
+        let config = self.get_config()?;
+        if !config.storage_enabled
+            || config.backup_path.is_none()
+            || config.backup_interval_hours == 0
+        {
+            return Ok(false);
+        }
+        let latest = backup::latest_snapshot_time(&self.backup_dir(&config)?)?;
+        Ok(latest.is_none_or(|latest| {
+            now - latest >= chrono::Duration::hours(i64::from(config.backup_interval_hours))
+        }))
+    }
+
+    /// Restore the recordings of a backup snapshot.
+    ///
+    /// The snapshot is verified before anything is changed. Recordings missing
+    /// from the library are copied back; recordings that differ from the
+    /// snapshot are replaced and the library version is moved to the trash.
+    /// Recordings not in the snapshot are kept. Files are restored as stored,
+    /// so encrypted recordings need the data key they were written with.
+    pub fn restore_backup(
+        &self,
+        name: &str,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<RestoreResult, String> {
+        let _guard = self.lock_backup()?;
+        let config = self.get_config()?;
+        if !config.storage_enabled {
+            return Err("Storage is disabled".to_string());
+        }
+        let snapshot_dir = backup::snapshot_dir(&self.backup_dir(&config)?, name)?;
+        let manifest = backup::verify_snapshot(&snapshot_dir)?;
+        let storage_dir = self.ensure_storage_dir()?;
+
+        let mut result = RestoreResult {
+            restored: 0,
+            replaced: 0,
+            unchanged: 0,
+        };
+        let recordings = manifest.recordings();
+        for (i, (base_name, files)) in recordings.iter().enumerate() {
+            let unchanged = files.iter().all(|f| {
+                backup::file_sha256(&storage_dir.join(&f.name)).is_ok_and(|sha| sha == f.sha256)
+            });
+            if unchanged {
+                result.unchanged += 1;
+                progress(i + 1, recordings.len());
+                continue;
+            }
+
+            let exists = storage_dir.join(format!("{}.json", base_name)).exists()
+                || find_audio_file(&storage_dir, base_name).is_some();
+            if exists {
+                let id = self
+                    .with_catalog(|c| c.find_by_base_name(base_name))?
+                    .map(|entry| entry.metadata.id)
+                    .unwrap_or_else(|| base_name.clone());
+                discard_recording(
+                    &storage_dir,
+                    &id,
+                    base_name,
+                    TrashReason::Replaced,
+                    config.trash_retention_days,
+                )?;
+                result.replaced += 1;
+            } else {
+                result.restored += 1;
+            }
+
+            // Sidecar last, as in a regular save
+            let mut names: Vec<String> = files.iter().map(|f| f.name.clone()).collect();
+            names.sort_by_key(|name| name.ends_with(".json"));
+            let mut transaction = atomic::SaveTransaction::begin(&storage_dir, base_name, &names)?;
+            for name in &names {
+                let content = std::fs::read(snapshot_dir.join(name))
+                    .map_err(|e| format!("Failed to read backup file {}: {}", name, e))?;
+                transaction.write(name, &content)?;
+            }
+            transaction.commit()?;
+            progress(i + 1, recordings.len());
+        }
+
+        self.rebuild_catalog()?;
+        tracing::info!(
+            snapshot = %name,
+            restored = result.restored,
+            replaced = result.replaced,
+            unchanged = result.unchanged,
+            "Storage: Backup restored"
+        );
+        Ok(result)
+    }
+
+    fn sync_password(&self) -> Result<Option<String>, String> {
+        if let Some(password) = &self.fixed_sync_password {
+            return Ok(Some(password.clone()));
+        }
+        Ok(
+            get_password_blocking(crypto::KEYRING_SERVICE, &self.sync_password_user)?
+                .filter(|password| !password.is_empty()),
+        )
+    }
+
+    /// Store the WebDAV password in the keyring (empty = remove it)
+    pub fn set_sync_password(&self, password: &str) -> Result<(), String> {
+        set_password_blocking(crypto::KEYRING_SERVICE, &self.sync_password_user, password)
+    }
+
+    /// Sync key as Base64, to be imported on the other devices.
+    /// Created on first use.
+    pub fn export_sync_key(&self) -> Result<String, String> {
+        Ok(self.sync_keys.get_or_create()?.to_base64())
+    }
+
+    /// Use the sync key exported on another device. The next sync compares
+    /// the whole library again.
+    pub fn import_sync_key(&self, key: &str) -> Result<(), String> {
+        let key = crypto::DataKey::from_base64(key)?;
+        let _guard = self.lock_sync()?;
+        self.sync_keys.set(key)?;
+        sync::reset_state(&self.ensure_storage_dir()?)
+    }
+
+    /// Sync configuration and state of the library
+    pub fn sync_status(&self) -> Result<SyncStatus, String> {
+        let config = self.get_config()?;
+        Ok(sync::status(
+            &self.ensure_storage_dir()?,
+            config.sync_url.as_deref(),
+            self.sync_password()?.is_some(),
+            config.sync_encrypt,
+        ))
+    }
+
+    fn lock_sync(&self) -> Result<std::sync::MutexGuard<'_, ()>, String> {
+        match self.sync_lock.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                Err("A sync is already running".to_string())
+            }
+            Err(std::sync::TryLockError::Poisoned(e)) => Ok(e.into_inner()),
+        }
+    }
+
+    /// Sync the library with the configured WebDAV server.
+    ///
+    /// Uploads local changes, downloads remote ones and keeps both versions
+    /// of recordings changed on both sides. An interrupted sync is resumed
+    /// by the next one. `progress` receives (processed, total) recordings.
+    pub fn sync_library(
+        &self,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<SyncResult, String> {
+        let _guard = self.lock_sync()?;
+        let config = self.get_config()?;
+        if !config.storage_enabled {
+            return Err("Storage is disabled".to_string());
+        }
+        let url = config
+            .sync_url
+            .as_deref()
+            .ok_or_else(|| "No sync server configured".to_string())?;
+        let password = self
+            .sync_password()?
+            .ok_or_else(|| "No sync password stored".to_string())?;
+        let storage_dir = self.ensure_storage_dir()?;
+
+        let client = webdav::WebDavClient::new(url, &config.sync_username, &password)?;
+        let options = sync::SyncOptions {
+            url,
+            storage_dir: &storage_dir,
+            keys: &self.keys,
+            encrypt_local: config.encryption_enabled,
+            sync_keys: &self.sync_keys,
+            encrypt_remote: config.sync_encrypt,
+            trash_retention_days: config.trash_retention_days,
+        };
+        let result = sync::sync_library(&client, &options, progress);
+
+        // Downloads may have happened before an error, too
+        if let Err(e) = self.rebuild_catalog() {
+            tracing::warn!(error = %e, "Storage: Failed to update catalog after sync");
+        }
+        let result = result?;
+        tracing::info!(
+            uploaded = result.uploaded,
+            downloaded = result.downloaded,
+            deleted_remote = result.deleted_remote,
+            deleted_local = result.deleted_local,
+            conflicts = result.conflicts.len(),
+            "Storage: Library synced"
+        );
+        Ok(result)
+    }
+
+    /// Find recordings with the same audio in the library.
+    ///
+    /// Recordings match on their content hash or, to tolerate re-encoding
+    /// (headers, sample rate, lossy codecs), on their loudness fingerprint.
+    /// Recordings saved before content hashes existed are hashed on the fly.
+    pub fn find_duplicates(&self) -> Result<Vec<DuplicateGroup>, String> {
+        // Oldest first, so each group starts with the original
+        let mut entries = self.with_catalog(|c| c.list())?;
+        entries.reverse();
+
+        let candidates: Vec<dedup::Candidate> = entries
+            .iter()
+            .map(|entry| {
+                let audio = match self.read_audio(&entry.base_name) {
+                    Ok(audio) => audio,
+                    Err(e) => {
+                        tracing::warn!(
+                            id = %entry.metadata.id,
+                            error = %e,
+                            "Storage: Cannot read audio for duplicate scan"
+                        );
+                        None
+                    }
+                };
+                let decoded = audio.as_deref().and_then(|a| codec::decode_samples(a).ok());
+                dedup::Candidate {
+                    content_hash: entry
+                        .metadata
+                        .content_hash
+                        .clone()
+                        .or_else(|| audio.as_deref().map(dedup::content_hash)),
+                    fingerprint: decoded.as_ref().map(dedup::Fingerprint::of),
+                }
+            })
+            .collect();
+
+        let groups: Vec<DuplicateGroup> = dedup::group(&candidates)
+            .into_iter()
+            .map(|members| DuplicateGroup {
+                exact: members
+                    .iter()
+                    .all(|&i| candidates[i].content_hash == candidates[members[0]].content_hash),
+                recordings: members
+                    .iter()
+                    .map(|&i| RecordingSummary::from(entries[i].metadata.clone()))
+                    .collect(),
+            })
+            .collect();
+
+        tracing::info!(
+            recordings = entries.len(),
+            groups = groups.len(),
+            "Storage: Duplicate scan finished"
+        );
+        Ok(groups)
+    }
+
+    /// Read, decrypt and decode a recording's audio to WAV, if it has any
+    fn read_audio(&self, base_name: &str) -> Result<Option<Vec<u8>>, String> {
+        let Some(audio_path) = find_audio_file(&self.ensure_storage_dir()?, base_name) else {
+            return Ok(None);
+        };
+        let content =
+            std::fs::read(&audio_path).map_err(|e| format!("Failed to read audio file: {}", e))?;
+        let audio = self.keys.open(&content, AAD_AUDIO)?;
+        codec::decode_to_wav(&audio).map(Some)
+    }
+
+    /// Get storage statistics
+    pub fn get_storage_stats(&self) -> Result<StorageStats, String> {
+        let config = self.get_config()?;
+        let ((recording_count, total_size, total_duration_ms), disk_usage_bytes, pinned_count) =
+            if PathBuf::from(&config.storage_path).exists() {
+                self.with_catalog(|c| {
+                    let rows = c.retention_rows()?;
+                    let disk_usage: u64 = rows
+                        .iter()
+                        .map(|row| recording_disk_size(c.storage_dir(), &row.base_name))
+                        .sum::<u64>()
+                        + trash::disk_size(c.storage_dir());
+                    let pinned = rows.iter().filter(|row| row.pinned).count();
+                    Ok((c.totals()?, disk_usage, pinned))
+                })?
+            } else {
+                ((0, 0, 0), 0, 0)
+            };
+        let quota_bytes = retention::RetentionPolicy::from(&config).quota_bytes;
+
+        Ok(StorageStats {
+            recording_count,
+            total_size_bytes: total_size,
+            total_duration_ms,
+            storage_path: config.storage_path,
+            max_recordings: config.max_recordings,
+            disk_usage_bytes,
+            quota_bytes,
+            quota_used_percent: quota_bytes
+                .map(|quota| (disk_usage_bytes as f64 / quota as f64 * 100.0) as f32),
+            pinned_count,
+            retention_days: config.retention_days,
+        })
+    }
+
+    /// Recordings that count toward baselines and trends (duplicates excluded)
+    fn counted_recordings(&self) -> Result<Vec<RecordingMetadata>, String> {
+        let mut recordings = self.list_recordings()?;
+        recordings.retain(|r| r.duplicate_of.is_none());
+        Ok(recordings)
+    }
+
+    /// Calculate baseline emotion from all recordings with emotion data.
+    /// Tie-breaker: Most recent recording when emotions have same frequency.
+    pub fn calculate_baseline_emotion(&self) -> Result<Option<BaselineResult>, String> {
+        Ok(analytics::baseline(&self.counted_recordings()?))
+    }
+
+    /// Baseline emotion (see [`Self::calculate_baseline_emotion`]) over the
+    /// recordings with `tag` only
+    pub fn calculate_tag_baseline_emotion(
+        &self,
+        tag: &str,
+    ) -> Result<Option<BaselineResult>, String> {
+        let tag = annotations::normalize_tag(tag);
+        let recordings = self.counted_recordings()?;
+        Ok(analytics::baseline(
+            recordings.iter().filter(|r| r.tags.contains(&tag)),
+        ))
+    }
+
+    /// Baseline emotion (see [`Self::calculate_baseline_emotion`]) over the
+    /// recordings of the last `days` days only
+    pub fn calculate_recent_baseline_emotion(
+        &self,
+        days: u32,
+    ) -> Result<Option<BaselineResult>, String> {
+        let recordings = self.counted_recordings()?;
+        Ok(analytics::baseline(analytics::within_days(
+            &recordings,
+            Utc::now(),
+            days,
+        )))
+    }
+
+    /// Emotion and tone trends: rolling baselines, weekday and time-of-day
+    /// distributions (local time) and notable recent shifts
+    pub fn get_emotion_trends(&self) -> Result<EmotionTrends, String> {
+        let recordings = self.counted_recordings()?;
+        Ok(analytics::trends(&recordings, Utc::now(), &chrono::Local))
+    }
+}
+
+impl Default for StorageManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Storage statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStats {
+    pub recording_count: usize,
+    pub total_size_bytes: usize,
+    pub total_duration_ms: u64,
+    pub storage_path: String,
+    pub max_recordings: usize,
+    /// Bytes the recordings occupy on disk (audio and sidecars, including the
+    /// trash, which counts toward the quota)
+    pub disk_usage_bytes: u64,
+    /// Storage quota (`None` = unlimited)
+    pub quota_bytes: Option<u64>,
+    pub quota_used_percent: Option<f32>,
+    pub pinned_count: usize,
+    pub retention_days: u32,
+}
+
+/// Storage manager of the active profile
+struct ActiveManager {
+    profile_id: String,
+    manager: Arc<StorageManager>,
+}
+
+/// Global storage manager of the active profile (lazy initialized)
+static STORAGE_MANAGER: std::sync::RwLock<Option<ActiveManager>> = std::sync::RwLock::new(None);
+
+/// Get or initialize the storage manager of the active profile.
+/// Starts with the active profile and storage section of the persisted settings.
+///
+/// Callers keep working on the returned manager even if the profile is
+/// switched meanwhile; the next call returns the new profile's manager.
+pub fn get_storage_manager() -> Arc<StorageManager> {
+    if let Some(active) = STORAGE_MANAGER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return active.manager.clone();
+    }
+
+    let mut guard = STORAGE_MANAGER.write().unwrap_or_else(|e| e.into_inner());
+    let active = guard.get_or_insert_with(|| {
+        let settings = crate::settings::get_settings_store().get();
+        let manager = StorageManager::new()
+            .with_keyring_user(&crypto::data_key_user(&settings.active_profile))
+            .with_sync_profile(&settings.active_profile);
+        if let Err(e) = manager.update_config(settings.storage) {
+            tracing::warn!("Failed to apply persisted storage settings: {e}");
+        }
+        ActiveManager {
+            profile_id: settings.active_profile,
+            manager: Arc::new(manager),
+        }
+    });
+    active.manager.clone()
+}
+
+/// Apply the storage config of the active profile.
+///
+/// Updates the current manager if the profile is unchanged, otherwise
+/// replaces it with a manager for the profile's library and data key.
+pub fn set_active_profile(profile_id: &str, config: StorageConfig) -> Result<(), String> {
+    let mut guard = STORAGE_MANAGER.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(active) = guard.as_ref().filter(|a| a.profile_id == profile_id) {
+        return active.manager.update_config(config);
+    }
+
+    let manager = StorageManager::with_config(config)
+        .with_keyring_user(&crypto::data_key_user(profile_id))
+        .with_sync_profile(profile_id);
+    *guard = Some(ActiveManager {
+        profile_id: profile_id.to_string(),
+        manager: Arc::new(manager),
+    });
+    tracing::info!(profile = %profile_id, "Storage: Switched profile");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shared with the tests of the storage submodules
+    pub(crate) fn create_test_metadata() -> RecordingMetadata {
+        RecordingMetadata::new(
+            3000,
+            16000,
+            96044,
+            AudioValidationMeta {
+                rms_energy: 0.05,
+                duration_ms: 3000,
+                sample_count: 48000,
+                passed: true,
+            },
+            "whisper-cpp".to_string(),
+            "german-turbo".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_recording_metadata_creation() {
+        let metadata = create_test_metadata();
+        assert!(!metadata.id.is_empty());
+        assert!(!metadata.created_at.is_empty());
+        assert_eq!(metadata.duration_ms, 3000);
+        assert_eq!(metadata.sample_rate, 16000);
+    }
+
+    #[test]
+    fn test_storage_config_default() {
+        let config = StorageConfig::default();
+        assert!(config.max_recordings > 0);
+        // Path contains "Hablara" or "hablara" (lowercase on Linux XDG)
+        assert!(
+            config.storage_path.to_lowercase().contains("hablara"),
+            "Storage path should contain 'hablara': {}",
+            config.storage_path
+        );
+    }
+
+    #[test]
+    fn test_filename_generation() {
+        let metadata = create_test_metadata();
+        let filename = StorageManager::generate_filename(&metadata);
+        assert!(filename.contains(&metadata.id[..8]));
+    }
+
+    #[test]
+    fn test_storage_manager_config() {
+        let manager = StorageManager::new();
+        let config = manager.get_config().unwrap();
+        assert!(config.max_recordings > 0);
+
+        let mut updated = config.clone();
+        updated.max_recordings = 50;
+        manager.update_config(updated).unwrap();
+
+        let new_config = manager.get_config().unwrap();
+        assert_eq!(new_config.max_recordings, 50);
+    }
+
+    #[test]
+    fn test_home_fallback_robustness() {
+        // Test that get_default_storage_path() uses appropriate directories
+        let path = get_default_storage_path();
+
+        // Path should be under home or temp directory
+        let home = dirs::home_dir();
+        let temp = std::env::temp_dir();
+
+        let is_under_home = home
+            .as_ref()
+            .map(|h| path.starts_with(h))
+            .unwrap_or(false);
+        let is_under_temp = path.starts_with(&temp);
+
+        assert!(
+            is_under_home || is_under_temp,
+            "Storage path should be under home or temp directory, got: {}",
+            path.display()
+        );
+
+        // Path should end with recordings
+        assert!(path.ends_with("recordings"));
+    }
+
+    #[test]
+    fn test_storage_path_ends_with_recordings() {
+        let path = get_default_storage_path();
+        assert!(path.ends_with("recordings"));
+    }
+
+    #[test]
+    fn test_storage_path_within_home_or_temp() {
+        let path = get_default_storage_path();
+        let home = dirs::home_dir();
+        let temp = std::env::temp_dir();
+        assert!(
+            home.map(|h| path.starts_with(h)).unwrap_or(false) || path.starts_with(&temp)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "app-store")]
+    fn test_app_store_uses_documents() {
+        let path = get_default_storage_path();
+        let path_str = path.to_string_lossy();
+        // Accept both English "Documents" and localized variants (e.g., German "Dokumente")
+        // or fallback to home directory with "Documents" appended
+        assert!(
+            path_str.contains("Documents") || path_str.contains("Dokumente") ||
+            (dirs::home_dir().is_some() && path.starts_with(dirs::home_dir().unwrap())),
+            "App Store path should be in Documents directory or home subdirectory: {}",
+            path_str
+        );
+    }
+
+    #[test]
+    #[cfg(all(not(feature = "app-store"), target_os = "macos"))]
+    fn test_direct_macos_uses_app_support() {
+        let path = get_default_storage_path();
+        assert!(path.to_string_lossy().contains("Application Support"));
+    }
+
+    #[test]
+    #[cfg(all(not(feature = "app-store"), target_os = "linux"))]
+    fn test_direct_linux_uses_xdg() {
+        let path = get_default_storage_path();
+        assert!(path.to_string_lossy().contains(".local/share"));
+    }
+
+    fn temp_manager(dir: &Path) -> StorageManager {
+        StorageManager::with_config(StorageConfig {
+            storage_path: dir.to_string_lossy().to_string(),
+            ..StorageConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_catalog_serves_lookups_after_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+
+        let id = manager.save_recording(&[1, 2, 3], &create_test_metadata()).unwrap();
+
+        assert_eq!(manager.list_recordings().unwrap().len(), 1);
+        assert_eq!(manager.get_recording(&id).unwrap().id, id);
+        assert_eq!(manager.get_recording_audio(&id).unwrap(), vec![1, 2, 3]);
+        assert!(dir.path().join(catalog::CATALOG_FILE_NAME).exists());
+
+        manager.delete_recording(&id).unwrap();
+        assert!(manager.list_recordings().unwrap().is_empty());
+        assert!(manager.get_recording(&id).is_err());
+    }
+
+    #[test]
+    fn test_catalog_rebuilt_when_out_of_sync_with_sidecars() {
+        let dir = tempfile::tempdir().unwrap();
+        let id = temp_manager(dir.path())
+            .save_recording(&[], &create_test_metadata())
+            .unwrap();
+
+        // Sidecar added behind the catalog's back (e.g. copied in by hand)
+        let mut external = create_test_metadata();
+        external.created_at = "2020-01-01T00:00:00+00:00".to_string();
+        std::fs::write(
+            dir.path().join(format!("{}.json", StorageManager::generate_filename(&external))),
+            serde_json::to_string(&external).unwrap(),
+        )
+        .unwrap();
+
+        let manager = temp_manager(dir.path());
+        let listed = manager.list_recordings().unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, id, "Newest recording should come first");
+        assert_eq!(listed[1].id, external.id);
+    }
+
+    #[test]
+    fn test_catalog_rebuilt_after_removal() {
+        let dir = tempfile::tempdir().unwrap();
+        temp_manager(dir.path())
+            .save_recording(&[], &create_test_metadata())
+            .unwrap();
+        std::fs::remove_file(dir.path().join(catalog::CATALOG_FILE_NAME)).unwrap();
+
+        let manager = temp_manager(dir.path());
+        assert_eq!(manager.list_recordings().unwrap().len(), 1);
+        assert_eq!(manager.rebuild_catalog().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_external_changes_applied_to_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+        let storage_dir = manager.storage_dir().unwrap();
+        let own_id = manager.save_recording(&[1, 2, 3], &create_test_metadata()).unwrap();
+        let own_name = StorageManager::generate_filename(&manager.get_recording(&own_id).unwrap());
+
+        // The app's own save is already indexed
+        assert!(manager
+            .apply_external_changes(&storage_dir, std::slice::from_ref(&own_name))
+            .unwrap()
+            .is_empty());
+
+        // Copied in from another device
+        let mut external = create_test_metadata();
+        external.created_at = "2020-01-01T00:00:00+00:00".to_string();
+        let external_name = StorageManager::generate_filename(&external);
+        let sidecar = storage_dir.join(format!("{}.json", external_name));
+        std::fs::write(&sidecar, serde_json::to_string(&external).unwrap()).unwrap();
+        // Half-synced sidecar of the own recording is skipped
+        std::fs::write(storage_dir.join(format!("{}.json", own_name)), b"{ \"id\"").unwrap();
+
+        let changes = manager
+            .apply_external_changes(&storage_dir, &[external_name.clone(), own_name.clone()])
+            .unwrap();
+        assert_eq!(
+            changes,
+            [RecordingChange {
+                kind: RecordingChangeKind::Added,
+                id: external.id.clone(),
+                base_name: external_name.clone(),
+            }]
+        );
+        assert_eq!(manager.list_recordings().unwrap().len(), 2);
+
+        external.title = Some("Bearbeitet".to_string());
+        std::fs::write(&sidecar, serde_json::to_string(&external).unwrap()).unwrap();
+        let changes = manager
+            .apply_external_changes(&storage_dir, std::slice::from_ref(&external_name))
+            .unwrap();
+        assert_eq!(changes[0].kind, RecordingChangeKind::Modified);
+        assert_eq!(
+            manager.get_recording(&external.id).unwrap().title.as_deref(),
+            Some("Bearbeitet")
+        );
+
+        std::fs::remove_file(&sidecar).unwrap();
+        let changes = manager
+            .apply_external_changes(&storage_dir, &[external_name])
+            .unwrap();
+        assert_eq!(changes[0].kind, RecordingChangeKind::Removed);
+        assert!(manager.get_recording(&external.id).is_err());
+
+        // Events queued for a previous storage path are ignored
+        assert!(manager
+            .apply_external_changes(Path::new("/elsewhere"), &[own_name])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cleanup_evicts_oldest_via_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            max_recordings: 2,
+            ..StorageConfig::default()
+        });
+
+        let mut ids = Vec::new();
+        for day in 1..=3 {
+            let mut metadata = create_test_metadata();
+            metadata.created_at = format!("2026-01-0{}T10:00:00+00:00", day);
+            ids.push(manager.save_recording(&[0], &metadata).unwrap());
+        }
+
+        let remaining: Vec<_> = manager.list_recordings().unwrap().into_iter().map(|r| r.id).collect();
+        assert_eq!(remaining, vec![ids[2].clone(), ids[1].clone()]);
+        assert_eq!(manager.get_storage_stats().unwrap().recording_count, 2);
+    }
+
+    #[test]
+    fn test_deleted_recording_restored_from_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+        let id = manager.save_recording(&[1, 2, 3], &create_test_metadata()).unwrap();
+
+        manager.delete_recording(&id).unwrap();
+        assert!(manager.list_recordings().unwrap().is_empty());
+
+        let trash = manager.list_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].metadata.id, id);
+        assert_eq!(trash[0].reason, TrashReason::Deleted);
+        assert!(trash[0].expires_at > trash[0].deleted_at);
+
+        let restored = manager.restore_recording(&id).unwrap();
+        assert_eq!(restored.id, id);
+        assert_eq!(manager.get_recording_audio(&id).unwrap(), vec![1, 2, 3]);
+        assert!(manager.list_trash().unwrap().is_empty());
+        assert!(manager.restore_recording(&id).is_err());
+    }
+
+    #[test]
+    fn test_cleanup_and_clear_go_through_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            max_recordings: 1,
+            ..StorageConfig::default()
+        });
+
+        let mut older = create_test_metadata();
+        older.created_at = "2026-01-01T10:00:00+00:00".to_string();
+        let older_id = manager.save_recording(&[0], &older).unwrap();
+        manager.save_recording(&[0], &create_test_metadata()).unwrap();
+
+        let trash = manager.list_trash().unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].metadata.id, older_id);
+        assert_eq!(trash[0].reason, TrashReason::Cleanup);
+
+        assert_eq!(manager.clear_all_recordings().unwrap(), 1);
+        assert_eq!(manager.list_trash().unwrap().len(), 2);
+
+        assert_eq!(manager.empty_trash().unwrap(), 2);
+        assert!(manager.list_trash().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_zero_retention_deletes_immediately() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            trash_retention_days: 0,
+            ..StorageConfig::default()
+        });
+        let id = manager.save_recording(&[0], &create_test_metadata()).unwrap();
+
+        manager.delete_recording(&id).unwrap();
+        assert!(manager.list_trash().unwrap().is_empty());
+        assert!(!dir.path().join(trash::TRASH_DIR_NAME).exists());
+        assert_eq!(catalog::count_sidecars(dir.path()), 0);
+    }
+
+    #[test]
+    fn test_library_archive_roundtrip() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let source = temp_manager(source_dir.path());
+        let with_audio = source.save_recording(&[1, 2, 3], &create_test_metadata()).unwrap();
+        let text_only = source.save_recording(&[], &create_test_metadata()).unwrap();
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let archive_path = archive_dir.path().join("library.tar.gz");
+        let exported = source.export_library(&archive_path, None).unwrap();
+        assert_eq!(exported.recordings, 2);
+        assert!(exported.bytes > 0);
+
+        // Target library is encrypted; archive content is re-sealed on import
+        let target_dir = tempfile::tempdir().unwrap();
+        let target = encrypted_manager(target_dir.path(), crypto::DataKey::generate());
+        let imported = target.import_library(&archive_path).unwrap();
+        assert_eq!(imported.imported, 2);
+        assert_eq!(target.get_recording_audio(&with_audio).unwrap(), vec![1, 2, 3]);
+        assert_eq!(target.get_recording(&text_only).unwrap().id, text_only);
+
+        // Importing again finds everything already present
+        let again = target.import_library(&archive_path).unwrap();
+        assert_eq!(again.duplicates, 2);
+        assert_eq!(target.list_recordings().unwrap().len(), 2);
+
+        // Staging directory is gone after import
+        let leftovers = std::fs::read_dir(target_dir.path())
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with(".import-"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_library_import_renames_colliding_ids() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let source = temp_manager(source_dir.path());
+        let metadata = create_test_metadata();
+        source.save_recording(&[1], &metadata).unwrap();
+
+        let archive_dir = tempfile::tempdir().unwrap();
+        let archive_path = archive_dir.path().join("library.tar.gz");
+        source
+            .export_library(&archive_path, Some(std::slice::from_ref(&metadata.id)))
+            .unwrap();
+
+        // Same ID, different recording
+        let target_dir = tempfile::tempdir().unwrap();
+        let target = temp_manager(target_dir.path());
+        let mut other = metadata.clone();
+        other.created_at = "2026-01-01T10:00:00+00:00".to_string();
+        target.save_recording(&[2], &other).unwrap();
+
+        let result = target.import_library(&archive_path).unwrap();
+        assert_eq!(result.renamed, 1);
+        assert_eq!(target.list_recordings().unwrap().len(), 2);
+        assert_eq!(target.get_recording_audio(&metadata.id).unwrap(), vec![2]);
+
+        assert!(source
+            .export_library(&archive_path, Some(&["missing".to_string()]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            backup_path: Some(backup_dir.path().to_string_lossy().to_string()),
+            ..StorageConfig::default()
+        });
+        assert!(manager.backup_due(Utc::now()).unwrap());
+
+        let edited = manager.save_recording(&[1, 2, 3], &create_test_metadata()).unwrap();
+        let deleted = manager.save_recording(&[4], &create_test_metadata()).unwrap();
+        let kept = manager.save_recording(&[5], &create_test_metadata()).unwrap();
+        let backup = manager.create_backup(|_, _| {}).unwrap();
+        assert_eq!(backup.snapshot.recording_count, 3);
+        assert_eq!(manager.list_backups().unwrap().len(), 1);
+        assert!(!manager.backup_due(Utc::now()).unwrap());
+
+        manager
+            .update_recording_metadata(
+                &edited,
+                &RecordingMetadataUpdate {
+                    title: Some("Bearbeitet".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        manager.delete_recording(&deleted).unwrap();
+        let added = manager.save_recording(&[6], &create_test_metadata()).unwrap();
+
+        let result = manager.restore_backup(&backup.snapshot.name, |_, _| {}).unwrap();
+        assert_eq!((result.restored, result.replaced, result.unchanged), (1, 1, 1));
+        assert_eq!(manager.get_recording(&edited).unwrap().title, None);
+        assert_eq!(manager.get_recording_audio(&deleted).unwrap(), vec![4]);
+        assert_eq!(manager.get_recording_audio(&kept).unwrap(), vec![5]);
+        // Recordings newer than the backup stay, replaced versions go to the trash
+        assert!(manager.get_recording(&added).is_ok());
+        let trash = manager.list_trash().unwrap();
+        assert!(trash
+            .iter()
+            .any(|t| t.metadata.id == edited && t.reason == TrashReason::Replaced));
+
+        assert!(manager.restore_backup("../outside", |_, _| {}).is_err());
+    }
+
+    fn test_wav() -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for i in 0..16000 {
+            writer.write_sample(((i as f32 * 0.02).sin() * 6000.0) as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn test_duplicate_audio_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+        let mut metadata = create_test_metadata();
+        metadata.emotion = Some(EmotionData {
+            primary: "stress".to_string(),
+            confidence: 0.8,
+            secondary: None,
+        });
+        let original = manager.save_recording(&test_wav(), &metadata).unwrap();
+        assert!(manager.get_recording(&original).unwrap().content_hash.is_some());
+
+        // Warn: saved, but not counted twice
+        let mut again = metadata.clone();
+        again.id = String::new();
+        again.created_at = String::new();
+        let duplicate = manager.save_recording(&test_wav(), &again).unwrap();
+        assert_eq!(
+            manager.get_recording(&duplicate).unwrap().duplicate_of.as_deref(),
+            Some(original.as_str())
+        );
+        assert_eq!(manager.calculate_baseline_emotion().unwrap().unwrap().sample_count, 1);
+
+        let groups = manager.find_duplicates().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].exact);
+        let ids: Vec<_> = groups[0].recordings.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, [original.as_str(), duplicate.as_str()]);
+
+        let mut config = manager.get_config().unwrap();
+        config.duplicate_policy = DuplicatePolicy::Reject;
+        manager.update_config(config.clone()).unwrap();
+        assert!(manager.save_recording(&test_wav(), &create_test_metadata()).is_err());
+
+        config.duplicate_policy = DuplicatePolicy::Merge;
+        manager.update_config(config).unwrap();
+        let mut tagged = create_test_metadata();
+        tagged.tags = vec!["Arbeit".to_string()];
+        assert_eq!(manager.save_recording(&test_wav(), &tagged).unwrap(), original);
+        assert_eq!(manager.get_recording(&original).unwrap().tags, ["arbeit"]);
+        assert_eq!(manager.list_recordings().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_flac_storage_returns_original_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            audio_codec: AudioCodec::Flac,
+            ..StorageConfig::default()
+        });
+        let wav = test_wav();
+        let metadata = create_test_metadata();
+        let id = manager.save_recording(&wav, &metadata).unwrap();
+
+        let base_name = StorageManager::generate_filename(&metadata);
+        assert!(dir.path().join(format!("{}.flac", base_name)).exists());
+        assert!(!dir.path().join(format!("{}.wav", base_name)).exists());
+        assert_eq!(manager.get_recording_audio(&id).unwrap(), wav);
+    }
+
+    #[test]
+    fn test_recompress_library_reports_savings() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = encrypted_manager(dir.path(), crypto::DataKey::generate());
+        let wav = test_wav();
+        let id = manager.save_recording(&wav, &create_test_metadata()).unwrap();
+        let text_only = manager.save_recording(&[1, 2, 3], &create_test_metadata()).unwrap();
+
+        let mut calls = Vec::new();
+        let result = manager
+            .recompress_library(AudioCodec::Flac, |done, total| calls.push((done, total)))
+            .unwrap();
+        assert_eq!(result.recompressed, 1);
+        assert_eq!(result.skipped, 1, "Non-PCM audio stays as it is");
+        assert!(result.bytes_saved > 0);
+        assert_eq!(calls, vec![(1, 2), (2, 2)]);
+        assert_eq!(manager.get_config().unwrap().audio_codec, AudioCodec::Flac);
+
+        // Still encrypted and still decodes to the original WAV
+        assert_eq!(manager.get_recording_audio(&id).unwrap(), wav);
+        assert_eq!(manager.get_recording_audio(&text_only).unwrap(), vec![1, 2, 3]);
+
+        let back = manager.recompress_library(AudioCodec::Wav, |_, _| {}).unwrap();
+        assert_eq!(back.recompressed, 1);
+        assert_eq!(back.bytes_saved, -result.bytes_saved);
+        assert_eq!(manager.get_recording_audio(&id).unwrap(), wav);
+    }
+
+    #[test]
+    fn test_interrupted_save_recovered_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let saved = temp_manager(dir.path())
+            .save_recording(b"RIFF-saved", &create_test_metadata())
+            .unwrap();
+
+        // Crash after the audio file of a second save was written
+        let base = "2026-02-01_09-00-00_deadbeef";
+        let files = [format!("{}.wav", base), format!("{}.json", base)];
+        let mut crashed = atomic::SaveTransaction::begin(dir.path(), base, &files).unwrap();
+        crashed.write(&files[0], b"RIFF-lost").unwrap();
+        std::mem::forget(crashed);
+
+        let manager = temp_manager(dir.path());
+        assert!(!dir.path().join(&files[0]).exists());
+        assert!(!dir.path().join(format!("{}.pending", base)).exists());
+        let ids: Vec<_> = manager.list_recordings().unwrap().into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, [saved]);
+    }
+
+    #[test]
+    fn test_cleanup_keeps_pinned_and_previews() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            max_recordings: 2,
+            ..StorageConfig::default()
+        });
+        let save = |day: u32| {
+            let mut metadata = create_test_metadata();
+            metadata.created_at = format!("2026-03-{:02}T10:00:00+00:00", day);
+            manager.save_recording(b"RIFF-audio", &metadata).unwrap()
+        };
+
+        let pinned = save(1);
+        manager
+            .update_recording_metadata(
+                &pinned,
+                &RecordingMetadataUpdate {
+                    pinned: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let evicted = save(2);
+        let newest = save(3);
+
+        // The oldest unpinned recording went to the trash
+        let mut ids: Vec<_> = manager.list_recordings().unwrap().into_iter().map(|m| m.id).collect();
+        ids.sort();
+        let mut expected = vec![pinned.clone(), newest.clone()];
+        expected.sort();
+        assert_eq!(ids, expected);
+        assert_eq!(manager.list_trash().unwrap()[0].metadata.id, evicted);
+
+        let stats = manager.get_storage_stats().unwrap();
+        assert_eq!(stats.pinned_count, 1);
+        assert!(stats.disk_usage_bytes > 0);
+        assert_eq!(stats.quota_bytes, Some(500 * 1024 * 1024));
+
+        // Dry run only reports
+        manager
+            .update_config(StorageConfig {
+                max_recordings: 1,
+                ..manager.get_config().unwrap()
+            })
+            .unwrap();
+        let plan = manager.preview_cleanup().unwrap();
+        assert_eq!(plan.evictions.len(), 1);
+        assert_eq!(plan.evictions[0].id, newest);
+        assert_eq!(plan.evictions[0].reason, EvictionReason::OverCount);
+        assert!(plan.freed_bytes > 0);
+        assert_eq!(manager.list_recordings().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_quota_counts_trash_and_purges_it_first() {
+        // Everything but the catalog database, trash included
+        fn dir_size(dir: &Path) -> u64 {
+            std::fs::read_dir(dir)
+                .unwrap()
+                .flatten()
+                .filter(|entry| {
+                    !entry
+                        .file_name()
+                        .to_string_lossy()
+                        .starts_with(catalog::CATALOG_FILE_NAME)
+                })
+                .map(|entry| match entry.metadata().unwrap() {
+                    m if m.is_dir() => dir_size(&entry.path()),
+                    m => m.len(),
+                })
+                .sum()
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            max_user_storage_mb: 1,
+            ..StorageConfig::default()
+        });
+        let audio = vec![0u8; 300 * 1024];
+        let save = |day: u32| {
+            let mut metadata = create_test_metadata();
+            metadata.created_at = format!("2026-03-{:02}T10:00:00+00:00", day);
+            manager.save_recording(&audio, &metadata).unwrap()
+        };
+        let live = || -> Vec<String> {
+            manager.list_recordings().unwrap().into_iter().map(|m| m.id).collect()
+        };
+        let trashed = || -> Vec<String> {
+            manager
+                .list_trash()
+                .unwrap()
+                .into_iter()
+                .map(|t| t.metadata.id)
+                .collect()
+        };
+
+        let ids: Vec<_> = (1..=3).map(save).collect();
+        manager.delete_recording(&ids[0]).unwrap();
+        assert_eq!(trashed(), [ids[0].clone()]);
+
+        // Over the quota with the trash: the trash goes first, recordings stay
+        let fourth = save(4);
+        assert_eq!(live().len(), 3);
+        assert!(trashed().is_empty());
+
+        // Over the quota without trash: the oldest recording moves to the trash
+        save(5);
+        assert!(!live().contains(&ids[1]));
+        assert_eq!(trashed(), [ids[1].clone()]);
+
+        // ... and is purged before the next eviction
+        save(6);
+        assert!(!live().contains(&ids[2]) && live().contains(&fourth));
+        assert_eq!(trashed(), [ids[2].clone()]);
+
+        let stats = manager.get_storage_stats().unwrap();
+        assert_eq!(dir_size(dir.path()), stats.disk_usage_bytes);
+        let library_bytes = stats.disk_usage_bytes - trash::disk_size(dir.path());
+        assert!(library_bytes <= 1024 * 1024);
+    }
+
+    #[test]
+    fn test_recovered_recording_keeps_pauses() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = StorageManager::with_config(StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            ..StorageConfig::default()
+        });
+        let pause = RecordingPause {
+            offset_ms: 1000,
+            duration_ms: 2500,
+        };
+        let mut spool = manager.create_spool(None).unwrap().unwrap();
+        spool.append(&vec![0.1; 16000]).unwrap();
+        spool.add_pause(pause.clone()).unwrap();
+        spool.append(&vec![0.1; 16000]).unwrap();
+        drop(spool);
+
+        let sessions = manager.list_interrupted_recordings().unwrap();
+        assert_eq!(sessions.len(), 1);
+        let recovered = manager
+            .recover_interrupted_recording(&sessions[0].id)
+            .unwrap();
+        assert_eq!(recovered.pauses, [pause]);
+        assert!(manager.list_interrupted_recordings().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_verify_and_repair_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+        let wav = test_wav();
+
+        let intact = create_test_metadata();
+        manager.save_recording(&wav, &intact).unwrap();
+        let truncated = create_test_metadata();
+        manager.save_recording(&wav, &truncated).unwrap();
+        let missing = create_test_metadata();
+        manager.save_recording(&wav, &missing).unwrap();
+        manager.save_recording(&[], &RecordingMetadata { file_size: 0, ..create_test_metadata() })
+            .unwrap();
+
+        let path = |m: &RecordingMetadata, ext: &str| {
+            dir.path().join(format!("{}.{}", StorageManager::generate_filename(m), ext))
+        };
+        std::fs::write(path(&truncated, "wav"), &wav[..wav.len() / 2]).unwrap();
+        std::fs::remove_file(path(&missing, "wav")).unwrap();
+        std::fs::write(dir.path().join("2026-01-25_20-30-45_a1b2c3d4.wav"), &wav).unwrap();
+        set_secure_permissions(&dir.path().join("2026-01-25_20-30-45_a1b2c3d4.wav")).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path(&intact, "json"), std::fs::Permissions::from_mode(0o644))
+                .unwrap();
+        }
+
+        let report = manager.verify_storage().unwrap();
+        assert_eq!(report.checked_recordings, 5);
+        let mut kinds: Vec<_> = report.issues.iter().map(|i| i.kind).collect();
+        kinds.sort_by_key(|k| format!("{:?}", k));
+        let mut expected = vec![
+            IntegrityIssueKind::CorruptAudio,
+            IntegrityIssueKind::MissingAudio,
+            IntegrityIssueKind::OrphanedAudio,
+        ];
+        if cfg!(unix) {
+            expected.push(IntegrityIssueKind::InsecurePermissions);
+        }
+        expected.sort_by_key(|k| format!("{:?}", k));
+        assert_eq!(kinds, expected);
+
+        let repair = manager.repair_storage().unwrap();
+        assert_eq!(repair.repaired, if cfg!(unix) { 2 } else { 1 });
+        assert_eq!(repair.quarantined, 1);
+        assert_eq!(repair.unresolved.len(), 1);
+        assert_eq!(repair.unresolved[0].kind, IntegrityIssueKind::MissingAudio);
+
+        // Rebuilt from the orphaned audio and indexed
+        let rebuilt = manager
+            .list_recordings()
+            .unwrap()
+            .into_iter()
+            .find(|m| m.id.starts_with("a1b2c3d4"))
+            .expect("metadata rebuilt for orphaned audio");
+        assert_eq!(rebuilt.duration_ms, 1000);
+        assert_eq!(rebuilt.created_at, "2026-01-25T20:30:45+00:00");
+        assert_eq!(manager.get_recording_audio(&rebuilt.id).unwrap(), wav);
+
+        assert!(manager.get_recording(&truncated.id).is_err());
+        assert_eq!(manager.list_quarantined().unwrap().len(), 1);
+        let after = manager.verify_storage().unwrap();
+        assert_eq!(after.issues.len(), 1);
+        assert_eq!(after.issues[0].kind, IntegrityIssueKind::MissingAudio);
+    }
+
+    #[test]
+    fn test_query_recordings_filters_and_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+        let mut ids = Vec::new();
+        for day in 1..=5 {
+            let mut metadata = create_test_metadata();
+            metadata.created_at = format!("2026-02-0{}T09:00:00+00:00", day);
+            if day % 2 == 1 {
+                metadata.emotion = Some(EmotionData {
+                    primary: "joy".to_string(),
+                    confidence: 0.9,
+                    secondary: None,
+                });
+            }
+            ids.push(manager.save_recording(&[1, 2, 3], &metadata).unwrap());
+        }
+
+        let query = RecordingQuery {
+            primary_emotion: Some("joy".to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let first = manager.query_recordings(&query).unwrap();
+        let first_ids: Vec<_> = first.items.iter().map(|m| m.id.clone()).collect();
+        assert_eq!(first_ids, [ids[4].clone(), ids[2].clone()]);
+
+        let second = manager
+            .query_recording_summaries(&RecordingQuery {
+                cursor: first.next_cursor.clone(),
+                ..query.clone()
+            })
+            .unwrap();
+        assert_eq!(second.items.len(), 1);
+        assert_eq!(second.items[0].id, ids[0]);
+        assert_eq!(second.items[0].primary_emotion.as_deref(), Some("joy"));
+        assert_eq!(second.items[0].analyses, [AnalysisKind::Emotion]);
+        assert!(second.next_cursor.is_none());
+
+        let range = manager
+            .query_recordings(&RecordingQuery {
+                created_after: Some("2026-02-02T00:00:00Z".to_string()),
+                created_before: Some("2026-02-04T00:00:00Z".to_string()),
+                sort: RecordingSort::OldestFirst,
+                ..Default::default()
+            })
+            .unwrap();
+        let range_ids: Vec<_> = range.items.iter().map(|m| m.id.clone()).collect();
+        assert_eq!(range_ids, [ids[1].clone(), ids[2].clone()]);
+    }
+
+    fn encrypted_manager(dir: &Path, key: crypto::DataKey) -> StorageManager {
+        StorageManager::with_config(StorageConfig {
+            storage_path: dir.to_string_lossy().to_string(),
+            encryption_enabled: true,
+            ..StorageConfig::default()
+        })
+        .with_data_key(key)
+    }
+
+    #[test]
+    fn test_encrypted_recording_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = encrypted_manager(dir.path(), crypto::DataKey::generate());
+
+        let id = manager.save_recording(b"RIFF-audio", &create_test_metadata()).unwrap();
+
+        for entry in std::fs::read_dir(dir.path()).unwrap().flatten() {
+            let path = entry.path();
+            if matches!(path.extension().and_then(|e| e.to_str()), Some("wav" | "json")) {
+                assert!(
+                    crypto::is_encrypted(&std::fs::read(&path).unwrap()),
+                    "{} should be encrypted",
+                    path.display()
+                );
+            }
+        }
+
+        assert_eq!(manager.get_recording_audio(&id).unwrap(), b"RIFF-audio");
+        assert_eq!(manager.get_recording(&id).unwrap().id, id);
+    }
+
+    #[test]
+    fn test_update_recording_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = encrypted_manager(dir.path(), crypto::DataKey::generate());
+
+        let mut metadata = create_test_metadata();
+        metadata.emotion = Some(EmotionData {
+            primary: "stress".to_string(),
+            confidence: 0.8,
+            secondary: None,
+        });
+        let tagged = manager.save_recording(b"RIFF-a", &metadata).unwrap();
+        let untagged = manager.save_recording(b"RIFF-b", &create_test_metadata()).unwrap();
+
+        let updated = manager
+            .update_recording_metadata(
+                &tagged,
+                &RecordingMetadataUpdate {
+                    title: Some("Teammeeting".to_string()),
+                    tags: Some(vec!["Arbeit".to_string()]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(updated.tags, ["arbeit"]);
+        assert!(updated.edited_at.is_some());
+
+        // Invalid updates leave the sidecar untouched
+        assert!(manager
+            .update_recording_metadata(
+                &tagged,
+                &RecordingMetadataUpdate {
+                    title: Some(String::new()),
+                    tags: Some(vec!["a/b".to_string()]),
+                    ..Default::default()
+                },
+            )
+            .is_err());
+
+        // Persisted in the (still encrypted) sidecar, not only in the catalog
+        manager.rebuild_catalog().unwrap();
+        let stored = manager.get_recording(&tagged).unwrap();
+        assert_eq!(stored.title.as_deref(), Some("Teammeeting"));
+        assert_eq!(stored.tags, ["arbeit"]);
+        for entry in std::fs::read_dir(dir.path()).unwrap().flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "json") {
+                assert!(crypto::is_encrypted(&std::fs::read(&path).unwrap()));
+            }
+        }
+
+        let page = manager
+            .query_recordings(&RecordingQuery {
+                tags: vec!["ARBEIT".to_string()],
+                ..Default::default()
+            })
+            .unwrap();
+        let page_ids: Vec<_> = page.items.iter().map(|m| m.id.clone()).collect();
+        assert_eq!(page_ids, [tagged]);
+        assert!(manager.get_recording(&untagged).unwrap().tags.is_empty());
+
+        let baseline = manager.calculate_tag_baseline_emotion("Arbeit").unwrap().unwrap();
+        assert_eq!(baseline.emotion, "stress");
+        assert_eq!(baseline.sample_count, 1);
+        assert!(manager.calculate_tag_baseline_emotion("urlaub").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_transcription_revisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+
+        let mut metadata = create_test_metadata();
+        metadata.transcription = Some(TranscriptionMeta {
+            text: "Termin mit Frau Meier am Montag".to_string(),
+            provider: "whisper-cpp".to_string(),
+            model: "german-turbo".to_string(),
+            language: "de".to_string(),
+            processing_time_ms: 1200,
+        });
+        let id = manager.save_recording(&[1, 2, 3], &metadata).unwrap();
+
+        let added = manager
+            .add_transcription_revision(
+                &id,
+                NewTranscriptionRevision {
+                    provider: "mlx-whisper".to_string(),
+                    model: "large-v3".to_string(),
+                    language: "de".to_string(),
+                    text: "Termin mit Frau Maier am Dienstag".to_string(),
+                    filtered_text: "Termin mit Frau Maier am Dienstag".to_string(),
+                    processing_time_ms: 2500,
+                },
+            )
+            .unwrap();
+        assert_eq!(added.revision, 2);
+
+        // New revision is active and searchable, the old one kept
+        manager.rebuild_catalog().unwrap();
+        let history = manager.get_transcription_history(&id).unwrap();
+        assert_eq!(history.active_revision, Some(2));
+        assert_eq!(history.revisions.len(), 2);
+        assert_eq!(history.revisions[0].model, "german-turbo");
+        assert_eq!(manager.search_recordings("Dienstag", None).unwrap().len(), 1);
+
+        let diff = manager.diff_transcription_revisions(&id, 1, 2).unwrap();
+        let changed: Vec<_> = diff
+            .iter()
+            .filter(|s| s.kind != DiffKind::Equal)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(changed, ["Meier", "Maier", "Montag", "Dienstag"]);
+
+        let restored = manager.set_active_transcription_revision(&id, 1).unwrap();
+        assert_eq!(restored.transcription.unwrap().model, "german-turbo");
+        assert!(manager.search_recordings("Dienstag", None).unwrap().is_empty());
+        assert!(manager.set_active_transcription_revision(&id, 7).is_err());
+        assert!(manager.diff_transcription_revisions(&id, 1, 7).is_err());
+    }
+
+    #[test]
+    fn test_mixed_library_and_conversion() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = crypto::DataKey::generate();
+
+        let plain_id = temp_manager(dir.path())
+            .save_recording(b"plain", &create_test_metadata())
+            .unwrap();
+        let secret_id = encrypted_manager(dir.path(), key.clone())
+            .save_recording(b"secret", &create_test_metadata())
+            .unwrap();
+
+        // Fresh manager: catalog rebuilt from mixed sidecars
+        std::fs::remove_file(dir.path().join(catalog::CATALOG_FILE_NAME)).unwrap();
+        let manager = encrypted_manager(dir.path(), key);
+        assert_eq!(manager.list_recordings().unwrap().len(), 2);
+        assert_eq!(manager.get_recording_audio(&plain_id).unwrap(), b"plain");
+        assert_eq!(manager.get_recording_audio(&secret_id).unwrap(), b"secret");
+
+        let result = manager.convert_library_encryption(true).unwrap();
+        assert_eq!((result.converted, result.skipped, result.failed), (2, 2, 0));
+
+        let result = manager.convert_library_encryption(false).unwrap();
+        assert_eq!((result.converted, result.skipped, result.failed), (4, 0, 0));
+        assert!(!manager.get_config().unwrap().encryption_enabled);
+
+        // Plaintext again: readable without any key
+        let manager = temp_manager(dir.path());
+        assert_eq!(manager.get_recording_audio(&secret_id).unwrap(), b"secret");
+        assert_eq!(manager.list_recordings().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_search_follows_save_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = temp_manager(dir.path());
+
+        let mut metadata = create_test_metadata();
+        metadata.transcription = Some(TranscriptionMeta {
+            text: "Heute habe ich mich über die Präsentation geärgert".to_string(),
+            provider: "whisper-cpp".to_string(),
+            model: "german-turbo".to_string(),
+            language: "de".to_string(),
+            processing_time_ms: 120,
+        });
+        let id = manager.save_recording(&[], &metadata).unwrap();
+
+        let results = manager.search_recordings("prasentationen", None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].recording_id, id);
+        assert!(results[0].score > 0.0);
+
+        let snippet = &results[0].snippets[0];
+        assert_eq!(snippet.field, SearchField::Transcription);
+        assert!(snippet
+            .parts
+            .iter()
+            .any(|p| p.highlighted && p.text == "Präsentation"));
+
+        assert!(manager.search_recordings("Urlaub", None).unwrap().is_empty());
+        assert!(manager.search_recordings(&"x".repeat(501), None).is_err());
+
+        manager.delete_recording(&id).unwrap();
+        assert!(manager.search_recordings("Präsentation", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_old_sidecar_upgraded_and_corrupt_one_quarantined() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Pre-versioning sidecar without schemaVersion and appVersion
+        let mut legacy = serde_json::to_value(create_test_metadata()).unwrap();
+        let legacy_id = legacy["id"].as_str().unwrap().to_string();
+        legacy.as_object_mut().unwrap().remove("schemaVersion");
+        legacy.as_object_mut().unwrap().remove("appVersion");
+        let legacy_path = dir.path().join("2025-01-01_10-00-00_legacy00.json");
+        std::fs::write(&legacy_path, serde_json::to_vec(&legacy).unwrap()).unwrap();
+
+        let broken_path = dir.path().join("2025-01-02_10-00-00_broken00.json");
+        std::fs::write(&broken_path, b"{ \"id\": ").unwrap();
+        std::fs::write(dir.path().join("2025-01-02_10-00-00_broken00.wav"), b"RIFF").unwrap();
+
+        let manager = temp_manager(dir.path());
+        let listed = manager.list_recordings().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, legacy_id);
+        assert_eq!(listed[0].schema_version, CURRENT_SCHEMA_VERSION);
+
+        // Possibly still being written or synced: left in place for now
+        assert!(manager.list_quarantined().unwrap().is_empty());
+        assert!(broken_path.exists());
+
+        // Unchanged for the settle delay: moved aside with its audio, not dropped
+        std::fs::File::options()
+            .write(true)
+            .open(&broken_path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - quarantine::SETTLE_DELAY * 2)
+            .unwrap();
+        manager.rebuild_catalog().unwrap();
+        let quarantined = manager.list_quarantined().unwrap();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].base_name, "2025-01-02_10-00-00_broken00");
+        assert!(quarantined[0].has_audio);
+        assert!(!dir.path().join("2025-01-02_10-00-00_broken00.wav").exists());
+
+        let result = manager.upgrade_sidecars().unwrap();
+        assert_eq!((result.upgraded, result.current, result.failed), (1, 0, 0));
+        let rewritten: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&legacy_path).unwrap()).unwrap();
+        assert_eq!(rewritten["schemaVersion"], CURRENT_SCHEMA_VERSION);
+
+        // Restoring a still-broken file puts it straight back into quarantine
+        manager.restore_quarantined("2025-01-02_10-00-00_broken00").unwrap();
+        assert_eq!(manager.list_quarantined().unwrap().len(), 1);
+        assert!(manager.restore_quarantined("../escape").is_err());
+    }
+
+    #[test]
+    fn test_newer_sidecar_reported_not_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut newer = serde_json::to_value(create_test_metadata()).unwrap();
+        newer["schemaVersion"] = (CURRENT_SCHEMA_VERSION + 1).into();
+        let newer_path = dir.path().join("2025-01-03_10-00-00_newer000.json");
+        std::fs::write(&newer_path, serde_json::to_vec(&newer).unwrap()).unwrap();
+
+        let manager = temp_manager(dir.path());
+        assert!(manager.list_recordings().unwrap().is_empty());
+
+        let report = manager.verify_storage().unwrap();
+        let issue = report
+            .issues
+            .iter()
+            .find(|i| i.kind == IntegrityIssueKind::NewerSchema)
+            .unwrap();
+        assert_eq!(issue.base_name, "2025-01-03_10-00-00_newer000");
+        assert!(!issue.repairable);
+
+        // Left for a newer app version: never quarantined
+        let repair = manager.repair_storage().unwrap();
+        assert_eq!(repair.quarantined, 0);
+        assert!(newer_path.exists());
+    }
+
+    #[test]
+    fn test_migrate_legacy_storage_no_legacy_dir() {
+        // Migration is a static once-per-process operation, so we just verify
+        // the function is safe to call (doesn't panic) and returns a valid result
+        let result = migrate_legacy_storage();
+        // Should succeed (either 0 files migrated or migration already done)
+        // or fail gracefully with an error message
+        match result {
+            Ok(count) => assert!(count >= 0, "Migration count should be non-negative"),
+            Err(e) => assert!(!e.is_empty(), "Error message should not be empty"),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Quarantine for Unreadable Sidecars
+//!
+//! Sidecars that cannot be parsed (corrupt JSON, failed migration) are moved
+//! together with their audio into `.quarantine/` inside the storage directory
+//! instead of being skipped. Catalog rebuilds only move sidecars that have not
+//! changed for [`SETTLE_DELAY`], so files still being written or synced stay
+//! in place; the integrity repair moves them right away and does the same for
+//! damaged audio. A `<base_name>.reason.json` note records why.
+//! Quarantined recordings can be listed and moved back after a fix or update.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::codec::AUDIO_EXTENSIONS;
+use super::atomic;
+
+/// Quarantine directory name (hidden, inside the storage directory)
+pub const QUARANTINE_DIR_NAME: &str = ".quarantine";
+
+/// Suffix of the note describing why a recording was quarantined
+const REASON_SUFFIX: &str = ".reason.json";
+
+/// How long an unparseable sidecar must stay unchanged before a catalog
+/// rebuild quarantines it (half-written or still syncing files settle sooner)
+pub const SETTLE_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// A recording moved aside because its metadata could not be read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedRecording {
+    /// File stem of the quarantined `.json` and audio (either may be missing)
+    pub base_name: String,
+    pub reason: String,
+    pub quarantined_at: String,
+    pub has_audio: bool,
+}
+
+fn quarantine_dir(storage_dir: &Path) -> PathBuf {
+    storage_dir.join(QUARANTINE_DIR_NAME)
+}
+
+/// Whether a file has not been modified for [`SETTLE_DELAY`]
+pub fn is_settled(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age >= SETTLE_DELAY)
+}
+
+/// Move a recording's files into quarantine and record the reason
+pub fn quarantine(storage_dir: &Path, base_name: &str, reason: &str) -> Result<(), String> {
+    let dir = quarantine_dir(storage_dir);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create quarantine directory: {}", e))?;
+
+    let mut has_audio = false;
+    for ext in AUDIO_EXTENSIONS {
+        let audio_path = storage_dir.join(format!("{}.{}", base_name, ext));
+        if audio_path.exists() {
+            std::fs::rename(&audio_path, dir.join(format!("{}.{}", base_name, ext)))
+                .map_err(|e| format!("Failed to quarantine audio: {}", e))?;
+            has_audio = true;
+        }
+    }
+    // Audio without a sidecar (integrity repair) is quarantined on its own
+    let json_path = storage_dir.join(format!("{}.json", base_name));
+    if json_path.exists() {
+        std::fs::rename(&json_path, dir.join(format!("{}.json", base_name)))
+            .map_err(|e| format!("Failed to quarantine metadata: {}", e))?;
+    }
+
+    let note = QuarantinedRecording {
+        base_name: base_name.to_string(),
+        reason: reason.to_string(),
+        quarantined_at: chrono::Utc::now().to_rfc3339(),
+        has_audio,
+    };
+    let note_path = dir.join(format!("{}{}", base_name, REASON_SUFFIX));
+    let content = serde_json::to_string_pretty(&note)
+        .map_err(|e| format!("Failed to serialize quarantine note: {}", e))?;
+    atomic::write_file(&note_path, content.as_bytes())?;
+
+    tracing::warn!(name = %base_name, reason = %reason, "Storage: Recording quarantined");
+    Ok(())
+}
+
+/// List quarantined recordings (oldest quarantine first)
+pub fn list(storage_dir: &Path) -> Result<Vec<QuarantinedRecording>, String> {
+    let dir = quarantine_dir(storage_dir);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut notes = Vec::new();
+    for entry in std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read quarantine directory: {}", e))?
+        .flatten()
+    {
+        let path = entry.path();
+        let is_note = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.ends_with(REASON_SUFFIX))
+            .unwrap_or(false);
+        if !is_note {
+            continue;
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<QuarantinedRecording>(&c).map_err(|e| e.to_string()))
+        {
+            Ok(note) => notes.push(note),
+            Err(e) => tracing::warn!(path = %path.display(), error = %e, "Storage: Invalid quarantine note"),
+        }
+    }
+
+    notes.sort_by(|a, b| a.quarantined_at.cmp(&b.quarantined_at));
+    Ok(notes)
+}
+
+/// Move a quarantined recording back into the storage directory.
+/// The caller is responsible for re-indexing it.
+pub fn restore(storage_dir: &Path, base_name: &str) -> Result<(), String> {
+    // Security: base_name comes from the frontend - no path components allowed
+    if base_name.is_empty()
+        || base_name.contains(['/', '\\'])
+        || base_name.contains("..")
+    {
+        return Err(format!("Invalid recording name: {}", base_name));
+    }
+
+    let dir = quarantine_dir(storage_dir);
+    let json_src = dir.join(format!("{}.json", base_name));
+    let has_audio = AUDIO_EXTENSIONS
+        .iter()
+        .any(|ext| dir.join(format!("{}.{}", base_name, ext)).exists());
+    if !json_src.exists() && !has_audio {
+        return Err(format!("Quarantined recording not found: {}", base_name));
+    }
+
+    let json_dest = storage_dir.join(format!("{}.json", base_name));
+    if json_dest.exists() {
+        return Err(format!("A recording named {} already exists", base_name));
+    }
+
+    for ext in AUDIO_EXTENSIONS {
+        let audio_src = dir.join(format!("{}.{}", base_name, ext));
+        if audio_src.exists() {
+            std::fs::rename(&audio_src, storage_dir.join(format!("{}.{}", base_name, ext)))
+                .map_err(|e| format!("Failed to restore audio: {}", e))?;
+        }
+    }
+    if json_src.exists() {
+        std::fs::rename(&json_src, &json_dest)
+            .map_err(|e| format!("Failed to restore metadata: {}", e))?;
+    }
+    let _ = std::fs::remove_file(dir.join(format!("{}{}", base_name, REASON_SUFFIX)));
+
+    tracing::info!(name = %base_name, "Storage: Recording restored from quarantine");
+    Ok(())
+}
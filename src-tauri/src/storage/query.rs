@@ -0,0 +1,397 @@
+//! Recording Queries
+//!
+//! Filtered, sorted and paginated listings. Sorting and pagination run in the
+//! catalog (keyset pagination on the sort column and ID); filters are applied
+//! to the decoded metadata, so encrypted libraries don't need plaintext filter
+//! columns.
+//!
+//! Cursors are opaque to the frontend: base64 of the sort order and the sort
+//! key of the last returned recording.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine as _};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use super::annotations::normalize_tag;
+use super::RecordingMetadata;
+
+/// Default and maximum number of recordings per page
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Characters of the transcript included in a summary
+const PREVIEW_CHARS: usize = 200;
+
+/// Sort order of a recording query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordingSort {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+    LongestFirst,
+    ShortestFirst,
+}
+
+impl RecordingSort {
+    /// Catalog column and whether it is sorted descending
+    pub fn column(self) -> (&'static str, bool) {
+        match self {
+            RecordingSort::NewestFirst => ("created_at", true),
+            RecordingSort::OldestFirst => ("created_at", false),
+            RecordingSort::LongestFirst => ("duration_ms", true),
+            RecordingSort::ShortestFirst => ("duration_ms", false),
+        }
+    }
+}
+
+/// Optional parts of a recording's analysis, used for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnalysisKind {
+    Transcription,
+    Emotion,
+    /// Combined analysis result (fallacies, enrichment, topic)
+    Analysis,
+    Tone,
+    Gfk,
+    Cognitive,
+    FourSides,
+}
+
+impl AnalysisKind {
+    pub const ALL: [AnalysisKind; 7] = [
+        AnalysisKind::Transcription,
+        AnalysisKind::Emotion,
+        AnalysisKind::Analysis,
+        AnalysisKind::Tone,
+        AnalysisKind::Gfk,
+        AnalysisKind::Cognitive,
+        AnalysisKind::FourSides,
+    ];
+
+    /// Whether a recording has this part
+    pub fn present_in(self, metadata: &RecordingMetadata) -> bool {
+        match self {
+            AnalysisKind::Transcription => metadata.transcription.is_some(),
+            AnalysisKind::Emotion => metadata.emotion.is_some(),
+            AnalysisKind::Analysis => metadata.analysis_result.is_some(),
+            AnalysisKind::Tone => metadata.tone.is_some(),
+            AnalysisKind::Gfk => metadata.gfk.is_some(),
+            AnalysisKind::Cognitive => metadata.cognitive.is_some(),
+            AnalysisKind::FourSides => metadata.four_sides.is_some(),
+        }
+    }
+}
+
+/// Input source of a recording; sidecars without one predate the field and
+/// were recorded
+fn source_of(metadata: &RecordingMetadata) -> &str {
+    metadata.source.as_deref().unwrap_or("recording")
+}
+
+/// Filters, sort order and page of a recording query (all filters optional)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RecordingQuery {
+    /// Only recordings created at or after this time (RFC 3339)
+    pub created_after: Option<String>,
+    /// Only recordings created before this time (RFC 3339)
+    pub created_before: Option<String>,
+    /// Primary emotion (case-insensitive)
+    pub primary_emotion: Option<String>,
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Input source ("recording", "text", "file", "audio-file")
+    pub source: Option<String>,
+    /// Tags a recording must all have
+    pub tags: Vec<String>,
+    /// Only pinned (`true`) or unpinned (`false`) recordings
+    pub pinned: Option<bool>,
+    /// Analyses a recording must have
+    pub with_analyses: Vec<AnalysisKind>,
+    /// Analyses a recording must not have
+    pub without_analyses: Vec<AnalysisKind>,
+    pub sort: RecordingSort,
+    /// Page size (default 50, max 500)
+    pub limit: Option<usize>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+impl RecordingQuery {
+    /// Page size after applying default and bounds
+    pub fn page_size(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Validate the query and prepare its filters
+    pub fn filter(&self) -> Result<RecordingFilter<'_>, String> {
+        let parse = |value: &Option<String>, name: &str| {
+            value
+                .as_deref()
+                .map(|v| {
+                    DateTime::parse_from_rfc3339(v)
+                        .map_err(|e| format!("Invalid {} '{}': {}", name, v, e))
+                })
+                .transpose()
+        };
+
+        Ok(RecordingFilter {
+            query: self,
+            tags: self.tags.iter().map(|t| normalize_tag(t)).collect(),
+            created_after: parse(&self.created_after, "createdAfter")?,
+            created_before: parse(&self.created_before, "createdBefore")?,
+        })
+    }
+
+    /// Decode the cursor, checking it belongs to this sort order
+    pub fn page_cursor(&self) -> Result<Option<PageCursor>, String> {
+        let Some(encoded) = self.cursor.as_deref() else {
+            return Ok(None);
+        };
+        let cursor = BASE64_URL
+            .decode(encoded)
+            .ok()
+            .and_then(|json| serde_json::from_slice::<PageCursor>(&json).ok())
+            .ok_or_else(|| "Invalid cursor".to_string())?;
+        if cursor.sort != self.sort {
+            return Err("Cursor belongs to a different sort order".to_string());
+        }
+        Ok(Some(cursor))
+    }
+}
+
+/// Validated filters of a [`RecordingQuery`]
+pub struct RecordingFilter<'a> {
+    query: &'a RecordingQuery,
+    tags: Vec<String>,
+    created_after: Option<DateTime<FixedOffset>>,
+    created_before: Option<DateTime<FixedOffset>>,
+}
+
+impl RecordingFilter<'_> {
+    pub fn matches(&self, metadata: &RecordingMetadata) -> bool {
+        let q = self.query;
+
+        if self.created_after.is_some() || self.created_before.is_some() {
+            // Recordings with an unparseable timestamp never match a date range
+            let Ok(created) = DateTime::parse_from_rfc3339(&metadata.created_at) else {
+                return false;
+            };
+            if self.created_after.is_some_and(|after| created < after)
+                || self.created_before.is_some_and(|before| created >= before)
+            {
+                return false;
+            }
+        }
+
+        if let Some(emotion) = &q.primary_emotion {
+            let matches = metadata
+                .emotion
+                .as_ref()
+                .is_some_and(|e| e.primary.eq_ignore_ascii_case(emotion));
+            if !matches {
+                return false;
+            }
+        }
+
+        q.provider.as_ref().is_none_or(|p| *p == metadata.provider)
+            && q.model.as_ref().is_none_or(|m| *m == metadata.model)
+            && q.source.as_deref().is_none_or(|s| s == source_of(metadata))
+            && self.tags.iter().all(|t| metadata.tags.contains(t))
+            && q.pinned.is_none_or(|p| p == metadata.pinned)
+            && q.with_analyses.iter().all(|k| k.present_in(metadata))
+            && !q.without_analyses.iter().any(|k| k.present_in(metadata))
+    }
+}
+
+/// Position after the last recording of a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageCursor {
+    pub sort: RecordingSort,
+    pub created_at: String,
+    pub duration_ms: u64,
+    pub id: String,
+}
+
+impl PageCursor {
+    pub fn after(sort: RecordingSort, metadata: &RecordingMetadata) -> Self {
+        Self {
+            sort,
+            created_at: metadata.created_at.clone(),
+            duration_ms: metadata.duration_ms,
+            id: metadata.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        BASE64_URL.encode(serde_json::to_vec(self).expect("cursor serializes"))
+    }
+}
+
+/// One page of query results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPage<T> {
+    pub items: Vec<T>,
+    /// Cursor for the next page, `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> RecordingPage<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> RecordingPage<U> {
+        RecordingPage {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Lightweight listing entry without transcript and analysis payloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSummary {
+    pub id: String,
+    pub created_at: String,
+    pub duration_ms: u64,
+    pub file_size: usize,
+    pub provider: String,
+    pub model: String,
+    pub source: String,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub pinned: bool,
+    pub primary_emotion: Option<String>,
+    /// Start of the transcript
+    pub preview: Option<String>,
+    /// Analyses present in the full metadata
+    pub analyses: Vec<AnalysisKind>,
+}
+
+impl From<RecordingMetadata> for RecordingSummary {
+    fn from(metadata: RecordingMetadata) -> Self {
+        let analyses = AnalysisKind::ALL
+            .into_iter()
+            .filter(|k| k.present_in(&metadata))
+            .collect();
+        let preview = metadata.transcription.as_ref().map(|t| {
+            let mut preview: String = t.text.chars().take(PREVIEW_CHARS).collect();
+            if t.text.chars().nth(PREVIEW_CHARS).is_some() {
+                preview.push('…');
+            }
+            preview
+        });
+
+        Self {
+            source: source_of(&metadata).to_string(),
+            primary_emotion: metadata.emotion.as_ref().map(|e| e.primary.clone()),
+            preview,
+            analyses,
+            title: metadata.title,
+            tags: metadata.tags,
+            pinned: metadata.pinned,
+            id: metadata.id,
+            created_at: metadata.created_at,
+            duration_ms: metadata.duration_ms,
+            file_size: metadata.file_size,
+            provider: metadata.provider,
+            model: metadata.model,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AudioValidationMeta, EmotionData};
+
+    fn metadata(created_at: &str) -> RecordingMetadata {
+        let mut m = RecordingMetadata::new(
+            1000,
+            16000,
+            32044,
+            AudioValidationMeta {
+                rms_energy: 0.05,
+                duration_ms: 1000,
+                sample_count: 16000,
+                passed: true,
+            },
+            "whisper-cpp".to_string(),
+            "german-turbo".to_string(),
+        );
+        m.created_at = created_at.to_string();
+        m
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let mut m = metadata("2026-03-01T12:00:00+01:00");
+        m.emotion = Some(EmotionData {
+            primary: "stress".to_string(),
+            confidence: 0.8,
+            secondary: None,
+        });
+        m.tags = vec!["arbeit".to_string(), "familie".to_string()];
+
+        let matches = |query: RecordingQuery| query.filter().unwrap().matches(&m);
+        assert!(matches(RecordingQuery::default()));
+        assert!(matches(RecordingQuery {
+            created_after: Some("2026-03-01T11:00:00Z".to_string()),
+            created_before: Some("2026-03-02T00:00:00Z".to_string()),
+            primary_emotion: Some("Stress".to_string()),
+            tags: vec!["Arbeit".to_string()],
+            source: Some("recording".to_string()),
+            with_analyses: vec![AnalysisKind::Emotion],
+            without_analyses: vec![AnalysisKind::Gfk],
+            ..Default::default()
+        }));
+        // 12:00+01:00 is 11:00 UTC; the upper bound is exclusive
+        assert!(!matches(RecordingQuery {
+            created_before: Some("2026-03-01T11:00:00Z".to_string()),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingQuery {
+            tags: vec!["arbeit".to_string(), "urlaub".to_string()],
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingQuery {
+            provider: Some("ollama".to_string()),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingQuery {
+            source: Some("text".to_string()),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingQuery {
+            with_analyses: vec![AnalysisKind::Transcription],
+            ..Default::default()
+        }));
+
+        let invalid = RecordingQuery {
+            created_after: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.filter().is_err());
+    }
+
+    #[test]
+    fn test_cursor_roundtrip_checks_sort() {
+        let m = metadata("2026-03-01T12:00:00Z");
+        let cursor = PageCursor::after(RecordingSort::LongestFirst, &m);
+        let mut query = RecordingQuery {
+            sort: RecordingSort::LongestFirst,
+            cursor: Some(cursor.encode()),
+            ..Default::default()
+        };
+        assert_eq!(query.page_cursor().unwrap(), Some(cursor));
+
+        query.sort = RecordingSort::NewestFirst;
+        assert!(query.page_cursor().is_err());
+        query.cursor = Some("not a cursor".to_string());
+        assert!(query.page_cursor().is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Retention & Quota
+//!
+//! Decides which recordings the automatic cleanup removes. Three limits apply,
+//! each evicting the oldest recordings first:
+//!
+//! 1. Age: recordings older than `retentionDays` (0 = no limit)
+//! 2. Count: more than `maxRecordings`
+//! 3. Quota: more than `maxUserStorageMb` on disk, audio and sidecars (0 = no limit)
+//!
+//! Pinned recordings are never evicted but still count toward the limits.
+//! The trash counts toward the quota as well; the cleanup purges its oldest
+//! entries before evicting recordings (see `cleanup_old_recordings`).
+//! The same plan backs the dry-run preview and the actual cleanup.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::StorageConfig;
+
+/// Limits applied by the cleanup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub max_recordings: usize,
+    pub quota_bytes: Option<u64>,
+    pub max_age_days: Option<u32>,
+}
+
+impl From<&StorageConfig> for RetentionPolicy {
+    fn from(config: &StorageConfig) -> Self {
+        Self {
+            max_recordings: config.max_recordings,
+            quota_bytes: (config.max_user_storage_mb > 0)
+                .then(|| config.max_user_storage_mb as u64 * 1024 * 1024),
+            max_age_days: (config.retention_days > 0).then_some(config.retention_days),
+        }
+    }
+}
+
+/// Catalog row the plan works on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionRow {
+    pub id: String,
+    pub base_name: String,
+    pub created_at: String,
+    pub pinned: bool,
+}
+
+/// Which limit a recording is evicted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvictionReason {
+    Expired,
+    OverCount,
+    OverQuota,
+}
+
+/// A recording the cleanup would remove
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedEviction {
+    pub id: String,
+    pub base_name: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+    pub reason: EvictionReason,
+}
+
+/// What a cleanup removes and what is left afterwards
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupPlan {
+    /// Oldest first
+    pub evictions: Vec<PlannedEviction>,
+    pub freed_bytes: u64,
+    pub remaining_count: usize,
+    pub remaining_bytes: u64,
+    /// Pinned recordings (never evicted)
+    pub pinned_count: usize,
+}
+
+/// Plan a cleanup of `rows` (oldest first) with their on-disk size
+pub fn plan(
+    rows: Vec<RetentionRow>,
+    size_of: impl Fn(&RetentionRow) -> u64,
+    policy: RetentionPolicy,
+    now: DateTime<Utc>,
+) -> CleanupPlan {
+    let rows: Vec<(RetentionRow, u64)> = rows
+        .into_iter()
+        .map(|row| {
+            let size = size_of(&row);
+            (row, size)
+        })
+        .collect();
+
+    let mut remaining_count = rows.len();
+    let mut remaining_bytes: u64 = rows.iter().map(|(_, size)| size).sum();
+    let pinned_count = rows.iter().filter(|(row, _)| row.pinned).count();
+    let cutoff = policy
+        .max_age_days
+        .map(|days| now - Duration::days(i64::from(days)));
+
+    let mut evictions = Vec::new();
+    for (row, size) in rows.into_iter().filter(|(row, _)| !row.pinned) {
+        // Unparseable timestamps never count as expired
+        let expired = cutoff.is_some_and(|cutoff| {
+            DateTime::parse_from_rfc3339(&row.created_at).is_ok_and(|created| created < cutoff)
+        });
+        let reason = if expired {
+            EvictionReason::Expired
+        } else if remaining_count > policy.max_recordings {
+            EvictionReason::OverCount
+        } else if policy
+            .quota_bytes
+            .is_some_and(|quota| remaining_bytes > quota)
+        {
+            EvictionReason::OverQuota
+        } else {
+            continue;
+        };
+
+        remaining_count -= 1;
+        remaining_bytes -= size;
+        evictions.push(PlannedEviction {
+            id: row.id,
+            base_name: row.base_name,
+            created_at: row.created_at,
+            size_bytes: size,
+            reason,
+        });
+    }
+
+    CleanupPlan {
+        freed_bytes: evictions.iter().map(|e| e.size_bytes).sum(),
+        evictions,
+        remaining_count,
+        remaining_bytes,
+        pinned_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(day: u32, pinned: bool) -> RetentionRow {
+        RetentionRow {
+            id: format!("id-{}", day),
+            base_name: format!("2026-03-{:02}_10-00-00_id", day),
+            created_at: format!("2026-03-{:02}T10:00:00+00:00", day),
+            pinned,
+        }
+    }
+
+    fn evicted(plan: &CleanupPlan) -> Vec<(&str, EvictionReason)> {
+        plan.evictions
+            .iter()
+            .map(|e| (e.id.as_str(), e.reason))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_applies_limits_oldest_first_and_keeps_pinned() {
+        let now = DateTime::parse_from_rfc3339("2026-03-20T12:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        // Day 1 is pinned: old enough to expire and oldest, but kept
+        let rows = vec![
+            row(1, true),
+            row(2, false),
+            row(10, false),
+            row(11, false),
+            row(12, false),
+            row(13, false),
+        ];
+        let size = |_: &RetentionRow| 100;
+
+        let unlimited = RetentionPolicy {
+            max_recordings: 100,
+            quota_bytes: None,
+            max_age_days: None,
+        };
+        assert!(plan(rows.clone(), size, unlimited, now)
+            .evictions
+            .is_empty());
+
+        let policy = RetentionPolicy {
+            max_recordings: 4,
+            quota_bytes: Some(300),
+            max_age_days: Some(14),
+        };
+        let result = plan(rows, size, policy, now);
+        assert_eq!(
+            evicted(&result),
+            [
+                ("id-2", EvictionReason::Expired),
+                ("id-10", EvictionReason::OverCount),
+                ("id-11", EvictionReason::OverQuota),
+            ]
+        );
+        assert_eq!(result.freed_bytes, 300);
+        assert_eq!(result.remaining_count, 3);
+        assert_eq!(result.remaining_bytes, 300);
+        assert_eq!(result.pinned_count, 1);
+    }
+
+    #[test]
+    fn test_pinned_recordings_can_exceed_limits() {
+        let now = Utc::now();
+        let rows = vec![row(1, true), row(2, true), row(3, false)];
+        let policy = RetentionPolicy {
+            max_recordings: 1,
+            quota_bytes: Some(1),
+            max_age_days: None,
+        };
+        let result = plan(rows, |_| 10, policy, now);
+        assert_eq!(evicted(&result), [("id-3", EvictionReason::OverCount)]);
+        assert_eq!(result.remaining_count, 2);
+    }
+}
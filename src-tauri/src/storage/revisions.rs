@@ -0,0 +1,353 @@
+//! Transcription Revisions
+//!
+//! Every transcription of a recording is kept as a numbered revision, so
+//! re-running it through another model doesn't lose the previous text. One
+//! revision is active and mirrored into `transcription` / `textFilter`, which
+//! search, analysis and the frontend keep reading.
+//!
+//! Word-level diffs between revisions use a longest common subsequence over
+//! whitespace-separated words.
+
+use serde::{Deserialize, Serialize};
+
+use super::{RecordingMetadata, TextFilterMeta, TranscriptionMeta};
+
+/// Upper bound for the LCS table (words of one revision × words of the other,
+/// after removing the common prefix and suffix) - about 16 MB
+pub const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// One transcription of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionRevision {
+    /// Revision number, starting at 1 in order of creation
+    pub revision: u32,
+    pub created_at: String,
+    pub provider: String,
+    pub model: String,
+    pub language: String,
+    /// Text as returned by the transcription engine
+    pub text: String,
+    /// Text after removing filler words, stutters and hallucinations
+    pub filtered_text: String,
+    pub processing_time_ms: u64,
+}
+
+/// A new transcription to add as revision
+#[derive(Debug, Clone)]
+pub struct NewTranscriptionRevision {
+    pub provider: String,
+    pub model: String,
+    pub language: String,
+    pub text: String,
+    pub filtered_text: String,
+    pub processing_time_ms: u64,
+}
+
+/// All revisions of a recording and the active one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptionHistory {
+    pub active_revision: Option<u32>,
+    pub revisions: Vec<TranscriptionRevision>,
+}
+
+impl From<&RecordingMetadata> for TranscriptionHistory {
+    fn from(metadata: &RecordingMetadata) -> Self {
+        Self {
+            active_revision: metadata.active_revision,
+            revisions: metadata.transcription_revisions.clone(),
+        }
+    }
+}
+
+/// Kind of a diff segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffKind {
+    /// In both revisions
+    Equal,
+    /// Only in the older (`from`) revision
+    Removed,
+    /// Only in the newer (`to`) revision
+    Inserted,
+}
+
+/// Consecutive words of the same kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSegment {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// Record the current transcription as revision 1 if the recording has no
+/// revisions yet (recordings saved by the frontend carry only the transcription)
+pub fn seed(metadata: &mut RecordingMetadata) {
+    if !metadata.transcription_revisions.is_empty() {
+        return;
+    }
+    let Some(transcription) = &metadata.transcription else {
+        return;
+    };
+
+    let text = metadata
+        .text_filter
+        .as_ref()
+        .map(|f| f.original_text.clone())
+        .unwrap_or_else(|| transcription.text.clone());
+    metadata
+        .transcription_revisions
+        .push(TranscriptionRevision {
+            revision: 1,
+            created_at: metadata.created_at.clone(),
+            provider: transcription.provider.clone(),
+            model: transcription.model.clone(),
+            language: transcription.language.clone(),
+            text,
+            filtered_text: transcription.text.clone(),
+            processing_time_ms: transcription.processing_time_ms,
+        });
+    metadata.active_revision = Some(1);
+}
+
+/// Add a transcription as the next revision and make it active
+pub fn add(
+    metadata: &mut RecordingMetadata,
+    new: NewTranscriptionRevision,
+    created_at: String,
+) -> TranscriptionRevision {
+    seed(metadata);
+
+    let revision = TranscriptionRevision {
+        revision: metadata
+            .transcription_revisions
+            .iter()
+            .map(|r| r.revision)
+            .max()
+            .unwrap_or(0)
+            + 1,
+        created_at,
+        provider: new.provider,
+        model: new.model,
+        language: new.language,
+        text: new.text,
+        filtered_text: new.filtered_text,
+        processing_time_ms: new.processing_time_ms,
+    };
+    metadata.transcription_revisions.push(revision.clone());
+    activate(metadata, revision.revision).expect("revision was just added");
+    revision
+}
+
+/// Look up a revision by number
+pub fn get(metadata: &RecordingMetadata, revision: u32) -> Result<&TranscriptionRevision, String> {
+    metadata
+        .transcription_revisions
+        .iter()
+        .find(|r| r.revision == revision)
+        .ok_or_else(|| format!("Transcription revision not found: {}", revision))
+}
+
+/// Make a revision active, replacing `transcription` and `textFilter` with it
+pub fn activate(metadata: &mut RecordingMetadata, revision: u32) -> Result<(), String> {
+    let active = get(metadata, revision)?.clone();
+
+    metadata.text_filter = (active.text != active.filtered_text).then(|| TextFilterMeta {
+        filler_words_removed: active
+            .text
+            .split_whitespace()
+            .count()
+            .saturating_sub(active.filtered_text.split_whitespace().count()),
+        hallucinations_detected: active.filtered_text.is_empty(),
+        original_text: active.text,
+        filtered_text: active.filtered_text.clone(),
+    });
+    metadata.transcription = Some(TranscriptionMeta {
+        text: active.filtered_text,
+        provider: active.provider,
+        model: active.model,
+        language: active.language,
+        processing_time_ms: active.processing_time_ms,
+    });
+    metadata.active_revision = Some(revision);
+    Ok(())
+}
+
+/// Append a word to the diff, merging it with the previous segment of the same kind
+fn push_word(segments: &mut Vec<DiffSegment>, kind: DiffKind, word: &str) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind => {
+            last.text.push(' ');
+            last.text.push_str(word);
+        }
+        _ => segments.push(DiffSegment {
+            kind,
+            text: word.to_string(),
+        }),
+    }
+}
+
+/// Word-level diff from `from` to `to`.
+/// Removed words are listed before the words that replace them.
+pub fn word_diff(from: &str, to: &str) -> Result<Vec<DiffSegment>, String> {
+    let a: Vec<&str> = from.split_whitespace().collect();
+    let b: Vec<&str> = to.split_whitespace().collect();
+
+    // Common prefix and suffix need no table
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let width = b_mid.len() + 1;
+    if (a_mid.len() + 1).saturating_mul(width) > MAX_DIFF_CELLS {
+        return Err("Revisions differ too much to compare word by word".to_string());
+    }
+
+    // lcs[i * width + j] = LCS length of a_mid[i..] and b_mid[j..]
+    let mut lcs = vec![0u32; (a_mid.len() + 1) * width];
+    for i in (0..a_mid.len()).rev() {
+        for j in (0..b_mid.len()).rev() {
+            lcs[i * width + j] = if a_mid[i] == b_mid[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut segments = Vec::new();
+    for word in &a[..prefix] {
+        push_word(&mut segments, DiffKind::Equal, word);
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < a_mid.len() && j < b_mid.len() {
+        if a_mid[i] == b_mid[j] {
+            push_word(&mut segments, DiffKind::Equal, a_mid[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            push_word(&mut segments, DiffKind::Removed, a_mid[i]);
+            i += 1;
+        } else {
+            push_word(&mut segments, DiffKind::Inserted, b_mid[j]);
+            j += 1;
+        }
+    }
+    for word in &a_mid[i..] {
+        push_word(&mut segments, DiffKind::Removed, word);
+    }
+    for word in &b_mid[j..] {
+        push_word(&mut segments, DiffKind::Inserted, word);
+    }
+
+    for word in &a[a.len() - suffix..] {
+        push_word(&mut segments, DiffKind::Equal, word);
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::AudioValidationMeta;
+
+    fn segment(kind: DiffKind, text: &str) -> DiffSegment {
+        DiffSegment {
+            kind,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let diff = word_diff(
+            "ich war heute im Bühro und dann zuhause",
+            "ich war heute im Büro und danach zuhause",
+        )
+        .unwrap();
+        assert_eq!(
+            diff,
+            [
+                segment(DiffKind::Equal, "ich war heute im"),
+                segment(DiffKind::Removed, "Bühro"),
+                segment(DiffKind::Inserted, "Büro"),
+                segment(DiffKind::Equal, "und"),
+                segment(DiffKind::Removed, "dann"),
+                segment(DiffKind::Inserted, "danach"),
+                segment(DiffKind::Equal, "zuhause"),
+            ]
+        );
+
+        assert_eq!(
+            word_diff("a b", "a b").unwrap(),
+            [segment(DiffKind::Equal, "a b")]
+        );
+        assert_eq!(
+            word_diff("", "neu").unwrap(),
+            [segment(DiffKind::Inserted, "neu")]
+        );
+        assert!(word_diff("", "").unwrap().is_empty());
+
+        let long = "wort ".repeat(3000);
+        assert!(word_diff(&long, &"anders ".repeat(3000)).is_err());
+    }
+
+    #[test]
+    fn test_add_and_activate_revisions() {
+        let mut m = RecordingMetadata::new(
+            1000,
+            16000,
+            32044,
+            AudioValidationMeta {
+                rms_energy: 0.05,
+                duration_ms: 1000,
+                sample_count: 16000,
+                passed: true,
+            },
+            "whisper-cpp".to_string(),
+            "german-turbo".to_string(),
+        );
+        m.created_at = "2026-03-01T10:00:00+00:00".to_string();
+        m.transcription = Some(TranscriptionMeta {
+            text: "erster Text".to_string(),
+            provider: "whisper-cpp".to_string(),
+            model: "german-turbo".to_string(),
+            language: "de".to_string(),
+            processing_time_ms: 900,
+        });
+
+        let added = add(
+            &mut m,
+            NewTranscriptionRevision {
+                provider: "mlx-whisper".to_string(),
+                model: "large-v3".to_string(),
+                language: "de".to_string(),
+                text: "äh zweiter Text".to_string(),
+                filtered_text: "zweiter Text".to_string(),
+                processing_time_ms: 1500,
+            },
+            "2026-03-02T10:00:00+00:00".to_string(),
+        );
+
+        // The original transcription was kept as revision 1
+        assert_eq!(added.revision, 2);
+        assert_eq!(m.transcription_revisions[0].filtered_text, "erster Text");
+        assert_eq!(m.transcription_revisions[0].created_at, m.created_at);
+        assert_eq!(m.active_revision, Some(2));
+        assert_eq!(m.transcription.as_ref().unwrap().model, "large-v3");
+        assert_eq!(m.text_filter.as_ref().unwrap().filler_words_removed, 1);
+
+        activate(&mut m, 1).unwrap();
+        assert_eq!(m.transcription.as_ref().unwrap().text, "erster Text");
+        assert!(m.text_filter.is_none());
+        assert!(activate(&mut m, 3).is_err());
+        assert_eq!(m.active_revision, Some(1));
+    }
+}
@@ -0,0 +1,274 @@
+//! Metadata Schema Versioning
+//!
+//! Every sidecar carries a `schemaVersion`. Older sidecars are upgraded on read
+//! by a chain of migration steps operating on the raw JSON, so the Rust structs
+//! only ever have to understand the current layout.
+//!
+//! Sidecars written before versioning was introduced have no `schemaVersion`
+//! and are treated as version 1.
+//!
+//! Adding a migration: bump [`CURRENT_SCHEMA_VERSION`], append a step to
+//! [`MIGRATIONS`] that upgrades from the previous version, and add a test.
+
+use serde_json::{Map, Value};
+
+use super::RecordingMetadata;
+
+/// Schema version written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 4;
+
+/// Version of sidecars that predate the `schemaVersion` field
+const UNVERSIONED_SCHEMA_VERSION: u32 = 1;
+
+/// A migration step upgrading a sidecar from version `n` to `n + 1`
+type MigrationStep = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// Migration chain, indexed by source version (first entry upgrades v1 → v2)
+const MIGRATIONS: &[MigrationStep] = &[migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// Schema version of a raw sidecar
+pub fn schema_version_of(value: &Value) -> u32 {
+    value
+        .get("schemaVersion")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(UNVERSIONED_SCHEMA_VERSION)
+}
+
+/// Why a sidecar could not be read
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// Written by a newer app version; must be left untouched
+    TooNew(u32),
+    /// Not valid JSON, migration failed, or does not match the current layout
+    Invalid(String),
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::TooNew(version) => write!(
+                f,
+                "Schema version {} is newer than supported version {}",
+                version, CURRENT_SCHEMA_VERSION
+            ),
+            SchemaError::Invalid(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Upgrade a raw sidecar to the current schema.
+/// Returns the upgraded JSON and whether any migration step ran.
+pub fn upgrade(mut value: Value) -> Result<(Value, bool), SchemaError> {
+    let version = schema_version_of(&value);
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(SchemaError::TooNew(version));
+    }
+    if version == 0 {
+        return Err(SchemaError::Invalid("Invalid schema version 0".to_string()));
+    }
+
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| SchemaError::Invalid("Metadata is not a JSON object".to_string()))?;
+
+    for from in version..CURRENT_SCHEMA_VERSION {
+        let step = MIGRATIONS[(from - 1) as usize];
+        step(object).map_err(|e| {
+            SchemaError::Invalid(format!("Migration v{} -> v{} failed: {}", from, from + 1, e))
+        })?;
+        object.insert("schemaVersion".to_string(), Value::from(from + 1));
+    }
+
+    Ok((value, version < CURRENT_SCHEMA_VERSION))
+}
+
+/// Parse a sidecar of any supported version into current metadata.
+/// Returns the metadata and whether it was upgraded from an older version.
+pub fn parse_metadata(bytes: &[u8]) -> Result<(RecordingMetadata, bool), SchemaError> {
+    let value: Value = serde_json::from_slice(bytes)
+        .map_err(|e| SchemaError::Invalid(format!("Invalid JSON: {}", e)))?;
+    let (value, upgraded) = upgrade(value)?;
+    let metadata = serde_json::from_value(value)
+        .map_err(|e| SchemaError::Invalid(format!("Invalid metadata: {}", e)))?;
+    Ok((metadata, upgraded))
+}
+
+/// v1 → v2: Fill fields that early releases did not always write.
+///
+/// - `appVersion` missing → `"unknown"`
+/// - `analysisResult` without `fallacies` / `enrichment` → empty values
+fn migrate_v1_to_v2(object: &mut Map<String, Value>) -> Result<(), String> {
+    object
+        .entry("appVersion")
+        .or_insert_with(|| Value::from("unknown"));
+
+    if let Some(analysis) = object.get_mut("analysisResult") {
+        if let Some(analysis) = analysis.as_object_mut() {
+            analysis
+                .entry("fallacies")
+                .or_insert_with(|| Value::Array(Vec::new()));
+            analysis
+                .entry("enrichment")
+                .or_insert_with(|| Value::from(""));
+        } else if !analysis.is_null() {
+            return Err("analysisResult is not an object".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// v2 → v3: User annotations (`title`, `notes`, `tags`, `editedAt`).
+///
+/// All are optional, so existing sidecars only gain an empty tag list. The
+/// version bump keeps older app versions from rewriting annotated sidecars
+/// and dropping the user's edits.
+fn migrate_v2_to_v3(object: &mut Map<String, Value>) -> Result<(), String> {
+    match object.entry("tags").or_insert_with(|| Value::Array(Vec::new())) {
+        Value::Array(_) => Ok(()),
+        _ => Err("tags is not an array".to_string()),
+    }
+}
+
+/// v3 → v4: Transcription revisions (`transcriptionRevisions`, `activeRevision`).
+///
+/// An existing transcription becomes revision 1, dated with the recording
+/// and with the unfiltered text from `textFilter` where one was stored.
+fn migrate_v3_to_v4(object: &mut Map<String, Value>) -> Result<(), String> {
+    if object.contains_key("transcriptionRevisions") {
+        return Ok(());
+    }
+
+    let field = |value: Option<&Value>| value.cloned().unwrap_or(Value::Null);
+    let revisions = match object.get("transcription") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Object(transcription)) => {
+            let filtered_text = field(transcription.get("text"));
+            let text = object
+                .get("textFilter")
+                .and_then(|f| f.get("originalText"))
+                .cloned()
+                .unwrap_or_else(|| filtered_text.clone());
+            let mut revision = Map::new();
+            revision.insert("revision".to_string(), Value::from(1));
+            revision.insert("createdAt".to_string(), field(object.get("createdAt")));
+            for key in ["provider", "model", "language", "processingTimeMs"] {
+                revision.insert(key.to_string(), field(transcription.get(key)));
+            }
+            revision.insert("text".to_string(), text);
+            revision.insert("filteredText".to_string(), filtered_text);
+            vec![Value::Object(revision)]
+        }
+        Some(_) => return Err("transcription is not an object".to_string()),
+    };
+
+    if !revisions.is_empty() {
+        object.insert("activeRevision".to_string(), Value::from(1));
+    }
+    object.insert("transcriptionRevisions".to_string(), Value::Array(revisions));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn v1_sidecar() -> Value {
+        json!({
+            "id": "6f1c1a52-0000-4000-8000-000000000000",
+            "createdAt": "2025-06-01T08:00:00+00:00",
+            "durationMs": 1000,
+            "sampleRate": 16000,
+            "fileSize": 32044,
+            "audioValidation": {
+                "rmsEnergy": 0.05,
+                "durationMs": 1000,
+                "sampleCount": 16000,
+                "passed": true
+            },
+            "vadStats": null,
+            "transcription": null,
+            "textFilter": null,
+            "provider": "whisper-cpp",
+            "model": "german-turbo",
+            "analysisResult": { "emotion": null }
+        })
+    }
+
+    #[test]
+    fn test_unversioned_sidecar_upgraded() {
+        let bytes = serde_json::to_vec(&v1_sidecar()).unwrap();
+        let (metadata, upgraded) = parse_metadata(&bytes).unwrap();
+
+        assert!(upgraded);
+        assert_eq!(metadata.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(metadata.app_version, "unknown");
+        let analysis = metadata.analysis_result.unwrap();
+        assert!(analysis.fallacies.is_empty());
+        assert_eq!(analysis.enrichment, "");
+        assert!(metadata.tags.is_empty());
+        assert_eq!(metadata.title, None);
+        assert!(metadata.transcription_revisions.is_empty());
+    }
+
+    #[test]
+    fn test_transcription_becomes_first_revision() {
+        let mut sidecar = v1_sidecar();
+        sidecar["appVersion"] = json!("1.1.4");
+        sidecar["analysisResult"] = Value::Null;
+        sidecar["schemaVersion"] = json!(3);
+        sidecar["transcription"] = json!({
+            "text": "Hallo Welt",
+            "provider": "whisper-cpp",
+            "model": "german-turbo",
+            "language": "de",
+            "processingTimeMs": 800
+        });
+        sidecar["textFilter"] = json!({
+            "originalText": "Hallo äh Welt",
+            "filteredText": "Hallo Welt",
+            "fillerWordsRemoved": 1,
+            "hallucinationsDetected": false
+        });
+
+        let (metadata, upgraded) = parse_metadata(&serde_json::to_vec(&sidecar).unwrap()).unwrap();
+        assert!(upgraded);
+        assert_eq!(metadata.active_revision, Some(1));
+        let revision = &metadata.transcription_revisions[0];
+        assert_eq!(revision.text, "Hallo äh Welt");
+        assert_eq!(revision.filtered_text, "Hallo Welt");
+        assert_eq!(revision.created_at, "2025-06-01T08:00:00+00:00");
+        assert_eq!(revision.processing_time_ms, 800);
+    }
+
+    #[test]
+    fn test_current_sidecar_not_upgraded() {
+        let (value, _) = upgrade(v1_sidecar()).unwrap();
+        let bytes = serde_json::to_vec(&value).unwrap();
+        let (_, upgraded) = parse_metadata(&bytes).unwrap();
+        assert!(!upgraded);
+    }
+
+    #[test]
+    fn test_newer_and_invalid_sidecars_rejected() {
+        let mut newer = v1_sidecar();
+        newer["schemaVersion"] = json!(CURRENT_SCHEMA_VERSION + 1);
+        assert_eq!(
+            upgrade(newer).unwrap_err(),
+            SchemaError::TooNew(CURRENT_SCHEMA_VERSION + 1)
+        );
+
+        assert!(matches!(parse_metadata(b"{ truncated"), Err(SchemaError::Invalid(_))));
+        assert!(matches!(parse_metadata(b"[]"), Err(SchemaError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_migration_chain_covers_all_versions() {
+        assert_eq!(
+            MIGRATIONS.len() as u32,
+            CURRENT_SCHEMA_VERSION - UNVERSIONED_SCHEMA_VERSION
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Full-Text Search
+//!
+//! Text analysis for the catalog's FTS5 index: German stemming with umlaut
+//! folding, plus snippet extraction with highlighted matches.
+//!
+//! The index only stores normalized terms, never the original text. Snippets
+//! are cut from the (decrypted) metadata at query time.
+
+use once_cell::sync::Lazy;
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use super::RecordingMetadata;
+
+/// Maximum query length in characters
+pub const MAX_QUERY_LENGTH: usize = 500;
+
+/// Default and maximum number of search results
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+pub const MAX_SEARCH_LIMIT: usize = 200;
+
+/// Words shown around the first match in a snippet
+const SNIPPET_CONTEXT_WORDS: usize = 8;
+
+/// Maximum words per snippet
+const SNIPPET_MAX_WORDS: usize = 24;
+
+/// Snowball German stemmer (shared, stateless)
+static GERMAN_STEMMER: Lazy<Stemmer> = Lazy::new(|| Stemmer::create(Algorithm::German));
+
+/// Searchable recording fields, in index column order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchField {
+    Transcription,
+    OriginalText,
+    Enrichment,
+    FallacyQuote,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 4] = [
+        SearchField::Transcription,
+        SearchField::OriginalText,
+        SearchField::Enrichment,
+        SearchField::FallacyQuote,
+    ];
+
+    /// bm25 column weight (matches in the transcription rank highest)
+    pub fn weight(self) -> f64 {
+        match self {
+            SearchField::Transcription => 10.0,
+            SearchField::OriginalText => 4.0,
+            SearchField::FallacyQuote => 3.0,
+            SearchField::Enrichment => 2.0,
+        }
+    }
+
+    /// Text of this field in a recording (empty if absent)
+    pub fn text(self, metadata: &RecordingMetadata) -> String {
+        match self {
+            SearchField::Transcription => metadata
+                .transcription
+                .as_ref()
+                .map(|t| t.text.clone())
+                .unwrap_or_default(),
+            SearchField::OriginalText => metadata
+                .text_filter
+                .as_ref()
+                .map(|f| f.original_text.clone())
+                .unwrap_or_default(),
+            SearchField::Enrichment => metadata
+                .analysis_result
+                .as_ref()
+                .map(|a| a.enrichment.clone())
+                .unwrap_or_default(),
+            SearchField::FallacyQuote => metadata
+                .analysis_result
+                .as_ref()
+                .map(|a| {
+                    a.fallacies
+                        .iter()
+                        .map(|f| f.quote.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Part of a snippet; highlighted parts matched the query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+/// Excerpt of one field around the matched terms
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSnippet {
+    pub field: SearchField,
+    pub parts: Vec<SnippetPart>,
+}
+
+/// A recording matching a search query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub recording_id: String,
+    pub created_at: String,
+    /// Relevance (higher is better)
+    pub score: f64,
+    pub snippets: Vec<SearchSnippet>,
+}
+
+/// A word in a text with its byte range and index term
+struct Token {
+    start: usize,
+    end: usize,
+    term: String,
+}
+
+/// Lowercase, strip diacritics (ä → a, é → e) and expand ß
+fn fold(word: &str) -> String {
+    let mut folded = String::with_capacity(word.len());
+    for c in word
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+    {
+        match c {
+            'ß' => folded.push_str("ss"),
+            c => folded.push(c),
+        }
+    }
+    folded
+}
+
+/// Index term for a single word: folded and stemmed
+fn term(word: &str) -> String {
+    GERMAN_STEMMER.stem(&fold(word)).into_owned()
+}
+
+/// Split text into words (runs of alphanumeric characters and their diacritics)
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, c.is_alphanumeric() || is_combining_mark(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let term = term(&text[s..i]);
+                if !term.is_empty() {
+                    tokens.push(Token { start: s, end: i, term });
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+/// Index terms of a text, in order (space-separated for the FTS column)
+pub fn index_terms(text: &str) -> Vec<String> {
+    tokenize(text).into_iter().map(|t| t.term).collect()
+}
+
+/// Distinct index terms of a search query
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    index_terms(query)
+        .into_iter()
+        .filter(|t| seen.insert(t.clone()))
+        .collect()
+}
+
+/// Cut a snippet around the first matched term.
+/// Returns `None` if no word of `text` matches `terms`.
+pub fn build_snippet(text: &str, terms: &HashSet<String>) -> Option<Vec<SnippetPart>> {
+    let tokens = tokenize(text);
+    let first = tokens.iter().position(|t| terms.contains(&t.term))?;
+
+    let from = first.saturating_sub(SNIPPET_CONTEXT_WORDS);
+    let to = (from + SNIPPET_MAX_WORDS).min(tokens.len());
+    let window = &tokens[from..to];
+
+    let start = if from == 0 { 0 } else { window[0].start };
+    let end = if to == tokens.len() {
+        text.len()
+    } else {
+        window[window.len() - 1].end
+    };
+
+    let mut parts = Vec::new();
+    let mut plain = String::new();
+    if start > 0 {
+        plain.push('…');
+    }
+
+    let mut cursor = start;
+    for token in window.iter().filter(|t| terms.contains(&t.term)) {
+        plain.push_str(&text[cursor..token.start]);
+        if !plain.is_empty() {
+            parts.push(SnippetPart {
+                text: std::mem::take(&mut plain),
+                highlighted: false,
+            });
+        }
+        parts.push(SnippetPart {
+            text: text[token.start..token.end].to_string(),
+            highlighted: true,
+        });
+        cursor = token.end;
+    }
+
+    plain.push_str(text[cursor..end].trim_end());
+    if end < text.len() {
+        plain.push('…');
+    }
+    if !plain.is_empty() {
+        parts.push(SnippetPart {
+            text: plain,
+            highlighted: false,
+        });
+    }
+
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_umlaut_folding_and_stemming() {
+        assert_eq!(term("Häuser"), term("hauser"));
+        assert_eq!(term("Straße"), term("strasse"));
+        assert_eq!(term("Gespräche"), term("Gespräch"));
+        assert_eq!(term("arbeiten"), term("Arbeit"));
+    }
+
+    #[test]
+    fn test_query_terms_deduplicated() {
+        assert_eq!(query_terms("Stress, stress! STRESS").len(), 1);
+        assert!(query_terms("  ...  ").is_empty());
+    }
+
+    #[test]
+    fn test_snippet_highlights_matches() {
+        let terms: HashSet<String> = query_terms("gespräch").into_iter().collect();
+        let parts = build_snippet("Das Gespräch mit Anna war gut.", &terms).unwrap();
+
+        assert_eq!(
+            parts,
+            vec![
+                SnippetPart { text: "Das ".into(), highlighted: false },
+                SnippetPart { text: "Gespräch".into(), highlighted: true },
+                SnippetPart { text: " mit Anna war gut.".into(), highlighted: false },
+            ]
+        );
+        assert!(build_snippet("Nichts davon", &terms).is_none());
+    }
+
+    #[test]
+    fn test_snippet_truncates_long_text() {
+        let text = format!("{} Termin {}", "wort ".repeat(30), "ende ".repeat(30));
+        let terms: HashSet<String> = query_terms("Termin").into_iter().collect();
+        let parts = build_snippet(&text, &terms).unwrap();
+
+        assert!(parts.first().unwrap().text.starts_with('…'));
+        assert!(parts.last().unwrap().text.ends_with('…'));
+        assert_eq!(parts.iter().filter(|p| p.highlighted).count(), 1);
+    }
+}
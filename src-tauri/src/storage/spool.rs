@@ -0,0 +1,497 @@
+//! Recording Spool
+//!
+//! Native recordings are written to `.spool/` inside the storage directory
+//! while they are captured, so a crash loses at most the last second instead
+//! of the whole session. Each session is a manifest (`<id>.json`, written
+//! when the session starts and after each break) and a block file
+//! (`<id>.pcm`): one block per
+//! second of 16 kHz mono 16-bit audio,
+//! `sample count u32 | payload length u32 | payload` (little endian).
+//! With encryption at rest, each payload is sealed with the library's data
+//! key, bound to the session and block number.
+//!
+//! A finished session is read back and removed. Sessions left behind by a
+//! crash are listed as interrupted and can be recovered into the library or
+//! discarded.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::crypto::{self, DataKey, AAD_SPOOL};
+use super::{atomic, InputDeviceMeta, RecordingPause};
+
+/// Spool directory name (hidden, inside the storage directory)
+pub const SPOOL_DIR_NAME: &str = ".spool";
+
+/// Sample rate of spooled audio (the recorder's output rate)
+pub const SPOOL_SAMPLE_RATE: u32 = 16000;
+
+/// Samples per block (one second); at most this much is lost in a crash
+const BLOCK_SAMPLES: usize = SPOOL_SAMPLE_RATE as usize;
+
+/// Sessions with a live writer in this process (never interrupted)
+static ACTIVE_SESSIONS: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+fn set_active(id: &str, active: bool) {
+    let mut guard = ACTIVE_SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let sessions = guard.get_or_insert_with(HashSet::new);
+    if active {
+        sessions.insert(id.to_string());
+    } else {
+        sessions.remove(id);
+    }
+}
+
+fn is_active(id: &str) -> bool {
+    ACTIVE_SESSIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|sessions| sessions.contains(id))
+}
+
+fn spool_dir(storage_dir: &Path) -> PathBuf {
+    storage_dir.join(SPOOL_DIR_NAME)
+}
+
+/// Manifest of a spooled session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpoolManifest {
+    id: String,
+    started_at: String,
+    sample_rate: u32,
+    encrypted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_device: Option<InputDeviceMeta>,
+    /// Completed breaks (pause and resume)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pauses: Vec<RecordingPause>,
+}
+
+impl SpoolManifest {
+    fn write(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize spool manifest: {}", e))?;
+        atomic::write_file(path, content.as_bytes())
+    }
+}
+
+/// A recording session that ended without being stopped (crash, power loss)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptedRecording {
+    pub id: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_device: Option<InputDeviceMeta>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pauses: Vec<RecordingPause>,
+}
+
+/// Associated data of one block: session and position, so blocks cannot be
+/// swapped between or within sessions
+fn block_aad(id: &str, index: u32) -> Vec<u8> {
+    let mut aad = AAD_SPOOL.to_vec();
+    aad.extend_from_slice(format!(":{}:{}", id, index).as_bytes());
+    aad
+}
+
+/// Writes one recording session to the spool
+pub struct SpoolWriter {
+    id: String,
+    manifest: SpoolManifest,
+    pcm_path: PathBuf,
+    manifest_path: PathBuf,
+    file: File,
+    key: Option<DataKey>,
+    /// Samples of the block not written yet
+    pending: Vec<i16>,
+    blocks: u32,
+    sample_count: usize,
+}
+
+impl SpoolWriter {
+    /// Start a new session in the storage directory's spool
+    /// (`key`: seal the audio with the library's data key)
+    pub fn create(
+        storage_dir: &Path,
+        key: Option<DataKey>,
+        input_device: Option<InputDeviceMeta>,
+    ) -> Result<Self, String> {
+        let dir = spool_dir(storage_dir);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create spool directory: {}", e))?;
+
+        let manifest = SpoolManifest {
+            id: uuid::Uuid::new_v4().to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            sample_rate: SPOOL_SAMPLE_RATE,
+            encrypted: key.is_some(),
+            input_device,
+            pauses: Vec::new(),
+        };
+        let manifest_path = dir.join(format!("{}.json", manifest.id));
+        let pcm_path = dir.join(format!("{}.pcm", manifest.id));
+
+        manifest.write(&manifest_path)?;
+        let file =
+            File::create(&pcm_path).map_err(|e| format!("Failed to create spool file: {}", e))?;
+
+        set_active(&manifest.id, true);
+        tracing::debug!(id = %manifest.id, encrypted = key.is_some(), "Storage: Recording spool started");
+        Ok(Self {
+            id: manifest.id.clone(),
+            manifest,
+            pcm_path,
+            manifest_path,
+            file,
+            key,
+            pending: Vec::with_capacity(BLOCK_SAMPLES),
+            blocks: 0,
+            sample_count: 0,
+        })
+    }
+
+    /// Append samples (16 kHz mono, -1.0..1.0)
+    pub fn append(&mut self, samples: &[f32]) -> Result<(), String> {
+        for &sample in samples {
+            self.pending.push(to_pcm16(sample));
+            if self.pending.len() == BLOCK_SAMPLES {
+                self.write_block()?;
+            }
+        }
+        self.sample_count += samples.len();
+        Ok(())
+    }
+
+    /// Samples appended so far
+    pub fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// Record a completed break, so a recovered session keeps it
+    pub fn add_pause(&mut self, pause: RecordingPause) -> Result<(), String> {
+        self.manifest.pauses.push(pause);
+        self.manifest.write(&self.manifest_path)
+    }
+
+    fn write_block(&mut self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let plain: Vec<u8> = self.pending.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let payload = match &self.key {
+            Some(key) => crypto::encrypt(key, &plain, &block_aad(&self.id, self.blocks))?,
+            None => plain,
+        };
+
+        let mut block = Vec::with_capacity(8 + payload.len());
+        block.extend_from_slice(&(self.pending.len() as u32).to_le_bytes());
+        block.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        block.extend_from_slice(&payload);
+        self.file
+            .write_all(&block)
+            .map_err(|e| format!("Failed to write spool file: {}", e))?;
+
+        self.pending.clear();
+        self.blocks += 1;
+        Ok(())
+    }
+
+    /// Finish the session: read the samples back and remove the spool files.
+    /// On error the files are kept, so the session can still be recovered.
+    pub fn into_samples(mut self) -> Result<Vec<f32>, String> {
+        self.write_block()?;
+        let samples = read_blocks(&self.pcm_path, &self.id, self.key.as_ref())?
+            .into_iter()
+            .map(from_pcm16)
+            .collect();
+        remove_session_files(&self.pcm_path, &self.manifest_path);
+        Ok(samples)
+    }
+}
+
+impl Drop for SpoolWriter {
+    fn drop(&mut self) {
+        // Files still present now belong to an interrupted session
+        if let Err(e) = self.write_block() {
+            tracing::warn!(id = %self.id, error = %e, "Storage: Failed to write last spool block");
+        }
+        set_active(&self.id, false);
+    }
+}
+
+/// Same conversion as the WAV export of recordings
+fn to_pcm16(sample: f32) -> i16 {
+    let s = sample.clamp(-1.0, 1.0);
+    if s < 0.0 {
+        (s * 32768.0) as i16
+    } else {
+        (s * 32767.0) as i16
+    }
+}
+
+fn from_pcm16(sample: i16) -> f32 {
+    if sample < 0 {
+        sample as f32 / 32768.0
+    } else {
+        sample as f32 / 32767.0
+    }
+}
+
+/// Blocks of a spool file as `(sample count, payload)`. A block cut off by a
+/// crash ends the file.
+fn blocks(bytes: &[u8]) -> Vec<(usize, &[u8])> {
+    let mut blocks = Vec::new();
+    let mut rest = bytes;
+    while rest.len() >= 8 {
+        let samples = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let Some(payload) = rest.get(8..8 + len) else {
+            break;
+        };
+        blocks.push((samples, payload));
+        rest = &rest[8 + len..];
+    }
+    blocks
+}
+
+fn read_blocks(path: &Path, id: &str, key: Option<&DataKey>) -> Result<Vec<i16>, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read spool file: {}", e))?;
+    let mut samples = Vec::new();
+    for (index, (_, payload)) in blocks(&bytes).into_iter().enumerate() {
+        let plain = match key {
+            Some(key) => crypto::decrypt(key, payload, &block_aad(id, index as u32))?,
+            None => payload.to_vec(),
+        };
+        samples.extend(
+            plain
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]])),
+        );
+    }
+    Ok(samples)
+}
+
+fn remove_session_files(pcm_path: &Path, manifest_path: &Path) {
+    for path in [pcm_path, manifest_path] {
+        if let Err(e) = std::fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(path = %path.display(), error = %e, "Storage: Failed to remove spool file");
+            }
+        }
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<SpoolManifest, String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+}
+
+/// Security: the id comes from the frontend - it must be a spool session id
+fn session_paths(storage_dir: &Path, id: &str) -> Result<(PathBuf, PathBuf), String> {
+    if uuid::Uuid::parse_str(id).is_err() {
+        return Err(format!("Invalid recording session: {}", id));
+    }
+    let dir = spool_dir(storage_dir);
+    let manifest_path = dir.join(format!("{}.json", id));
+    if !manifest_path.exists() || is_active(id) {
+        return Err(format!("Interrupted recording not found: {}", id));
+    }
+    Ok((dir.join(format!("{}.pcm", id)), manifest_path))
+}
+
+fn duration_ms(samples: usize, sample_rate: u32) -> u64 {
+    samples as u64 * 1000 / sample_rate.max(1) as u64
+}
+
+/// Sessions left behind by a crash (oldest first)
+pub fn list_interrupted(storage_dir: &Path) -> Result<Vec<InterruptedRecording>, String> {
+    let dir = spool_dir(storage_dir);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut sessions = Vec::new();
+    for entry in std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read spool directory: {}", e))?
+        .flatten()
+    {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let manifest = match read_manifest(&path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Storage: Invalid spool manifest");
+                continue;
+            }
+        };
+        if is_active(&manifest.id) {
+            continue;
+        }
+
+        let bytes = std::fs::read(dir.join(format!("{}.pcm", manifest.id))).unwrap_or_default();
+        let samples: usize = blocks(&bytes).iter().map(|(samples, _)| samples).sum();
+        sessions.push(InterruptedRecording {
+            duration_ms: duration_ms(samples, manifest.sample_rate),
+            id: manifest.id,
+            started_at: manifest.started_at,
+            input_device: manifest.input_device,
+            pauses: manifest.pauses,
+        });
+    }
+
+    sessions.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(sessions)
+}
+
+/// Audio of an interrupted session as WAV, with its description
+pub(super) fn read_interrupted(
+    storage_dir: &Path,
+    id: &str,
+    key: Option<&DataKey>,
+) -> Result<(Vec<u8>, InterruptedRecording), String> {
+    let (pcm_path, manifest_path) = session_paths(storage_dir, id)?;
+    let manifest = read_manifest(&manifest_path)?;
+    let key = match (manifest.encrypted, key) {
+        (true, None) => return Err("Data key for the interrupted recording is missing".to_string()),
+        (true, key) => key,
+        (false, _) => None,
+    };
+    let samples = if pcm_path.exists() {
+        read_blocks(&pcm_path, &manifest.id, key)?
+    } else {
+        vec![]
+    };
+    if samples.is_empty() {
+        return Err("The interrupted recording contains no audio".to_string());
+    }
+
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: manifest.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut cursor, spec)
+        .map_err(|e| format!("Failed to write WAV: {}", e))?;
+    for &sample in &samples {
+        writer
+            .write_sample(sample)
+            .map_err(|e| format!("Failed to write WAV: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to write WAV: {}", e))?;
+
+    let session = InterruptedRecording {
+        duration_ms: duration_ms(samples.len(), manifest.sample_rate),
+        id: manifest.id,
+        started_at: manifest.started_at,
+        input_device: manifest.input_device,
+        pauses: manifest.pauses,
+    };
+    Ok((cursor.into_inner(), session))
+}
+
+/// Remove an interrupted session
+pub fn discard(storage_dir: &Path, id: &str) -> Result<(), String> {
+    let (pcm_path, manifest_path) = session_paths(storage_dir, id)?;
+    remove_session_files(&pcm_path, &manifest_path);
+    tracing::info!(id = %id, "Storage: Interrupted recording discarded");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finished_session_is_read_back_and_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = SpoolWriter::create(dir.path(), None, None).unwrap();
+        writer.append(&[0.0, 0.5, -0.5]).unwrap();
+        writer.append(&vec![1.0; BLOCK_SAMPLES]).unwrap();
+        assert_eq!(writer.sample_count(), BLOCK_SAMPLES + 3);
+        assert!(
+            list_interrupted(dir.path()).unwrap().is_empty(),
+            "Live session is not interrupted"
+        );
+
+        let samples = writer.into_samples().unwrap();
+        assert_eq!(samples.len(), BLOCK_SAMPLES + 3);
+        assert!((samples[1] - 0.5).abs() < 1e-4);
+        assert!((samples[2] + 0.5).abs() < 1e-4);
+        assert_eq!(samples[BLOCK_SAMPLES + 2], 1.0);
+        assert_eq!(std::fs::read_dir(spool_dir(dir.path())).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_interrupted_encrypted_session_is_recovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = DataKey::generate();
+        let mut writer = SpoolWriter::create(dir.path(), Some(key.clone()), None).unwrap();
+        writer.append(&vec![0.25; BLOCK_SAMPLES]).unwrap();
+        let pause = RecordingPause {
+            offset_ms: 1000,
+            duration_ms: 4000,
+        };
+        writer.add_pause(pause.clone()).unwrap();
+        writer.append(&vec![0.25; BLOCK_SAMPLES]).unwrap();
+        drop(writer);
+
+        // A crash can cut the last block short
+        let pcm = std::fs::read_dir(spool_dir(dir.path()))
+            .unwrap()
+            .flatten()
+            .map(|e| e.path())
+            .find(|p| p.extension().is_some_and(|ext| ext == "pcm"))
+            .unwrap();
+        let content = std::fs::read(&pcm).unwrap();
+        let plain = to_pcm16(0.25).to_le_bytes().repeat(4);
+        assert!(
+            !content.windows(plain.len()).any(|w| w == plain),
+            "Audio is sealed"
+        );
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&pcm)
+            .unwrap()
+            .write_all(&[0x80, 0x3e, 0, 0, 0xff, 0xff, 0, 0, 0x12])
+            .unwrap();
+
+        let sessions = list_interrupted(dir.path()).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].duration_ms, 2000);
+        assert_eq!(sessions[0].pauses, std::slice::from_ref(&pause));
+
+        let id = sessions[0].id.clone();
+        assert!(
+            read_interrupted(dir.path(), &id, None).is_err(),
+            "Needs the data key"
+        );
+        let (wav, session) = read_interrupted(dir.path(), &id, Some(&key)).unwrap();
+        assert_eq!(session.duration_ms, 2000);
+        assert_eq!(session.pauses, [pause]);
+        assert_eq!(
+            hound::WavReader::new(std::io::Cursor::new(wav))
+                .unwrap()
+                .len() as usize,
+            BLOCK_SAMPLES * 2
+        );
+
+        assert!(read_interrupted(dir.path(), "../../etc/passwd", None).is_err());
+        discard(dir.path(), &id).unwrap();
+        assert!(list_interrupted(dir.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1079 @@
+//! Library Sync (WebDAV)
+//!
+//! Mirrors the storage directory to a WebDAV collection, so several devices
+//! (e.g. laptop and desktop) share one library without a cloud provider.
+//! The collection has the same flat layout as the storage directory.
+//!
+//! A hidden state file (`.hablara-sync.json`) remembers every file's checksum
+//! and remote ETag at the last sync. Comparing both sides against it tells,
+//! per recording, which side changed:
+//!
+//! - only local: upload (or delete remotely)
+//! - only remote: download (or move the local recording to the trash)
+//! - both: conflict. The local version wins and the remote version is kept
+//!   as a copy with a new ID. Identical changes are no conflict, and an edit
+//!   wins over a deletion on the other side.
+//!
+//! The state is saved after every file, so an interrupted sync resumes where
+//! it stopped instead of mistaking its own half-done work for conflicts.
+//!
+//! Files travel decrypted from the library's data key. With client-side
+//! encryption they are sealed with a separate sync key that every device
+//! shares (exported from one keyring, imported into the other), so the server
+//! only stores ciphertext. Downloads are stored per the local encryption setting.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use uuid::Uuid;
+
+use super::archive::hex;
+use super::backup::{file_sha256, modified_ms};
+use super::crypto::{is_encrypted, KeyStore, AAD_AUDIO, AAD_METADATA};
+use super::trash::TrashReason;
+use super::watcher::recording_base_name;
+use super::webdav::WebDavClient;
+use super::{atomic, schema, StorageManager};
+
+/// Sync state inside the storage directory (hidden, never synced itself)
+pub const SYNC_STATE_NAME: &str = ".hablara-sync.json";
+
+/// Keyring user holding the WebDAV password of a profile
+pub fn password_user(profile_id: &str) -> String {
+    format!("webdav-password-{}", profile_id)
+}
+
+/// Keyring user holding the sync key of a profile
+pub fn sync_key_user(profile_id: &str) -> String {
+    format!("storage-sync-key-{}", profile_id)
+}
+
+/// File name → checksum (local) or ETag (remote)
+type Files = BTreeMap<String, String>;
+
+/// A file as of the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncedFile {
+    /// SHA-256 of the local file
+    sha256: String,
+    etag: String,
+    /// Size and modification time of the local file, to skip rehashing it
+    size: u64,
+    modified_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SyncState {
+    remote_url: String,
+    /// Whether files were uploaded with client-side encryption
+    encrypted: bool,
+    /// Set while a sync runs; still set after an interrupted one
+    in_progress: bool,
+    last_synced_at: Option<String>,
+    /// Base name → files as of the last sync
+    recordings: BTreeMap<String, BTreeMap<String, SyncedFile>>,
+}
+
+fn load_state(storage_dir: &Path) -> SyncState {
+    let Ok(content) = std::fs::read(storage_dir.join(SYNC_STATE_NAME)) else {
+        return SyncState::default();
+    };
+    // A lost state only costs a full comparison, never data
+    serde_json::from_slice(&content).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Sync: Ignoring unreadable sync state");
+        SyncState::default()
+    })
+}
+
+fn save_state(storage_dir: &Path, state: &SyncState) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(state)
+        .map_err(|e| format!("Failed to serialize sync state: {}", e))?;
+    atomic::write_file(&storage_dir.join(SYNC_STATE_NAME), &content)
+}
+
+/// Forget the sync state, so the next sync compares both sides in full
+pub fn reset_state(storage_dir: &Path) -> Result<(), String> {
+    match std::fs::remove_file(storage_dir.join(SYNC_STATE_NAME)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to reset sync state: {}", e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Sync configuration and state of a library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    /// A server URL is configured
+    pub configured: bool,
+    /// A password is stored in the keyring
+    pub has_password: bool,
+    pub encrypted: bool,
+    pub last_synced_at: Option<String>,
+    /// The last sync was interrupted; the next one resumes it
+    pub interrupted: bool,
+    pub synced_recordings: usize,
+}
+
+/// Sync status of the library in `storage_dir` for the server at `url`
+pub fn status(
+    storage_dir: &Path,
+    url: Option<&str>,
+    has_password: bool,
+    encrypted: bool,
+) -> SyncStatus {
+    let state = load_state(storage_dir);
+    let current = url.is_some_and(|url| url == state.remote_url);
+    SyncStatus {
+        configured: url.is_some(),
+        has_password,
+        encrypted,
+        last_synced_at: state.last_synced_at.filter(|_| current),
+        interrupted: current && state.in_progress,
+        synced_recordings: if current { state.recordings.len() } else { 0 },
+    }
+}
+
+/// A recording changed on both sides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    /// Recording whose local version was kept
+    pub id: String,
+    pub base_name: String,
+    /// Copy holding the remote version
+    pub copy_id: String,
+    pub copy_base_name: String,
+}
+
+/// Result of a sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncResult {
+    /// Recordings uploaded (new or changed locally)
+    pub uploaded: usize,
+    /// Recordings downloaded (new or changed remotely)
+    pub downloaded: usize,
+    /// Recordings deleted locally and now removed from the server
+    pub deleted_remote: usize,
+    /// Recordings deleted remotely and now moved to the local trash
+    pub deleted_local: usize,
+    pub unchanged: usize,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// What to sync and how
+pub struct SyncOptions<'a> {
+    pub url: &'a str,
+    pub storage_dir: &'a Path,
+    /// Data key of the library
+    pub keys: &'a KeyStore,
+    pub encrypt_local: bool,
+    /// Key shared by all devices for client-side encryption
+    pub sync_keys: &'a KeyStore,
+    pub encrypt_remote: bool,
+    pub trash_retention_days: u32,
+}
+
+fn aad(name: &str) -> &'static [u8] {
+    if name.ends_with(".json") {
+        AAD_METADATA
+    } else {
+        AAD_AUDIO
+    }
+}
+
+/// Audio before sidecar, so the other side never sees metadata without audio
+fn upload_order(files: &Files) -> Vec<&String> {
+    let mut names: Vec<&String> = files.keys().collect();
+    names.sort_by_key(|name| name.ends_with(".json"));
+    names
+}
+
+/// Whether `files` are exactly the files of the last sync
+fn matches_synced(
+    files: &Files,
+    synced: &BTreeMap<String, SyncedFile>,
+    version: impl Fn(&SyncedFile) -> &String,
+) -> bool {
+    files.len() == synced.len()
+        && files
+            .iter()
+            .all(|(name, v)| synced.get(name).is_some_and(|f| version(f) == v))
+}
+
+/// Local recording files grouped by base name. Files unchanged since the last
+/// sync are not hashed again. Recordings with a save in progress are skipped.
+fn local_files(
+    storage_dir: &Path,
+    state: &SyncState,
+) -> Result<(BTreeMap<String, Files>, BTreeSet<String>), String> {
+    let mut local: BTreeMap<String, Files> = BTreeMap::new();
+    let mut busy = BTreeSet::new();
+    let entries = std::fs::read_dir(storage_dir)
+        .map_err(|e| format!("Failed to read storage directory: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) == Some(atomic::JOURNAL_EXTENSION) {
+            if let Some(stem) = path.file_stem() {
+                busy.insert(stem.to_string_lossy().to_string());
+            }
+            continue;
+        }
+        let Some(base_name) = recording_base_name(storage_dir, &path) else {
+            continue;
+        };
+        let Some(metadata) = entry.metadata().ok().filter(|m| m.is_file()) else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        let modified = modified_ms(&metadata);
+        let cached = state
+            .recordings
+            .get(&base_name)
+            .and_then(|files| files.get(&name))
+            .filter(|f| {
+                f.size == metadata.len() && modified.is_some() && f.modified_ms == modified
+            });
+        let sha256 = match cached {
+            Some(file) => file.sha256.clone(),
+            None => file_sha256(&path)?,
+        };
+        local.entry(base_name).or_default().insert(name, sha256);
+    }
+    Ok((local, busy))
+}
+
+/// Sync the library with the WebDAV collection.
+/// `progress` receives (processed, total) recordings.
+pub fn sync_library(
+    client: &WebDavClient,
+    options: &SyncOptions,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<SyncResult, String> {
+    let storage_dir = options.storage_dir;
+    let remote_files = match client.list()? {
+        Some(files) => files,
+        None => {
+            client.create_collection()?;
+            Vec::new()
+        }
+    };
+    let mut remote: BTreeMap<String, Files> = BTreeMap::new();
+    for file in remote_files {
+        if let Some(base_name) = recording_base_name(storage_dir, &storage_dir.join(&file.name)) {
+            remote
+                .entry(base_name)
+                .or_default()
+                .insert(file.name, file.etag);
+        }
+    }
+
+    let mut state = load_state(storage_dir);
+    if state.remote_url != options.url {
+        state = SyncState {
+            remote_url: options.url.to_string(),
+            encrypted: options.encrypt_remote,
+            ..SyncState::default()
+        };
+    }
+    if state.encrypted != options.encrypt_remote {
+        // Encryption toggled: upload everything again in the new format
+        for file in state.recordings.values_mut().flat_map(|f| f.values_mut()) {
+            file.sha256.clear();
+            file.modified_ms = None;
+        }
+        state.encrypted = options.encrypt_remote;
+    }
+    if options.encrypt_remote && options.sync_keys.get()?.is_none() {
+        // Another device may have encrypted the collection with its key already
+        let sidecar = remote
+            .values()
+            .flat_map(|files| files.keys())
+            .find(|name| name.ends_with(".json"));
+        if let Some(name) = sidecar {
+            if is_encrypted(&client.get(name)?) {
+                return Err(
+                    "The sync folder is encrypted with another device's sync key; import that key first"
+                        .to_string(),
+                );
+            }
+        }
+        options.sync_keys.get_or_create()?;
+    }
+
+    let (local, busy) = local_files(storage_dir, &state)?;
+    state.in_progress = true;
+    save_state(storage_dir, &state)?;
+
+    let base_names: BTreeSet<String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(state.recordings.keys())
+        .filter(|base_name| !busy.contains(*base_name))
+        .cloned()
+        .collect();
+
+    let mut syncer = Syncer {
+        client,
+        options,
+        state,
+        result: SyncResult::default(),
+    };
+    let empty = Files::new();
+    for (i, base_name) in base_names.iter().enumerate() {
+        syncer.sync_recording(
+            base_name,
+            local.get(base_name).unwrap_or(&empty),
+            remote.get(base_name).unwrap_or(&empty),
+        )?;
+        progress(i + 1, base_names.len());
+    }
+
+    let Syncer {
+        mut state, result, ..
+    } = syncer;
+    state.in_progress = false;
+    state.last_synced_at = Some(Utc::now().to_rfc3339());
+    save_state(storage_dir, &state)?;
+    Ok(result)
+}
+
+struct Syncer<'a> {
+    client: &'a WebDavClient,
+    options: &'a SyncOptions<'a>,
+    state: SyncState,
+    result: SyncResult,
+}
+
+impl Syncer<'_> {
+    fn dir(&self) -> &Path {
+        self.options.storage_dir
+    }
+
+    fn synced(&self, base_name: &str) -> BTreeMap<String, SyncedFile> {
+        self.state
+            .recordings
+            .get(base_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Sync state entry of a local file
+    fn synced_file(&self, name: &str, sha256: String, etag: String) -> SyncedFile {
+        let metadata = std::fs::metadata(self.dir().join(name)).ok();
+        SyncedFile {
+            sha256,
+            etag,
+            size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+            modified_ms: metadata.as_ref().and_then(modified_ms),
+        }
+    }
+
+    /// Update the state of one file and save it
+    fn record(
+        &mut self,
+        base_name: &str,
+        name: &str,
+        file: Option<SyncedFile>,
+    ) -> Result<(), String> {
+        let files = self
+            .state
+            .recordings
+            .entry(base_name.to_string())
+            .or_default();
+        match file {
+            Some(file) => {
+                files.insert(name.to_string(), file);
+            }
+            None => {
+                files.remove(name);
+            }
+        }
+        if files.is_empty() {
+            self.state.recordings.remove(base_name);
+        }
+        save_state(self.options.storage_dir, &self.state)
+    }
+
+    fn sync_recording(
+        &mut self,
+        base_name: &str,
+        local: &Files,
+        remote: &Files,
+    ) -> Result<(), String> {
+        let synced = self.synced(base_name);
+        let local_changed = !matches_synced(local, &synced, |f| &f.sha256);
+        let remote_changed = !matches_synced(remote, &synced, |f| &f.etag);
+
+        match (local_changed, remote_changed) {
+            (false, false) => self.result.unchanged += 1,
+            // Deleted on both sides
+            _ if local.is_empty() && remote.is_empty() => {
+                self.state.recordings.remove(base_name);
+                save_state(self.dir(), &self.state)?;
+            }
+            // An edit wins over a deletion on the other side
+            (true, _) if !remote_changed || remote.is_empty() => {
+                self.push(base_name, local, remote)?;
+                if local.is_empty() {
+                    self.result.deleted_remote += 1;
+                } else {
+                    self.result.uploaded += 1;
+                }
+            }
+            (_, true) if !local_changed || local.is_empty() => {
+                self.pull(base_name, local, remote)?;
+                if remote.is_empty() {
+                    self.result.deleted_local += 1;
+                } else {
+                    self.result.downloaded += 1;
+                }
+            }
+            _ => self.resolve(base_name, local, remote)?,
+        }
+        Ok(())
+    }
+
+    fn upload(&mut self, base_name: &str, name: &str) -> Result<(), String> {
+        let content = std::fs::read(self.dir().join(name))
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        let plaintext = self.options.keys.open(&content, aad(name))?;
+        let body =
+            self.options
+                .sync_keys
+                .seal(&plaintext, aad(name), self.options.encrypt_remote)?;
+        let etag = self.client.put(name, body)?;
+        let file = self.synced_file(name, hex(&Sha256::digest(&content)), etag);
+        self.record(base_name, name, Some(file))
+    }
+
+    /// Download a file; returns it as stored remotely and decrypted
+    fn download(&self, name: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+        let content = self.client.get(name)?;
+        if is_encrypted(&content) && self.options.sync_keys.get()?.is_none() {
+            return Err("The sync folder is encrypted; import the sync key first".to_string());
+        }
+        let plaintext = self
+            .options
+            .sync_keys
+            .open(&content, aad(name))
+            .map_err(|e| format!("Failed to decrypt {} from the sync folder: {}", name, e))?;
+        Ok((content, plaintext))
+    }
+
+    /// Write downloaded files of one recording as a unit (sidecar last).
+    /// Returns the stored files.
+    fn store(
+        &mut self,
+        base_name: &str,
+        files: &[(String, Vec<u8>)],
+        remote: &Files,
+    ) -> Result<Files, String> {
+        let names: Vec<String> = files.iter().map(|(name, _)| name.clone()).collect();
+        let mut transaction = atomic::SaveTransaction::begin(self.dir(), base_name, &names)?;
+        let mut stored = Files::new();
+        for (name, plaintext) in files {
+            let content =
+                self.options
+                    .keys
+                    .seal(plaintext, aad(name), self.options.encrypt_local)?;
+            transaction.write(name, &content)?;
+            stored.insert(name.clone(), hex(&Sha256::digest(&content)));
+        }
+        transaction.commit()?;
+
+        for (name, sha256) in &stored {
+            let etag = remote.get(name).cloned().unwrap_or_default();
+            let file = self.synced_file(name, sha256.clone(), etag);
+            self.record(base_name, name, Some(file))?;
+        }
+        Ok(stored)
+    }
+
+    /// Make the remote recording match the local one
+    fn push(&mut self, base_name: &str, local: &Files, remote: &Files) -> Result<(), String> {
+        let synced = self.synced(base_name);
+        for name in upload_order(local) {
+            let up_to_date = synced
+                .get(name)
+                .is_some_and(|f| f.sha256 == local[name] && remote.get(name) == Some(&f.etag));
+            if !up_to_date {
+                self.upload(base_name, name)?;
+            }
+        }
+        // Sidecar first, so the other side never sees metadata without audio
+        for name in upload_order(remote).into_iter().rev() {
+            if !local.contains_key(name) {
+                self.client.delete(name)?;
+                self.record(base_name, name, None)?;
+            }
+        }
+        for name in synced.keys().filter(|name| !local.contains_key(*name)) {
+            self.record(base_name, name, None)?;
+        }
+        Ok(())
+    }
+
+    /// Make the local recording match the remote one
+    fn pull(&mut self, base_name: &str, local: &Files, remote: &Files) -> Result<(), String> {
+        let synced = self.synced(base_name);
+        if remote.is_empty() {
+            let id = self.local_id(base_name);
+            super::discard_recording(
+                self.dir(),
+                &id,
+                base_name,
+                TrashReason::RemovedBySync,
+                self.options.trash_retention_days,
+            )?;
+        } else {
+            let mut files = Vec::new();
+            for name in upload_order(remote) {
+                let up_to_date = synced
+                    .get(name)
+                    .is_some_and(|f| f.etag == remote[name] && local.get(name) == Some(&f.sha256));
+                if !up_to_date {
+                    files.push((name.clone(), self.download(name)?.1));
+                }
+            }
+            if !files.is_empty() {
+                self.store(base_name, &files, remote)?;
+            }
+            // E.g. audio recompressed into another codec on the other device
+            for name in local.keys().filter(|name| !remote.contains_key(*name)) {
+                std::fs::remove_file(self.dir().join(name))
+                    .map_err(|e| format!("Failed to delete {}: {}", name, e))?;
+            }
+        }
+        for name in synced.keys().chain(local.keys()) {
+            if !remote.contains_key(name) {
+                self.record(base_name, name, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Both sides changed since the last sync (or were never synced)
+    fn resolve(&mut self, base_name: &str, local: &Files, remote: &Files) -> Result<(), String> {
+        let mut remote_content = BTreeMap::new();
+        for name in remote.keys() {
+            remote_content.insert(name.clone(), self.download(name)?);
+        }
+        let mut local_plaintext = BTreeMap::new();
+        for name in local.keys() {
+            let content = std::fs::read(self.dir().join(name))
+                .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+            local_plaintext.insert(name.clone(), self.options.keys.open(&content, aad(name))?);
+        }
+
+        let identical = local_plaintext.len() == remote_content.len()
+            && local_plaintext.iter().all(|(name, plaintext)| {
+                remote_content
+                    .get(name)
+                    .is_some_and(|(_, remote_plaintext)| remote_plaintext == plaintext)
+            });
+        if identical {
+            // Same change on both sides, e.g. the first sync of a copied library
+            for name in upload_order(local) {
+                let (content, _) = &remote_content[name];
+                if is_encrypted(content) != self.options.encrypt_remote {
+                    self.upload(base_name, name)?;
+                } else {
+                    let file = self.synced_file(name, local[name].clone(), remote[name].clone());
+                    self.record(base_name, name, Some(file))?;
+                }
+            }
+            self.result.unchanged += 1;
+            return Ok(());
+        }
+
+        let sidecar = remote_content
+            .iter()
+            .find(|(name, _)| name.ends_with(".json"))
+            .map(|(_, (_, plaintext))| plaintext.clone());
+        let copy = match sidecar {
+            Some(json) => Some(self.save_copy(&json, &remote_content)?),
+            None => {
+                tracing::warn!(name = %base_name, "Sync: Remote version has no metadata, keeping local version only");
+                None
+            }
+        };
+
+        // The local version wins
+        self.state.recordings.remove(base_name);
+        self.push(base_name, local, remote)?;
+
+        if let Some((copy_id, copy_base_name)) = copy {
+            let id = self.local_id(base_name);
+            tracing::info!(id = %id, copy = %copy_id, "Sync: Conflict, kept remote version as copy");
+            self.result.conflicts.push(SyncConflict {
+                id,
+                base_name: base_name.to_string(),
+                copy_id,
+                copy_base_name,
+            });
+        } else {
+            self.result.uploaded += 1;
+        }
+        Ok(())
+    }
+
+    /// Keep the remote version of a conflicting recording under a new ID
+    /// and upload it. Returns the new ID and base name.
+    fn save_copy(
+        &mut self,
+        json: &[u8],
+        remote_content: &BTreeMap<String, (Vec<u8>, Vec<u8>)>,
+    ) -> Result<(String, String), String> {
+        let (mut metadata, _) = schema::parse_metadata(json).map_err(|e| e.to_string())?;
+        metadata.id = Uuid::new_v4().to_string();
+        let copy_base_name = StorageManager::generate_filename(&metadata);
+        let json = serde_json::to_vec_pretty(&metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+
+        let mut files: Vec<(String, Vec<u8>)> = remote_content
+            .iter()
+            .filter_map(|(name, (_, plaintext))| {
+                let (_, extension) = name.rsplit_once('.')?;
+                let content = if extension == "json" {
+                    json.clone()
+                } else {
+                    plaintext.clone()
+                };
+                Some((format!("{}.{}", copy_base_name, extension), content))
+            })
+            .collect();
+        files.sort_by_key(|(name, _)| name.ends_with(".json"));
+
+        let stored = self.store(&copy_base_name, &files, &Files::new())?;
+        self.push(&copy_base_name, &stored, &Files::new())?;
+        Ok((metadata.id, copy_base_name))
+    }
+
+    /// ID of a local recording (its base name if the sidecar is unreadable)
+    fn local_id(&self, base_name: &str) -> String {
+        let sidecar = self.dir().join(format!("{}.json", base_name));
+        super::read_sidecar(&sidecar, self.options.keys)
+            .map(|metadata| metadata.id)
+            .unwrap_or_else(|_| base_name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::crypto::DataKey;
+    use super::super::tests::create_test_metadata;
+    use super::super::{RecordingMetadata, StorageConfig};
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+
+    const USER: &str = "journal";
+    const PASSWORD: &str = "secret";
+
+    /// In-memory WebDAV stand-in serving one collection at `/dav/`
+    #[derive(Default)]
+    struct Collection {
+        exists: bool,
+        files: BTreeMap<String, (Vec<u8>, u64)>,
+        version: u64,
+        /// Uploads accepted before the server starts failing them
+        uploads_left: Option<usize>,
+    }
+
+    struct StandIn {
+        url: String,
+        collection: Arc<Mutex<Collection>>,
+    }
+
+    impl StandIn {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/dav", listener.local_addr().unwrap());
+            let collection = Arc::new(Mutex::new(Collection::default()));
+            let shared = collection.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    handle(stream, &shared);
+                }
+            });
+            Self { url, collection }
+        }
+
+        fn client(&self) -> WebDavClient {
+            WebDavClient::new(&self.url, USER, PASSWORD).unwrap()
+        }
+
+        fn file(&self, name: &str) -> Option<Vec<u8>> {
+            let collection = self.collection.lock().unwrap();
+            collection
+                .files
+                .get(name)
+                .map(|(content, _)| content.clone())
+        }
+
+        fn names(&self) -> Vec<String> {
+            self.collection
+                .lock()
+                .unwrap()
+                .files
+                .keys()
+                .cloned()
+                .collect()
+        }
+    }
+
+    fn respond(stream: &mut TcpStream, status: &str, headers: &[(&str, String)], body: &[u8]) {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+            status,
+            body.len()
+        );
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        let _ = stream.write_all(response.as_bytes());
+        let _ = stream.write_all(body);
+    }
+
+    fn propfind_entry(href: &str, file: Option<(usize, u64)>) -> String {
+        match file {
+            Some((size, version)) => format!(
+                "<D:response><D:href>{}</D:href><D:propstat><D:prop><D:resourcetype/>\
+                 <D:getetag>\"v{}\"</D:getetag><D:getcontentlength>{}</D:getcontentlength>\
+                 </D:prop></D:propstat></D:response>",
+                href, version, size
+            ),
+            None => format!(
+                "<D:response><D:href>{}</D:href><D:propstat><D:prop>\
+                 <D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response>",
+                href
+            ),
+        }
+    }
+
+    fn handle(mut stream: TcpStream, shared: &Mutex<Collection>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let (mut length, mut authorization) = (0, String::new());
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).is_err() || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "content-length" => length = value.trim().parse().unwrap_or(0),
+                    "authorization" => authorization = value.trim().to_string(),
+                    _ => {}
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+
+        let expected = format!("Basic {}", BASE64.encode(format!("{}:{}", USER, PASSWORD)));
+        if authorization != expected {
+            return respond(&mut stream, "401 Unauthorized", &[], b"");
+        }
+
+        let name = path
+            .trim_start_matches("/dav")
+            .trim_start_matches('/')
+            .to_string();
+        let mut c = shared.lock().unwrap();
+        match (method.as_str(), name.is_empty()) {
+            ("MKCOL", true) => {
+                c.exists = true;
+                respond(&mut stream, "201 Created", &[], b"");
+            }
+            (_, _) if !c.exists => respond(&mut stream, "404 Not Found", &[], b""),
+            ("PROPFIND", true) => {
+                let mut xml =
+                    String::from("<?xml version=\"1.0\"?><D:multistatus xmlns:D=\"DAV:\">");
+                xml.push_str(&propfind_entry("/dav/", None));
+                for (name, (content, version)) in &c.files {
+                    xml.push_str(&propfind_entry(
+                        &format!("/dav/{}", name),
+                        Some((content.len(), *version)),
+                    ));
+                }
+                xml.push_str("</D:multistatus>");
+                respond(&mut stream, "207 Multi-Status", &[], xml.as_bytes());
+            }
+            ("PROPFIND", false) => match c.files.get(&name) {
+                Some((content, version)) => {
+                    let xml = format!(
+                        "<D:multistatus xmlns:D=\"DAV:\">{}</D:multistatus>",
+                        propfind_entry(&path, Some((content.len(), *version)))
+                    );
+                    respond(&mut stream, "207 Multi-Status", &[], xml.as_bytes());
+                }
+                None => respond(&mut stream, "404 Not Found", &[], b""),
+            },
+            ("GET", false) => match c.files.get(&name) {
+                Some((content, _)) => {
+                    let content = content.clone();
+                    respond(&mut stream, "200 OK", &[], &content);
+                }
+                None => respond(&mut stream, "404 Not Found", &[], b""),
+            },
+            ("PUT", false) => {
+                if c.uploads_left == Some(0) {
+                    return respond(&mut stream, "503 Service Unavailable", &[], b"");
+                }
+                if let Some(left) = c.uploads_left.as_mut() {
+                    *left -= 1;
+                }
+                c.version += 1;
+                let version = c.version;
+                c.files.insert(name, (body, version));
+                respond(
+                    &mut stream,
+                    "201 Created",
+                    &[("ETag", format!("\"v{}\"", version))],
+                    b"",
+                );
+            }
+            ("DELETE", false) => match c.files.remove(&name) {
+                Some(_) => respond(&mut stream, "204 No Content", &[], b""),
+                None => respond(&mut stream, "404 Not Found", &[], b""),
+            },
+            _ => respond(&mut stream, "405 Method Not Allowed", &[], b""),
+        }
+    }
+
+    struct Device {
+        dir: tempfile::TempDir,
+        keys: KeyStore,
+        sync_keys: KeyStore,
+    }
+
+    impl Device {
+        fn new(sync_key: &DataKey) -> Self {
+            Self {
+                dir: tempfile::tempdir().unwrap(),
+                keys: KeyStore::with_key("test-data-key", DataKey::generate()),
+                sync_keys: KeyStore::with_key("test-sync-key", sync_key.clone()),
+            }
+        }
+
+        fn sync(&self, server: &StandIn) -> Result<SyncResult, String> {
+            let options = SyncOptions {
+                url: &server.url,
+                storage_dir: self.dir.path(),
+                keys: &self.keys,
+                encrypt_local: true,
+                sync_keys: &self.sync_keys,
+                encrypt_remote: true,
+                trash_retention_days: 30,
+            };
+            sync_library(&server.client(), &options, |_, _| {})
+        }
+
+        /// Save a recording (encrypted with this device's data key)
+        fn save(&self, metadata: &RecordingMetadata, audio: &[u8]) -> String {
+            let base_name = StorageManager::generate_filename(metadata);
+            let json = serde_json::to_vec_pretty(metadata).unwrap();
+            let dir = self.dir.path();
+            let sealed_json = self.keys.seal(&json, AAD_METADATA, true).unwrap();
+            let sealed_audio = self.keys.seal(audio, AAD_AUDIO, true).unwrap();
+            std::fs::write(dir.join(format!("{}.json", base_name)), sealed_json).unwrap();
+            std::fs::write(dir.join(format!("{}.wav", base_name)), sealed_audio).unwrap();
+            base_name
+        }
+
+        fn metadata(&self, base_name: &str) -> RecordingMetadata {
+            super::super::read_sidecar(
+                &self.dir.path().join(format!("{}.json", base_name)),
+                &self.keys,
+            )
+            .unwrap()
+        }
+
+        fn sidecars(&self) -> Vec<String> {
+            let mut names: Vec<String> = std::fs::read_dir(self.dir.path())
+                .unwrap()
+                .flatten()
+                .filter_map(|e| {
+                    e.file_name()
+                        .to_str()?
+                        .strip_suffix(".json")
+                        .map(str::to_string)
+                })
+                .filter(|name| !name.starts_with('.'))
+                .collect();
+            names.sort();
+            names
+        }
+    }
+
+    #[test]
+    fn test_sync_between_devices() {
+        let server = StandIn::start();
+        let sync_key = DataKey::generate();
+        let laptop = Device::new(&sync_key);
+        let desktop = Device::new(&sync_key);
+
+        let metadata = create_test_metadata();
+        let base_name = laptop.save(&metadata, b"RIFF");
+
+        let first = laptop.sync(&server).unwrap();
+        assert_eq!(first.uploaded, 1);
+        // The server only sees ciphertext
+        let remote = server.file(&format!("{}.wav", base_name)).unwrap();
+        assert!(is_encrypted(&remote));
+        assert_eq!(laptop.sync_keys.open(&remote, AAD_AUDIO).unwrap(), b"RIFF");
+
+        let second = desktop.sync(&server).unwrap();
+        assert_eq!(second.downloaded, 1);
+        assert_eq!(desktop.metadata(&base_name).id, metadata.id);
+        assert_eq!(desktop.sync(&server).unwrap().unchanged, 1);
+
+        // Deleted on the desktop: removed remotely, then trashed on the laptop
+        for extension in ["json", "wav"] {
+            std::fs::remove_file(
+                desktop
+                    .dir
+                    .path()
+                    .join(format!("{}.{}", base_name, extension)),
+            )
+            .unwrap();
+        }
+        assert_eq!(desktop.sync(&server).unwrap().deleted_remote, 1);
+        assert!(server.names().is_empty());
+        assert_eq!(laptop.sync(&server).unwrap().deleted_local, 1);
+        assert!(laptop.sidecars().is_empty());
+
+        let status = status(laptop.dir.path(), Some(&server.url), true, true);
+        assert!(status.last_synced_at.is_some());
+        assert!(!status.interrupted);
+    }
+
+    #[test]
+    fn test_storage_manager_sync_keeps_state() {
+        let server = StandIn::start();
+        let dir = tempfile::tempdir().unwrap();
+        let config = StorageConfig {
+            storage_path: dir.path().to_string_lossy().to_string(),
+            sync_url: Some(server.url.clone()),
+            sync_username: USER.to_string(),
+            sync_encrypt: false,
+            ..StorageConfig::default()
+        };
+        let open = || {
+            StorageManager::with_config(config.clone())
+                .with_data_key(DataKey::generate())
+                .with_sync_password(PASSWORD)
+        };
+        let manager = open();
+        manager
+            .save_recording(b"RIFF", &create_test_metadata())
+            .unwrap();
+
+        assert_eq!(manager.sync_library(|_, _| {}).unwrap().uploaded, 1);
+        assert_eq!(manager.sync_library(|_, _| {}).unwrap().unchanged, 1);
+
+        // The sync state is no recording: kept through catalog rebuilds
+        let state = dir.path().join(SYNC_STATE_NAME);
+        assert!(state.exists());
+        assert!(manager.list_quarantined().unwrap().is_empty());
+        assert_eq!(manager.upgrade_sidecars().unwrap().failed, 0);
+
+        let reopened = open();
+        assert_eq!(reopened.list_recordings().unwrap().len(), 1);
+        assert!(state.exists());
+        assert!(reopened.sync_status().unwrap().last_synced_at.is_some());
+    }
+
+    #[test]
+    fn test_conflict_keeps_both_versions() {
+        let server = StandIn::start();
+        let sync_key = DataKey::generate();
+        let laptop = Device::new(&sync_key);
+        let desktop = Device::new(&sync_key);
+
+        let mut metadata = create_test_metadata();
+        let base_name = laptop.save(&metadata, b"RIFF");
+        laptop.sync(&server).unwrap();
+        desktop.sync(&server).unwrap();
+
+        metadata.title = Some("Laptop".to_string());
+        laptop.save(&metadata, b"RIFF");
+        metadata.title = Some("Desktop".to_string());
+        desktop.save(&metadata, b"RIFF");
+
+        assert_eq!(laptop.sync(&server).unwrap().uploaded, 1);
+        let result = desktop.sync(&server).unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(conflict.id, metadata.id);
+
+        // Desktop keeps its version and the laptop's as a copy
+        assert_eq!(
+            desktop.metadata(&base_name).title.as_deref(),
+            Some("Desktop")
+        );
+        let copy = desktop.metadata(&conflict.copy_base_name);
+        assert_eq!(copy.id, conflict.copy_id);
+        assert_eq!(copy.title.as_deref(), Some("Laptop"));
+
+        // The laptop receives both
+        let result = laptop.sync(&server).unwrap();
+        assert_eq!(result.downloaded, 2);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(laptop.sidecars().len(), 2);
+        assert_eq!(
+            laptop.metadata(&base_name).title.as_deref(),
+            Some("Desktop")
+        );
+    }
+
+    #[test]
+    fn test_interrupted_sync_resumes() {
+        let server = StandIn::start();
+        let sync_key = DataKey::generate();
+        let laptop = Device::new(&sync_key);
+        for _ in 0..3 {
+            laptop.save(&create_test_metadata(), b"RIFF");
+        }
+
+        // Connection drops after three files
+        server.collection.lock().unwrap().uploads_left = Some(3);
+        assert!(laptop.sync(&server).is_err());
+        assert!(status(laptop.dir.path(), Some(&server.url), true, true).interrupted);
+
+        server.collection.lock().unwrap().uploads_left = None;
+        let result = laptop.sync(&server).unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.uploaded, 2);
+        assert_eq!(result.unchanged, 1);
+        assert_eq!(server.names().len(), 6);
+
+        // A device without the sync key cannot read the library
+        let stranger = Device {
+            sync_keys: KeyStore::with_key("test-sync-key", DataKey::generate()),
+            ..Device::new(&sync_key)
+        };
+        assert!(stranger.sync(&server).unwrap_err().contains("decrypt"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Trash for Deleted Recordings
+//!
+//! Deleting a recording (by the user, "clear all" or automatic cleanup) moves
+//! its WAV and JSON into `.trash/` inside the storage directory. A
+//! `<base_name>.trash.json` note records the recording ID, when and why it was
+//! deleted. Files keep their format, so encrypted recordings stay encrypted.
+//!
+//! Trashed recordings can be restored until they expire after the configured
+//! retention period or the trash is emptied. The trash counts toward the
+//! storage quota; when it is exceeded, the oldest trashed recordings are
+//! purged first.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::codec::AUDIO_EXTENSIONS;
+use super::{atomic, delete_recording_files, recording_disk_size, RecordingMetadata};
+
+/// Trash directory name (hidden, inside the storage directory)
+pub const TRASH_DIR_NAME: &str = ".trash";
+
+/// Suffix of the note describing a trashed recording
+const NOTE_SUFFIX: &str = ".trash.json";
+
+/// Why a recording was moved to the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrashReason {
+    /// Deleted by the user
+    Deleted,
+    /// Removed by "clear all recordings"
+    Cleared,
+    /// Evicted by automatic cleanup (over the recording limit)
+    Cleanup,
+    /// Replaced by the version from a backup
+    Replaced,
+    /// Deleted on another device (library sync)
+    RemovedBySync,
+}
+
+/// Note stored next to trashed files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashNote {
+    pub id: String,
+    pub base_name: String,
+    pub deleted_at: String,
+    pub reason: TrashReason,
+}
+
+impl TrashNote {
+    /// Whether the note is older than `retention_days`
+    pub fn is_expired(&self, retention_days: u32, now: DateTime<Utc>) -> bool {
+        DateTime::parse_from_rfc3339(&self.deleted_at)
+            .map(|deleted| {
+                now - deleted.with_timezone(&Utc) > Duration::days(retention_days as i64)
+            })
+            .unwrap_or(true)
+    }
+}
+
+/// A recording in the trash, as shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedRecording {
+    pub metadata: RecordingMetadata,
+    pub deleted_at: String,
+    pub reason: TrashReason,
+    /// When the recording will be removed for good
+    pub expires_at: String,
+}
+
+/// Path of the trash directory
+pub fn trash_dir(storage_dir: &Path) -> PathBuf {
+    storage_dir.join(TRASH_DIR_NAME)
+}
+
+/// Move a recording's files into the trash and write its note
+pub fn move_to_trash(
+    storage_dir: &Path,
+    id: &str,
+    base_name: &str,
+    reason: TrashReason,
+) -> Result<(), String> {
+    let dir = trash_dir(storage_dir);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+    for ext in AUDIO_EXTENSIONS.into_iter().chain(["json"]) {
+        let src = storage_dir.join(format!("{}.{}", base_name, ext));
+        if src.exists() {
+            std::fs::rename(&src, dir.join(format!("{}.{}", base_name, ext)))
+                .map_err(|e| format!("Failed to move {} file to trash: {}", ext, e))?;
+        }
+    }
+
+    let note = TrashNote {
+        id: id.to_string(),
+        base_name: base_name.to_string(),
+        deleted_at: Utc::now().to_rfc3339(),
+        reason,
+    };
+    let note_path = dir.join(format!("{}{}", base_name, NOTE_SUFFIX));
+    let content = serde_json::to_string_pretty(&note)
+        .map_err(|e| format!("Failed to serialize trash note: {}", e))?;
+    atomic::write_file(&note_path, content.as_bytes())?;
+
+    Ok(())
+}
+
+/// Whether a file in the trash directory is a note (notes are never encrypted)
+pub fn is_note(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.ends_with(NOTE_SUFFIX))
+        .unwrap_or(false)
+}
+
+/// All trash notes, most recently deleted first
+pub fn list_notes(storage_dir: &Path) -> Result<Vec<TrashNote>, String> {
+    let dir = trash_dir(storage_dir);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut notes = Vec::new();
+    for entry in std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read trash directory: {}", e))?
+        .flatten()
+    {
+        let path = entry.path();
+        if !is_note(&path) {
+            continue;
+        }
+
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str::<TrashNote>(&c).map_err(|e| e.to_string()))
+        {
+            Ok(note) => notes.push(note),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Storage: Invalid trash note")
+            }
+        }
+    }
+
+    notes.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(notes)
+}
+
+/// Move a trashed recording's files back into the storage directory
+pub fn restore_files(storage_dir: &Path, note: &TrashNote) -> Result<(), String> {
+    let dir = trash_dir(storage_dir);
+    let json_dest = storage_dir.join(format!("{}.json", note.base_name));
+    if json_dest.exists() {
+        return Err(format!(
+            "A recording named {} already exists",
+            note.base_name
+        ));
+    }
+
+    for ext in AUDIO_EXTENSIONS {
+        let audio_src = dir.join(format!("{}.{}", note.base_name, ext));
+        if audio_src.exists() {
+            std::fs::rename(
+                &audio_src,
+                storage_dir.join(format!("{}.{}", note.base_name, ext)),
+            )
+            .map_err(|e| format!("Failed to restore audio: {}", e))?;
+        }
+    }
+    std::fs::rename(dir.join(format!("{}.json", note.base_name)), &json_dest)
+        .map_err(|e| format!("Failed to restore metadata: {}", e))?;
+    remove_note(storage_dir, note);
+    Ok(())
+}
+
+/// Permanently delete a trashed recording
+pub fn purge(storage_dir: &Path, note: &TrashNote) -> Result<(), String> {
+    delete_recording_files(&trash_dir(storage_dir), &note.base_name)?;
+    remove_note(storage_dir, note);
+    Ok(())
+}
+
+/// Bytes the trash occupies on disk (files and notes)
+pub fn disk_size(storage_dir: &Path) -> u64 {
+    std::fs::read_dir(trash_dir(storage_dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Purge the oldest trashed recordings until the trash and `library_bytes`
+/// fit into `quota_bytes`. Returns the number of purged recordings and the
+/// bytes freed.
+pub fn purge_over_quota(
+    storage_dir: &Path,
+    library_bytes: u64,
+    quota_bytes: u64,
+) -> Result<(usize, u64), String> {
+    let mut trash_bytes = disk_size(storage_dir);
+    let mut purged = 0;
+    let mut freed_bytes = 0;
+    // Notes are listed most recently deleted first
+    for note in list_notes(storage_dir)?.iter().rev() {
+        if library_bytes + trash_bytes <= quota_bytes {
+            break;
+        }
+        let note_path = trash_dir(storage_dir).join(format!("{}{}", note.base_name, NOTE_SUFFIX));
+        let size = recording_disk_size(&trash_dir(storage_dir), &note.base_name)
+            + std::fs::metadata(&note_path).map(|m| m.len()).unwrap_or(0);
+        purge(storage_dir, note)?;
+        trash_bytes = trash_bytes.saturating_sub(size);
+        purged += 1;
+        freed_bytes += size;
+    }
+    Ok((purged, freed_bytes))
+}
+
+fn remove_note(storage_dir: &Path, note: &TrashNote) {
+    let path = trash_dir(storage_dir).join(format!("{}{}", note.base_name, NOTE_SUFFIX));
+    if let Err(e) = std::fs::remove_file(&path) {
+        tracing::warn!(path = %path.display(), error = %e, "Storage: Failed to remove trash note");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_expiry() {
+        let now = Utc::now();
+        let note = |days_ago: i64| TrashNote {
+            id: "id".to_string(),
+            base_name: "base".to_string(),
+            deleted_at: (now - Duration::days(days_ago)).to_rfc3339(),
+            reason: TrashReason::Deleted,
+        };
+
+        assert!(!note(5).is_expired(30, now));
+        assert!(note(31).is_expired(30, now));
+        assert!(note(1).is_expired(0, now));
+    }
+}
@@ -0,0 +1,270 @@
+//! Storage Directory Watcher
+//!
+//! Notices recordings that are added, removed or edited outside the app (synced
+//! folders, manual edits). File system events are collected per recording and
+//! handed over once the recording has been quiet for [`DEBOUNCE`], so a sync
+//! client writing audio and sidecar in several chunks produces a single change.
+//!
+//! The watcher only reports which recordings were touched; comparing them with
+//! the catalog and classifying the change is done by the storage manager.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+
+use super::atomic::TEMP_SUFFIX;
+use super::codec::AUDIO_EXTENSIONS;
+
+/// Quiet period before a touched recording is reported
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// What happened to a recording
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecordingChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A recording changed outside the app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingChange {
+    pub kind: RecordingChangeKind,
+    pub id: String,
+    pub base_name: String,
+}
+
+/// Base name of the recording a path belongs to.
+///
+/// Only audio files and sidecars directly inside the storage directory count;
+/// hidden files (catalog), temporary files and save journals are ignored.
+pub fn recording_base_name(storage_dir: &Path, path: &Path) -> Option<String> {
+    if path.parent() != Some(storage_dir) {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?;
+    if name.starts_with('.') || name.ends_with(TEMP_SUFFIX) {
+        return None;
+    }
+    let extension = path.extension()?.to_str()?;
+    if extension != "json" && !AUDIO_EXTENSIONS.contains(&extension) {
+        return None;
+    }
+    path.file_stem()?.to_str().map(str::to_string)
+}
+
+/// Collects touched recordings until each has been quiet for the debounce period
+#[derive(Debug, Default)]
+pub struct Debouncer {
+    /// Base name → time of the last event
+    pending: HashMap<String, Instant>,
+}
+
+impl Debouncer {
+    /// Record an event for a recording, restarting its quiet period
+    pub fn touch(&mut self, base_name: String, now: Instant) {
+        self.pending.insert(base_name, now);
+    }
+
+    /// Time until the next recording settles (`None` if nothing is pending)
+    pub fn next_timeout(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .values()
+            .map(|last| (*last + DEBOUNCE).saturating_duration_since(now))
+            .min()
+    }
+
+    /// Remove and return the recordings that have been quiet long enough (sorted)
+    pub fn take_settled(&mut self, now: Instant) -> Vec<String> {
+        let mut settled: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= DEBOUNCE)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &settled {
+            self.pending.remove(name);
+        }
+        settled.sort();
+        settled
+    }
+}
+
+enum Message {
+    Event(notify::Result<Event>),
+    Stop,
+}
+
+/// Watches one storage directory until dropped
+pub struct StorageWatcher {
+    dir: PathBuf,
+    sender: mpsc::Sender<Message>,
+    // Dropped before the worker is joined, so no events arrive after a stop
+    watcher: Option<RecommendedWatcher>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl StorageWatcher {
+    /// Start watching `dir`. `on_settled` runs on a background thread with the
+    /// base names of the recordings touched since its last call.
+    pub fn start(
+        dir: PathBuf,
+        mut on_settled: impl FnMut(Vec<String>) + Send + 'static,
+    ) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+
+        let event_sender = sender.clone();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = event_sender.send(Message::Event(event));
+        })
+        .map_err(|e| format!("Failed to create storage watcher: {}", e))?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+        let worker_dir = dir.clone();
+        let worker = std::thread::Builder::new()
+            .name("storage-watcher".to_string())
+            .spawn(move || {
+                let mut debouncer = Debouncer::default();
+                loop {
+                    let message = match debouncer.next_timeout(Instant::now()) {
+                        Some(timeout) => receiver.recv_timeout(timeout),
+                        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+                    match message {
+                        // Reads (including the manager's own) are not changes
+                        Ok(Message::Event(Ok(event))) if !event.kind.is_access() => {
+                            let now = Instant::now();
+                            for path in &event.paths {
+                                if let Some(name) = recording_base_name(&worker_dir, path) {
+                                    debouncer.touch(name, now);
+                                }
+                            }
+                        }
+                        Ok(Message::Event(Ok(_))) | Err(RecvTimeoutError::Timeout) => {}
+                        Ok(Message::Event(Err(e))) => {
+                            tracing::warn!(error = %e, "Storage: Watcher error");
+                        }
+                        Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                    }
+
+                    let settled = debouncer.take_settled(Instant::now());
+                    if !settled.is_empty() {
+                        on_settled(settled);
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start storage watcher: {}", e))?;
+
+        tracing::info!(path = %dir.display(), "Storage: Watching for external changes");
+        Ok(Self {
+            dir,
+            sender,
+            watcher: Some(watcher),
+            worker: Some(worker),
+        })
+    }
+
+    /// Directory being watched
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for StorageWatcher {
+    fn drop(&mut self) {
+        self.watcher.take();
+        let _ = self.sender.send(Message::Stop);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        tracing::info!(path = %self.dir.display(), "Storage: Stopped watching");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_base_name_filters_paths() {
+        let dir = Path::new("/data/recordings");
+        let name = |file: &str| recording_base_name(dir, &dir.join(file));
+
+        assert_eq!(
+            name("2026-03-01_10-00-00_abcd1234.json").as_deref(),
+            Some("2026-03-01_10-00-00_abcd1234")
+        );
+        assert_eq!(
+            name("2026-03-01_10-00-00_abcd1234.flac").as_deref(),
+            Some("2026-03-01_10-00-00_abcd1234")
+        );
+        assert_eq!(name("2026-03-01_10-00-00_abcd1234.json.tmp"), None);
+        assert_eq!(name("2026-03-01_10-00-00_abcd1234.pending"), None);
+        assert_eq!(name(".hablara-catalog.sqlite3"), None);
+        assert_eq!(name("notes.txt"), None);
+        assert_eq!(
+            recording_base_name(dir, &dir.join(".trash").join("a.json")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_period() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::default();
+        assert_eq!(debouncer.next_timeout(start), None);
+
+        debouncer.touch("a".to_string(), start);
+        debouncer.touch("b".to_string(), start + Duration::from_millis(300));
+        // A burst on "a" restarts its quiet period
+        debouncer.touch("a".to_string(), start + Duration::from_millis(400));
+        assert_eq!(
+            debouncer.next_timeout(start + Duration::from_millis(400)),
+            Some(Duration::from_millis(400))
+        );
+
+        assert!(debouncer
+            .take_settled(start + Duration::from_millis(700))
+            .is_empty());
+        assert_eq!(
+            debouncer.take_settled(start + Duration::from_millis(800)),
+            ["b"]
+        );
+        assert_eq!(
+            debouncer.take_settled(start + Duration::from_millis(900)),
+            ["a"]
+        );
+        assert_eq!(debouncer.next_timeout(start), None);
+    }
+
+    #[test]
+    fn test_watcher_reports_touched_recordings() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_dir = dir.path().canonicalize().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let watcher = StorageWatcher::start(storage_dir.clone(), move |names| {
+            let _ = sender.send(names);
+        })
+        .unwrap();
+
+        std::fs::write(storage_dir.join("rec.wav"), b"RIFF").unwrap();
+        std::fs::write(storage_dir.join("rec.json"), b"{}").unwrap();
+        std::fs::write(storage_dir.join("rec.json.tmp"), b"{").unwrap();
+
+        let names = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(names, ["rec"]);
+
+        drop(watcher);
+        std::fs::write(storage_dir.join("other.json"), b"{}").unwrap();
+        assert!(receiver.recv_timeout(DEBOUNCE * 2).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! WebDAV Client
+//!
+//! The few WebDAV operations library sync needs, on a single flat collection:
+//! list (`PROPFIND`, depth 1), download, upload, delete and create the
+//! collection. Responses are parsed by element name only, so namespace
+//! prefixes (`D:`, `d:`, `lp1:`, none) make no difference.
+
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+/// Timeout for connecting to the server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Timeout for a whole request (uploads of long recordings included)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Properties requested when listing the collection
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:resourcetype/><d:getetag/><d:getcontentlength/><d:getlastmodified/></d:prop>
+</d:propfind>"#;
+
+/// A file in the remote collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFile {
+    pub name: String,
+    /// Entity tag, or size and modification time for servers without ETags
+    pub etag: String,
+    pub size: u64,
+}
+
+/// Client for one WebDAV collection
+pub struct WebDavClient {
+    /// Collection URL, always ending in `/`
+    base_url: String,
+    username: String,
+    password: String,
+    http: Client,
+}
+
+impl WebDavClient {
+    pub fn new(url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let http = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create WebDAV client: {}", e))?;
+        let base_url = format!("{}/", url.trim_end_matches('/'));
+        Ok(Self {
+            base_url,
+            username: username.to_string(),
+            password: password.to_string(),
+            http,
+        })
+    }
+
+    fn request(&self, method: Method, name: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, encode_segment(name)))
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    fn send(&self, request: RequestBuilder, what: &str) -> Result<Response, String> {
+        let response = request
+            .send()
+            .map_err(|e| format!("WebDAV {} failed: {}", what, e))?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err("WebDAV server rejected the credentials".to_string());
+        }
+        Ok(response)
+    }
+
+    fn propfind(&self, name: &str, depth: &str) -> Result<Option<Vec<PropEntry>>, String> {
+        let method = Method::from_bytes(b"PROPFIND").expect("valid method");
+        let request = self
+            .request(method, name)
+            .header("Depth", depth)
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(PROPFIND_BODY);
+        let response = self.send(request, "listing")?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            StatusCode::MULTI_STATUS => {
+                let body = response
+                    .text()
+                    .map_err(|e| format!("WebDAV listing failed: {}", e))?;
+                Ok(Some(parse_multistatus(&body)))
+            }
+            status => Err(format!("WebDAV listing failed: HTTP {}", status)),
+        }
+    }
+
+    /// Files in the collection (`None` if the collection does not exist)
+    pub fn list(&self) -> Result<Option<Vec<RemoteFile>>, String> {
+        Ok(self.propfind("", "1")?.map(|entries| {
+            entries
+                .into_iter()
+                .filter(|e| !e.collection)
+                .filter_map(PropEntry::into_remote_file)
+                .collect()
+        }))
+    }
+
+    /// A single file (`None` if it does not exist)
+    pub fn stat(&self, name: &str) -> Result<Option<RemoteFile>, String> {
+        Ok(self
+            .propfind(name, "0")?
+            .and_then(|entries| entries.into_iter().find(|e| !e.collection))
+            .and_then(PropEntry::into_remote_file))
+    }
+
+    /// Create the collection
+    pub fn create_collection(&self) -> Result<(), String> {
+        let method = Method::from_bytes(b"MKCOL").expect("valid method");
+        let response = self.send(self.request(method, ""), "create folder")?;
+        // 405: created meanwhile
+        if response.status().is_success() || response.status() == StatusCode::METHOD_NOT_ALLOWED {
+            Ok(())
+        } else {
+            Err(format!(
+                "WebDAV create folder failed: HTTP {}",
+                response.status()
+            ))
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Result<Vec<u8>, String> {
+        let response = self.send(self.request(Method::GET, name), "download")?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "WebDAV download of {} failed: HTTP {}",
+                name,
+                response.status()
+            ));
+        }
+        response
+            .bytes()
+            .map(|b| b.to_vec())
+            .map_err(|e| format!("WebDAV download of {} failed: {}", name, e))
+    }
+
+    /// Upload a file; returns its new ETag
+    pub fn put(&self, name: &str, content: Vec<u8>) -> Result<String, String> {
+        let size = content.len() as u64;
+        let request = self
+            .request(Method::PUT, name)
+            .header("Content-Type", "application/octet-stream")
+            .body(content);
+        let response = self.send(request, "upload")?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "WebDAV upload of {} failed: HTTP {}",
+                name,
+                response.status()
+            ));
+        }
+        // Servers may omit the ETag on PUT (or return a weak one); ask for it then
+        let etag = response
+            .headers()
+            .get("ETag")
+            .and_then(|v| v.to_str().ok())
+            .filter(|v| !v.starts_with("W/"))
+            .map(str::to_string);
+        match etag {
+            Some(etag) => Ok(etag),
+            None => self
+                .stat(name)?
+                .filter(|f| f.size == size)
+                .map(|f| f.etag)
+                .ok_or_else(|| format!("WebDAV upload of {} could not be confirmed", name)),
+        }
+    }
+
+    /// Delete a file (already missing counts as deleted)
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        let response = self.send(self.request(Method::DELETE, name), "delete")?;
+        if response.status().is_success() || response.status() == StatusCode::NOT_FOUND {
+            Ok(())
+        } else {
+            Err(format!(
+                "WebDAV delete of {} failed: HTTP {}",
+                name,
+                response.status()
+            ))
+        }
+    }
+}
+
+/// Percent-encode a file name for use as a URL path segment
+fn encode_segment(name: &str) -> String {
+    let mut encoded = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decode a percent-encoded URL path
+fn decode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// One `<response>` of a multistatus body
+#[derive(Debug, Default)]
+struct PropEntry {
+    href: String,
+    etag: Option<String>,
+    size: Option<u64>,
+    last_modified: Option<String>,
+    collection: bool,
+}
+
+impl PropEntry {
+    fn into_remote_file(self) -> Option<RemoteFile> {
+        let name = decode_path(self.href.trim_end_matches('/'))
+            .rsplit('/')
+            .next()?
+            .to_string();
+        if name.is_empty() {
+            return None;
+        }
+        let size = self.size.unwrap_or(0);
+        let etag = match (self.etag, self.last_modified) {
+            (Some(etag), _) => etag,
+            (None, Some(modified)) => format!("{}-{}", size, modified),
+            (None, None) => return None,
+        };
+        Some(RemoteFile { name, etag, size })
+    }
+}
+
+/// Parse a `207 Multi-Status` body
+fn parse_multistatus(xml: &str) -> Vec<PropEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<PropEntry> = None;
+    let mut text = String::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let closing = tag.starts_with('/');
+        let self_closing = tag.ends_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .trim_end_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default();
+        let local_name = name.rsplit(':').next().unwrap_or(name);
+
+        if !closing {
+            match local_name {
+                "response" if !self_closing => current = Some(PropEntry::default()),
+                "collection" => {
+                    if let Some(entry) = current.as_mut() {
+                        entry.collection = true;
+                    }
+                }
+                _ => {}
+            }
+            text.clear();
+            continue;
+        }
+
+        let value = unescape_xml(text.trim());
+        text.clear();
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        match local_name {
+            "href" => entry.href = value,
+            "getetag" if !value.is_empty() => entry.etag = Some(value),
+            "getcontentlength" => entry.size = value.parse().ok(),
+            "getlastmodified" if !value.is_empty() => entry.last_modified = Some(value),
+            "response" => entries.extend(current.take()),
+            _ => {}
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus_with_prefixes() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:multistatus xmlns:D="DAV:" xmlns:lp1="DAV:">
+  <D:response>
+    <D:href>/dav/hablara/</D:href>
+    <D:propstat><D:prop>
+      <lp1:resourcetype><D:collection/></lp1:resourcetype>
+    </D:prop></D:propstat>
+  </D:response>
+  <D:response>
+    <D:href>/dav/hablara/2026-03-01_10-00-00_abcd1234.json</D:href>
+    <D:propstat><D:prop>
+      <lp1:resourcetype/>
+      <lp1:getetag>&quot;1a-5f0&quot;</lp1:getetag>
+      <lp1:getcontentlength>26</lp1:getcontentlength>
+    </D:prop></D:propstat>
+  </D:response>
+  <response xmlns="DAV:">
+    <href>http://host/dav/hablara/with%20space.wav</href>
+    <propstat><prop>
+      <getcontentlength>4</getcontentlength>
+      <getlastmodified>Sun, 01 Mar 2026 10:00:00 GMT</getlastmodified>
+    </prop></propstat>
+  </response>
+</D:multistatus>"#;
+
+        let files: Vec<RemoteFile> = parse_multistatus(xml)
+            .into_iter()
+            .filter(|e| !e.collection)
+            .filter_map(PropEntry::into_remote_file)
+            .collect();
+        assert_eq!(
+            files,
+            [
+                RemoteFile {
+                    name: "2026-03-01_10-00-00_abcd1234.json".to_string(),
+                    etag: "\"1a-5f0\"".to_string(),
+                    size: 26,
+                },
+                RemoteFile {
+                    name: "with space.wav".to_string(),
+                    etag: "4-Sun, 01 Mar 2026 10:00:00 GMT".to_string(),
+                    size: 4,
+                },
+            ]
+        );
+        assert_eq!(encode_segment("with space.wav"), "with%20space.wav");
+    }
+}
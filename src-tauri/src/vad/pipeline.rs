@@ -10,16 +10,16 @@ use anyhow::Result;
 use super::{SileroVad, SmoothedVad, VoiceActivityDetector, VAD_FRAME_SIZE};
 
 /// Default VAD threshold (30% probability for speech detection)
-const DEFAULT_THRESHOLD: f32 = 0.3;
+pub const DEFAULT_THRESHOLD: f32 = 0.3;
 
 /// Default prefill frames (15 = 450ms context before speech)
-const DEFAULT_PREFILL: usize = 15;
+pub const DEFAULT_PREFILL: usize = 15;
 
 /// Default hangover frames (15 = 450ms after speech ends)
-const DEFAULT_HANGOVER: usize = 15;
+pub const DEFAULT_HANGOVER: usize = 15;
 
 /// Default onset frames (2 = 60ms to confirm speech start)
-const DEFAULT_ONSET: usize = 2;
+pub const DEFAULT_ONSET: usize = 2;
 
 /// Result from VAD filtering with timing metadata
 #[derive(Debug, Clone)]
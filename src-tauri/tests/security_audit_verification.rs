@@ -26,6 +26,7 @@ fn test_file_permissions_owner_only() {
             max_recordings: 100,
             max_user_storage_mb: 500,
             storage_path: storage_path.clone(),
+            ..StorageConfig::default()
         };
         let manager = StorageManager::with_config(config);
 
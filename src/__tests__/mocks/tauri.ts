@@ -32,14 +32,18 @@ export const createMockInvoke = () => {
 
       // Audio commands
       case 'native_start_recording':
+      case 'native_pause_recording':
+      case 'native_resume_recording':
         return Promise.resolve();
       case 'native_stop_recording':
         return Promise.resolve('base64audiodata');
       case 'native_get_audio_level':
         return Promise.resolve(0.5);
+      case 'set_audio_input_device':
+        return Promise.resolve();
       case 'list_audio_devices':
         return Promise.resolve([
-          { name: 'Default Device', isDefault: true },
+          { id: 'Default Device#0', index: '0', name: 'Default Device', isDefault: true, sampleRate: 48000 },
         ]);
 
       // Transcription commands
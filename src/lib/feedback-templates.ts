@@ -13,6 +13,8 @@ export interface PersonalizedFeedback {
   baselineEmotion: string;
   baselineConfidence: number;
   baselineSampleCount: number;
+  /** Baseline window in days (null = all recordings) */
+  baselineWindowDays: number | null;
   currentEmotion: string;
   currentConfidence: number;
   confidenceDelta: number;
@@ -215,6 +215,71 @@ export interface AudioValidationMeta {
   passed: boolean;
 }
 
+/** Microphone and capture config of a native recording (before resampling to 16 kHz mono) */
+export interface InputDeviceMeta {
+  name: string;
+  deviceId?: string; // Stable id ("name#index") the device was selected with
+  sampleRate: number;
+  channels: number;
+  sampleFormat: string;
+  failoverFrom?: string; // Device lost mid-recording; the rest came from this one
+}
+
+/** Deliberate break in a native recording (`native_pause_recording` / `native_resume_recording`) */
+export interface RecordingPause {
+  offsetMs: number; // Position in the recorded audio; the break itself is not recorded
+  durationMs: number; // Length of the break (wall clock)
+}
+
+/**
+ * Native recording session a crash left in the spool (`list_interrupted_recordings`).
+ * Save it with `recover_interrupted_recording(id)` or delete it with
+ * `discard_interrupted_recording(id)`.
+ */
+export interface InterruptedRecording {
+  id: string;
+  startedAt: string; // ISO 8601, becomes createdAt of the recovered recording
+  durationMs: number; // Audio on disk (up to the last full second)
+  inputDevice?: InputDeviceMeta;
+  pauses?: RecordingPause[]; // Breaks before the crash
+}
+
+/** Input device in `audio:device-added` / `audio:device-removed` events */
+export interface InputDeviceRef {
+  id: string;
+  name: string;
+}
+
+/** Payload of `audio:default-device-changed` */
+export interface DefaultDeviceChange {
+  device: InputDeviceRef | null; // null: no input device left
+}
+
+/** Payload of `audio:device-lost` (the open microphone failed or stopped delivering audio) */
+export interface DeviceLostEvent {
+  device: InputDeviceMeta;
+  error: string;
+  recording: boolean; // Samples captured so far are kept
+  failover: InputDeviceMeta | null; // Default device capture continues on, null if stopped
+}
+
+/** Completed speech segment of the running recording (`audio:speech-segment`) */
+export interface SpeechSegmentEvent {
+  index: number; // Numbered from 0 per recording
+  startMs: number; // Position in the recorded audio
+  endMs: number;
+  audio: string; // Base64 WAV, 16kHz mono
+}
+
+/** Input device from `list_audio_devices`; select it via `set_audio_input_device(id)` */
+export interface AudioInputDevice {
+  id: string; // Stable id: name plus position among same-named devices
+  index: string;
+  name: string;
+  isDefault: boolean;
+  sampleRate: number;
+}
+
 export interface VadStatsMeta {
   originalSamples: number;
   filteredSamples: number;
@@ -232,6 +297,31 @@ export interface TranscriptionMeta {
   processingTimeMs: number;
 }
 
+/** One transcription of a recording (re-transcriptions add revisions) */
+export interface TranscriptionRevision {
+  revision: number;
+  createdAt: string;
+  provider: string;
+  model: string;
+  language: string;
+  text: string;
+  filteredText: string;
+  processingTimeMs: number;
+}
+
+/** Word-level diff segment between two transcription revisions */
+export interface TranscriptionDiffSegment {
+  kind: "equal" | "removed" | "inserted";
+  text: string;
+}
+
+/** Recording changed outside the app (payload of `storage:recordings-changed`) */
+export interface RecordingChange {
+  kind: "added" | "removed" | "modified";
+  id: string;
+  baseName: string;
+}
+
 /** Metadata from text filtering (filler word removal, hallucination detection) */
 export interface TextFilterMeta {
   originalText: string;
@@ -302,6 +392,26 @@ export interface RecordingMetadata {
 
   // Analysis Status Tracking (P1-4: LLM Error Fallback UX)
   analysisStatus?: AnalysisStatus;
+
+  // User annotations (schema v3) - set via update_recording_metadata
+  title?: string;
+  notes?: string;
+  tags?: string[];
+  editedAt?: string;
+  pinned?: boolean;
+
+  // Transcription history (schema v4) - active revision mirrored into `transcription`
+  transcriptionRevisions?: TranscriptionRevision[];
+  activeRevision?: number;
+
+  // Duplicate detection - SHA-256 of the decoded audio, set at save time
+  contentHash?: string;
+  duplicateOf?: string;
+
+  // Microphone of native recordings, filled in by the backend on save
+  inputDevice?: InputDeviceMeta;
+  // Breaks of paused native recordings (not speech pauses), filled in by the backend on save
+  pauses?: RecordingPause[];
 }
 
 /**
@@ -399,12 +509,133 @@ export interface StorageConfig {
   storagePath: string;
 }
 
+/** Independent recording library with its own storage settings and data key */
+export interface StorageProfile {
+  id: string;
+  name: string;
+  storage: StorageConfig;
+}
+
+export interface ProfileList {
+  activeProfile: string;
+  profiles: StorageProfile[];
+}
+
+/** Lightweight listing entry (see query_recording_summaries) */
+export interface RecordingSummary {
+  id: string;
+  createdAt: string;
+  durationMs: number;
+  fileSize: number;
+  provider: string;
+  model: string;
+  source: string;
+  title: string | null;
+  tags: string[];
+  pinned: boolean;
+  primaryEmotion: string | null;
+  preview: string | null;
+  analyses: string[];
+}
+
+/** What saving audio that is already in the library does */
+export type DuplicatePolicy = "warn" | "reject" | "merge";
+
+/** Recordings with the same audio (find_duplicates) */
+export interface DuplicateGroup {
+  /** All recordings have the same content hash (otherwise matched by fingerprint) */
+  exact: boolean;
+  /** Oldest first */
+  recordings: RecordingSummary[];
+}
+
+/** Snapshot in the backup directory */
+export interface BackupSnapshot {
+  name: string;
+  createdAt: string;
+  recordingCount: number;
+  fileCount: number;
+  totalBytes: number;
+}
+
+export interface BackupResult {
+  snapshot: BackupSnapshot;
+  copiedFiles: number;
+  copiedBytes: number;
+  linkedFiles: number;
+  removedSnapshots: string[];
+}
+
+export interface RestoreResult {
+  restored: number;
+  replaced: number;
+  unchanged: number;
+}
+
+/** Payload of `storage:backup-progress` */
+export interface BackupProgress {
+  operation: "backup" | "restore";
+  processed: number;
+  total: number;
+}
+
+/** Payload of `storage:backup-failed` */
+export interface BackupFailure {
+  operation: "backup" | "restore";
+  error: string;
+  scheduled: boolean;
+}
+
+/** Sync configuration and state (get_sync_status) */
+export interface SyncStatus {
+  configured: boolean;
+  hasPassword: boolean;
+  encrypted: boolean;
+  lastSyncedAt: string | null;
+  /** The last sync was interrupted; the next one resumes it */
+  interrupted: boolean;
+  syncedRecordings: number;
+}
+
+/** Recording changed on both devices: local version kept, remote version copied */
+export interface SyncConflict {
+  id: string;
+  baseName: string;
+  copyId: string;
+  copyBaseName: string;
+}
+
+export interface SyncResult {
+  uploaded: number;
+  downloaded: number;
+  deletedRemote: number;
+  deletedLocal: number;
+  unchanged: number;
+  conflicts: SyncConflict[];
+}
+
+/** Payload of `storage:sync-progress` */
+export interface SyncProgress {
+  processed: number;
+  total: number;
+}
+
+/** Payload of `storage:sync-failed` */
+export interface SyncFailure {
+  error: string;
+}
+
 export interface StorageStats {
   recordingCount: number;
   totalSizeBytes: number;
   totalDurationMs: number;
   storagePath: string;
   maxRecordings: number;
+  diskUsageBytes: number;
+  quotaBytes: number | null;
+  quotaUsedPercent: number | null;
+  pinnedCount: number;
+  retentionDays: number;
 }
 
 export interface StorageSettings {